@@ -0,0 +1,194 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::transform::components::Transform;
+use bevy::utils::tracing::warn;
+use bevy::utils::HashMap;
+use bevy_openxr_core::{XRAnchorId, XRDevice};
+
+/// World-locked content through spatial anchors (`XR_MSFT_spatial_anchor`, `XR_MSFT_spatial_anchor_persistence`).
+///
+/// Add `XRAnchor` to an entity: an anchor is created at its `Transform`, and the `Transform` is
+/// refreshed from the anchor each frame. Creation results are delivered as `XRAnchorEvent`s
+#[derive(Default)]
+pub struct XRAnchorPlugin;
+
+impl Plugin for XRAnchorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRPersistedAnchors>()
+            .add_event::<XRAnchorEvent>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                anchor_create_system.system().label("anchor_create"),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                anchor_locate_system.system().after("anchor_create"),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, anchor_destroy_system.system());
+    }
+}
+
+/// Spatial anchor of the entity. Transform of the entity is world-locked to the anchor
+#[derive(Debug, Clone, Default)]
+pub struct XRAnchor {
+    id: Option<XRAnchorId>,
+    persist_as: Option<String>,
+    failed: bool,
+}
+
+impl XRAnchor {
+    /// Anchor created at the `Transform` of the entity
+    pub fn new() -> Self {
+        XRAnchor::default()
+    }
+
+    /// Anchor loaded from the anchor store by `name`. If not found, anchor is created at the `Transform`
+    /// of the entity and persisted with `name`
+    pub fn persisted(name: &str) -> Self {
+        XRAnchor {
+            persist_as: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn id(&self) -> Option<XRAnchorId> {
+        self.id
+    }
+
+    pub fn persisted_name(&self) -> Option<&str> {
+        self.persist_as.as_deref()
+    }
+
+    /// Anchor could not be created, see `XRAnchorEvent::Failed`
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum XRAnchorEvent {
+    /// New anchor was created for the entity
+    Created(Entity),
+
+    /// Anchor was loaded from the anchor store
+    Loaded(Entity),
+
+    /// Anchor could not be created (see `XRRuntimeError` events for details)
+    Failed(Entity),
+}
+
+/// Names of the anchors in the anchor store, refreshed when anchors are persisted
+#[derive(Debug, Default)]
+pub struct XRPersistedAnchors {
+    pub names: Vec<String>,
+    loaded: bool,
+}
+
+fn anchor_create_system(
+    mut xr_device: ResMut<XRDevice>,
+    mut persisted: ResMut<XRPersistedAnchors>,
+    mut anchor_events: EventWriter<XRAnchorEvent>,
+    mut query: Query<(Entity, &mut XRAnchor, &Transform)>,
+) {
+    if !xr_device.is_in_frame() {
+        return;
+    }
+
+    let mut pending = query
+        .iter_mut()
+        .filter(|(_, anchor, _)| anchor.id.is_none() && !anchor.failed)
+        .peekable();
+
+    if pending.peek().is_none() {
+        return;
+    }
+
+    if !xr_device.supports_anchors() {
+        warn!(target: "bevy_openxr::session", "Spatial anchors not supported by the runtime");
+        for (entity, mut anchor, _) in pending {
+            anchor.failed = true;
+            anchor_events.send(XRAnchorEvent::Failed(entity));
+        }
+        return;
+    }
+
+    if !persisted.loaded {
+        persisted.names = xr_device.persisted_anchor_names();
+        persisted.loaded = true;
+    }
+
+    for (entity, mut anchor, transform) in pending {
+        let persist_as = anchor.persist_as.clone();
+
+        if let Some(name) = &persist_as {
+            if persisted.names.contains(name) {
+                anchor.id = xr_device.load_anchor(name);
+                if anchor.id.is_some() {
+                    anchor_events.send(XRAnchorEvent::Loaded(entity));
+                    continue;
+                }
+            }
+        }
+
+        anchor.id = xr_device.create_anchor(transform);
+
+        let id = match anchor.id {
+            Some(id) => id,
+            None => {
+                anchor.failed = true;
+                anchor_events.send(XRAnchorEvent::Failed(entity));
+                continue;
+            }
+        };
+
+        if let Some(name) = persist_as {
+            if xr_device.persist_anchor(id, &name) && !persisted.names.contains(&name) {
+                persisted.names.push(name);
+            }
+        }
+
+        anchor_events.send(XRAnchorEvent::Created(entity));
+    }
+}
+
+fn anchor_locate_system(
+    mut xr_device: ResMut<XRDevice>,
+    mut query: Query<(&XRAnchor, &mut Transform)>,
+) {
+    if !xr_device.is_in_frame() {
+        return;
+    }
+
+    for (anchor, mut transform) in query.iter_mut() {
+        let id = match anchor.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        // keep the previous pose while the anchor is not tracked
+        if let Some(located) = xr_device.locate_anchor(id) {
+            transform.translation = located.translation;
+            transform.rotation = located.rotation;
+        }
+    }
+}
+
+/// Destroys anchors of despawned entities, and of entities the `XRAnchor` was removed from
+fn anchor_destroy_system(
+    mut xr_device: ResMut<XRDevice>,
+    mut anchor_ids: Local<HashMap<Entity, XRAnchorId>>,
+    removed: RemovedComponents<XRAnchor>,
+    query: Query<(Entity, &XRAnchor), Changed<XRAnchor>>,
+) {
+    for (entity, anchor) in query.iter() {
+        if let Some(id) = anchor.id {
+            anchor_ids.insert(entity, id);
+        }
+    }
+
+    for entity in removed.iter() {
+        if let Some(id) = anchor_ids.remove(&entity) {
+            xr_device.destroy_anchor(id);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::app::{App, CoreStage};
+use bevy::asset::Assets;
+use bevy::audio::{Audio, AudioSource, Decodable};
+use bevy::ecs::prelude::*;
+use bevy::ecs::schedule::StageLabel;
+use bevy::utils::tracing::warn;
+use bevy_openxr_core::{XRPauseBehavior, XRPauseState};
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+struct XRAudioStage;
+
+/// Output stream playing the sounds queued to bevy `Audio`. Non-send resource
+struct XRAudioOutput {
+    _stream: OutputStream,
+    stream_handle: OutputStreamHandle,
+
+    /// Sounds still playing, muted while paused (`XRPauseBehavior::mute_audio`)
+    sinks: Vec<Sink>,
+
+    muted: bool,
+}
+
+/// Plays the sounds queued to bevy `Audio` on the selected Quest output device (`QuestAudioDevices`)
+/// or the default output device, before bevy_audio plays them at `CoreStage::PostUpdate`. If the
+/// device can't be opened, bevy_audio plays the sounds, and they are not muted while paused
+pub(crate) fn configure_audio(app: &mut App) {
+    #[cfg(all(target_os = "android", feature = "quest-audio"))]
+    let device = app
+        .world
+        .get_resource::<crate::platform::oculus_android::QuestAudioDevices>()
+        .and_then(|devices| devices.output.as_ref()?.cpal_device());
+    #[cfg(not(all(target_os = "android", feature = "quest-audio")))]
+    let device: Option<rodio::cpal::Device> = None;
+
+    let stream = match &device {
+        Some(device) => OutputStream::try_from_device(device),
+        None => OutputStream::try_default(),
+    };
+
+    match stream {
+        Ok((stream, stream_handle)) => {
+            app.insert_non_send_resource(XRAudioOutput {
+                _stream: stream,
+                stream_handle,
+                sinks: Vec::new(),
+                muted: false,
+            })
+            .add_stage_before(
+                CoreStage::PostUpdate,
+                XRAudioStage,
+                SystemStage::single(play_queued_audio_system.exclusive_system()),
+            );
+        }
+        Err(e) => {
+            warn!(target: "bevy_openxr::audio", error = ?e, "Could not open audio output");
+        }
+    }
+}
+
+fn play_queued_audio_system(world: &mut World) {
+    let world = world.cell();
+    let mut output = world.get_non_send_mut::<XRAudioOutput>().unwrap();
+
+    let muted = match (
+        world.get_resource::<XRPauseBehavior>(),
+        world.get_resource::<XRPauseState>(),
+    ) {
+        (Some(behavior), Some(pause_state)) => behavior.mute_audio && pause_state.paused,
+        _ => false,
+    };
+    let volume = if muted { 0. } else { 1. };
+
+    output.sinks.retain(|sink| !sink.empty());
+    if muted != output.muted {
+        for sink in output.sinks.iter() {
+            sink.set_volume(volume);
+        }
+        output.muted = muted;
+    }
+
+    let (audio, audio_sources) = match (
+        world.get_resource::<Audio<AudioSource>>(),
+        world.get_resource::<Assets<AudioSource>>(),
+    ) {
+        (Some(audio), Some(audio_sources)) => (audio, audio_sources),
+        _ => return,
+    };
+
+    // sounds not loaded yet stay queued for the next update
+    let mut queue = audio.queue.write();
+    for _ in 0..queue.len() {
+        let handle = queue.pop_back().unwrap();
+        let audio_source = match audio_sources.get(&handle) {
+            Some(audio_source) => audio_source,
+            None => {
+                queue.push_front(handle);
+                continue;
+            }
+        };
+
+        match Sink::try_new(&output.stream_handle) {
+            Ok(sink) => {
+                sink.set_volume(volume);
+                sink.append(audio_source.decoder());
+                output.sinks.push(sink);
+            }
+            Err(e) => {
+                warn!(target: "bevy_openxr::audio", error = ?e, "Could not play audio");
+            }
+        }
+    }
+}
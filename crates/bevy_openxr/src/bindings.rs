@@ -0,0 +1,385 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::app::{prelude::*, Events};
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::{info, warn};
+use bevy::utils::HashMap;
+use bevy_openxr_core::XrError;
+
+use crate::handedness::XRHandedness;
+
+/// Runtime remapping of the bindings of app actions, e.g. for left-handed players or accessibility.
+///
+/// The app registers the default bindings of its actions to `XRActionBindings`, and suggests the
+/// resolved bindings (`XRActionBindings::suggested_bindings`) to the runtime when creating its
+/// actions. Remaps (`XRActionBindings::remap`) are stored as overrides of the defaults, loaded from
+/// and saved to `XRBindingsSettings::path`.
+///
+/// OpenXR fixes the bindings once the action sets have been attached to the session, so a change
+/// is sent as `XRBindingsChanged`: apps apply it in their own mapping from actions to app logic
+/// (`XRActionBindings::is_bound`), or suggest the new bindings in the next session
+#[derive(Default)]
+pub struct XRBindingsPlugin;
+
+impl Plugin for XRBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Events<XRBindingsChanged>>() {
+            app.add_event::<XRBindingsChanged>();
+        }
+
+        app.init_resource::<XRBindingsSettings>()
+            .init_resource::<XRActionBindings>()
+            .add_startup_system(load_bindings_system.system())
+            .add_system_to_stage(CoreStage::PostUpdate, bindings_changed_system.system());
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XRBindingsSettings {
+    /// File the overrides are loaded from at startup, and saved to when changed
+    pub path: Option<PathBuf>,
+}
+
+/// Input path of an interaction profile an action is bound to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct XRBinding {
+    /// e.g. `/interaction_profiles/oculus/touch_controller`
+    pub profile: String,
+
+    /// e.g. `/user/hand/right/input/trigger/value`
+    pub path: String,
+}
+
+impl XRBinding {
+    pub fn new(profile: &str, path: &str) -> Self {
+        XRBinding {
+            profile: profile.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// Same input of the other hand, e.g. `/user/hand/left/input/x/click` for
+    /// `/user/hand/right/input/x/click`
+    pub fn mirrored(&self) -> Self {
+        const LEFT: &str = "/user/hand/left";
+        const RIGHT: &str = "/user/hand/right";
+
+        let path = if let Some(rest) = self.path.strip_prefix(LEFT) {
+            format!("{}{}", RIGHT, rest)
+        } else if let Some(rest) = self.path.strip_prefix(RIGHT) {
+            format!("{}{}", LEFT, rest)
+        } else {
+            self.path.clone()
+        };
+
+        XRBinding {
+            profile: self.profile.clone(),
+            path,
+        }
+    }
+}
+
+/// Sent when the bindings of an action have been remapped or reset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XRBindingsChanged {
+    pub action: String,
+}
+
+/// Default bindings of the app actions, and their remapped overrides. In left-handed mode
+/// (`XRHandedness::Left`), the defaults are mirrored to the other hand
+#[derive(Debug, Clone, Default)]
+pub struct XRActionBindings {
+    defaults: HashMap<String, Vec<XRBinding>>,
+    mirrored_defaults: HashMap<String, Vec<XRBinding>>,
+    overrides: HashMap<String, Vec<XRBinding>>,
+    left_handed: bool,
+
+    /// Actions changed since the last `XRBindingsChanged` events
+    changed: Vec<String>,
+
+    /// Overrides have been changed by the app, and should be saved
+    unsaved: bool,
+}
+
+impl XRActionBindings {
+    /// Registers the default bindings of `action`, e.g. when creating the action
+    pub fn set_default(&mut self, action: &str, bindings: Vec<XRBinding>) {
+        let mirrored = bindings.iter().map(|binding| binding.mirrored()).collect();
+        self.mirrored_defaults.insert(action.to_string(), mirrored);
+        self.defaults.insert(action.to_string(), bindings);
+    }
+
+    /// Mirrors the default bindings for a left-handed user. Set by `XRHandednessPlugin`
+    pub fn set_handedness(&mut self, handedness: XRHandedness) {
+        let left_handed = handedness == XRHandedness::Left;
+        if left_handed == self.left_handed {
+            return;
+        }
+        self.left_handed = left_handed;
+
+        let overrides = &self.overrides;
+        let actions = self
+            .defaults
+            .keys()
+            .filter(|action| !overrides.contains_key(*action))
+            .cloned();
+        self.changed.extend(actions);
+    }
+
+    /// Replaces the bindings of `action`. An empty list unbinds the action
+    pub fn remap(&mut self, action: &str, bindings: Vec<XRBinding>) {
+        self.overrides.insert(action.to_string(), bindings);
+        self.changed.push(action.to_string());
+        self.unsaved = true;
+    }
+
+    /// Restores the default bindings of `action`
+    pub fn reset(&mut self, action: &str) {
+        if self.overrides.remove(action).is_some() {
+            self.changed.push(action.to_string());
+            self.unsaved = true;
+        }
+    }
+
+    pub fn reset_all(&mut self) {
+        let actions = self.overrides.drain().map(|(action, _)| action);
+        self.changed.extend(actions);
+        self.unsaved = true;
+    }
+
+    pub fn is_remapped(&self, action: &str) -> bool {
+        self.overrides.contains_key(action)
+    }
+
+    /// Current bindings of `action`: the override, or the defaults
+    pub fn bindings(&self, action: &str) -> &[XRBinding] {
+        let defaults = match self.left_handed {
+            true => &self.mirrored_defaults,
+            false => &self.defaults,
+        };
+
+        self.overrides
+            .get(action)
+            .or_else(|| defaults.get(action))
+            .map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    /// `action` is currently bound to `path` in `profile`
+    pub fn is_bound(&self, action: &str, profile: &str, path: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.profile == profile && binding.path == path)
+    }
+
+    /// Actions with bindings, defaults or overrides
+    pub fn actions(&self) -> impl Iterator<Item = &String> {
+        self.defaults.keys().chain(
+            self.overrides
+                .keys()
+                .filter(move |action| !self.defaults.contains_key(*action)),
+        )
+    }
+
+    /// Current bindings grouped by interaction profile, as `(action, path)` pairs converted with
+    /// `xrStringToPath`. Suggest them with `Instance::suggest_interaction_profile_bindings`, building
+    /// each `openxr::Binding` from the app's action of the name
+    pub fn suggested_bindings(
+        &self,
+        instance: &openxr::Instance,
+    ) -> Result<HashMap<openxr::Path, Vec<(String, openxr::Path)>>, XrError> {
+        let mut profiles: HashMap<openxr::Path, Vec<(String, openxr::Path)>> = HashMap::default();
+
+        for action in self.actions() {
+            for binding in self.bindings(action) {
+                let profile = instance.string_to_path(&binding.profile)?;
+                let path = instance.string_to_path(&binding.path)?;
+                profiles
+                    .entry(profile)
+                    .or_default()
+                    .push((action.clone(), path));
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads overrides from `path`, replacing the current ones. Defaults are kept
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.read_from(BufReader::new(File::open(path)?))
+    }
+
+    // File format: line-based text, one record per line. Only overrides are stored
+    //
+    //   unbind <action>
+    //   bind <action> <profile> <path>
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut actions = self.overrides.keys().collect::<Vec<_>>();
+        actions.sort();
+
+        for action in actions {
+            writeln!(writer, "unbind {}", action)?;
+            for binding in self.overrides[action].iter() {
+                writeln!(
+                    writer,
+                    "bind {} {} {}",
+                    action, binding.profile, binding.path
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        let mut overrides: HashMap<String, Vec<XRBinding>> = HashMap::default();
+
+        for line in reader.lines() {
+            let line = line?;
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+
+            match parts.as_slice() {
+                [] => continue,
+                ["unbind", action] => {
+                    overrides.entry(action.to_string()).or_default();
+                }
+                ["bind", action, profile, path] => overrides
+                    .entry(action.to_string())
+                    .or_default()
+                    .push(XRBinding::new(profile, path)),
+                _ => return Err(invalid_data("unknown record")),
+            }
+        }
+
+        let previous = std::mem::replace(&mut self.overrides, overrides);
+        self.changed
+            .extend(previous.into_iter().map(|(action, _)| action));
+        self.changed.extend(self.overrides.keys().cloned());
+
+        Ok(())
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn load_bindings_system(settings: Res<XRBindingsSettings>, mut bindings: ResMut<XRActionBindings>) {
+    let path = match &settings.path {
+        Some(path) if path.exists() => path,
+        _ => return,
+    };
+
+    match bindings.load(path) {
+        Ok(()) => {
+            info!(target: "bevy_openxr::bindings", path = %path.display(), "Loaded action bindings")
+        }
+        Err(error) => {
+            warn!(target: "bevy_openxr::bindings", path = %path.display(), %error, "Failed to load action bindings")
+        }
+    }
+}
+
+fn bindings_changed_system(
+    settings: Res<XRBindingsSettings>,
+    mut bindings: ResMut<XRActionBindings>,
+    mut changes: EventWriter<XRBindingsChanged>,
+) {
+    if bindings.changed.is_empty() {
+        return;
+    }
+
+    let mut changed = std::mem::take(&mut bindings.changed);
+    changed.sort();
+    changed.dedup();
+
+    for action in changed {
+        changes.send(XRBindingsChanged { action });
+    }
+
+    // changes from loading the file are not saved back
+    if !std::mem::take(&mut bindings.unsaved) {
+        return;
+    }
+
+    if let Some(path) = &settings.path {
+        if let Err(error) = bindings.save(path) {
+            warn!(target: "bevy_openxr::bindings", path = %path.display(), %error, "Failed to save action bindings");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOUCH: &str = "/interaction_profiles/oculus/touch_controller";
+
+    #[test]
+    fn test_remap() {
+        let mut bindings = XRActionBindings::default();
+        bindings.set_default(
+            "select",
+            vec![XRBinding::new(
+                TOUCH,
+                "/user/hand/right/input/trigger/value",
+            )],
+        );
+        bindings.set_default(
+            "menu",
+            vec![XRBinding::new(TOUCH, "/user/hand/left/input/menu/click")],
+        );
+
+        bindings.remap(
+            "select",
+            vec![XRBinding::new(TOUCH, "/user/hand/left/input/trigger/value")],
+        );
+        bindings.remap("menu", Vec::new());
+        assert!(bindings.is_bound("select", TOUCH, "/user/hand/left/input/trigger/value"));
+        assert!(bindings.bindings("menu").is_empty());
+
+        let mut data = Vec::new();
+        bindings.write_to(&mut data).unwrap();
+
+        let mut loaded = XRActionBindings::default();
+        loaded.set_default(
+            "menu",
+            vec![XRBinding::new(TOUCH, "/user/hand/left/input/menu/click")],
+        );
+        loaded.read_from(&data[..]).unwrap();
+        assert_eq!(loaded.bindings("select"), bindings.bindings("select"));
+        assert!(loaded.is_remapped("menu"));
+        assert!(loaded.bindings("menu").is_empty());
+
+        loaded.reset("menu");
+        assert_eq!(loaded.bindings("menu").len(), 1);
+    }
+
+    #[test]
+    fn test_handedness() {
+        let mut bindings = XRActionBindings::default();
+        bindings.set_default(
+            "select",
+            vec![XRBinding::new(
+                TOUCH,
+                "/user/hand/right/input/trigger/value",
+            )],
+        );
+
+        bindings.set_handedness(XRHandedness::Left);
+        assert!(bindings.is_bound("select", TOUCH, "/user/hand/left/input/trigger/value"));
+        assert_eq!(
+            XRBinding::new(TOUCH, "/user/gamepad/input/a/click")
+                .mirrored()
+                .path,
+            "/user/gamepad/input/a/click"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::math::Vec3;
+use bevy::transform::prelude::*;
+use bevy::utils::tracing::{info, warn};
+use bevy_openxr_core::event::XRCameraTransformsUpdated;
+
+use crate::laser_pointer::{XRAimPoses, XRHand};
+use crate::render_graph::camera::pose::center_eye;
+
+/// User calibration of the tracking origin: the floor height, and a seated offset moving the head at
+/// rest to a fixed eye height. The calibration is applied as the `Transform` of the entities with an
+/// `XRRig` (the parent of the XR camera and the tracked entities), loaded from
+/// `XRCalibrationSettings::path` at startup and saved there when changed.
+///
+/// Recalibration is requested with `XRCalibrationRequest`, e.g. when the user presses the trigger
+/// at the rest position, and completed with `XRCalibrated`
+#[derive(Default)]
+pub struct XRCalibrationPlugin;
+
+impl Plugin for XRCalibrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<XRCalibrationRequest>()
+            .add_event::<XRCalibrated>()
+            .init_resource::<XRCalibrationSettings>()
+            .init_resource::<XRCalibration>()
+            .add_startup_system(load_calibration_system.system())
+            .add_system(calibration_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRCalibrationSettings {
+    /// File the calibration is loaded from at startup, and saved to when changed
+    pub path: Option<PathBuf>,
+
+    /// Eye height of a seated user after `XRCalibrationRequest::Seated`
+    pub seated_eye_height: f32,
+}
+
+impl Default for XRCalibrationSettings {
+    fn default() -> Self {
+        XRCalibrationSettings {
+            path: None,
+            seated_eye_height: 1.2,
+        }
+    }
+}
+
+/// Parent entity of the XR camera and the tracked entities, transformed by the calibration. Apps
+/// moving the user (e.g. locomotion) move a parent of the rig
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XRRig;
+
+/// Calibration of the tracking origin, in the XR reference space
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct XRCalibration {
+    /// Height of the physical floor in the reference space
+    pub floor_height: f32,
+
+    /// Offset of the seated rest position, `None` when standing
+    pub seated_offset: Option<Vec3>,
+}
+
+impl XRCalibration {
+    /// Transform of the rig: moves the floor to zero, and the seated rest position to the seated
+    /// eye height
+    pub fn to_transform(&self) -> Transform {
+        let offset = Vec3::new(0., self.floor_height, 0.) + self.seated_offset.unwrap_or_default();
+        Transform::from_translation(-offset)
+    }
+
+    /// Calibrates the floor to the height of `position`, e.g. a controller held on the floor
+    pub fn calibrate_floor(&mut self, position: Vec3) {
+        self.floor_height = position.y;
+    }
+
+    /// Calibrates the seated offset so that `head` (at rest) is at `eye_height` above the floor,
+    /// centered horizontally
+    pub fn calibrate_seated(&mut self, head: Vec3, eye_height: f32) {
+        self.seated_offset = Some(head - Vec3::new(0., self.floor_height + eye_height, 0.));
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    // File format: line-based text, one value per line
+    //
+    //   floor_height <meters>
+    //   seated_offset <x> <y> <z>
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "floor_height {}", self.floor_height)?;
+        if let Some(offset) = self.seated_offset {
+            writeln!(
+                writer,
+                "seated_offset {} {} {}",
+                offset.x, offset.y, offset.z
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut calibration = XRCalibration::default();
+
+        for line in reader.lines() {
+            let line = line?;
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+
+            match parts.as_slice() {
+                [] => continue,
+                ["floor_height", height] => calibration.floor_height = parse(height)?,
+                ["seated_offset", x, y, z] => {
+                    calibration.seated_offset = Some(Vec3::new(parse(x)?, parse(y)?, parse(z)?))
+                }
+                _ => return Err(invalid_data("unknown record")),
+            }
+        }
+
+        Ok(calibration)
+    }
+}
+
+fn parse(value: &str) -> io::Result<f32> {
+    value.parse().map_err(|_| invalid_data("invalid number"))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Recalibration of `XRCalibration`, from the poses of the current frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XRCalibrationRequest {
+    /// Floor at the height of the aim pose of `hand`, e.g. with the controller held on the floor
+    Floor { hand: XRHand },
+
+    /// Seated offset from the head pose, with the user sitting at rest
+    Seated,
+
+    /// Standing, removes the seated offset
+    Standing,
+
+    /// Default calibration
+    Reset,
+}
+
+/// Sent when `XRCalibration` has been changed by an `XRCalibrationRequest`
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRCalibrated {
+    pub calibration: XRCalibration,
+}
+
+fn load_calibration_system(
+    settings: Res<XRCalibrationSettings>,
+    mut calibration: ResMut<XRCalibration>,
+) {
+    let path = match &settings.path {
+        Some(path) if path.exists() => path,
+        _ => return,
+    };
+
+    match XRCalibration::load(path) {
+        Ok(loaded) => {
+            info!(target: "bevy_openxr::calibration", path = %path.display(), ?loaded, "Loaded calibration");
+            *calibration = loaded;
+        }
+        Err(error) => {
+            warn!(target: "bevy_openxr::calibration", path = %path.display(), %error, "Failed to load calibration")
+        }
+    }
+}
+
+fn calibration_system(
+    mut head: Local<Option<Transform>>,
+    settings: Res<XRCalibrationSettings>,
+    aim_poses: Option<Res<XRAimPoses>>,
+    mut calibration: ResMut<XRCalibration>,
+    mut camera_transforms_updated: EventReader<XRCameraTransformsUpdated>,
+    (mut requests, mut calibrated): (EventReader<XRCalibrationRequest>, EventWriter<XRCalibrated>),
+    mut rigs: Query<&mut Transform, With<XRRig>>,
+) {
+    if let Some(event) = camera_transforms_updated.iter().last() {
+        *head = center_eye(&event.transforms);
+    }
+
+    for request in requests.iter() {
+        let previous = *calibration;
+        match request {
+            XRCalibrationRequest::Floor { hand } => {
+                match aim_poses
+                    .as_ref()
+                    .and_then(|aim_poses| aim_poses.get(*hand))
+                {
+                    Some(pose) => calibration.calibrate_floor(pose.translation),
+                    None => {
+                        warn!(target: "bevy_openxr::calibration", ?hand, "Hand not tracked, floor not calibrated")
+                    }
+                }
+            }
+            XRCalibrationRequest::Seated => match *head {
+                Some(head) => {
+                    calibration.calibrate_seated(head.translation, settings.seated_eye_height)
+                }
+                None => {
+                    warn!(target: "bevy_openxr::calibration", "Head not tracked, seated offset not calibrated")
+                }
+            },
+            XRCalibrationRequest::Standing => calibration.seated_offset = None,
+            XRCalibrationRequest::Reset => *calibration = XRCalibration::default(),
+        }
+
+        if *calibration == previous {
+            continue;
+        }
+
+        info!(target: "bevy_openxr::calibration", calibration = ?*calibration, "Calibrated");
+        if let Some(path) = &settings.path {
+            if let Err(error) = calibration.save(path) {
+                warn!(target: "bevy_openxr::calibration", path = %path.display(), %error, "Failed to save calibration");
+            }
+        }
+
+        calibrated.send(XRCalibrated {
+            calibration: *calibration,
+        });
+    }
+
+    let rig_transform = calibration.to_transform();
+    for mut transform in rigs.iter_mut() {
+        if *transform != rig_transform {
+            *transform = rig_transform;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibrate() {
+        let mut calibration = XRCalibration::default();
+        calibration.calibrate_floor(Vec3::new(0.3, -0.05, -0.2));
+        calibration.calibrate_seated(Vec3::new(0.1, 1.05, 0.2), 1.2);
+
+        let head = calibration
+            .to_transform()
+            .mul_vec3(Vec3::new(0.1, 1.05, 0.2));
+        assert!(head.abs_diff_eq(Vec3::new(0., 1.2, 0.), 1e-5));
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let calibration = XRCalibration {
+            floor_height: 0.02,
+            seated_offset: Some(Vec3::new(0.1, -0.4, 0.25)),
+        };
+
+        let mut data = Vec::new();
+        calibration.write_to(&mut data).unwrap();
+        assert_eq!(XRCalibration::read_from(&data[..]).unwrap(), calibration);
+
+        assert!(XRCalibration::read_from(&b"seated 1"[..]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::{info, warn};
+use bevy::wgpu::{WgpuRenderState, WgpuRendererHandles};
+use bevy_openxr_core::{XRCapturedImage, XRDevice};
+
+/// Reads back rendered views from the swapchain, e.g. for screenshots, bug reports or automated
+/// image tests. Request a capture through the `XRCapture` resource; captured images are sent as
+/// `XRFrameCaptured` events.
+///
+/// Capturing stalls the frame until the GPU has rendered it, so it should not be done every frame
+#[derive(Default)]
+pub struct XRCapturePlugin;
+
+impl Plugin for XRCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRCapture>()
+            .add_event::<XRFrameCaptured>();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CaptureRequest {
+    views: Vec<u32>,
+
+    /// Write the images as PNG files `<prefix>_view<index>.png`
+    path_prefix: Option<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+pub struct XRCapture {
+    request: Option<CaptureRequest>,
+}
+
+impl XRCapture {
+    /// Captures `views` of the next rendered frame
+    pub fn capture(&mut self, views: &[u32]) {
+        self.request = Some(CaptureRequest {
+            views: views.to_vec(),
+            path_prefix: None,
+        });
+    }
+
+    /// Captures `views` of the next rendered frame and writes them as PNG files
+    /// `<path_prefix>_view<index>.png`. Files are written in a background thread
+    pub fn save_png<P: AsRef<Path>>(&mut self, views: &[u32], path_prefix: P) {
+        self.request = Some(CaptureRequest {
+            views: views.to_vec(),
+            path_prefix: Some(path_prefix.as_ref().to_path_buf()),
+        });
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.request.is_some()
+    }
+}
+
+/// Views captured by a `XRCapture` request
+#[derive(Debug, Clone)]
+pub struct XRFrameCaptured {
+    pub images: Vec<XRCapturedImage>,
+}
+
+/// Captures the rendered frame if requested. Called from `post_render_system`, before the
+/// swapchain image is released
+pub(crate) fn capture_frame(world: &mut World, xr_device: &XRDevice) {
+    let should_render = match world.get_resource::<WgpuRenderState>() {
+        Some(render_state) => render_state.should_render,
+        None => return,
+    };
+
+    if !should_render {
+        return;
+    }
+
+    let request = match world
+        .get_resource_mut::<XRCapture>()
+        .and_then(|mut capture| capture.request.take())
+    {
+        Some(request) => request,
+        None => return,
+    };
+
+    let images = {
+        let handles = world.get_resource::<WgpuRendererHandles>().unwrap();
+        xr_device.capture_views(&handles.device, &handles.queue, &request.views)
+    };
+
+    if images.len() != request.views.len() {
+        warn!(target: "bevy_openxr::swapchain", requested = ?request.views, captured = images.len(), "Could not capture all views (unsupported swapchain format?)");
+    }
+
+    if let Some(path_prefix) = request.path_prefix {
+        let images = images.clone();
+        std::thread::spawn(move || write_pngs(&path_prefix, &images));
+    }
+
+    if let Some(mut events) = world.get_resource_mut::<Events<XRFrameCaptured>>() {
+        events.send(XRFrameCaptured { images });
+    }
+}
+
+fn write_pngs(path_prefix: &Path, images: &[XRCapturedImage]) {
+    for image in images {
+        let mut path = path_prefix.as_os_str().to_owned();
+        path.push(format!("_view{}.png", image.view_index));
+        let path = PathBuf::from(path);
+
+        match write_png(&path, image) {
+            Ok(()) => info!(target: "bevy_openxr::swapchain", path = ?path, "Wrote captured view"),
+            Err(e) => {
+                warn!(target: "bevy_openxr::swapchain", path = ?path, error = ?e, "Could not write captured view")
+            }
+        }
+    }
+}
+
+pub(crate) fn write_png(path: &Path, image: &XRCapturedImage) -> Result<(), png::EncodingError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.width, image.height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&image.data)
+}
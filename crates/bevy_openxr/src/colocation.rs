@@ -0,0 +1,207 @@
+use std::convert::TryInto;
+
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::math::{Quat, Vec3};
+use bevy::transform::components::Transform;
+
+use crate::anchors::XRAnchor;
+
+/// Co-location: aligning the reference spaces of devices in the same room, e.g. for local
+/// multiplayer.
+///
+/// Each device places an `XRAnchor` at the same physical spot (e.g. a marker on the floor). One
+/// device exports the pose of its anchor as an `XRSpaceAlignment`, which the app sends to the other
+/// devices (`to_bytes` / `from_bytes`). Importing it on another device gives the transform from the
+/// reference space of the exporting device into the local reference space. Requests are completed
+/// once the anchor has been created, as `XRColocationEvent`s.
+///
+/// Anchors themselves are not shared (e.g. `XR_FB_spatial_entity_sharing`), as the `openxr` crate
+/// does not expose the sharing extensions
+#[derive(Default)]
+pub struct XRColocationPlugin;
+
+impl Plugin for XRColocationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<XRColocationRequest>()
+            .add_event::<XRColocationEvent>()
+            .add_system(colocation_system.system());
+    }
+}
+
+/// Pose of the shared anchor in the reference space of the exporting device. Reference spaces are
+/// gravity-aligned, so only the rotation around the Y axis is shared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XRSpaceAlignment {
+    pub translation: Vec3,
+    pub yaw: f32,
+}
+
+impl XRSpaceAlignment {
+    pub fn from_transform(transform: &Transform) -> Self {
+        let forward = transform.rotation * -Vec3::Z;
+        XRSpaceAlignment {
+            translation: transform.translation,
+            yaw: (-forward.x).atan2(-forward.z),
+        }
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: Quat::from_rotation_y(self.yaw),
+            ..Default::default()
+        }
+    }
+
+    /// Transform from the reference space of the exporting device into the local reference space,
+    /// given the pose of the shared anchor in the local reference space
+    pub fn remote_to_local(&self, local_anchor: &Transform) -> Transform {
+        let local = XRSpaceAlignment::from_transform(local_anchor).to_transform();
+        let remote = self.to_transform();
+
+        let rotation = local.rotation * remote.rotation.inverse();
+        Transform {
+            translation: local.translation - rotation * remote.translation,
+            rotation,
+            ..Default::default()
+        }
+    }
+
+    /// Little-endian `x, y, z, yaw`
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        let values = [
+            self.translation.x,
+            self.translation.y,
+            self.translation.z,
+            self.yaw,
+        ];
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(values.iter()) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 16 {
+            return None;
+        }
+
+        let mut values = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()));
+        let mut next = || values.next().unwrap();
+
+        Some(XRSpaceAlignment {
+            translation: Vec3::new(next(), next(), next()),
+            yaw: next(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum XRColocationRequest {
+    /// Export the pose of the anchor entity
+    Export { anchor: Entity },
+
+    /// Import an alignment exported by another device, relative to the anchor entity
+    Import {
+        anchor: Entity,
+        alignment: XRSpaceAlignment,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum XRColocationEvent {
+    Exported {
+        anchor: Entity,
+        alignment: XRSpaceAlignment,
+    },
+
+    /// `remote_to_local` transforms poses from the reference space of the exporting device into the
+    /// local reference space
+    Imported {
+        anchor: Entity,
+        remote_to_local: Transform,
+    },
+
+    /// Anchor entity does not exist, or its anchor could not be created
+    Failed { anchor: Entity },
+}
+
+fn colocation_system(
+    mut pending: Local<Vec<XRColocationRequest>>,
+    mut requests: EventReader<XRColocationRequest>,
+    mut events: EventWriter<XRColocationEvent>,
+    anchors: Query<(&XRAnchor, &Transform)>,
+) {
+    pending.extend(requests.iter().cloned());
+
+    pending.retain(|request| {
+        let anchor = match request {
+            XRColocationRequest::Export { anchor } => *anchor,
+            XRColocationRequest::Import { anchor, .. } => *anchor,
+        };
+
+        let transform = match anchors.get(anchor) {
+            Ok((xr_anchor, _)) if xr_anchor.is_failed() => None,
+            Ok((xr_anchor, transform)) if xr_anchor.id().is_some() => Some(transform),
+            // anchor is created at the next frame
+            Ok(_) => return true,
+            Err(_) => None,
+        };
+
+        let event = match (request, transform) {
+            (XRColocationRequest::Export { .. }, Some(transform)) => XRColocationEvent::Exported {
+                anchor,
+                alignment: XRSpaceAlignment::from_transform(transform),
+            },
+            (XRColocationRequest::Import { alignment, .. }, Some(transform)) => {
+                XRColocationEvent::Imported {
+                    anchor,
+                    remote_to_local: alignment.remote_to_local(transform),
+                }
+            }
+            (_, None) => XRColocationEvent::Failed { anchor },
+        };
+
+        events.send(event);
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_to_local() {
+        // same physical spot, in two reference spaces rotated and offset from each other
+        let remote_anchor = Transform {
+            translation: Vec3::new(1., 0., -2.),
+            rotation: Quat::from_rotation_y(0.5),
+            ..Default::default()
+        };
+        let local_anchor = Transform {
+            translation: Vec3::new(-0.5, 0.1, 3.),
+            rotation: Quat::from_rotation_y(2.),
+            ..Default::default()
+        };
+
+        let alignment = XRSpaceAlignment::from_bytes(
+            &XRSpaceAlignment::from_transform(&remote_anchor).to_bytes(),
+        )
+        .unwrap();
+        assert!((alignment.yaw - 0.5).abs() < 1e-5);
+
+        let remote_to_local = alignment.remote_to_local(&local_anchor);
+
+        // a point 1m in front of the anchor
+        let remote_point = remote_anchor.translation + remote_anchor.rotation * -Vec3::Z;
+        let local_point = local_anchor.translation + local_anchor.rotation * -Vec3::Z;
+        assert!((remote_to_local.mul_vec3(remote_point) - local_point).length() < 1e-4);
+
+        assert!(XRSpaceAlignment::from_bytes(&[0; 8]).is_none());
+    }
+}
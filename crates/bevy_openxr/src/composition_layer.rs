@@ -0,0 +1,177 @@
+use bevy::app::prelude::*;
+use bevy::asset::{AssetEvent, Assets, Handle};
+use bevy::ecs::prelude::*;
+use bevy::render::texture::{Texture, TextureFormat};
+use bevy::transform::components::{GlobalTransform, Transform};
+use bevy::utils::tracing::warn;
+use bevy::utils::{HashMap, HashSet};
+use bevy::wgpu::WgpuRendererHandles;
+use bevy_openxr_core::{XRDevice, XRLayerId, XRLayerOrder, XRLayerShape};
+
+/// Displays images on cylinder (`XR_KHR_composition_layer_cylinder`) and equirect
+/// (`XR_KHR_composition_layer_equirect`) surfaces, composited by the runtime at full quality.
+///
+/// Add `XRCompositionLayer` with an RGBA8 image to an entity, the layer is posed by its `GlobalTransform`.
+/// Modifying the image asset (e.g. video frames) uploads it to the layer. Layers are composited over
+/// the rendered scene unless `XRCompositionLayer::order` places them below it
+#[derive(Default)]
+pub struct XRCompositionLayerPlugin;
+
+impl Plugin for XRCompositionLayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRCompositionLayers>()
+            .add_system_to_stage(CoreStage::PostUpdate, composition_layer_system.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                composition_layer_destroy_system.system(),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRCompositionLayer {
+    pub shape: XRLayerShape,
+
+    /// Layer image, `TextureFormat::Rgba8UnormSrgb` or `TextureFormat::Rgba8Unorm`
+    pub image: Handle<Texture>,
+
+    pub visible: bool,
+
+    /// Order relative to the projection layer and other layers, `XRLayerOrder::OVERLAY` by default
+    pub order: XRLayerOrder,
+}
+
+impl XRCompositionLayer {
+    pub fn cylinder(
+        image: Handle<Texture>,
+        radius: f32,
+        central_angle: f32,
+        aspect_ratio: f32,
+    ) -> Self {
+        XRCompositionLayer {
+            shape: XRLayerShape::Cylinder {
+                radius,
+                central_angle,
+                aspect_ratio,
+            },
+            image,
+            visible: true,
+            order: XRLayerOrder::OVERLAY,
+        }
+    }
+
+    pub fn equirect(image: Handle<Texture>, radius: f32) -> Self {
+        XRCompositionLayer {
+            shape: XRLayerShape::Equirect { radius },
+            image,
+            visible: true,
+            order: XRLayerOrder::OVERLAY,
+        }
+    }
+}
+
+struct LayerState {
+    id: XRLayerId,
+    image: Handle<Texture>,
+    size: (u32, u32),
+}
+
+/// Layers created for entities
+#[derive(Default)]
+struct XRCompositionLayers {
+    layers: HashMap<Entity, LayerState>,
+}
+
+fn composition_layer_system(
+    mut xr_device: ResMut<XRDevice>,
+    wgpu_handles: Res<WgpuRendererHandles>,
+    textures: Res<Assets<Texture>>,
+    mut texture_events: EventReader<AssetEvent<Texture>>,
+    mut layers: ResMut<XRCompositionLayers>,
+    query: Query<(Entity, &XRCompositionLayer, &GlobalTransform)>,
+) {
+    let modified = texture_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect::<HashSet<_>>();
+
+    for (entity, layer, transform) in query.iter() {
+        let texture = match textures.get(&layer.image) {
+            Some(texture) => texture,
+            None => continue, // not loaded yet
+        };
+
+        let size = (texture.size.width, texture.size.height);
+
+        // (re)create the swapchain when image size changes
+        let mut upload = modified.contains(&&layer.image);
+        let recreate = match layers.layers.get(&entity) {
+            Some(state) => state.size != size || state.image != layer.image,
+            None => true,
+        };
+
+        if recreate {
+            if let Some(state) = layers.layers.remove(&entity) {
+                xr_device.destroy_layer(state.id);
+            }
+
+            match texture.format {
+                TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm => (),
+                format => {
+                    warn!(target: "bevy_openxr::swapchain", ?format, "Unsupported composition layer image format");
+                    continue;
+                }
+            }
+
+            let id = match xr_device.create_layer(
+                &wgpu_handles.device,
+                layer.shape.clone(),
+                size.0,
+                size.1,
+            ) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            layers.layers.insert(
+                entity,
+                LayerState {
+                    id,
+                    image: layer.image.clone(),
+                    size,
+                },
+            );
+            upload = true;
+        }
+
+        let id = layers.layers[&entity].id;
+
+        if upload {
+            xr_device.write_layer_image(id, &wgpu_handles.queue, &texture.data);
+        }
+
+        xr_device.update_layer(
+            id,
+            layer.shape.clone(),
+            Transform::from(*transform),
+            layer.visible,
+        );
+        xr_device.set_layer_order(id, layer.order);
+    }
+}
+
+/// Destroys layers of despawned entities, and of entities the `XRCompositionLayer` was removed from
+fn composition_layer_destroy_system(
+    mut xr_device: ResMut<XRDevice>,
+    mut layers: ResMut<XRCompositionLayers>,
+    removed: RemovedComponents<XRCompositionLayer>,
+) {
+    for entity in removed.iter() {
+        if let Some(state) = layers.layers.remove(&entity) {
+            xr_device.destroy_layer(state.id);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::math::{Quat, Vec3};
+use bevy::transform::prelude::*;
+use bevy_openxr_core::{hand_tracking::HandPoseState, XRSystem};
+
+use crate::hand_tracking::{joint_transform, HandJoint};
+use crate::laser_pointer::XRHand;
+
+/// Grip, aim and palm poses of each hand, in the XR reference space.
+///
+/// Grip and aim poses (and the palm pose, where `XRDevice::supports_palm_pose`) are set by the app
+/// from its actions. While a hand is tracked, its palm pose is taken from the palm joint unless
+/// `XRControllerPoseSettings::palm_from_hand_tracking` is disabled. For runtimes without
+/// `XR_EXT_palm_pose`, `XRControllerPose::palm_or_fallback` approximates the palm from the grip
+/// pose, so that objects attached to the palm are placed consistently across controllers
+#[derive(Default)]
+pub struct XRControllerPosePlugin;
+
+impl Plugin for XRControllerPosePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRControllerPoseSettings>()
+            .init_resource::<XRControllerPoseState>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                hand_palm_system
+                    .system()
+                    .label(XRSystem::PoseUpdate)
+                    .after(XRSystem::EventPoll),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRControllerPoseSettings {
+    /// Set palm poses from the palm joints of tracked hands
+    pub palm_from_hand_tracking: bool,
+}
+
+impl Default for XRControllerPoseSettings {
+    fn default() -> Self {
+        XRControllerPoseSettings {
+            palm_from_hand_tracking: true,
+        }
+    }
+}
+
+/// Poses of one hand. `None` if not tracked
+#[derive(Debug, Clone, Default)]
+pub struct XRControllerPose {
+    /// Grip pose: origin at the center of the held controller, -Z along its handle
+    pub grip: Option<Transform>,
+
+    /// Aim pose, pointing along -Z
+    pub aim: Option<Transform>,
+
+    /// Palm pose, oriented like the palm joint of hand tracking: -Y points out of the palm, -Z
+    /// towards the finger tips
+    pub palm: Option<Transform>,
+}
+
+impl XRControllerPose {
+    /// Palm pose, or an approximation from the grip pose if the palm pose is not available
+    pub fn palm_or_fallback(&self, hand: XRHand) -> Option<Transform> {
+        self.palm
+            .or_else(|| self.grip.map(|grip| palm_from_grip(&grip, hand)))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XRControllerPoseState {
+    pub left: XRControllerPose,
+    pub right: XRControllerPose,
+}
+
+impl XRControllerPoseState {
+    pub fn get(&self, hand: XRHand) -> &XRControllerPose {
+        match hand {
+            XRHand::Left => &self.left,
+            XRHand::Right => &self.right,
+        }
+    }
+
+    pub fn get_mut(&mut self, hand: XRHand) -> &mut XRControllerPose {
+        match hand {
+            XRHand::Left => &mut self.left,
+            XRHand::Right => &mut self.right,
+        }
+    }
+}
+
+/// Offset of the palm center from the grip origin, towards the outside of the hand
+const PALM_OFFSET: f32 = 0.03;
+
+/// Approximate palm pose of a hand holding a controller at `grip`. The palm wraps around the
+/// handle from the outside of the hand (+X for the right hand), facing the handle, with the finger
+/// tips towards the front of the controller
+fn palm_from_grip(grip: &Transform, hand: XRHand) -> Transform {
+    let side = match hand {
+        XRHand::Left => -1.,
+        XRHand::Right => 1.,
+    };
+
+    Transform {
+        translation: grip.translation + grip.rotation * Vec3::new(side * PALM_OFFSET, 0., 0.),
+        rotation: grip.rotation * Quat::from_rotation_z(-side * std::f32::consts::FRAC_PI_2),
+        ..Default::default()
+    }
+}
+
+fn hand_palm_system(
+    mut from_hands: Local<[bool; 2]>,
+    settings: Res<XRControllerPoseSettings>,
+    hand_pose: Res<HandPoseState>,
+    mut poses: ResMut<XRControllerPoseState>,
+) {
+    if !settings.palm_from_hand_tracking {
+        return;
+    }
+
+    let hands = [
+        (XRHand::Left, hand_pose.left.as_ref()),
+        (XRHand::Right, hand_pose.right.as_ref()),
+    ];
+
+    for (index, (hand, joints)) in hands.iter().enumerate() {
+        let pose = poses.get_mut(*hand);
+        match joints {
+            Some(joints) => {
+                pose.palm = Some(joint_transform(joints, HandJoint::Palm));
+                from_hands[index] = true;
+            }
+            // clear the last hand-tracked pose, poses set by the app are left as is
+            None if from_hands[index] => {
+                pose.palm = None;
+                from_hands[index] = false;
+            }
+            None => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palm_from_grip() {
+        let grip = Transform::from_translation(Vec3::new(0.2, 1., -0.3));
+
+        let right = palm_from_grip(&grip, XRHand::Right);
+        assert!((right.translation - Vec3::new(0.23, 1., -0.3)).length() < 1e-5);
+        // out of the palm, towards the handle
+        assert!((right.rotation * -Vec3::Y - -Vec3::X).length() < 1e-5);
+        assert!((right.rotation * -Vec3::Z - -Vec3::Z).length() < 1e-5);
+
+        let left = palm_from_grip(&grip, XRHand::Left);
+        assert!((left.translation - Vec3::new(0.17, 1., -0.3)).length() < 1e-5);
+        assert!((left.rotation * -Vec3::Y - Vec3::X).length() < 1e-5);
+
+        let pose = XRControllerPose {
+            grip: Some(grip),
+            ..Default::default()
+        };
+        assert_eq!(
+            pose.palm_or_fallback(XRHand::Right)
+                .map(|palm| palm.translation),
+            Some(right.translation)
+        );
+        assert!(XRControllerPose::default()
+            .palm_or_fallback(XRHand::Left)
+            .is_none());
+    }
+}
@@ -0,0 +1,305 @@
+use bevy::app::prelude::*;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::prelude::*;
+use bevy::input::{keyboard::KeyCode, Input};
+use bevy::math::{Vec2, Vec3};
+use bevy::pbr::{prelude::*, PbrBundle};
+use bevy::render::{pipeline::PrimitiveTopology, prelude::*};
+use bevy::transform::prelude::*;
+use bevy_openxr_core::{hand_tracking::HandPoseState, XRDevice, XrFovf};
+
+use crate::hand_tracking::{joint_transform, HandJoint};
+use crate::laser_pointer::XRAimPoses;
+use crate::render_graph::camera::camera::XREyeViews;
+
+use num_traits::FromPrimitive;
+
+/// Debug lines of the XR state: eye frusta, reference space axes, play area bounds, aim rays and
+/// hand joints. Lines are drawn in the XR reference space (like the hand visualization), and can be
+/// toggled at runtime with `XRDebugSettings::enabled` or `toggle_key`
+#[derive(Default)]
+pub struct XRDebugPlugin;
+
+impl Plugin for XRDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRDebugSettings>()
+            .add_startup_system(setup.system())
+            .add_system(debug_toggle_system.system())
+            // after the eye views have been updated at PostUpdate
+            .add_system_to_stage(CoreStage::Last, debug_lines_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRDebugSettings {
+    pub enabled: bool,
+
+    /// Key toggling `enabled`, e.g. for the simulation or a connected keyboard
+    pub toggle_key: Option<KeyCode>,
+
+    pub eye_frusta: bool,
+    pub reference_axes: bool,
+    pub play_area: bool,
+    pub aim_rays: bool,
+    pub hand_joints: bool,
+
+    /// Length of the drawn eye frusta
+    pub frustum_length: f32,
+
+    /// Length of the reference space axes
+    pub axis_length: f32,
+
+    /// Length of the aim rays
+    pub ray_length: f32,
+}
+
+impl Default for XRDebugSettings {
+    fn default() -> Self {
+        XRDebugSettings {
+            enabled: true,
+            toggle_key: Some(KeyCode::F3),
+            eye_frusta: true,
+            reference_axes: true,
+            play_area: true,
+            aim_rays: true,
+            hand_joints: true,
+            frustum_length: 0.3,
+            axis_length: 0.5,
+            ray_length: 2.,
+        }
+    }
+}
+
+/// Lines of one color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DebugLines {
+    Frusta,
+    AxisX,
+    AxisY,
+    AxisZ,
+    PlayArea,
+    AimRays,
+    HandJoints,
+}
+
+impl DebugLines {
+    const ALL: [DebugLines; 7] = [
+        DebugLines::Frusta,
+        DebugLines::AxisX,
+        DebugLines::AxisY,
+        DebugLines::AxisZ,
+        DebugLines::PlayArea,
+        DebugLines::AimRays,
+        DebugLines::HandJoints,
+    ];
+
+    fn color(&self) -> Color {
+        match self {
+            DebugLines::Frusta => Color::YELLOW,
+            DebugLines::AxisX => Color::RED,
+            DebugLines::AxisY => Color::GREEN,
+            DebugLines::AxisZ => Color::BLUE,
+            DebugLines::PlayArea => Color::CYAN,
+            DebugLines::AimRays => Color::WHITE,
+            DebugLines::HandJoints => Color::PINK,
+        }
+    }
+}
+
+fn line_mesh(lines: &[(Vec3, Vec3)]) -> Mesh {
+    let positions = lines
+        .iter()
+        .flat_map(|(start, end)| vec![start.to_array(), end.to_array()])
+        .collect::<Vec<_>>();
+
+    // attributes required by the PBR pipeline, not used by unlit materials
+    let normals = vec![[0., 1., 0.]; positions.len()];
+    let uvs = vec![[0., 0.]; positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for lines in DebugLines::ALL.iter() {
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: meshes.add(line_mesh(&[])),
+                material: materials.add(StandardMaterial {
+                    base_color: lines.color(),
+                    unlit: true,
+                    ..Default::default()
+                }),
+                visible: Visible {
+                    is_visible: false,
+                    is_transparent: false,
+                },
+                ..Default::default()
+            })
+            .insert(*lines);
+    }
+}
+
+fn debug_toggle_system(
+    keyboard: Option<Res<Input<KeyCode>>>,
+    mut settings: ResMut<XRDebugSettings>,
+) {
+    let (keyboard, key) = match (keyboard, settings.toggle_key) {
+        (Some(keyboard), Some(key)) => (keyboard, key),
+        _ => return,
+    };
+
+    if keyboard.just_pressed(key) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+/// Lines from the eye to the corners of its field of view at `length`, and between the corners
+fn frustum_lines(pose: &Transform, fov: &XrFovf, length: f32) -> Vec<(Vec3, Vec3)> {
+    let corners = [
+        Vec2::new(fov.angle_left.tan(), fov.angle_down.tan()),
+        Vec2::new(fov.angle_right.tan(), fov.angle_down.tan()),
+        Vec2::new(fov.angle_right.tan(), fov.angle_up.tan()),
+        Vec2::new(fov.angle_left.tan(), fov.angle_up.tan()),
+    ]
+    .iter()
+    .map(|corner| pose.mul_vec3(Vec3::new(corner.x, corner.y, -1.) * length))
+    .collect::<Vec<_>>();
+
+    (0..4)
+        .flat_map(|index| {
+            vec![
+                (pose.translation, corners[index]),
+                (corners[index], corners[(index + 1) % 4]),
+            ]
+        })
+        .collect()
+}
+
+/// Play area rect on the floor, centered at the origin
+fn play_area_lines(width: f32, depth: f32) -> Vec<(Vec3, Vec3)> {
+    let (x, z) = (width / 2., depth / 2.);
+    let corners = [
+        Vec3::new(-x, 0., -z),
+        Vec3::new(x, 0., -z),
+        Vec3::new(x, 0., z),
+        Vec3::new(-x, 0., z),
+    ];
+
+    (0..4)
+        .map(|index| (corners[index], corners[(index + 1) % 4]))
+        .collect()
+}
+
+/// Bones between each joint and its parent joint
+fn hand_lines(joints: &openxr::HandJointLocations) -> Vec<(Vec3, Vec3)> {
+    (0..joints.len())
+        .filter_map(|index| {
+            let joint: HandJoint = FromPrimitive::from_usize(index)?;
+            let parent = joint.parent()?;
+            Some((
+                joint_transform(joints, joint).translation,
+                joint_transform(joints, parent).translation,
+            ))
+        })
+        .collect()
+}
+
+fn debug_lines_system(
+    mut play_area: Local<Option<(f32, f32)>>,
+    settings: Res<XRDebugSettings>,
+    eye_views: Option<Res<XREyeViews>>,
+    aim_poses: Option<Res<XRAimPoses>>,
+    hand_pose: Option<Res<HandPoseState>>,
+    xr_device: Option<ResMut<XRDevice>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut query: Query<(&DebugLines, &Handle<Mesh>, &mut Visible)>,
+) {
+    if settings.enabled && settings.play_area && play_area.is_none() {
+        *play_area = xr_device.and_then(|mut xr_device| xr_device.play_area_bounds());
+    }
+
+    for (debug_lines, mesh, mut visible) in query.iter_mut() {
+        let lines = match debug_lines {
+            _ if !settings.enabled => Vec::new(),
+            DebugLines::Frusta if settings.eye_frusta => eye_views
+                .iter()
+                .flat_map(|eye_views| eye_views.views.iter())
+                .flat_map(|view| frustum_lines(&view.pose, &view.fov, settings.frustum_length))
+                .collect(),
+            DebugLines::AxisX | DebugLines::AxisY | DebugLines::AxisZ
+                if settings.reference_axes =>
+            {
+                let axis = match debug_lines {
+                    DebugLines::AxisX => Vec3::X,
+                    DebugLines::AxisY => Vec3::Y,
+                    _ => Vec3::Z,
+                };
+                vec![(Vec3::ZERO, axis * settings.axis_length)]
+            }
+            DebugLines::PlayArea if settings.play_area => play_area
+                .map(|(width, depth)| play_area_lines(width, depth))
+                .unwrap_or_default(),
+            DebugLines::AimRays if settings.aim_rays => aim_poses
+                .iter()
+                .flat_map(|aim_poses| aim_poses.left.iter().chain(aim_poses.right.iter()))
+                .map(|aim| {
+                    let end = aim.translation + aim.rotation * -Vec3::Z * settings.ray_length;
+                    (aim.translation, end)
+                })
+                .collect(),
+            DebugLines::HandJoints if settings.hand_joints => hand_pose
+                .iter()
+                .flat_map(|hand_pose| hand_pose.left.iter().chain(hand_pose.right.iter()))
+                .flat_map(hand_lines)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // empty meshes are not drawn
+        let is_visible = !lines.is_empty();
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+
+        if is_visible {
+            if let Some(mesh) = meshes.get_mut(mesh) {
+                *mesh = line_mesh(&lines);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frustum_lines() {
+        let fov = XrFovf {
+            angle_left: -std::f32::consts::FRAC_PI_4,
+            angle_right: std::f32::consts::FRAC_PI_4,
+            angle_down: -std::f32::consts::FRAC_PI_4,
+            angle_up: std::f32::consts::FRAC_PI_4,
+        };
+        let pose = Transform::from_translation(Vec3::new(0., 1.6, 0.));
+
+        let lines = frustum_lines(&pose, &fov, 1.);
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0].0, pose.translation);
+        assert!((lines[0].1 - Vec3::new(-1., 0.6, -1.)).length() < 1e-5);
+
+        let lines = play_area_lines(2., 3.);
+        assert_eq!(
+            lines[0],
+            (Vec3::new(-1., 0., -1.5), Vec3::new(1., 0., -1.5))
+        );
+    }
+}
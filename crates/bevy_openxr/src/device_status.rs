@@ -0,0 +1,212 @@
+use bevy::app::prelude::*;
+use bevy::core::Time;
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::{info, warn};
+use bevy::utils::Duration;
+use bevy_openxr_core::{
+    event::XRPerformanceNotification, XRPerformanceDomain, XRPerformanceNotificationLevel,
+    XRPerformanceSubDomain,
+};
+
+use crate::platform;
+
+/// Battery and thermal status of the device in the `XRDeviceStatus` resource, so that apps can warn
+/// the user and lower their workload (e.g. `XRRenderScale`) before the device throttles.
+///
+/// Battery and the platform thermal status are polled every `XRDeviceStatusSettings::interval` (on
+/// Android from `BatteryManager` and `PowerManager`, unknown on other platforms). The CPU / GPU
+/// thermal levels come from the `XRPerformanceNotification`s of the runtime
+#[derive(Default)]
+pub struct XRDeviceStatusPlugin;
+
+impl Plugin for XRDeviceStatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRDeviceStatusSettings>()
+            .init_resource::<XRDeviceStatus>()
+            .add_system_to_stage(CoreStage::PreUpdate, device_status_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRDeviceStatusSettings {
+    /// Interval of polling the platform
+    pub interval: Duration,
+
+    /// Battery level below which a warning is logged
+    pub low_battery_level: f32,
+}
+
+impl Default for XRDeviceStatusSettings {
+    fn default() -> Self {
+        XRDeviceStatusSettings {
+            interval: Duration::from_secs(10),
+            low_battery_level: 0.15,
+        }
+    }
+}
+
+/// Thermal status of the platform (Android `PowerManager.THERMAL_STATUS_*`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum XRThermalStatus {
+    None,
+    Light,
+    Moderate,
+    Severe,
+    Critical,
+    Emergency,
+    Shutdown,
+}
+
+impl XRThermalStatus {
+    pub(crate) fn from_android(status: i32) -> Option<Self> {
+        match status {
+            0 => Some(XRThermalStatus::None),
+            1 => Some(XRThermalStatus::Light),
+            2 => Some(XRThermalStatus::Moderate),
+            3 => Some(XRThermalStatus::Severe),
+            4 => Some(XRThermalStatus::Critical),
+            5 => Some(XRThermalStatus::Emergency),
+            6 => Some(XRThermalStatus::Shutdown),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XRDeviceStatus {
+    /// Battery charge in `0..=1`, `None` if unknown
+    pub battery_level: Option<f32>,
+
+    /// `None` if unknown
+    pub charging: Option<bool>,
+
+    /// Thermal status of the platform, `None` if unknown
+    pub thermal_status: Option<XRThermalStatus>,
+
+    /// Thermal level of the CPU, as notified by the runtime
+    pub cpu_thermal: XRPerformanceNotificationLevel,
+
+    /// Thermal level of the GPU, as notified by the runtime
+    pub gpu_thermal: XRPerformanceNotificationLevel,
+}
+
+impl Default for XRDeviceStatus {
+    fn default() -> Self {
+        XRDeviceStatus {
+            battery_level: None,
+            charging: None,
+            thermal_status: None,
+            cpu_thermal: XRPerformanceNotificationLevel::Normal,
+            gpu_thermal: XRPerformanceNotificationLevel::Normal,
+        }
+    }
+}
+
+impl XRDeviceStatus {
+    /// The runtime or the platform reports the device heating up: the app should lower its workload
+    pub fn thermal_warning(&self) -> bool {
+        self.cpu_thermal >= XRPerformanceNotificationLevel::Warning
+            || self.gpu_thermal >= XRPerformanceNotificationLevel::Warning
+            || self.thermal_status >= Some(XRThermalStatus::Moderate)
+    }
+
+    fn apply_notification(&mut self, notification: &XRPerformanceNotification) {
+        if notification.sub_domain != XRPerformanceSubDomain::Thermal {
+            return;
+        }
+
+        match notification.domain {
+            XRPerformanceDomain::Cpu => self.cpu_thermal = notification.to_level,
+            XRPerformanceDomain::Gpu => self.gpu_thermal = notification.to_level,
+        }
+    }
+}
+
+fn device_status_system(
+    mut last_poll: Local<Option<f64>>,
+    time: Res<Time>,
+    settings: Res<XRDeviceStatusSettings>,
+    mut performance_notifications: EventReader<XRPerformanceNotification>,
+    mut status: ResMut<XRDeviceStatus>,
+) {
+    let mut updated = *status;
+    for notification in performance_notifications.iter() {
+        updated.apply_notification(notification);
+    }
+
+    let now = time.seconds_since_startup();
+    let poll = match *last_poll {
+        Some(last_poll) => now - last_poll >= settings.interval.as_secs_f64(),
+        None => true,
+    };
+
+    if poll {
+        *last_poll = Some(now);
+        match platform::power_status() {
+            Ok(power) => {
+                updated.battery_level = power.battery_level;
+                updated.charging = power.charging;
+                updated.thermal_status = power.thermal_status;
+            }
+            Err(e) => {
+                warn!(target: "bevy_openxr::platform", error = ?e, "Could not query the battery and thermal status")
+            }
+        }
+    }
+
+    if updated == *status {
+        return;
+    }
+
+    let low_battery = |status: &XRDeviceStatus| {
+        status.charging != Some(true)
+            && status
+                .battery_level
+                .map_or(false, |level| level < settings.low_battery_level)
+    };
+
+    if low_battery(&updated) && !low_battery(&status) {
+        warn!(target: "bevy_openxr::platform", battery_level = ?updated.battery_level, "Battery low");
+    }
+
+    if updated.thermal_warning() && !status.thermal_warning() {
+        warn!(target: "bevy_openxr::platform", status = ?updated, "Device heating up");
+    } else if !updated.thermal_warning() && status.thermal_warning() {
+        info!(target: "bevy_openxr::platform", status = ?updated, "Device cooled down");
+    }
+
+    *status = updated;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thermal_warning() {
+        let mut status = XRDeviceStatus::default();
+        assert!(!status.thermal_warning());
+
+        status.apply_notification(&XRPerformanceNotification {
+            domain: XRPerformanceDomain::Gpu,
+            sub_domain: XRPerformanceSubDomain::Rendering,
+            from_level: XRPerformanceNotificationLevel::Normal,
+            to_level: XRPerformanceNotificationLevel::Warning,
+        });
+        assert!(!status.thermal_warning());
+
+        status.apply_notification(&XRPerformanceNotification {
+            domain: XRPerformanceDomain::Gpu,
+            sub_domain: XRPerformanceSubDomain::Thermal,
+            from_level: XRPerformanceNotificationLevel::Normal,
+            to_level: XRPerformanceNotificationLevel::Warning,
+        });
+        assert!(status.thermal_warning());
+
+        let status = XRDeviceStatus {
+            thermal_status: Some(XRThermalStatus::Light),
+            ..Default::default()
+        };
+        assert!(!status.thermal_warning());
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::debug;
+use bevy::utils::Duration;
+use bevy_openxr_core::XRDevice;
+
+/// Adjusts the render scale (fraction of the swapchain resolution rendered and submitted) based on
+/// frame timing, so that frame rate is kept under load.
+///
+/// The runtime blocks `xrWaitFrame` for the time the app is ahead of the compositor. Little or no
+/// waiting, or dropped frames, indicate that the frame does not fit into the display period
+#[derive(Default)]
+pub struct XRDynamicResolutionPlugin;
+
+impl Plugin for XRDynamicResolutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRDynamicResolution>()
+            .init_resource::<XRRenderScale>()
+            .add_system_to_stage(CoreStage::PreUpdate, dynamic_resolution_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRDynamicResolution {
+    pub enabled: bool,
+
+    /// Lowest allowed render scale
+    pub min_scale: f32,
+
+    /// Highest allowed render scale, up to `XrOptions::supersampling`
+    pub max_scale: f32,
+
+    /// Render scale change per adjustment
+    pub step: f32,
+
+    /// Scale down if `xrWaitFrame` blocks less than this fraction of the display period
+    pub low_headroom: f32,
+
+    /// Scale up if `xrWaitFrame` blocks more than this fraction of the display period
+    pub high_headroom: f32,
+
+    /// Frames to wait between adjustments, so that the effect of the previous one is measured
+    pub frames_between_adjustments: u32,
+}
+
+impl Default for XRDynamicResolution {
+    fn default() -> Self {
+        XRDynamicResolution {
+            enabled: true,
+            min_scale: 0.6,
+            max_scale: 1.,
+            step: 0.05,
+            low_headroom: 0.05,
+            high_headroom: 0.3,
+            frames_between_adjustments: 30,
+        }
+    }
+}
+
+/// Scale of the recommended view size rendered and submitted, in `0.1..=XrOptions::supersampling`,
+/// the same for all views. Can be changed at any frame, by the app or by `XRDynamicResolutionPlugin`:
+/// the camera projection renders into the top-left sub-rect of each view, and the submitted image
+/// rects are scaled accordingly. Starts at `XrOptions::supersampling`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XRRenderScale(pub f32);
+
+impl Default for XRRenderScale {
+    fn default() -> Self {
+        XRRenderScale(1.)
+    }
+}
+
+#[derive(Default)]
+struct ControllerState {
+    frames_since_adjustment: u32,
+    dropped_frames: u64,
+    min_headroom: Option<Duration>,
+}
+
+fn dynamic_resolution_system(
+    settings: Res<XRDynamicResolution>,
+    mut state: Local<ControllerState>,
+    mut render_scale: ResMut<XRRenderScale>,
+    xr_device: Res<XRDevice>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    let timings = match xr_device.get_frame_timings() {
+        Some(timings) => timings.clone(),
+        None => return,
+    };
+
+    // worst frame since the last adjustment
+    state.min_headroom = Some(match state.min_headroom {
+        Some(headroom) => headroom.min(timings.frame_wait),
+        None => timings.frame_wait,
+    });
+
+    state.frames_since_adjustment += 1;
+    if state.frames_since_adjustment < settings.frames_between_adjustments {
+        return;
+    }
+
+    let display_period = timings.display_period.as_secs_f32();
+    let headroom = state.min_headroom.take().unwrap().as_secs_f32();
+    let dropped = timings.dropped_frames > state.dropped_frames;
+
+    state.frames_since_adjustment = 0;
+    state.dropped_frames = timings.dropped_frames;
+
+    if display_period <= 0. {
+        return;
+    }
+
+    let scale = if dropped || headroom < display_period * settings.low_headroom {
+        render_scale.0 - settings.step
+    } else if headroom > display_period * settings.high_headroom {
+        render_scale.0 + settings.step
+    } else {
+        return;
+    };
+
+    let scale = scale.max(settings.min_scale).min(settings.max_scale);
+    if (scale - render_scale.0).abs() < f32::EPSILON {
+        return;
+    }
+
+    debug!(target: "bevy_openxr::frame", scale, headroom, dropped, "Render scale changed");
+
+    render_scale.0 = scale;
+}
+
+/// Applies `XRRenderScale` to the swapchain. Also after swapchain (re)creation, which resets the
+/// scale of the swapchain
+pub(crate) fn render_scale_system(
+    render_scale: Res<XRRenderScale>,
+    mut xr_device: ResMut<XRDevice>,
+) {
+    let max_scale = match xr_device.get_max_render_scale() {
+        Some(max_scale) => max_scale,
+        None => return,
+    };
+
+    let scale = render_scale.0.max(0.1).min(max_scale);
+    if let Some(applied) = xr_device.get_render_scale() {
+        if (applied - scale).abs() > f32::EPSILON {
+            xr_device.set_render_scale(scale);
+        }
+    }
+}
@@ -1,7 +1,10 @@
+use bevy_openxr_core::XrError;
+
 #[derive(Debug)]
 pub enum Error {
     XRLoad(openxr::LoadError),
     XR(openxr::sys::Result),
+    Device(XrError),
 
     #[cfg(target_os = "android")]
     JNI(jni::errors::Error),
@@ -15,6 +18,12 @@ impl From<openxr::sys::Result> for Error {
     }
 }
 
+impl From<XrError> for Error {
+    fn from(e: XrError) -> Self {
+        Error::Device(e)
+    }
+}
+
 impl From<openxr::LoadError> for Error {
     fn from(e: openxr::LoadError) -> Self {
         Error::XRLoad(e)
@@ -0,0 +1,202 @@
+use bevy::app::{prelude::*, Events};
+use bevy::asset::Assets;
+use bevy::core::Time;
+use bevy::ecs::prelude::*;
+use bevy::math::Vec3;
+use bevy::pbr::{prelude::*, PbrBundle};
+use bevy::render::prelude::*;
+use bevy::transform::prelude::*;
+use bevy::utils::Duration;
+use bevy_openxr_core::event::XRCameraTransformsUpdated;
+
+use crate::laser_pointer::{
+    closest_target, XRAimPoses, XRPointerEvent, XRPointerSource, XRPointerTarget,
+};
+use crate::render_graph::camera::pose::center_eye;
+
+/// Head gaze pointer: a ray along the forward (-Z) direction of the head, hit-testing entities with
+/// an `XRPointerTarget`. Hits are sent as the same `XRPointerEvent`s as the laser pointers, with
+/// `XRPointerSource::Head`. Looking at a target for `XRGazePointerSettings::dwell_time` selects it.
+///
+/// By default the gaze pointer is a fallback, active only while there are no aim poses (`XRAimPoses`),
+/// i.e. neither controllers nor hand tracking are in use
+#[derive(Default)]
+pub struct XRGazePointerPlugin;
+
+impl Plugin for XRGazePointerPlugin {
+    fn build(&self, app: &mut App) {
+        // shared with XRLaserPointerPlugin
+        if !app.world.contains_resource::<Events<XRPointerEvent>>() {
+            app.add_event::<XRPointerEvent>();
+        }
+
+        app.init_resource::<XRGazePointerSettings>()
+            .add_startup_system(setup.system())
+            .add_system(gaze_pointer_system.system());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRGazePointerMode {
+    /// Active while neither hand has an aim pose
+    Fallback,
+
+    /// Always active, e.g. as an accessibility option
+    Always,
+
+    Disabled,
+}
+
+#[derive(Debug, Clone)]
+pub struct XRGazePointerSettings {
+    pub mode: XRGazePointerMode,
+
+    /// Distance of the cursor when nothing is hit
+    pub max_distance: f32,
+
+    /// Time to look at a target before it is selected (`XRPointerEvent::Select`). `None` disables
+    /// dwell selection
+    pub dwell_time: Option<Duration>,
+
+    /// Radius of the cursor dot. The dot grows up to twice the size while dwelling
+    pub cursor_radius: f32,
+    pub color: Color,
+}
+
+impl Default for XRGazePointerSettings {
+    fn default() -> Self {
+        XRGazePointerSettings {
+            mode: XRGazePointerMode::Fallback,
+            max_distance: 3.,
+            dwell_time: Some(Duration::from_millis(1200)),
+            cursor_radius: 0.008,
+            color: Color::rgb(0.9, 0.9, 1.),
+        }
+    }
+}
+
+#[derive(Default)]
+struct GazeCursor {
+    hovered: Option<Entity>,
+
+    /// Time spent on the hovered target
+    dwell: Duration,
+
+    /// Hovered target has been selected
+    selected: bool,
+}
+
+fn setup(
+    mut commands: Commands,
+    settings: Res<XRGazePointerSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Icosphere {
+                radius: 1.,
+                subdivisions: 2,
+            })),
+            material: materials.add(StandardMaterial {
+                base_color: settings.color,
+                unlit: true,
+                ..Default::default()
+            }),
+            visible: Visible {
+                is_visible: false,
+                is_transparent: false,
+            },
+            ..Default::default()
+        })
+        .insert(GazeCursor::default());
+}
+
+fn gaze_pointer_system(
+    mut head: Local<Option<Transform>>,
+    time: Res<Time>,
+    settings: Res<XRGazePointerSettings>,
+    aim_poses: Option<Res<XRAimPoses>>,
+    mut camera_transforms_updated: EventReader<XRCameraTransformsUpdated>,
+    mut pointer_events: EventWriter<XRPointerEvent>,
+    mut cursors: Query<(&mut GazeCursor, &mut Transform, &mut Visible)>,
+    targets: Query<(Entity, &XRPointerTarget, &GlobalTransform)>,
+) {
+    if let Some(event) = camera_transforms_updated.iter().last() {
+        *head = center_eye(&event.transforms);
+    }
+
+    let active = match settings.mode {
+        XRGazePointerMode::Fallback => aim_poses.map_or(true, |aim_poses| {
+            aim_poses.left.is_none() && aim_poses.right.is_none()
+        }),
+        XRGazePointerMode::Always => true,
+        XRGazePointerMode::Disabled => false,
+    };
+
+    let source = XRPointerSource::Head;
+
+    for (mut cursor, mut transform, mut visible) in cursors.iter_mut() {
+        let head = match *head {
+            Some(head) if active => head,
+            _ => {
+                visible.is_visible = false;
+                if let Some(entity) = cursor.hovered.take() {
+                    pointer_events.send(XRPointerEvent::Leave { source, entity });
+                }
+                continue;
+            }
+        };
+
+        let origin = head.translation;
+        let direction = head.rotation * -Vec3::Z;
+        let hit = closest_target(origin, direction, settings.max_distance, targets.iter());
+
+        if cursor.hovered != hit.map(|(entity, _)| entity) {
+            if let Some(entity) = cursor.hovered {
+                pointer_events.send(XRPointerEvent::Leave { source, entity });
+            }
+
+            if let Some((entity, distance)) = hit {
+                pointer_events.send(XRPointerEvent::Enter {
+                    source,
+                    entity,
+                    point: origin + direction * distance,
+                });
+            }
+
+            cursor.hovered = hit.map(|(entity, _)| entity);
+            cursor.dwell = Duration::default();
+            cursor.selected = false;
+        }
+
+        let mut progress = 0.;
+        if let Some((entity, distance)) = hit {
+            let point = origin + direction * distance;
+            pointer_events.send(XRPointerEvent::Move {
+                source,
+                entity,
+                point,
+            });
+
+            if let Some(dwell_time) = settings.dwell_time.filter(|_| !cursor.selected) {
+                cursor.dwell += time.delta();
+                progress = (cursor.dwell.as_secs_f32() / dwell_time.as_secs_f32()).min(1.);
+
+                if cursor.dwell >= dwell_time {
+                    pointer_events.send(XRPointerEvent::Select {
+                        source,
+                        entity,
+                        point,
+                    });
+                    cursor.selected = true;
+                }
+            }
+        }
+
+        let distance = hit.map_or(settings.max_distance, |(_, distance)| distance);
+        transform.translation = origin + direction * distance;
+        transform.scale = Vec3::splat(settings.cursor_radius * (1. + progress));
+        visible.is_visible = true;
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::math::Vec3;
+use bevy::render::prelude::*;
+use bevy::transform::prelude::*;
+use bevy_openxr_core::{event::XRCameraTransformsUpdated, hand_tracking::HandPoseState};
+
+use crate::hand_tracking::{joint_transform, HandJoint};
+use crate::handedness::XRHandedness;
+use crate::laser_pointer::XRHand;
+use crate::render_graph::camera::pose::center_eye;
+
+/// Hand menus: entities with an `XRHandMenu` follow a joint of a tracked hand, and are shown only
+/// while the palm faces the head. Visibility is applied to the whole hierarchy of the entity.
+///
+/// The transform of the menu entity is set in the XR reference space, so it should not have a parent
+#[derive(Default)]
+pub struct XRHandMenuPlugin;
+
+impl Plugin for XRHandMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(hand_menu_system.system());
+    }
+}
+
+/// Anchors the entity (and its children) to a hand joint
+#[derive(Debug, Clone)]
+pub struct XRHandMenu {
+    /// Hand of a right-handed user, swapped in left-handed mode (`XRHandedness`)
+    pub hand: XRHand,
+
+    /// Joint the menu follows, e.g. `Palm` or `Wrist`
+    pub joint: HandJoint,
+
+    /// Transform of the menu relative to the joint. -Y of the joint points out of the palm, -Z
+    /// towards the finger tips
+    pub offset: Transform,
+
+    /// Cosine of the largest angle between the palm normal and the direction to the head at which
+    /// the menu is shown
+    pub min_facing: f32,
+}
+
+impl Default for XRHandMenu {
+    fn default() -> Self {
+        XRHandMenu {
+            hand: XRHand::Left,
+            joint: HandJoint::Palm,
+            offset: Transform::from_translation(Vec3::new(0., -0.06, 0.)),
+            min_facing: 0.7,
+        }
+    }
+}
+
+/// Whether the palm normal (-Y of the palm joint) points towards `head`
+fn palm_faces_head(palm: &Transform, head: Vec3, min_facing: f32) -> bool {
+    let to_head = head - palm.translation;
+    if to_head.length_squared() == 0. {
+        return false;
+    }
+
+    let normal = palm.rotation * -Vec3::Y;
+    normal.dot(to_head.normalize()) >= min_facing
+}
+
+fn hand_menu_system(
+    mut head: Local<Option<Vec3>>,
+    hand_pose: Res<HandPoseState>,
+    handedness: Option<Res<XRHandedness>>,
+    mut camera_transforms_updated: EventReader<XRCameraTransformsUpdated>,
+    mut menus: Query<(Entity, &XRHandMenu, &mut Transform)>,
+    children: Query<&Children>,
+    mut visibles: Query<&mut Visible>,
+) {
+    if let Some(event) = camera_transforms_updated.iter().last() {
+        *head = center_eye(&event.transforms).map(|transform| transform.translation);
+    }
+
+    for (entity, menu, mut transform) in menus.iter_mut() {
+        let hand = handedness
+            .as_ref()
+            .map_or(menu.hand, |handedness| handedness.resolve(menu.hand));
+
+        let joints = match hand {
+            XRHand::Left => hand_pose.left.as_ref(),
+            XRHand::Right => hand_pose.right.as_ref(),
+        };
+
+        let visible = match (joints, *head) {
+            (Some(joints), Some(head)) => {
+                *transform = joint_transform(joints, menu.joint).mul_transform(menu.offset);
+
+                let palm = joint_transform(joints, HandJoint::Palm);
+                palm_faces_head(&palm, head, menu.min_facing)
+            }
+            _ => false,
+        };
+
+        set_visible_recursive(entity, visible, &children, &mut visibles);
+    }
+}
+
+/// Sets visibility of `entity` and all its descendants
+pub(crate) fn set_visible_recursive(
+    entity: Entity,
+    is_visible: bool,
+    children: &Query<&Children>,
+    visibles: &mut Query<&mut Visible>,
+) {
+    if let Ok(mut visible) = visibles.get_mut(entity) {
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+    }
+
+    if let Ok(entity_children) = children.get(entity) {
+        for child in entity_children.iter() {
+            set_visible_recursive(*child, is_visible, children, visibles);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::Quat;
+
+    #[test]
+    fn test_palm_faces_head() {
+        let head = Vec3::new(0., 1.6, 0.);
+
+        // palm up, below the head
+        let mut palm = Transform::from_translation(Vec3::new(0., 1.2, -0.3));
+        palm.rotation = Quat::from_rotation_z(std::f32::consts::PI);
+        assert!(palm_faces_head(&palm, head, 0.7));
+
+        // palm down
+        palm.rotation = Quat::IDENTITY;
+        assert!(!palm_faces_head(&palm, head, 0.7));
+
+        assert!(!palm_faces_head(
+            &Transform::from_translation(head),
+            head,
+            0.7
+        ));
+    }
+}
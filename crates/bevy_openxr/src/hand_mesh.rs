@@ -0,0 +1,275 @@
+use bevy::app::prelude::*;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::prelude::*;
+use bevy::math::{Mat4, Vec3};
+use bevy::pbr::{prelude::*, PbrBundle};
+use bevy::render::{
+    mesh::{Indices, Mesh},
+    pipeline::PrimitiveTopology,
+    prelude::*,
+};
+use bevy::utils::tracing::debug;
+use bevy_openxr_core::{
+    hand_tracking::HandPoseState, math::pose_to_transform, XRDevice, XRHandMesh, XRInputSources,
+};
+use openxr::HandJointLocations;
+
+use crate::hand_tracking::XRInputVisualization;
+use crate::laser_pointer::XRHand;
+
+/// Runtime provided hand meshes (`XR_FB_hand_tracking_mesh`, e.g. Quest): the shape of the user's
+/// hands as calibrated by the runtime. Meshes are fetched once hands are tracked, and stored in
+/// `XRHandMeshes` as `Mesh` assets in the bind pose, together with the skinning data.
+///
+/// With `XRHandMeshSettings::render`, a mesh entity per hand is skinned (on the CPU) to the tracked
+/// joints each frame. The entities are in the XR reference space, and follow `XRInputVisualization`
+#[derive(Default)]
+pub struct XRHandMeshPlugin;
+
+impl Plugin for XRHandMeshPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRHandMeshSettings>()
+            .init_resource::<XRHandMeshes>()
+            .add_system(hand_mesh_fetch_system.system())
+            .add_system(hand_mesh_skinning_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRHandMeshSettings {
+    /// Spawn skinned mesh entities of the hands
+    pub render: bool,
+    pub color: Color,
+}
+
+impl Default for XRHandMeshSettings {
+    fn default() -> Self {
+        XRHandMeshSettings {
+            render: true,
+            color: Color::rgb(0.8, 0.7, 0.6),
+        }
+    }
+}
+
+/// Hand mesh of one hand
+#[derive(Debug, Clone)]
+pub struct XRHandMeshAsset {
+    /// Mesh in the bind pose
+    pub mesh: Handle<Mesh>,
+
+    /// Bind pose and skinning data of the mesh
+    pub source: XRHandMesh,
+}
+
+/// Hand meshes, `None` until fetched from the runtime, or if not supported
+#[derive(Debug, Default)]
+pub struct XRHandMeshes {
+    pub left: Option<XRHandMeshAsset>,
+    pub right: Option<XRHandMeshAsset>,
+
+    /// Meshes have been requested from the runtime
+    fetched: bool,
+}
+
+impl XRHandMeshes {
+    pub fn get(&self, hand: XRHand) -> Option<&XRHandMeshAsset> {
+        match hand {
+            XRHand::Left => self.left.as_ref(),
+            XRHand::Right => self.right.as_ref(),
+        }
+    }
+}
+
+/// Hand mesh entity, skinned to the joints of `hand`
+struct SkinnedHand {
+    hand: XRHand,
+
+    /// Inverses of the joint bind poses
+    inverse_bind_poses: Vec<Mat4>,
+}
+
+/// Mesh of `hand_mesh` in the bind pose
+pub fn hand_mesh_to_mesh(hand_mesh: &XRHandMesh) -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, hand_mesh.positions.clone());
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, hand_mesh.normals.clone());
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, hand_mesh.uvs.clone());
+    mesh.set_indices(Some(Indices::U16(hand_mesh.indices.clone())));
+    mesh
+}
+
+fn hand_mesh_fetch_system(
+    mut commands: Commands,
+    settings: Res<XRHandMeshSettings>,
+    xr_device: Option<ResMut<XRDevice>>,
+    mut hand_meshes: ResMut<XRHandMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut xr_device = match xr_device {
+        Some(xr_device) if !hand_meshes.fetched && xr_device.has_hand_tracking() => xr_device,
+        _ => return,
+    };
+    hand_meshes.fetched = true;
+
+    let material = materials.add(StandardMaterial {
+        base_color: settings.color,
+        ..Default::default()
+    });
+
+    for (hand, hand_ext) in [
+        (XRHand::Left, openxr::HandEXT::LEFT),
+        (XRHand::Right, openxr::HandEXT::RIGHT),
+    ]
+    .iter()
+    {
+        let source = match xr_device.get_hand_mesh(*hand_ext) {
+            Some(source) => source,
+            None => {
+                debug!(target: "bevy_openxr::session", ?hand, "Hand mesh not available");
+                continue;
+            }
+        };
+
+        let mesh = meshes.add(hand_mesh_to_mesh(&source));
+
+        if settings.render {
+            commands
+                .spawn_bundle(PbrBundle {
+                    // skinned copy, the bind pose mesh is left as is
+                    mesh: meshes.add(hand_mesh_to_mesh(&source)),
+                    material: material.clone(),
+                    visible: Visible {
+                        is_visible: false,
+                        is_transparent: false,
+                    },
+                    ..Default::default()
+                })
+                .insert(SkinnedHand {
+                    hand: *hand,
+                    inverse_bind_poses: source
+                        .joint_bind_poses
+                        .iter()
+                        .map(|pose| pose.compute_matrix().inverse())
+                        .collect(),
+                });
+        }
+
+        let asset = Some(XRHandMeshAsset { mesh, source });
+        match hand {
+            XRHand::Left => hand_meshes.left = asset,
+            XRHand::Right => hand_meshes.right = asset,
+        }
+    }
+}
+
+fn hand_mesh_skinning_system(
+    hand_pose: Res<HandPoseState>,
+    hand_meshes: Res<XRHandMeshes>,
+    input_sources: Res<XRInputSources>,
+    input_visualization: Res<XRInputVisualization>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut hands: Query<(&SkinnedHand, &Handle<Mesh>, &mut Visible)>,
+) {
+    let (show_hands, _) = input_visualization.shown(&input_sources);
+
+    for (skinned, mesh, mut visible) in hands.iter_mut() {
+        let joints = match skinned.hand {
+            XRHand::Left => hand_pose.left.as_ref(),
+            XRHand::Right => hand_pose.right.as_ref(),
+        };
+
+        let (joints, source) = match (joints, hand_meshes.get(skinned.hand)) {
+            (Some(joints), Some(asset)) if show_hands => (joints, &asset.source),
+            _ => {
+                if visible.is_visible {
+                    visible.is_visible = false;
+                }
+                continue;
+            }
+        };
+
+        let skin = skin_matrices(joints, &skinned.inverse_bind_poses);
+        let (positions, normals) = skin_vertices(source, &skin);
+
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+            mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        }
+
+        if !visible.is_visible {
+            visible.is_visible = true;
+        }
+    }
+}
+
+/// Transforms from the bind pose to the tracked joint poses
+fn skin_matrices(joints: &HandJointLocations, inverse_bind_poses: &[Mat4]) -> Vec<Mat4> {
+    joints
+        .iter()
+        .zip(inverse_bind_poses.iter())
+        .map(|(joint, inverse_bind_pose)| {
+            pose_to_transform(&joint.pose).compute_matrix() * *inverse_bind_pose
+        })
+        .collect()
+}
+
+/// Linear blend skinning of the positions and normals of `hand_mesh`
+fn skin_vertices(hand_mesh: &XRHandMesh, skin: &[Mat4]) -> (Vec<[f32; 3]>, Vec<[f32; 3]>) {
+    hand_mesh
+        .positions
+        .iter()
+        .zip(hand_mesh.normals.iter())
+        .zip(
+            hand_mesh
+                .blend_indices
+                .iter()
+                .zip(hand_mesh.blend_weights.iter()),
+        )
+        .map(|((position, normal), (indices, weights))| {
+            let position = Vec3::from(*position);
+            let normal = Vec3::from(*normal);
+
+            let mut skinned_position = Vec3::ZERO;
+            let mut skinned_normal = Vec3::ZERO;
+            for (index, weight) in indices.iter().zip(weights.iter()) {
+                if let Some(matrix) = skin.get(*index as usize).filter(|_| *weight > 0.) {
+                    skinned_position += matrix.transform_point3(position) * *weight;
+                    skinned_normal += matrix.transform_vector3(normal) * *weight;
+                }
+            }
+
+            (
+                skinned_position.into(),
+                skinned_normal.normalize_or_zero().into(),
+            )
+        })
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skin_vertices() {
+        let hand_mesh = XRHandMesh {
+            positions: vec![[0., 0., -0.1], [0., 0., -0.2]],
+            normals: vec![[0., 1., 0.], [0., 1., 0.]],
+            blend_indices: vec![[0, 0, 0, 0], [0, 1, 0, 0]],
+            blend_weights: vec![[1., 0., 0., 0.], [0.5, 0.5, 0., 0.]],
+            ..Default::default()
+        };
+
+        // joint 1 moved up by 0.1
+        let skin = vec![
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(0., 0.1, 0.)),
+        ];
+
+        let (positions, normals) = skin_vertices(&hand_mesh, &skin);
+        assert_eq!(positions[0], [0., 0., -0.1]);
+        assert!(Vec3::from(positions[1]).abs_diff_eq(Vec3::new(0., 0.05, -0.2), 1e-6));
+        assert_eq!(normals[1], [0., 1., 0.]);
+    }
+}
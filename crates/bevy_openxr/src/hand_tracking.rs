@@ -6,7 +6,13 @@ use bevy::pbr::{prelude::*, PbrBundle};
 use bevy::prelude::Handle;
 use bevy::render::prelude::*;
 use bevy::transform::prelude::*;
-use bevy_openxr_core::{event::XRState, hand_tracking::HandPoseState};
+use bevy_openxr_core::{
+    event::XrSessionState, hand_tracking::HandPoseState, XRInputSources, XRSystem,
+};
+use openxr::HandJointLocations;
+
+use crate::hand_menu::set_visible_recursive;
+use crate::laser_pointer::XRHand;
 
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
@@ -17,24 +23,75 @@ pub struct OpenXRHandTrackingPlugin;
 impl Plugin for OpenXRHandTrackingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<HandTrackingState>()
+            .init_resource::<XRHandTrackingSettings>()
+            .init_resource::<XRInputVisualization>()
             .add_startup_system(setup.system())
             .add_system(hand_visibility_system.system())
-            .add_system(hand_system.system());
+            .add_system(hand_system.system().label(XRSystem::PoseUpdate))
+            .add_system(controller_model_system.system());
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XRHandTrackingSettings {
+    /// Spawn the joints of each hand as a hierarchy (wrist → metacarpals → phalanges, see
+    /// `HandJoint::parent`), with `Transform`s local to the parent joint. Otherwise every joint is a
+    /// root entity with its pose in the XR reference space
+    pub joint_hierarchy: bool,
+}
+
+/// Which of hands and controllers are visualized when both are connected (`XRInputSources`).
+/// Hand joint entities are hidden while hands are not shown, and `XRControllerModel` entities while
+/// controllers are not shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRInputVisualization {
+    /// Hide controller models while any hand is tracked
+    PreferHands,
+
+    /// Hide hands while any controller is connected
+    PreferControllers,
+
+    /// Show all connected input sources
+    Both,
+}
+
+impl Default for XRInputVisualization {
+    fn default() -> Self {
+        XRInputVisualization::Both
+    }
+}
+
+impl XRInputVisualization {
+    /// Whether hands and controllers are shown, `(hands, controllers)`
+    pub fn shown(&self, input_sources: &XRInputSources) -> (bool, bool) {
+        match self {
+            XRInputVisualization::PreferHands => (true, !input_sources.hands_active()),
+            XRInputVisualization::PreferControllers => (!input_sources.controllers_active(), true),
+            XRInputVisualization::Both => (true, true),
+        }
     }
 }
 
+/// Controller model provided by the app, e.g. a glTF scene. Visibility of the entity (and its
+/// children) follows the connection state of the controller and `XRInputVisualization`; the
+/// transform is left to the app
+#[derive(Debug, Clone)]
+pub struct XRControllerModel {
+    pub hand: XRHand,
+}
+
 struct LeftHand(usize);
 struct RightHand(usize);
 
 fn setup(
     mut commands: Commands,
+    settings: Res<XRHandTrackingSettings>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     // https://www.khronos.org/registry/OpenXR/specs/1.0/html/xrspec.html
     // "Conventions of hand joints"
 
-    // FIXME add parent objects
     let material_1 = materials.add(StandardMaterial {
         base_color: Color::rgb(0., 0.7, 0.),
         //unlit: true,
@@ -54,29 +111,48 @@ fn setup(
     });
 
     // left hand
-    for i in 0..openxr::HAND_JOINT_COUNT {
-        commands
-            .spawn_bundle(get_joint_box(
-                i,
-                &mut meshes,
-                &material_1,
-                &material_2,
-                &material_3,
-            ))
-            .insert(LeftHand(i));
-    }
+    let left_joints = (0..openxr::HAND_JOINT_COUNT)
+        .map(|i| {
+            commands
+                .spawn_bundle(get_joint_box(
+                    i,
+                    &mut meshes,
+                    &material_1,
+                    &material_2,
+                    &material_3,
+                ))
+                .insert(LeftHand(i))
+                .id()
+        })
+        .collect::<Vec<_>>();
 
     // right hand
-    for i in 0..openxr::HAND_JOINT_COUNT {
-        commands
-            .spawn_bundle(get_joint_box(
-                i,
-                &mut meshes,
-                &material_1,
-                &material_2,
-                &material_3,
-            ))
-            .insert(RightHand(i));
+    let right_joints = (0..openxr::HAND_JOINT_COUNT)
+        .map(|i| {
+            commands
+                .spawn_bundle(get_joint_box(
+                    i,
+                    &mut meshes,
+                    &material_1,
+                    &material_2,
+                    &material_3,
+                ))
+                .insert(RightHand(i))
+                .id()
+        })
+        .collect::<Vec<_>>();
+
+    if settings.joint_hierarchy {
+        for joints in [left_joints, right_joints].iter() {
+            for (i, entity) in joints.iter().enumerate() {
+                let joint: HandJoint = FromPrimitive::from_usize(i).unwrap();
+                if let Some(parent) = joint.parent() {
+                    commands
+                        .entity(joints[parent as usize])
+                        .push_children(&[*entity]);
+                }
+            }
+        }
     }
 }
 
@@ -132,18 +208,15 @@ pub struct HandTrackingState {
 
 fn hand_visibility_system(
     mut hand_tracking_state: ResMut<HandTrackingState>,
-    mut xr_state_events: EventReader<XRState>,
+    mut xr_state_events: EventReader<XrSessionState>,
     mut hand_boxes: QuerySet<(
         Query<(&mut Transform, &LeftHand, &mut Visible), With<LeftHand>>,
         Query<(&mut Transform, &RightHand, &mut Visible), With<RightHand>>,
     )>,
 ) {
     for state_event in xr_state_events.iter() {
-        let visible = match state_event {
-            XRState::RunningFocused => true,
-            XRState::Paused | XRState::Exiting | XRState::Running => false,
-            XRState::SkipFrame => continue,
-        };
+        // hands are tracked only while the session receives XR input
+        let visible = state_event.is_focused();
 
         // println!("Change hands visibility to {}", visible);
 
@@ -163,6 +236,9 @@ fn hand_visibility_system(
 
 fn hand_system(
     hand_pose: Res<HandPoseState>,
+    settings: Res<XRHandTrackingSettings>,
+    input_sources: Res<XRInputSources>,
+    input_visualization: Res<XRInputVisualization>,
     mut hand_tracking_state: ResMut<HandTrackingState>,
     mut hand_boxes: QuerySet<(
         Query<(&mut Transform, &LeftHand, &mut Visible), With<LeftHand>>,
@@ -173,7 +249,9 @@ fn hand_system(
         return;
     }
 
-    if let Some(left) = hand_pose.left {
+    let (show_hands, _) = input_visualization.shown(&input_sources);
+
+    if let Some(left) = hand_pose.left.filter(|_| show_hands) {
         if !hand_tracking_state.left_visible {
             for (_, _, mut visible) in hand_boxes.q0_mut().iter_mut() {
                 visible.is_visible = true;
@@ -182,10 +260,7 @@ fn hand_system(
         }
 
         for (mut hand, idx, _) in hand_boxes.q0_mut().iter_mut() {
-            let pos = &left[idx.0].pose.position;
-            let ori = &left[idx.0].pose.orientation;
-            hand.translation = Vec3::new(pos.x, pos.y, pos.z);
-            hand.rotation = Quat::from_xyzw(ori.x, ori.y, ori.z, ori.w);
+            *hand = joint_entity_transform(&left, idx.0, settings.joint_hierarchy);
 
             /*
             let flags = left[idx.0].location_flags;
@@ -209,7 +284,7 @@ fn hand_system(
         hand_tracking_state.left_visible = false;
     }
 
-    if let Some(right) = hand_pose.right {
+    if let Some(right) = hand_pose.right.filter(|_| show_hands) {
         if !hand_tracking_state.right_visible {
             for (_, _, mut visible) in hand_boxes.q1_mut().iter_mut() {
                 visible.is_visible = true;
@@ -218,10 +293,7 @@ fn hand_system(
         }
 
         for (mut hand, idx, _) in hand_boxes.q1_mut().iter_mut() {
-            let pos = &right[idx.0].pose.position;
-            let ori = &right[idx.0].pose.orientation;
-            hand.translation = Vec3::new(pos.x, pos.y, pos.z);
-            hand.rotation = Quat::from_xyzw(ori.x, ori.y, ori.z, ori.w);
+            *hand = joint_entity_transform(&right, idx.0, settings.joint_hierarchy);
         }
     } else {
         for (_, _, mut visible) in hand_boxes.q1_mut().iter_mut() {
@@ -231,9 +303,71 @@ fn hand_system(
     }
 }
 
+fn controller_model_system(
+    input_sources: Res<XRInputSources>,
+    input_visualization: Res<XRInputVisualization>,
+    models: Query<(Entity, &XRControllerModel)>,
+    children: Query<&Children>,
+    mut visibles: Query<&mut Visible>,
+) {
+    let (_, show_controllers) = input_visualization.shown(&input_sources);
+
+    for (entity, model) in models.iter() {
+        let connected = match model.hand {
+            XRHand::Left => input_sources.left_controller,
+            XRHand::Right => input_sources.right_controller,
+        };
+
+        set_visible_recursive(
+            entity,
+            show_controllers && connected,
+            &children,
+            &mut visibles,
+        );
+    }
+}
+
+/// Transform of the entity of joint `index`: the pose in the XR reference space, or relative to the
+/// parent joint in a joint hierarchy
+fn joint_entity_transform(joints: &HandJointLocations, index: usize, hierarchy: bool) -> Transform {
+    let joint: HandJoint = FromPrimitive::from_usize(index).unwrap();
+    let transform = joint_transform(joints, joint);
+
+    match joint.parent() {
+        Some(parent) if hierarchy => local_transform(&joint_transform(joints, parent), &transform),
+        _ => transform,
+    }
+}
+
+/// `child` relative to `parent`, both without scale
+fn local_transform(parent: &Transform, child: &Transform) -> Transform {
+    let inverse_rotation = parent.rotation.inverse();
+
+    Transform {
+        translation: inverse_rotation * (child.translation - parent.translation),
+        rotation: inverse_rotation * child.rotation,
+        ..Default::default()
+    }
+}
+
+/// Pose of `joint` in the XR reference space. -Z points towards the finger tips, and -Y out of the palm
+pub(crate) fn joint_transform(joints: &HandJointLocations, joint: HandJoint) -> Transform {
+    let pose = &joints[joint as usize].pose;
+    Transform {
+        translation: Vec3::new(pose.position.x, pose.position.y, pose.position.z),
+        rotation: Quat::from_xyzw(
+            pose.orientation.x,
+            pose.orientation.y,
+            pose.orientation.z,
+            pose.orientation.w,
+        ),
+        ..Default::default()
+    }
+}
+
 // https://www.khronos.org/registry/OpenXR/specs/1.0/html/xrspec.html
 // typedef enum XrHandJointEXT
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HandJoint {
     Palm = 0,
     Wrist = 1,
@@ -263,9 +397,89 @@ pub enum HandJoint {
     LittleTip = 25,
 }
 
+impl HandJoint {
+    /// Parent in the joint hierarchy: the wrist is the root, palm and metacarpals are children of the
+    /// wrist, and each phalanx is a child of the previous joint of its finger
+    pub fn parent(&self) -> Option<HandJoint> {
+        match self {
+            HandJoint::Wrist => None,
+            HandJoint::Palm
+            | HandJoint::ThumbMetacarpal
+            | HandJoint::IndexMetacarpal
+            | HandJoint::MiddleMetacarpal
+            | HandJoint::RingMetacarpal
+            | HandJoint::LittleMetacarpal => Some(HandJoint::Wrist),
+            _ => FromPrimitive::from_usize(*self as usize - 1),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    //use super::*;
+    use super::*;
+
+    #[test]
+    fn test_joint_hierarchy() {
+        assert_eq!(HandJoint::Wrist.parent(), None);
+        assert_eq!(HandJoint::Palm.parent(), Some(HandJoint::Wrist));
+        assert_eq!(HandJoint::ThumbMetacarpal.parent(), Some(HandJoint::Wrist));
+        assert_eq!(HandJoint::ThumbTip.parent(), Some(HandJoint::ThumbDistal));
+        assert_eq!(HandJoint::LittleMetacarpal.parent(), Some(HandJoint::Wrist));
+        assert_eq!(
+            HandJoint::LittleProximal.parent(),
+            Some(HandJoint::LittleMetacarpal)
+        );
+
+        let parent = Transform {
+            translation: Vec3::new(0., 1., 0.),
+            rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            ..Default::default()
+        };
+        let child = Transform {
+            translation: Vec3::new(0., 1., -1.),
+            rotation: parent.rotation,
+            ..Default::default()
+        };
+
+        let local = local_transform(&parent, &child);
+        assert!(local.translation.abs_diff_eq(Vec3::new(1., 0., 0.), 1e-5));
+        assert!(local.rotation.abs_diff_eq(Quat::IDENTITY, 1e-5));
+        assert!(parent
+            .mul_transform(local)
+            .translation
+            .abs_diff_eq(child.translation, 1e-5));
+    }
+
+    #[test]
+    fn test_input_visualization() {
+        let input_sources = XRInputSources {
+            left_hand: true,
+            right_controller: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            XRInputVisualization::PreferHands.shown(&input_sources),
+            (true, false)
+        );
+        assert_eq!(
+            XRInputVisualization::PreferControllers.shown(&input_sources),
+            (false, true)
+        );
+        assert_eq!(
+            XRInputVisualization::Both.shown(&input_sources),
+            (true, true)
+        );
+
+        // no hands tracked, controllers are shown
+        assert_eq!(
+            XRInputVisualization::PreferHands.shown(&XRInputSources {
+                right_controller: true,
+                ..Default::default()
+            }),
+            (true, true)
+        );
+    }
 
     #[test]
     fn test_a() {
@@ -0,0 +1,98 @@
+use bevy::app::{prelude::*, Events};
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::info;
+
+use crate::bindings::XRActionBindings;
+use crate::laser_pointer::XRHand;
+
+/// Left-handed mode: `XRHandedness` swaps the roles of the hands. Roles are named as for a
+/// right-handed user (e.g. the hand menu on the left hand), and resolved to the physical hand with
+/// `XRHandedness::resolve`. Applied to the default action bindings (`XRActionBindings`), the laser
+/// pointer of the dominant hand (`XRLaserPointerSettings::dominant_hand_only`) and hand menus.
+///
+/// Changing the resource sends `XRHandednessChanged`
+#[derive(Default)]
+pub struct XRHandednessPlugin;
+
+impl Plugin for XRHandednessPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Events<XRHandednessChanged>>() {
+            app.add_event::<XRHandednessChanged>();
+        }
+
+        app.init_resource::<XRHandedness>()
+            .add_system_to_stage(CoreStage::PreUpdate, handedness_system.system());
+    }
+}
+
+/// Dominant hand of the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XRHandedness {
+    Right,
+    Left,
+}
+
+impl Default for XRHandedness {
+    fn default() -> Self {
+        XRHandedness::Right
+    }
+}
+
+impl XRHandedness {
+    pub fn dominant_hand(&self) -> XRHand {
+        self.resolve(XRHand::Right)
+    }
+
+    pub fn off_hand(&self) -> XRHand {
+        self.resolve(XRHand::Left)
+    }
+
+    /// Physical hand of `role`, a hand of a right-handed user
+    pub fn resolve(&self, role: XRHand) -> XRHand {
+        match self {
+            XRHandedness::Right => role,
+            XRHandedness::Left => role.other(),
+        }
+    }
+}
+
+/// Sent when `XRHandedness` changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XRHandednessChanged {
+    pub handedness: XRHandedness,
+}
+
+fn handedness_system(
+    mut previous: Local<XRHandedness>,
+    handedness: Res<XRHandedness>,
+    bindings: Option<ResMut<XRActionBindings>>,
+    mut handedness_changes: EventWriter<XRHandednessChanged>,
+) {
+    if *handedness == *previous {
+        return;
+    }
+    *previous = *handedness;
+
+    info!(target: "bevy_openxr::session", handedness = ?*handedness, "Handedness changed");
+
+    if let Some(mut bindings) = bindings {
+        bindings.set_handedness(*handedness);
+    }
+
+    handedness_changes.send(XRHandednessChanged {
+        handedness: *handedness,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(XRHandedness::Right.dominant_hand(), XRHand::Right);
+        assert_eq!(XRHandedness::Left.dominant_hand(), XRHand::Left);
+        assert_eq!(XRHandedness::Left.off_hand(), XRHand::Right);
+        assert_eq!(XRHandedness::Left.resolve(XRHand::Left), XRHand::Right);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use bevy::app::prelude::*;
+use bevy::asset::{AssetServer, Handle};
+use bevy::core::{Time, Timer};
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::prelude::*;
+use bevy::input::{keyboard::KeyCode, Input};
+use bevy::math::Rect;
+use bevy::render::prelude::*;
+use bevy::text::{Font, Text, TextAlignment, TextStyle};
+use bevy::ui::{entity::TextBundle, PositionType, Style, Val};
+use bevy::utils::Duration;
+use bevy_openxr_core::{
+    diagnostics::XRDiagnosticsPlugin, event::XrSessionState, XRDevice, XRInputSources,
+};
+
+/// Head-locked diagnostics panel for in-headset development: frame rate, frame times, dropped
+/// frames, session state and interaction profiles. Drawn as bevy UI text, so `XRUiPlugin` and an
+/// `XRUiCameraBundle` are required. Frame times are read from `FrameTimeDiagnosticsPlugin` and
+/// `XRDiagnosticsPlugin`, if added.
+///
+/// Hidden by default. Toggled with `XRDiagnosticsHud::toggle_key`, or by sending
+/// `XRDiagnosticsHudToggle`, e.g. from a controller button chord of the app
+#[derive(Default)]
+pub struct XRDiagnosticsHudPlugin;
+
+impl Plugin for XRDiagnosticsHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRDiagnosticsHud>()
+            .add_event::<XRDiagnosticsHudToggle>()
+            .add_startup_system(setup.system())
+            .add_system(hud_toggle_system.system())
+            .add_system(hud_text_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRDiagnosticsHud {
+    pub visible: bool,
+
+    /// Key toggling `visible`
+    pub toggle_key: Option<KeyCode>,
+
+    /// Font asset path, loaded at startup
+    pub font_path: String,
+    pub font_size: f32,
+    pub color: Color,
+
+    /// Interval of text updates, so that the values stay readable
+    pub refresh_interval: Duration,
+}
+
+impl Default for XRDiagnosticsHud {
+    fn default() -> Self {
+        XRDiagnosticsHud {
+            visible: false,
+            toggle_key: Some(KeyCode::F12),
+            font_path: "fonts/FiraMono-Medium.ttf".to_string(),
+            font_size: 24.,
+            color: Color::rgb(0.9, 1., 0.9),
+            refresh_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Toggles the visibility of the diagnostics HUD
+#[derive(Debug, Clone)]
+pub struct XRDiagnosticsHudToggle;
+
+struct HudText {
+    refresh: Timer,
+}
+
+fn setup(mut commands: Commands, settings: Res<XRDiagnosticsHud>, asset_server: Res<AssetServer>) {
+    let font: Handle<Font> = asset_server.load(settings.font_path.as_str());
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Percent(5.),
+                    left: Val::Percent(5.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font,
+                    font_size: settings.font_size,
+                    color: settings.color,
+                },
+                TextAlignment::default(),
+            ),
+            visible: Visible {
+                is_visible: settings.visible,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .insert(HudText {
+            refresh: Timer::new(settings.refresh_interval, true),
+        });
+}
+
+fn hud_toggle_system(
+    keyboard: Option<Res<Input<KeyCode>>>,
+    mut toggle_events: EventReader<XRDiagnosticsHudToggle>,
+    mut settings: ResMut<XRDiagnosticsHud>,
+) {
+    let key_pressed = match (keyboard, settings.toggle_key) {
+        (Some(keyboard), Some(key)) => keyboard.just_pressed(key),
+        _ => false,
+    };
+
+    if toggle_events.iter().count() % 2 == 1 || key_pressed {
+        settings.visible = !settings.visible;
+    }
+}
+
+fn hud_text_system(
+    time: Res<Time>,
+    settings: Res<XRDiagnosticsHud>,
+    diagnostics: Option<Res<Diagnostics>>,
+    xr_device: Option<Res<XRDevice>>,
+    session_state: Option<Res<XrSessionState>>,
+    input_sources: Option<Res<XRInputSources>>,
+    mut query: Query<(&mut HudText, &mut Text, &mut Visible)>,
+) {
+    for (mut hud, mut text, mut visible) in query.iter_mut() {
+        if visible.is_visible != settings.visible {
+            visible.is_visible = settings.visible;
+        }
+
+        if !settings.visible || !hud.refresh.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let average = |id| {
+            diagnostics
+                .as_ref()
+                .and_then(|diagnostics| diagnostics.get(id))
+                .and_then(|diagnostic| diagnostic.average())
+        };
+
+        let mut value = String::new();
+        if let Some(fps) = average(FrameTimeDiagnosticsPlugin::FPS) {
+            let _ = writeln!(value, "fps      {:.1}", fps);
+        }
+        if let Some(cpu) = average(XRDiagnosticsPlugin::CPU_FRAME_TIME) {
+            let _ = writeln!(value, "cpu      {:.2} ms", cpu);
+        }
+
+        if let Some(timings) = xr_device
+            .as_ref()
+            .and_then(|xr_device| xr_device.get_frame_timings())
+        {
+            let _ = writeln!(
+                value,
+                "wait     {:.2} ms / {:.2} ms",
+                timings.frame_wait.as_secs_f64() * 1000.,
+                timings.display_period.as_secs_f64() * 1000.
+            );
+            let _ = writeln!(value, "dropped  {}", timings.dropped_frames);
+        }
+
+        if let Some(session_state) = session_state.as_ref() {
+            let _ = writeln!(value, "session  {:?}", **session_state);
+        }
+
+        if let Some(input_sources) = input_sources.as_ref() {
+            for (hand, profile) in ["left", "right"]
+                .iter()
+                .zip(input_sources.interaction_profiles.iter())
+            {
+                let profile = profile.as_deref().unwrap_or("-");
+                let _ = writeln!(value, "{:<8} {}", hand, profile);
+            }
+        }
+
+        text.sections[0].value = value;
+    }
+}
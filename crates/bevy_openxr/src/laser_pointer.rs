@@ -0,0 +1,414 @@
+use bevy::app::{prelude::*, Events};
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::prelude::*;
+use bevy::math::Vec3;
+use bevy::pbr::{prelude::*, PbrBundle};
+use bevy::render::prelude::*;
+use bevy::transform::prelude::*;
+use bevy_openxr_core::{hand_tracking::HandPoseState, XRSystem};
+
+use crate::hand_tracking::{joint_transform, HandJoint};
+use crate::handedness::XRHandedness;
+
+/// Laser pointers (line and cursor dot) along the aim pose of each hand, hit-testing entities with
+/// an `XRPointerTarget`. Hits are sent as `XRPointerEvent`s.
+///
+/// Aim poses are read from `XRAimPoses`, which is filled from hand tracking unless
+/// `XRLaserPointerSettings::aim_from_hand_tracking` is disabled (e.g. for controller poses set by the
+/// app). Like the hand visualization, pointers are placed in the XR reference space
+#[derive(Default)]
+pub struct XRLaserPointerPlugin;
+
+impl Plugin for XRLaserPointerPlugin {
+    fn build(&self, app: &mut App) {
+        // shared with XRGazePointerPlugin
+        if !app.world.contains_resource::<Events<XRPointerEvent>>() {
+            app.add_event::<XRPointerEvent>();
+        }
+
+        app.init_resource::<XRLaserPointerSettings>()
+            .init_resource::<XRAimPoses>()
+            .add_startup_system(setup.system())
+            .add_system(
+                hand_aim_system
+                    .system()
+                    .label(AIM_SYSTEM)
+                    .label(XRSystem::PoseUpdate),
+            )
+            .add_system(laser_pointer_system.system().after(AIM_SYSTEM));
+    }
+}
+
+const AIM_SYSTEM: &str = "xr_hand_aim";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XRHand {
+    Left,
+    Right,
+}
+
+impl XRHand {
+    pub fn other(&self) -> XRHand {
+        match self {
+            XRHand::Left => XRHand::Right,
+            XRHand::Right => XRHand::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRLaserPointerSettings {
+    pub enabled: bool,
+
+    /// Show only the pointer of the dominant hand (`XRHandedness`)
+    pub dominant_hand_only: bool,
+
+    /// Derive aim poses from the palm joints of hand tracking
+    pub aim_from_hand_tracking: bool,
+
+    /// Length of the line when nothing is hit
+    pub max_distance: f32,
+
+    pub line_width: f32,
+    pub cursor_radius: f32,
+    pub color: Color,
+
+    /// Alpha of the line when nothing is hit. The line is fully opaque when pointing at a target
+    pub idle_alpha: f32,
+}
+
+impl Default for XRLaserPointerSettings {
+    fn default() -> Self {
+        XRLaserPointerSettings {
+            enabled: true,
+            dominant_hand_only: false,
+            aim_from_hand_tracking: true,
+            max_distance: 5.,
+            line_width: 0.003,
+            cursor_radius: 0.01,
+            color: Color::rgb(0.9, 0.9, 1.),
+            idle_alpha: 0.3,
+        }
+    }
+}
+
+/// Aim pose of each hand, pointing along -Z. `None` if the hand is not tracked
+#[derive(Debug, Clone, Default)]
+pub struct XRAimPoses {
+    pub left: Option<Transform>,
+    pub right: Option<Transform>,
+}
+
+impl XRAimPoses {
+    pub fn get(&self, hand: XRHand) -> Option<&Transform> {
+        match hand {
+            XRHand::Left => self.left.as_ref(),
+            XRHand::Right => self.right.as_ref(),
+        }
+    }
+}
+
+/// Entity that can be pointed at, hit-tested as a sphere around its `GlobalTransform`
+#[derive(Debug, Clone)]
+pub struct XRPointerTarget {
+    pub radius: f32,
+}
+
+/// Pointer the event originates from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XRPointerSource {
+    /// Laser pointer of the hand
+    Hand(XRHand),
+
+    /// Head gaze pointer, see `XRGazePointerPlugin`
+    Head,
+}
+
+#[derive(Debug, Clone)]
+pub enum XRPointerEvent {
+    /// Pointer started pointing at the target
+    Enter {
+        source: XRPointerSource,
+        entity: Entity,
+        point: Vec3,
+    },
+    /// Pointer moved on the target
+    Move {
+        source: XRPointerSource,
+        entity: Entity,
+        point: Vec3,
+    },
+    /// Pointer stopped pointing at the target
+    Leave {
+        source: XRPointerSource,
+        entity: Entity,
+    },
+    /// Target was selected, e.g. by dwelling on it with the gaze pointer
+    Select {
+        source: XRPointerSource,
+        entity: Entity,
+        point: Vec3,
+    },
+}
+
+struct LaserPointer {
+    hand: XRHand,
+    hovered: Option<Entity>,
+}
+
+struct LaserLine;
+struct LaserCursor;
+
+fn setup(
+    mut commands: Commands,
+    settings: Res<XRLaserPointerSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let line_mesh = meshes.add(Mesh::from(shape::Cube { size: 1. }));
+    let cursor_mesh = meshes.add(Mesh::from(shape::Icosphere {
+        radius: 1.,
+        subdivisions: 2,
+    }));
+
+    for hand in [XRHand::Left, XRHand::Right].iter() {
+        let material = materials.add(StandardMaterial {
+            base_color: settings.color,
+            unlit: true,
+            ..Default::default()
+        });
+
+        let visible = Visible {
+            is_visible: false,
+            is_transparent: true,
+        };
+
+        commands
+            .spawn_bundle((
+                LaserPointer {
+                    hand: *hand,
+                    hovered: None,
+                },
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(pointer_part(&line_mesh, &material, &visible))
+                    .insert(LaserLine);
+                parent
+                    .spawn_bundle(pointer_part(&cursor_mesh, &material, &visible))
+                    .insert(LaserCursor);
+            });
+    }
+}
+
+fn pointer_part(
+    mesh: &Handle<Mesh>,
+    material: &Handle<StandardMaterial>,
+    visible: &Visible,
+) -> PbrBundle {
+    PbrBundle {
+        mesh: mesh.clone(),
+        material: material.clone(),
+        visible: visible.clone(),
+        ..Default::default()
+    }
+}
+
+fn hand_aim_system(
+    settings: Res<XRLaserPointerSettings>,
+    hand_pose: Res<HandPoseState>,
+    mut aim_poses: ResMut<XRAimPoses>,
+) {
+    if !settings.aim_from_hand_tracking {
+        return;
+    }
+
+    let palm_pose = |joints: &Option<openxr::HandJointLocations>| {
+        joints
+            .as_ref()
+            .map(|joints| joint_transform(joints, HandJoint::Palm))
+    };
+
+    // -Z of the palm joint points towards the finger tips
+    aim_poses.left = palm_pose(&hand_pose.left);
+    aim_poses.right = palm_pose(&hand_pose.right);
+}
+
+/// Closest target hit by the ray within `max_distance`, and the distance to it
+pub(crate) fn closest_target<'a>(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    targets: impl Iterator<Item = (Entity, &'a XRPointerTarget, &'a GlobalTransform)>,
+) -> Option<(Entity, f32)> {
+    targets
+        .filter_map(|(entity, target, target_transform)| {
+            ray_sphere_distance(
+                origin,
+                direction,
+                target_transform.translation,
+                target.radius,
+            )
+            .filter(|distance| *distance <= max_distance)
+            .map(|distance| (entity, distance))
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+}
+
+/// Distance along the ray to the first intersection with the sphere, if in front of the origin
+fn ray_sphere_distance(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let to_center = center - origin;
+    let projection = to_center.dot(direction);
+    let distance_squared = to_center.length_squared() - projection * projection;
+    let radius_squared = radius * radius;
+
+    if distance_squared > radius_squared {
+        return None;
+    }
+
+    let half_chord = (radius_squared - distance_squared).sqrt();
+    let distance = if projection - half_chord >= 0. {
+        projection - half_chord
+    } else {
+        projection + half_chord
+    };
+
+    if distance >= 0. {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+fn laser_pointer_system(
+    settings: Res<XRLaserPointerSettings>,
+    handedness: Option<Res<XRHandedness>>,
+    aim_poses: Res<XRAimPoses>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pointer_events: EventWriter<XRPointerEvent>,
+    mut pointers: Query<(&mut LaserPointer, &mut Transform, &Children)>,
+    mut parts: Query<
+        (
+            &mut Transform,
+            &mut Visible,
+            &Handle<StandardMaterial>,
+            Option<&LaserLine>,
+        ),
+        Without<LaserPointer>,
+    >,
+    targets: Query<(Entity, &XRPointerTarget, &GlobalTransform)>,
+) {
+    let dominant_hand = handedness.map_or(XRHand::Right, |handedness| handedness.dominant_hand());
+
+    for (mut pointer, mut transform, children) in pointers.iter_mut() {
+        let shown =
+            settings.enabled && (!settings.dominant_hand_only || pointer.hand == dominant_hand);
+
+        let aim = match aim_poses.get(pointer.hand) {
+            Some(aim) if shown => *aim,
+            _ => {
+                for child in children.iter() {
+                    if let Ok((_, mut visible, _, _)) = parts.get_mut(*child) {
+                        visible.is_visible = false;
+                    }
+                }
+
+                if let Some(entity) = pointer.hovered.take() {
+                    pointer_events.send(XRPointerEvent::Leave {
+                        source: XRPointerSource::Hand(pointer.hand),
+                        entity,
+                    });
+                }
+                continue;
+            }
+        };
+
+        *transform = aim;
+
+        let origin = aim.translation;
+        let direction = aim.rotation * -Vec3::Z;
+        let hit = closest_target(origin, direction, settings.max_distance, targets.iter());
+
+        match (pointer.hovered, hit) {
+            (Some(hovered), Some((entity, _))) if hovered == entity => (),
+            (hovered, _) => {
+                if let Some(entity) = hovered {
+                    pointer_events.send(XRPointerEvent::Leave {
+                        source: XRPointerSource::Hand(pointer.hand),
+                        entity,
+                    });
+                }
+
+                if let Some((entity, distance)) = hit {
+                    pointer_events.send(XRPointerEvent::Enter {
+                        source: XRPointerSource::Hand(pointer.hand),
+                        entity,
+                        point: origin + direction * distance,
+                    });
+                }
+            }
+        }
+
+        if let Some((entity, distance)) = hit {
+            pointer_events.send(XRPointerEvent::Move {
+                source: XRPointerSource::Hand(pointer.hand),
+                entity,
+                point: origin + direction * distance,
+            });
+        }
+        pointer.hovered = hit.map(|(entity, _)| entity);
+
+        let length = hit.map_or(settings.max_distance, |(_, distance)| distance);
+        let alpha = if hit.is_some() {
+            1.
+        } else {
+            settings.idle_alpha
+        };
+
+        for child in children.iter() {
+            let (mut part_transform, mut visible, material, line) = match parts.get_mut(*child) {
+                Ok(part) => part,
+                Err(_) => continue,
+            };
+
+            if line.is_some() {
+                visible.is_visible = true;
+                // unit cube stretched from the origin along -Z
+                part_transform.translation = Vec3::new(0., 0., -length / 2.);
+                part_transform.scale = Vec3::new(settings.line_width, settings.line_width, length);
+
+                if let Some(material) = materials.get_mut(material) {
+                    material.base_color = settings.color;
+                    material.base_color.set_a(alpha);
+                }
+            } else {
+                visible.is_visible = hit.is_some();
+                part_transform.translation = Vec3::new(0., 0., -length);
+                part_transform.scale = Vec3::splat(settings.cursor_radius);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_sphere_distance() {
+        let origin = Vec3::ZERO;
+        let direction = -Vec3::Z;
+
+        let distance = ray_sphere_distance(origin, direction, Vec3::new(0., 0., -2.), 0.5);
+        assert!((distance.unwrap() - 1.5).abs() < 1e-6);
+
+        // inside the sphere
+        let distance = ray_sphere_distance(origin, direction, Vec3::ZERO, 0.5);
+        assert!((distance.unwrap() - 0.5).abs() < 1e-6);
+
+        assert!(ray_sphere_distance(origin, direction, Vec3::new(0., 0., 2.), 0.5).is_none());
+        assert!(ray_sphere_distance(origin, direction, Vec3::new(1., 0., -2.), 0.5).is_none());
+    }
+}
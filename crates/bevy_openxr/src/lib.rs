@@ -1,51 +1,356 @@
-use bevy::app::{App, Plugin, ScheduleRunnerPlugin, ScheduleRunnerSettings};
+use bevy::app::{
+    App, CoreStage, Plugin, PluginGroup, PluginGroupBuilder, ScheduleRunnerPlugin,
+    ScheduleRunnerSettings,
+};
 use bevy::ecs::prelude::*;
 
 pub mod prelude {
     pub use crate::{
-        render_graph::camera::{camera::XRCameraBundle, projection::XRProjection},
-        HandPoseEvent, OpenXRPlugin, OpenXRSettings,
+        anchors::{XRAnchor, XRAnchorEvent, XRAnchorPlugin, XRPersistedAnchors},
+        bindings::{
+            XRActionBindings, XRBinding, XRBindingsChanged, XRBindingsPlugin, XRBindingsSettings,
+        },
+        calibration::{
+            XRCalibrated, XRCalibration, XRCalibrationPlugin, XRCalibrationRequest,
+            XRCalibrationSettings, XRRig,
+        },
+        capture::{XRCapture, XRCapturePlugin, XRFrameCaptured},
+        colocation::{
+            XRColocationEvent, XRColocationPlugin, XRColocationRequest, XRSpaceAlignment,
+        },
+        composition_layer::{XRCompositionLayer, XRCompositionLayerPlugin},
+        controller_pose::{
+            XRControllerPose, XRControllerPosePlugin, XRControllerPoseSettings,
+            XRControllerPoseState,
+        },
+        debug::{XRDebugPlugin, XRDebugSettings},
+        device_status::{
+            XRDeviceStatus, XRDeviceStatusPlugin, XRDeviceStatusSettings, XRThermalStatus,
+        },
+        dynamic_resolution::{XRDynamicResolution, XRDynamicResolutionPlugin, XRRenderScale},
+        gaze_pointer::{XRGazePointerMode, XRGazePointerPlugin, XRGazePointerSettings},
+        hand_menu::{XRHandMenu, XRHandMenuPlugin},
+        hand_mesh::{XRHandMeshAsset, XRHandMeshPlugin, XRHandMeshSettings, XRHandMeshes},
+        hand_tracking::{
+            OpenXRHandTrackingPlugin, XRControllerModel, XRHandTrackingSettings,
+            XRInputVisualization,
+        },
+        handedness::{XRHandedness, XRHandednessChanged, XRHandednessPlugin},
+        hud::{XRDiagnosticsHud, XRDiagnosticsHudPlugin, XRDiagnosticsHudToggle},
+        laser_pointer::{
+            XRAimPoses, XRHand, XRLaserPointerPlugin, XRLaserPointerSettings, XRPointerEvent,
+            XRPointerSource, XRPointerTarget,
+        },
+        passthrough::{XRPassthrough, XRPassthroughPlugin},
+        platform::{XRLoader, XRPlatformLoader, XrInstanceBuilder},
+        recording::{XRRecorder, XRRecording, XRRecordingPlugin},
+        render_graph::camera::{
+            camera::{
+                XRCameraBundle, XRCameraConfig, XRCameraTransformMode, XREye, XREyeView,
+                XREyeViews, XRViewMetrics,
+            },
+            projection::XRProjection,
+        },
+        render_graph::post_process::XRPostProcessing,
+        render_graph::secondary_views::{XRSecondaryViewCamera, XR_SECONDARY_CAMERAS},
+        render_graph::textures::{
+            XRRenderTextures, XR_PREVIOUS_FRAME_TEXTURE_HANDLE, XR_SWAPCHAIN_TEXTURE_HANDLE,
+        },
+        render_graph::view_passes::{XR_LEFT_CAMERA, XR_RIGHT_CAMERA},
+        runner::XRIdleInterval,
+        simulation::{
+            XRSimulatedControl, XRSimulatedController, XRSimulatedControllers, XRSimulatedControls,
+            XRSimulatedInput, XRSimulationPlugin, XRSimulationSettings,
+        },
+        spatial_audio::{
+            XRAudioListener, XRAudioSurface, XRRoomGeometry, XRSpatialAudio, XRSpatialAudioBackend,
+            XRSpatialAudioPlugin, XRSurfaceKind,
+        },
+        throw_assist::{XRThrowAssistPlugin, XRVelocityTracker},
+        thumbstick::{
+            XRFlickDirection, XRResponseCurve, XRThumbstick, XRThumbstickFlick, XRThumbstickPlugin,
+            XRThumbstickSettings, XRThumbstickState,
+        },
+        ui::{XRUiCamera, XRUiCameraBundle, XRUiPlugin},
+        ui_pointer::{XRUiPanel, XRUiPointerButtons, XRUiPointerPlugin},
+        visibility_mask::XRVisibilityMaskPlugin,
+        window::XRVirtualWindow,
+        HandPoseEvent, OpenXRPlugin, OpenXRPlugins, OpenXRSettings, XRDeviceOptions,
     };
 
+    #[cfg(all(target_os = "android", feature = "quest-audio"))]
+    pub use crate::platform::oculus_android::{QuestAudioDevice, QuestAudioDevices};
+    #[cfg(target_os = "android")]
+    pub use crate::platform::oculus_android::{QuestDevice, QuestPlatformConfig};
+
+    #[cfg(feature = "synthetic-hands")]
+    pub use crate::synthetic_hands::{XRSyntheticHandsPlugin, XRSyntheticHandsSettings};
+
+    pub use bevy_openxr_core::{
+        diagnostics::{XRDiagnosticsPlugin, XREventDebugPlugin},
+        event::{
+            XRExitRequest, XRFovChanged, XRFrameSkipReason, XRFrameSkipped, XRPauseChanged,
+            XRPerformanceNotification, XrFrameStatus, XrSessionState,
+        },
+        xr_session_running, XRAvailability, XRCapturedImage, XREnvironmentBlendMode, XRFrameLoop,
+        XRHandAimState, XRHandGesture, XRHandGestureEvent, XRHandJointsMotionRange, XRHandMesh,
+        XRInputSource, XRInputSourceChanged, XRInputSources, XRKeycodeMap, XRKeymap, XRLayerOrder,
+        XRLayerShape, XRPauseBehavior, XRPauseState, XRPerformanceDomain, XRPerformanceLevel,
+        XRSimulationTime, XRStage, XRStereoMode, XRSystem, XRSystemInfo, XRSystemKeyboard,
+        XRViewConfigInfo, XRViewConfigView, XrOptions, XrTime, PALM_POSE_PATH,
+    };
     pub use openxr::HandJointLocations;
 }
 
-use bevy::utils::tracing::warn;
+use bevy::utils::tracing::{debug, error, warn};
 use bevy::wgpu::{WgpuBackend, WgpuOptions};
-use bevy::window::{CreateWindow, Window, WindowId, Windows};
+use bevy_openxr_core::{OpenXRCorePlugin, XRAvailability, XRDevice, XrInstance, XrOptions};
 use openxr::HandJointLocations;
 
-mod error;
+pub mod anchors;
+#[cfg(feature = "audio")]
+mod audio;
+pub mod bindings;
+pub mod calibration;
+pub mod capture;
+pub mod colocation;
+pub mod composition_layer;
+pub mod controller_pose;
+pub mod debug;
+pub mod device_status;
+pub mod dynamic_resolution;
+pub mod error;
+pub mod gaze_pointer;
+pub mod hand_menu;
+pub mod hand_mesh;
 mod hand_tracking;
-mod platform;
+pub mod handedness;
+pub mod hud;
+pub mod laser_pointer;
+pub mod passthrough;
+pub mod platform;
 
-mod render_graph;
+pub mod recording;
+pub mod render_graph;
+pub mod runner;
+pub mod simulation;
+pub mod spatial_audio;
+pub mod synthetic_hands;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_utils;
+pub mod throw_assist;
+pub mod thumbstick;
+pub mod ui;
+pub mod ui_pointer;
+pub mod visibility_mask;
+pub mod window;
 
 pub use hand_tracking::*;
+pub use platform::XrInstanceBuilder;
 pub use render_graph::OpenXRWgpuPlugin;
 
 #[derive(Default)]
 pub struct OpenXRPlugin;
 
-#[derive(Debug)]
-pub struct OpenXRSettings {}
+/// Bevy plugins and the XR plugins, in the order XR requires: `OpenXRPlugin` creates the instance
+/// before `WgpuPlugin` creates the device through it, and `OpenXRCorePlugin` / `OpenXRWgpuPlugin`
+/// are added after `WgpuPlugin`. Use instead of `DefaultPlugins`; members can be disabled with
+/// `add_plugins_with`:
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins_with(OpenXRPlugins, |group| group.disable::<OpenXRHandTrackingPlugin>())
+///     .run();
+/// ```
+pub struct OpenXRPlugins;
+
+impl PluginGroup for OpenXRPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group.add(bevy::log::LogPlugin::default());
+        group.add(OpenXRPlugin);
+        group.add(bevy::core::CorePlugin::default());
+        group.add(bevy::transform::TransformPlugin::default());
+        group.add(bevy::diagnostic::DiagnosticsPlugin::default());
+        group.add(bevy::input::InputPlugin::default());
+        group.add(bevy::window::WindowPlugin::default());
+        group.add(bevy::asset::AssetPlugin::default());
+        group.add(bevy::scene::ScenePlugin::default());
+        group.add(bevy::render::RenderPlugin::default());
+        group.add(bevy::pbr::PbrPlugin::default());
+        group.add(bevy::wgpu::WgpuPlugin::default());
+        group.add(OpenXRCorePlugin);
+        group.add(OpenXRWgpuPlugin);
+        group.add(OpenXRHandTrackingPlugin);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenXRSettings {
+    /// Drive the app with `ScheduleRunnerPlugin`, throttled to `XRIdleInterval` while the session is
+    /// not running. Disable if the app provides its own runner, e.g. `WinitPlugin` for a desktop
+    /// window next to the XR session
+    pub use_schedule_runner: bool,
+
+    /// Application name, reported to the runtime (shown e.g. in runtime overlays)
+    pub application_name: String,
+
+    /// Application version, reported to the runtime
+    pub application_version: u32,
+
+    /// OpenXR API layers to enable, e.g. `XR_APILAYER_LUNARG_core_validation`. Also read from
+    /// `BEVY_OPENXR_API_LAYERS` (separated by `:`)
+    pub api_layers: Vec<String>,
+
+    /// Log runtime messages through `XR_EXT_debug_utils`
+    pub debug_utils: bool,
+
+    /// Options for the Vulkan device created through OpenXR
+    pub device: XRDeviceOptions,
+
+    /// Continue as a regular (non-XR) bevy app if OpenXR can't be initialized, e.g. no runtime is
+    /// installed, instead of exiting. `XRAvailability` tells which mode the app runs in. The app
+    /// provides the window (`WinitPlugin`) and a regular camera, and plugins using `XRDevice`
+    /// directly (e.g. `XRCapturePlugin`) must only be added when XR is available
+    pub desktop_fallback: bool,
+}
 
 impl Default for OpenXRSettings {
     fn default() -> Self {
-        OpenXRSettings {}
+        OpenXRSettings {
+            use_schedule_runner: true,
+            application_name: platform::DEFAULT_APPLICATION_NAME.to_string(),
+            application_version: 1,
+            api_layers: std::env::var("BEVY_OPENXR_API_LAYERS")
+                .map(|layers| {
+                    layers
+                        .split(':')
+                        .filter(|layer| !layer.is_empty())
+                        .map(|layer| layer.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            debug_utils: cfg!(debug_assertions),
+            device: XRDeviceOptions::default(),
+            desktop_fallback: false,
+        }
+    }
+}
+
+/// Vulkan device creation options, passed to `wgpu_openxr` at initialization
+#[derive(Debug, Clone)]
+pub struct XRDeviceOptions {
+    /// Enable `VK_LAYER_KHRONOS_validation`. Requires Vulkan SDK (or the validation layer) to be installed
+    pub vulkan_validation: bool,
+
+    /// Device extensions that must be supported, initialization fails otherwise
+    pub required_device_extensions: Vec<&'static str>,
+
+    /// Device extensions that are enabled if supported
+    pub optional_device_extensions: Vec<&'static str>,
+
+    /// Additional wgpu features to request from the device
+    pub features: wgpu::Features,
+}
+
+impl Default for XRDeviceOptions {
+    fn default() -> Self {
+        XRDeviceOptions {
+            vulkan_validation: cfg!(debug_assertions)
+                && std::env::var("BEVY_OPENXR_VALIDATION").is_ok(),
+            required_device_extensions: Vec::new(),
+            optional_device_extensions: Vec::new(),
+            features: wgpu::Features::empty(),
+        }
     }
 }
 
 impl Plugin for OpenXRPlugin {
     fn build(&self, app: &mut App) {
-        {
-            let settings = app.world.insert_resource(OpenXRSettings::default());
+        let settings = app
+            .world
+            .get_resource_or_insert_with(OpenXRSettings::default)
+            .clone();
 
-            println!("Settings: {:?}", settings);
-        };
+        debug!(target: "bevy_openxr", "Settings: {:?}", settings);
+
+        #[cfg(target_os = "android")]
+        platform::oculus_android::configure_platform(app);
+
+        #[cfg(feature = "audio")]
+        audio::configure_audio(app);
 
         // must be initialized at startup, so that bevy_wgpu has access
-        platform::initialize_openxr();
+        if !app.world.contains_resource::<XrInstance>() {
+            let headless = app
+                .world
+                .get_resource::<XrOptions>()
+                .map_or(false, |options| options.headless);
+            let loader = app.world.remove_resource::<platform::XRLoader>();
+            let builder = app
+                .world
+                .remove_resource::<XrInstanceBuilder>()
+                .unwrap_or_else(|| {
+                    let builder = settings.api_layers.iter().fold(
+                        XrInstanceBuilder::new()
+                            .with_application(
+                                &settings.application_name,
+                                settings.application_version,
+                            )
+                            .with_debug_utils(settings.debug_utils)
+                            .with_headless(headless)
+                            .with_device_options(settings.device.clone()),
+                        |builder, layer| builder.with_api_layer(layer),
+                    );
+
+                    match loader {
+                        Some(loader) => builder.with_boxed_loader(loader.0),
+                        None => builder,
+                    }
+                });
+
+            let xr_instance = match builder.build() {
+                Ok(xr_instance) => xr_instance,
+                Err(e) if settings.desktop_fallback => {
+                    let reason = match e {
+                        error::Error::XRLoad(_) => "OpenXR loader not found".to_string(),
+                        e => format!("{:?}", e),
+                    };
+                    fall_back_to_desktop(app, reason);
+                    return;
+                }
+                Err(error::Error::XRLoad(_)) => {
+                    error!(target: "bevy_openxr::platform", "Could not load openxr loader. Make sure that you have openxr_loader.dll (Windows), libopenxr_loader.dylib (MacOS) or libopenxr_loader.so (Linux) in the library load path");
+                    std::process::exit(255);
+                }
+                Err(e) => panic!("Could not initialize OpenXR instance: {:?}", e),
+            };
+
+            app.world.insert_resource(xr_instance);
+        }
+
+        // the session is created here (and not in `OpenXRCorePlugin`), so that a failing session
+        // can still fall back to desktop
+        if !app.world.contains_resource::<XRDevice>() {
+            let options = app
+                .world
+                .get_resource::<XrOptions>()
+                .cloned()
+                .unwrap_or_default();
+            let xr_instance = app.world.remove_resource::<XrInstance>().unwrap();
+
+            match xr_instance.into_device_with_options(options) {
+                Ok(xr_device) => app.world.insert_resource(xr_device),
+                Err(e) if settings.desktop_fallback => {
+                    fall_back_to_desktop(app, e.to_string());
+                    return;
+                }
+                Err(e) => panic!("Could not create OpenXR session: {}", e),
+            }
+        }
+
+        app.insert_resource(XRAvailability::Available);
 
         let mut wgpu_options = app
             .world
@@ -57,18 +362,33 @@ impl Plugin for OpenXRPlugin {
         wgpu_options.backend = WgpuBackend::Vulkan;
         warn!("Set WgpuBackend to WgpuBackend::Vulkan (only one supported for OpenXR currently)");
 
-        app
-            // FIXME should handposeevent be conditional based on options
-            .insert_resource(wgpu_options)
-            .insert_resource(ScheduleRunnerSettings::run_loop(
+        if settings.use_schedule_runner {
+            // frame pacing is done by xrWaitFrame at XRStage::FrameLoop, so run the loop without
+            // waiting, and throttle it while idle
+            app.insert_resource(ScheduleRunnerSettings::run_loop(
                 std::time::Duration::from_micros(0),
             ))
             .add_plugin(ScheduleRunnerPlugin::default())
+            .init_resource::<runner::XRIdleInterval>()
+            .add_system_to_stage(CoreStage::Last, runner::idle_throttle_system.system());
+        }
+
+        app
+            // FIXME should handposeevent be conditional based on options
+            .insert_resource(wgpu_options)
             .add_event::<HandPoseEvent>()
-            .add_system(handle_create_window_events.system());
+            .add_plugin(window::XRVirtualWindowPlugin);
     }
 }
 
+/// Continues as a regular bevy app, see `OpenXRSettings::desktop_fallback`
+fn fall_back_to_desktop(app: &mut App, reason: String) {
+    warn!(target: "bevy_openxr::platform", %reason, "OpenXR unavailable, continuing without XR");
+
+    app.insert_resource(XRAvailability::Unavailable(reason))
+        .add_event::<HandPoseEvent>();
+}
+
 pub struct HandPoseEvent {
     pub left: Option<HandJointLocations>,
     pub right: Option<HandJointLocations>,
@@ -84,28 +404,3 @@ impl std::fmt::Debug for HandPoseEvent {
         )
     }
 }
-
-fn handle_create_window_events(
-    mut windows: ResMut<Windows>,
-    mut create_window_events: EventReader<CreateWindow>,
-    // mut window_created_events: EventWriter<WindowCreated>,
-) {
-    for _create_window_event in create_window_events.iter() {
-        if let None = windows.get_primary() {
-            windows.add(Window::new(
-                WindowId::primary(),
-                &Default::default(),
-                896,
-                1008,
-                1.,
-                None,
-            ));
-        }
-
-        /*
-        window_created_events.send(WindowCreated {
-            id: create_window_event.id,
-        });
-         */
-    }
-}
@@ -0,0 +1,68 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::render::pass::ClearColor;
+use bevy::utils::tracing::warn;
+use bevy_openxr_core::XRDevice;
+
+/// Composites the camera passthrough (`XR_FB_passthrough`, e.g. Quest) under the rendered scene.
+/// Toggled and adjusted through the `XRPassthrough` resource.
+///
+/// While passthrough is enabled, `ClearColor` alpha is set to zero so that the cleared areas show the passthrough.
+/// The alpha stays zero after disabling if the environment blend mode is see-through (`XREnvironmentBlendMode`)
+#[derive(Default)]
+pub struct XRPassthroughPlugin;
+
+impl Plugin for XRPassthroughPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRPassthrough>()
+            .add_system_to_stage(CoreStage::PostUpdate, passthrough_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRPassthrough {
+    /// Show passthrough under the rendered scene
+    pub enabled: bool,
+
+    /// Opacity of the passthrough image, in range 0..=1
+    pub opacity: f32,
+}
+
+impl Default for XRPassthrough {
+    fn default() -> Self {
+        XRPassthrough {
+            enabled: true,
+            opacity: 1.,
+        }
+    }
+}
+
+fn passthrough_system(
+    mut passthrough: ResMut<XRPassthrough>,
+    mut xr_device: ResMut<XRDevice>,
+    mut clear_color: ResMut<ClearColor>,
+    mut opaque_alpha: Local<Option<f32>>,
+) {
+    if !passthrough.is_changed() {
+        return;
+    }
+
+    if !xr_device.set_passthrough(passthrough.enabled, passthrough.opacity) {
+        warn!(target: "bevy_openxr::session", "Passthrough not supported by the runtime, disabling");
+        passthrough.enabled = false;
+    }
+
+    match passthrough.enabled {
+        true => {
+            if opaque_alpha.is_none() {
+                *opaque_alpha = Some(clear_color.0.a());
+            }
+            clear_color.0.set_a(0.);
+        }
+        false => {
+            if let Some(alpha) = opaque_alpha.take() {
+                clear_color.0.set_a(alpha);
+            }
+        }
+    }
+}
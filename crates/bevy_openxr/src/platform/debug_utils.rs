@@ -0,0 +1,79 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+
+use bevy::utils::tracing::{debug, error, info, warn};
+use openxr::sys;
+
+use crate::error::Error;
+
+/// Registers a `XR_EXT_debug_utils` messenger logging runtime and API layer messages. Returns
+/// `Ok(false)` if the extension is not enabled. The messenger is destroyed with the instance
+pub(crate) fn create_messenger(instance: &openxr::Instance) -> Result<bool, Error> {
+    let fp = match instance.exts().ext_debug_utils.as_ref() {
+        Some(fp) => fp,
+        None => return Ok(false),
+    };
+
+    let create_info = sys::DebugUtilsMessengerCreateInfoEXT {
+        ty: sys::DebugUtilsMessengerCreateInfoEXT::TYPE,
+        next: std::ptr::null(),
+        message_severities: sys::DebugUtilsMessageSeverityFlagsEXT::VERBOSE
+            | sys::DebugUtilsMessageSeverityFlagsEXT::INFO
+            | sys::DebugUtilsMessageSeverityFlagsEXT::WARNING
+            | sys::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+        message_types: sys::DebugUtilsMessageTypeFlagsEXT::GENERAL
+            | sys::DebugUtilsMessageTypeFlagsEXT::VALIDATION
+            | sys::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE
+            | sys::DebugUtilsMessageTypeFlagsEXT::CONFORMANCE,
+        user_callback: Some(debug_utils_callback),
+        user_data: std::ptr::null_mut(),
+    };
+
+    let mut messenger = sys::DebugUtilsMessengerEXT::NULL;
+    let ret = unsafe {
+        (fp.create_debug_utils_messenger)(instance.as_raw(), &create_info, &mut messenger)
+    };
+
+    if ret.into_raw() < 0 {
+        return Err(Error::XR(ret));
+    }
+
+    Ok(true)
+}
+
+unsafe fn to_str<'a>(ptr: *const c_char) -> &'a str {
+    if ptr.is_null() {
+        return "";
+    }
+
+    CStr::from_ptr(ptr).to_str().unwrap_or("<invalid utf-8>")
+}
+
+unsafe extern "system" fn debug_utils_callback(
+    severity: sys::DebugUtilsMessageSeverityFlagsEXT,
+    types: sys::DebugUtilsMessageTypeFlagsEXT,
+    data: *const sys::DebugUtilsMessengerCallbackDataEXT,
+    _user_data: *mut c_void,
+) -> sys::Bool32 {
+    if data.is_null() {
+        return sys::FALSE;
+    }
+
+    let data = &*data;
+    let message_id = to_str(data.message_id);
+    let function = to_str(data.function_name);
+    let message = to_str(data.message);
+
+    if severity.contains(sys::DebugUtilsMessageSeverityFlagsEXT::ERROR) {
+        error!(target: "bevy_openxr::debug_utils", ?types, message_id, function, "{}", message);
+    } else if severity.contains(sys::DebugUtilsMessageSeverityFlagsEXT::WARNING) {
+        warn!(target: "bevy_openxr::debug_utils", ?types, message_id, function, "{}", message);
+    } else if severity.contains(sys::DebugUtilsMessageSeverityFlagsEXT::INFO) {
+        info!(target: "bevy_openxr::debug_utils", ?types, message_id, function, "{}", message);
+    } else {
+        debug!(target: "bevy_openxr::debug_utils", ?types, message_id, function, "{}", message);
+    }
+
+    // do not abort the call that triggered the message
+    sys::FALSE
+}
@@ -1,66 +1,286 @@
-use crate::error::Error;
-use bevy_openxr_core::{set_xr_instance, XrInstance};
-use openxr::{ExtensionSet, Instance};
+use crate::{device_status::XRThermalStatus, error::Error, XRDeviceOptions};
+use bevy::utils::tracing::{info, warn};
+use bevy_openxr_core::{XrError, XrInstance};
+use openxr::{Entry, ExtensionSet, Instance};
+
+mod debug_utils;
 
 // Platform-specific loaders
 #[cfg(target_os = "android")] // FIXME change only for oculus instead of android
 pub mod oculus_android;
 
-// Loader trait, can be overridden
-pub(crate) trait OpenXRInstance {
-    fn load_bevy_openxr() -> Result<openxr::Entry, Error> {
-        panic!("OpenXRInstance::load_bevy_openxr unimplemented for this platform");
+/// Platform loader: loads the OpenXR loader library and creates the instance. Runtimes with their
+/// own loader initialization (e.g. Android runtimes other than Oculus) are supported by implementing
+/// this, and setting it with `XrInstanceBuilder::with_loader`, or inserting it as an `XRLoader`
+/// resource before `OpenXRPlugin` is added
+pub trait XRPlatformLoader: Send + Sync + 'static {
+    /// Loads the loader library, and initializes it if the platform requires
+    fn load(&self) -> Result<Entry, Error>;
+
+    /// Creates the instance. `extensions` are the extensions supported by the runtime, as modified
+    /// by the builder. Defaults to creating the instance with the application info, extensions and
+    /// API layers of `builder`
+    fn instantiate(
+        &self,
+        entry: &Entry,
+        builder: &XrInstanceBuilder,
+        extensions: &mut ExtensionSet,
+    ) -> Result<Instance, Error> {
+        let xr_instance = entry.create_instance(
+            &builder.application_info(),
+            &extensions,
+            None,
+            &builder.api_layers(),
+        )?;
+
+        Ok(xr_instance)
     }
+}
 
-    fn instantiate(&mut self, _extensions: &mut ExtensionSet) -> Result<Instance, Error> {
-        panic!("OpenXRInstance::instantiate unimplemented for this platform");
+/// Platform loader set by the app or a platform crate, used by `OpenXRPlugin` when building the
+/// instance from `OpenXRSettings`
+pub struct XRLoader(pub Box<dyn XRPlatformLoader>);
+
+impl XRLoader {
+    pub fn new(loader: impl XRPlatformLoader) -> Self {
+        XRLoader(Box::new(loader))
     }
 }
 
-// Default
-#[cfg(not(target_os = "android"))] // FIXME use platform_oculus_android?
-impl OpenXRInstance for openxr::Entry {
-    fn load_bevy_openxr() -> Result<openxr::Entry, Error> {
-        // FIXME: use ::load by default, path from config?
-        Ok(openxr::Entry::load()?)
+/// Loader from the library load path (`openxr_loader.dll`, `libopenxr_loader.so`, ...)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DesktopLoader;
+
+impl XRPlatformLoader for DesktopLoader {
+    fn load(&self) -> Result<Entry, Error> {
+        Ok(Entry::load()?)
     }
+}
+
+/// Loader of the platform the crate is built for
+#[cfg(not(target_os = "android"))]
+pub type DefaultPlatformLoader = DesktopLoader;
 
-    fn instantiate(&mut self, extensions: &mut ExtensionSet) -> Result<Instance, Error> {
-        let app_info = &openxr::ApplicationInfo {
-            application_name: "hello openxr",
-            engine_name: "bevy",
-            application_version: 1, // FIXME allow user to submit application version?
-            engine_version: 1,      // FIXME pull bevy version from somewhere?
-        };
+/// Loader of the platform the crate is built for
+#[cfg(target_os = "android")] // FIXME change only for oculus instead of android
+pub type DefaultPlatformLoader = oculus_android::OculusAndroidLoader;
 
-        let xr_instance = self
-            .create_instance(app_info, &extensions, None, &[])
-            .unwrap();
+/// Battery and thermal status of the platform, polled by `XRDeviceStatusPlugin`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct PowerStatus {
+    pub battery_level: Option<f32>,
+    pub charging: Option<bool>,
+    pub thermal_status: Option<XRThermalStatus>,
+}
 
-        Ok(xr_instance)
+#[cfg(not(target_os = "android"))]
+pub(crate) fn power_status() -> Result<PowerStatus, Error> {
+    Ok(PowerStatus::default())
+}
+
+#[cfg(target_os = "android")]
+pub(crate) use oculus_android::power_status;
+
+type ExtensionsFn = Box<dyn Fn(&mut ExtensionSet) + Send + Sync>;
+
+pub(crate) const DEFAULT_APPLICATION_NAME: &str = "bevy";
+
+/// Engine reported to the runtime
+const ENGINE_NAME: &str = "bevy";
+
+/// Version of the bevy dependency, `(major, minor, patch)`. Keep in sync with `Cargo.toml`, as
+/// bevy does not export its version
+const BEVY_VERSION: (u32, u32, u32) = (0, 5, 0);
+
+/// Engine version reported to the runtime, packed as `major << 22 | minor << 12 | patch` (as Vulkan does)
+pub(crate) fn engine_version() -> u32 {
+    let (major, minor, patch) = BEVY_VERSION;
+    (major << 22) | (minor << 12) | patch
+}
+
+/// Constructs the OpenXR instance and the wgpu device for it.
+///
+/// `OpenXRPlugin` builds the instance from `OpenXRSettings` (with the `XRLoader` resource, if
+/// inserted), unless an `XrInstanceBuilder` resource has been inserted - or an `XrInstance` resource,
+/// if the app constructs the instance itself.
+/// `OpenXRPlugin` converts the `XrInstance` resource into `XRDevice`, creating the session
+pub struct XrInstanceBuilder {
+    application_name: String,
+    application_version: u32,
+    api_layers: Vec<String>,
+    debug_utils: bool,
+    headless: bool,
+    extensions: Vec<ExtensionsFn>,
+    device: XRDeviceOptions,
+    loader: Box<dyn XRPlatformLoader>,
+}
+
+impl Default for XrInstanceBuilder {
+    fn default() -> Self {
+        XrInstanceBuilder {
+            application_name: DEFAULT_APPLICATION_NAME.to_string(),
+            application_version: 1,
+            api_layers: Vec::new(),
+            debug_utils: false,
+            headless: false,
+            extensions: Vec::new(),
+            device: XRDeviceOptions::default(),
+            loader: Box::new(DefaultPlatformLoader::default()),
+        }
     }
 }
 
-pub(crate) fn initialize_openxr() {
-    let mut entry = match openxr::Entry::load_bevy_openxr() {
-        Ok(entry) => entry,
-        Err(_) => {
-            println!("Could not load openxr loader. Make sure that you have openxr_loader.dll (Windows), libopenxr_loader.dylib (MacOS) or libopenxr_loader.so (Linux) in the library load path");
-            std::process::exit(255);
+impl XrInstanceBuilder {
+    pub fn new() -> Self {
+        XrInstanceBuilder::default()
+    }
+
+    pub fn with_application(mut self, name: &str, version: u32) -> Self {
+        self.application_name = name.to_string();
+        self.application_version = version;
+        self
+    }
+
+    /// Enable an OpenXR API layer, e.g. `XR_APILAYER_LUNARG_core_validation`. Layers not installed
+    /// are skipped with a warning
+    pub fn with_api_layer(mut self, layer: &str) -> Self {
+        self.api_layers.push(layer.to_string());
+        self
+    }
+
+    /// Log runtime and API layer messages (`XR_EXT_debug_utils`) to target `bevy_openxr::debug_utils`,
+    /// if the runtime supports the extension
+    pub fn with_debug_utils(mut self, enabled: bool) -> Self {
+        self.debug_utils = enabled;
+        self
+    }
+
+    /// Enable `XR_MND_headless` if the runtime supports it, for sessions without rendering
+    /// (`XrOptions::headless`)
+    pub fn with_headless(mut self, enabled: bool) -> Self {
+        self.headless = enabled;
+        self
+    }
+
+    /// Modify the extension set before creating the instance. The set initially contains
+    /// all extensions supported by the runtime
+    pub fn with_extensions(
+        mut self,
+        extensions: impl Fn(&mut ExtensionSet) + Send + Sync + 'static,
+    ) -> Self {
+        self.extensions.push(Box::new(extensions));
+        self
+    }
+
+    pub fn with_device_options(mut self, device: XRDeviceOptions) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Loads and instantiates with `loader` instead of `DefaultPlatformLoader`
+    pub fn with_loader(self, loader: impl XRPlatformLoader) -> Self {
+        self.with_boxed_loader(Box::new(loader))
+    }
+
+    pub fn with_boxed_loader(mut self, loader: Box<dyn XRPlatformLoader>) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    pub fn application_info(&self) -> openxr::ApplicationInfo<'_> {
+        openxr::ApplicationInfo {
+            application_name: &self.application_name,
+            engine_name: ENGINE_NAME,
+            application_version: self.application_version,
+            engine_version: engine_version(),
         }
-    };
-    let mut extensions = entry.enumerate_extensions().unwrap();
+    }
 
-    // because of https://gitlab.freedesktop.org/monado/monado/-/issues/98
-    extensions.mnd_headless = false;
+    pub fn api_layers(&self) -> Vec<&str> {
+        self.api_layers.iter().map(|layer| layer.as_str()).collect()
+    }
+
+    pub fn build(mut self) -> Result<XrInstance, Error> {
+        let entry = self.loader.load()?;
+        let mut extensions = entry.enumerate_extensions()?;
 
-    let instance = entry.instantiate(&mut extensions).unwrap();
-    let wgpu_openxr = wgpu::wgpu_openxr::new(
-        wgpu::BackendBit::VULKAN,
-        &instance,
-        wgpu::wgpu_openxr::OpenXROptions::default(),
-    )
-    .unwrap();
+        if !self.api_layers.is_empty() {
+            let available = entry.enumerate_layers()?;
+            self.api_layers.retain(|layer| {
+                let found = available.iter().any(|props| &props.layer_name == layer);
+                if !found {
+                    warn!(target: "bevy_openxr::platform", layer = %layer, "OpenXR API layer not found, skipping");
+                }
+                found
+            });
+            info!(target: "bevy_openxr::platform", layers = ?self.api_layers, "Enabling OpenXR API layers");
+        }
+
+        let debug_utils = self.debug_utils && extensions.ext_debug_utils;
+        if self.debug_utils && !debug_utils {
+            warn!(target: "bevy_openxr::platform", "XR_EXT_debug_utils not supported by the runtime");
+        }
+        extensions.ext_debug_utils = debug_utils;
+
+        // only when requested, because of https://gitlab.freedesktop.org/monado/monado/-/issues/98
+        let headless = self.headless && extensions.mnd_headless;
+        if self.headless && !headless {
+            warn!(target: "bevy_openxr::platform", "XR_MND_headless not supported by the runtime");
+        }
+        extensions.mnd_headless = headless;
+
+        for modify_extensions in self.extensions.iter() {
+            modify_extensions(&mut extensions);
+        }
+
+        let instance = self.loader.instantiate(&entry, &self, &mut extensions)?;
+
+        if debug_utils {
+            debug_utils::create_messenger(&instance)?;
+        }
+
+        if self.device.vulkan_validation {
+            enable_vulkan_validation();
+        }
+
+        let wgpu_openxr = wgpu::wgpu_openxr::new(
+            wgpu::BackendBit::VULKAN,
+            &instance,
+            wgpu_openxr_options(&self.device),
+        )
+        .map_err(|error| XrError::Graphics(format!("{:?}", error)))?;
+
+        Ok(XrInstance::new(wgpu_openxr, instance))
+    }
+}
+
+const VULKAN_VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+fn wgpu_openxr_options(options: &XRDeviceOptions) -> wgpu::wgpu_openxr::OpenXROptions {
+    wgpu::wgpu_openxr::OpenXROptions {
+        required_device_extensions: options.required_device_extensions.clone(),
+        optional_device_extensions: options.optional_device_extensions.clone(),
+        features: options.features,
+        ..Default::default()
+    }
+}
+
+/// Vulkan instance is created by the OpenXR runtime (`xrCreateVulkanInstanceKHR`), so layers are
+/// enabled through the Vulkan loader environment variable, which is honored for runtime-created instances too
+fn enable_vulkan_validation() {
+    let layers = std::env::var("VK_INSTANCE_LAYERS").unwrap_or_default();
+    if layers
+        .split(':')
+        .any(|layer| layer == VULKAN_VALIDATION_LAYER)
+    {
+        return;
+    }
+
+    let layers = match layers.is_empty() {
+        true => VULKAN_VALIDATION_LAYER.to_string(),
+        false => format!("{}:{}", layers, VULKAN_VALIDATION_LAYER),
+    };
 
-    set_xr_instance(XrInstance::new(wgpu_openxr, instance));
+    info!(target: "bevy_openxr::platform", layers = %layers, "Enabling Vulkan validation layers");
+    std::env::set_var("VK_INSTANCE_LAYERS", layers);
 }
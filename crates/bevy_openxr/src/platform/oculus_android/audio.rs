@@ -0,0 +1,177 @@
+use bevy::app::App;
+use bevy::utils::tracing::{info, warn};
+use jni::objects::{JObject, JValue};
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+use crate::error::Error;
+
+// android.media.AudioDeviceInfo types
+const TYPE_BUILTIN_SPEAKER: i32 = 2;
+const TYPE_WIRED_HEADSET: i32 = 3;
+const TYPE_WIRED_HEADPHONES: i32 = 4;
+const TYPE_BLUETOOTH_A2DP: i32 = 8;
+const TYPE_BUILTIN_MIC: i32 = 15;
+const TYPE_USB_HEADSET: i32 = 22;
+const TYPE_BLE_HEADSET: i32 = 26;
+
+// AudioManager.GET_DEVICES_INPUTS, AudioManager.GET_DEVICES_OUTPUTS
+const GET_DEVICES_INPUTS: i32 = 1;
+const GET_DEVICES_OUTPUTS: i32 = 2;
+
+/// Output device types in the order of preference: headphones connected to the headset, then its
+/// speakers. Other sinks (USB devices, remote submix, telephony...) are never selected, as these are
+/// the ones attached by developer tools
+const OUTPUT_PREFERENCE: &[i32] = &[
+    TYPE_WIRED_HEADSET,
+    TYPE_WIRED_HEADPHONES,
+    TYPE_USB_HEADSET,
+    TYPE_BLUETOOTH_A2DP,
+    TYPE_BLE_HEADSET,
+    TYPE_BUILTIN_SPEAKER,
+];
+
+const INPUT_PREFERENCE: &[i32] = &[TYPE_WIRED_HEADSET, TYPE_USB_HEADSET, TYPE_BUILTIN_MIC];
+
+/// Audio device of the headset, from `AudioManager.getDevices`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestAudioDevice {
+    /// Device id, for `AAudioStreamBuilder_setDeviceId` (or `oboe::AudioStreamBuilder::set_device_id`)
+    pub id: i32,
+
+    /// `AudioDeviceInfo` type, e.g. `2` for the built-in speaker
+    pub device_type: i32,
+
+    pub product_name: String,
+
+    /// Position in `AudioManager.getDevices`, which cpal enumerates in the same order
+    position: usize,
+
+    input: bool,
+}
+
+impl QuestAudioDevice {
+    /// The cpal device, e.g. for opening a rodio `OutputStream` (`OutputStream::try_from_device`)
+    /// or a cpal input stream on it
+    pub fn cpal_device(&self) -> Option<rodio::cpal::Device> {
+        let host = rodio::cpal::default_host();
+        let device = match self.input {
+            true => host.input_devices().ok()?.nth(self.position)?,
+            false => host.output_devices().ok()?.nth(self.position)?,
+        };
+
+        // the order was not kept, e.g. devices were connected in between
+        match device.name() {
+            Ok(name) if name == self.product_name => Some(device),
+            _ => None,
+        }
+    }
+}
+
+/// Headset audio devices, selected when `OpenXRPlugin` is added. AAudio streams opened without a
+/// device id use the default device, which may be a sink attached by developer tools. Sounds played
+/// through bevy `Audio` go to `output`, and audio engines pass `output` / `input` ids to the stream
+/// builder (or open the cpal device, `QuestAudioDevice::cpal_device`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuestAudioDevices {
+    pub output: Option<QuestAudioDevice>,
+    pub input: Option<QuestAudioDevice>,
+}
+
+impl QuestAudioDevices {
+    /// Enumerates the audio devices and selects the headset ones, e.g. again after headphones have
+    /// been connected
+    pub fn select() -> Result<Self, Error> {
+        Ok(QuestAudioDevices {
+            output: select_device(enumerate_devices(GET_DEVICES_OUTPUTS)?, OUTPUT_PREFERENCE),
+            input: select_device(enumerate_devices(GET_DEVICES_INPUTS)?, INPUT_PREFERENCE),
+        })
+    }
+
+    pub fn output_id(&self) -> Option<i32> {
+        self.output.as_ref().map(|device| device.id)
+    }
+
+    pub fn input_id(&self) -> Option<i32> {
+        self.input.as_ref().map(|device| device.id)
+    }
+}
+
+pub(crate) fn configure_audio(app: &mut App) {
+    match QuestAudioDevices::select() {
+        Ok(devices) => {
+            info!(target: "bevy_openxr::platform", output = ?devices.output, input = ?devices.input, "Selected audio devices");
+            app.insert_resource(devices);
+        }
+        Err(e) => {
+            warn!(target: "bevy_openxr::platform", error = ?e, "Could not enumerate audio devices");
+            app.insert_resource(QuestAudioDevices::default());
+        }
+    }
+}
+
+fn select_device(devices: Vec<QuestAudioDevice>, preference: &[i32]) -> Option<QuestAudioDevice> {
+    devices
+        .into_iter()
+        .filter_map(|device| {
+            preference
+                .iter()
+                .position(|device_type| *device_type == device.device_type)
+                .map(|rank| (rank, device))
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, device)| device)
+}
+
+/// Audio devices of the `flags` direction. The NDK has no device enumeration for AAudio, the ids
+/// come from the Java `AudioManager`
+fn enumerate_devices(flags: i32) -> Result<Vec<QuestAudioDevice>, Error> {
+    let native_activity = ndk_glue::native_activity();
+    let vm = unsafe { jni::JavaVM::from_raw(native_activity.vm()) }?;
+    let env = vm.attach_current_thread()?;
+    let activity = JObject::from(native_activity.activity());
+
+    // Context.AUDIO_SERVICE
+    let service = env.new_string("audio")?;
+    let audio_manager = env
+        .call_method(
+            activity,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[JValue::Object(service.into())],
+        )?
+        .l()?;
+
+    let array = env
+        .call_method(
+            audio_manager,
+            "getDevices",
+            "(I)[Landroid/media/AudioDeviceInfo;",
+            &[JValue::Int(flags)],
+        )?
+        .l()?
+        .into_inner();
+
+    let mut devices = Vec::new();
+    for idx in 0..env.get_array_length(array)? {
+        let device = env.get_object_array_element(array, idx)?;
+
+        let id = env.call_method(device, "getId", "()I", &[])?.i()?;
+        let device_type = env.call_method(device, "getType", "()I", &[])?.i()?;
+        let product_name = env
+            .call_method(device, "getProductName", "()Ljava/lang/CharSequence;", &[])?
+            .l()?;
+        let product_name = env
+            .call_method(product_name, "toString", "()Ljava/lang/String;", &[])?
+            .l()?;
+
+        devices.push(QuestAudioDevice {
+            id,
+            device_type,
+            product_name: env.get_string(product_name.into())?.into(),
+            position: idx as usize,
+            input: flags == GET_DEVICES_INPUTS,
+        });
+    }
+
+    Ok(devices)
+}
@@ -1,53 +1,303 @@
+//! Oculus Quest platform layer.
+//!
+//! The application manifest must declare the VR launcher category, and the hand tracking feature and
+//! permission if hand tracking is used. With `cargo-apk`, in the `Cargo.toml` of the application:
+//!
+//! ```toml
+//! [[package.metadata.android.uses_feature]]
+//! name = "android.hardware.vr.headtracking"
+//! required = true
+//! version = 1
+//!
+//! [[package.metadata.android.uses_feature]]
+//! name = "oculus.software.handtracking"
+//! required = false
+//!
+//! [[package.metadata.android.uses_permission]]
+//! name = "com.oculus.permission.HAND_TRACKING"
+//!
+//! [[package.metadata.android.application.meta_data]]
+//! name = "com.oculus.supportedDevices"
+//! value = "quest|quest2" # see QuestPlatformConfig::supported_devices_manifest_value()
+//!
+//! [[package.metadata.android.application.activity.intent_filter]]
+//! actions = ["android.intent.action.MAIN"]
+//! categories = ["com.oculus.intent.category.VR", "android.intent.category.LAUNCHER"]
+//! ```
+//!
+//! With the `quest-audio` feature, the headset audio devices are selected at startup into the
+//! `QuestAudioDevices` resource. Sounds played through bevy `Audio` go to the selected output device,
+//! and other audio engines open their streams on the selected devices.
+
 use std::{ffi, ptr};
 
+use bevy::app::App;
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::{info, warn};
+use bevy_openxr_core::{event::XrSessionState, XRDevice, XRPerformanceLevel, XrOptions};
+use jni::objects::{JObject, JValue};
 use openxr::{Entry, ExtensionSet, Instance};
 
-use super::OpenXRInstance;
+use super::{PowerStatus, XRPlatformLoader, XrInstanceBuilder};
+use crate::device_status::XRThermalStatus;
 use crate::error::Error;
 
-impl OpenXRInstance for openxr::Entry {
-    fn load_bevy_openxr() -> Result<openxr::Entry, Error> {
+#[cfg(feature = "quest-audio")]
+mod audio;
+
+#[cfg(feature = "quest-audio")]
+pub use audio::{QuestAudioDevice, QuestAudioDevices};
+
+const HAND_TRACKING_PERMISSION: &str = "com.oculus.permission.HAND_TRACKING";
+
+/// Quest headset models
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuestDevice {
+    Quest,
+    Quest2,
+}
+
+impl QuestDevice {
+    /// Value used in the `com.oculus.supportedDevices` manifest meta-data
+    pub fn manifest_name(&self) -> &'static str {
+        match self {
+            QuestDevice::Quest => "quest",
+            QuestDevice::Quest2 => "quest2",
+        }
+    }
+
+    /// System name reported by the Oculus runtime
+    fn system_name(&self) -> &'static str {
+        match self {
+            QuestDevice::Quest => "Oculus Quest",
+            QuestDevice::Quest2 => "Oculus Quest2",
+        }
+    }
+}
+
+/// Quest platform configuration. Insert as a resource before adding `OpenXRPlugin` to override defaults
+#[derive(Debug, Clone)]
+pub struct QuestPlatformConfig {
+    /// Devices the application is built for. Warning is logged when running on another device
+    pub target_devices: Vec<QuestDevice>,
+
+    /// Enable hand tracking, and request the hand tracking permission at startup.
+    /// `XrOptions::hand_trackers` takes precedence when `XrOptions` is inserted by the app
+    pub hand_tracking: bool,
+
+    /// CPU performance level, set when the session begins. Used when
+    /// `XrOptions::cpu_performance_level` is not set
+    pub cpu_level: Option<XRPerformanceLevel>,
+
+    /// GPU performance level, set when the session begins. Used when
+    /// `XrOptions::gpu_performance_level` is not set
+    pub gpu_level: Option<XRPerformanceLevel>,
+}
+
+impl Default for QuestPlatformConfig {
+    fn default() -> Self {
+        QuestPlatformConfig {
+            target_devices: vec![QuestDevice::Quest, QuestDevice::Quest2],
+            hand_tracking: true,
+            cpu_level: Some(XRPerformanceLevel::SustainedHigh),
+            gpu_level: Some(XRPerformanceLevel::SustainedHigh),
+        }
+    }
+}
+
+impl QuestPlatformConfig {
+    /// Value for the `com.oculus.supportedDevices` manifest meta-data, e.g. `quest|quest2`
+    pub fn supported_devices_manifest_value(&self) -> String {
+        self.target_devices
+            .iter()
+            .map(|device| device.manifest_name())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
+/// Applies `QuestPlatformConfig` into `XrOptions` as defaults for the options the app did not set,
+/// and requests the runtime permissions. Called by `OpenXRPlugin`, before `OpenXRCorePlugin`
+/// consumes the options
+pub(crate) fn configure_platform(app: &mut App) {
+    let config = app
+        .world
+        .get_resource_or_insert_with(QuestPlatformConfig::default)
+        .clone();
+
+    // hand tracking is a plain flag, so only options inserted by the app can override it
+    let mut options = match app.world.get_resource::<XrOptions>() {
+        Some(options) => options.clone(),
+        None => XrOptions {
+            hand_trackers: config.hand_tracking,
+            ..Default::default()
+        },
+    };
+
+    options.cpu_performance_level = options.cpu_performance_level.or(config.cpu_level);
+    options.gpu_performance_level = options.gpu_performance_level.or(config.gpu_level);
+    let hand_trackers = options.hand_trackers;
+    app.insert_resource(options);
+
+    if hand_trackers {
+        if let Err(e) = request_permissions(&[HAND_TRACKING_PERMISSION]) {
+            warn!(target: "bevy_openxr::platform", error = ?e, "Could not request hand tracking permission");
+        }
+    }
+
+    #[cfg(feature = "quest-audio")]
+    audio::configure_audio(app);
+
+    app.add_system(target_device_check_system.system());
+}
+
+fn target_device_check_system(
+    mut checked: Local<bool>,
+    config: Res<QuestPlatformConfig>,
+    xr_device: Res<XRDevice>,
+    mut state_events: EventReader<XrSessionState>,
+) {
+    if *checked || state_events.iter().next().is_none() {
+        return;
+    }
+    *checked = true;
+
+    let system_name = xr_device.get_system_name();
+    if !config
+        .target_devices
+        .iter()
+        .any(|device| device.system_name() == system_name)
+    {
+        warn!(
+            target: "bevy_openxr::platform",
+            system_name,
+            target_devices = ?config.target_devices,
+            "Running on a device not in QuestPlatformConfig::target_devices"
+        );
+    }
+}
+
+/// Requests the not yet granted Android runtime `permissions` from the user
+fn request_permissions(permissions: &[&str]) -> Result<(), Error> {
+    let native_activity = ndk_glue::native_activity();
+    let vm = unsafe { jni::JavaVM::from_raw(native_activity.vm()) }?;
+    let env = vm.attach_current_thread()?;
+    let activity = JObject::from(native_activity.activity());
+
+    let mut missing = Vec::new();
+    for permission in permissions {
+        let name = env.new_string(permission)?;
+
+        // PackageManager.PERMISSION_GRANTED == 0
+        let granted = env
+            .call_method(
+                activity,
+                "checkSelfPermission",
+                "(Ljava/lang/String;)I",
+                &[JValue::Object(name.into())],
+            )?
+            .i()?
+            == 0;
+
+        if !granted {
+            missing.push(name);
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    info!(target: "bevy_openxr::platform", ?permissions, "Requesting permissions");
+
+    let array = env.new_object_array(
+        missing.len() as i32,
+        env.find_class("java/lang/String")?,
+        JObject::null(),
+    )?;
+
+    for (idx, name) in missing.into_iter().enumerate() {
+        env.set_object_array_element(array, idx as i32, name)?;
+    }
+
+    env.call_method(
+        activity,
+        "requestPermissions",
+        "([Ljava/lang/String;I)V",
+        &[JValue::Object(JObject::from(array)), JValue::Int(0)],
+    )?;
+
+    Ok(())
+}
+
+/// Battery level and charging state from `BatteryManager`, thermal status from `PowerManager`
+pub(crate) fn power_status() -> Result<PowerStatus, Error> {
+    let native_activity = ndk_glue::native_activity();
+    let vm = unsafe { jni::JavaVM::from_raw(native_activity.vm()) }?;
+    let env = vm.attach_current_thread()?;
+    let activity = JObject::from(native_activity.activity());
+
+    let get_system_service = |name: &str| -> Result<JObject, Error> {
+        let name = env.new_string(name)?;
+        Ok(env
+            .call_method(
+                activity,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[JValue::Object(name.into())],
+            )?
+            .l()?)
+    };
+
+    // Context.BATTERY_SERVICE, BatteryManager.BATTERY_PROPERTY_CAPACITY == 4 (percent, or
+    // Integer.MIN_VALUE if not supported)
+    let battery_manager = get_system_service("batterymanager")?;
+    let capacity = env
+        .call_method(battery_manager, "getIntProperty", "(I)I", &[JValue::Int(4)])?
+        .i()?;
+    let charging = env
+        .call_method(battery_manager, "isCharging", "()Z", &[])?
+        .z()?;
+
+    // Context.POWER_SERVICE
+    let power_manager = get_system_service("power")?;
+    let thermal_status = env
+        .call_method(power_manager, "getCurrentThermalStatus", "()I", &[])?
+        .i()?;
+
+    Ok(PowerStatus {
+        battery_level: match capacity {
+            0..=100 => Some(capacity as f32 / 100.),
+            _ => None,
+        },
+        charging: Some(charging),
+        thermal_status: XRThermalStatus::from_android(thermal_status),
+    })
+}
+
+/// Oculus mobile loader: `libopenxr_loader.so` of the Oculus mobile SDK, initialized with
+/// `xrInitializeLoaderKHR`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OculusAndroidLoader;
+
+impl XRPlatformLoader for OculusAndroidLoader {
+    fn load(&self) -> Result<Entry, Error> {
         // Dynamic loading of the library
         // Expects lib/[arm64-v8a, ...]/libopenxr_loader.so to be present
         // libopenxr_loader.so is provided by Oculus mobile SDK
         // https://developer.oculus.com/downloads/package/oculus-openxr-mobile-sdk/
         let entry = Entry::load()?;
+        initialize_android_loader(&entry)?;
 
-        // FIXME SAFETY need to send nullptr (as per OpenXR docs), is this safe enough?
-        let instance: openxr::sys::Instance = unsafe { std::mem::zeroed() };
-
-        // Get address pointer to xrInitializeLoaderKHR through xrGetInstanceProcAddress
-        let loader_init_khr = unsafe { openxr::raw::LoaderInitKHR::load(&entry, instance) }?;
-
-        // construct XrLoaderInitInfoAndroidKHR
-        // https://developer.oculus.com/downloads/package/oculus-openxr-mobile-sdk/
-        let (application_vm, application_context) = get_android_vm_and_jni_context()?;
-        let android_khr = openxr::sys::LoaderInitInfoAndroidKHR {
-            ty: openxr::sys::StructureType::LOADER_INIT_INFO_ANDROID_KHR,
-            next: ptr::null(),
-            application_vm,
-            application_context,
-        };
-
-        // call xrInitializeLoaderKHR with the Android info
-        // must be called, otherwise loader library throws error "xrInitializeLoaderOCULUS"
-        let ret = unsafe {
-            (loader_init_khr.initialize_loader)(
-                &android_khr as *const _ as *const openxr::sys::LoaderInitInfoBaseHeaderKHR,
-            )
-        };
-
-        // Handle result
-        if ret == openxr::sys::Result::SUCCESS {
-            Ok(entry)
-        } else {
-            Err(Error::XR(ret))
-        }
+        Ok(entry)
     }
 
-    fn instantiate(&mut self, extensions: &mut ExtensionSet) -> Result<Instance, Error> {
-        let (application_vm, application_context) = get_android_vm_and_jni_context()?;
-
+    fn instantiate(
+        &self,
+        entry: &Entry,
+        builder: &XrInstanceBuilder,
+        extensions: &mut ExtensionSet,
+    ) -> Result<Instance, Error> {
         extensions.fb_display_refresh_rate = true;
         /*
         let other_extensions = extensions.other
@@ -61,22 +311,52 @@ impl OpenXRInstance for openxr::Entry {
 
         let other_extensions = Vec::new();
 
-        let xr_instance = self.create_instance(
-            &openxr::ApplicationInfo {
-                application_name: "hello openxr",
-                engine_name: "bevy",
-                application_version: 1, // FIXME allow user to submit application version?
-                engine_version: 1,      // FIXME pull bevy version from somewhere?
-            },
+        let xr_instance = entry.create_instance(
+            &builder.application_info(),
             &extensions,
             Some(other_extensions),
-            &[],
+            &builder.api_layers(),
         )?;
 
         Ok(xr_instance)
     }
 }
 
+/// Initializes the loader with the Java VM and activity of the app (`xrInitializeLoaderKHR`), as
+/// Android loaders require before any other call. For custom Android loaders
+pub fn initialize_android_loader(entry: &Entry) -> Result<(), Error> {
+    // FIXME SAFETY need to send nullptr (as per OpenXR docs), is this safe enough?
+    let instance: openxr::sys::Instance = unsafe { std::mem::zeroed() };
+
+    // Get address pointer to xrInitializeLoaderKHR through xrGetInstanceProcAddress
+    let loader_init_khr = unsafe { openxr::raw::LoaderInitKHR::load(entry, instance) }?;
+
+    // construct XrLoaderInitInfoAndroidKHR
+    // https://developer.oculus.com/downloads/package/oculus-openxr-mobile-sdk/
+    let (application_vm, application_context) = get_android_vm_and_jni_context()?;
+    let android_khr = openxr::sys::LoaderInitInfoAndroidKHR {
+        ty: openxr::sys::StructureType::LOADER_INIT_INFO_ANDROID_KHR,
+        next: ptr::null(),
+        application_vm,
+        application_context,
+    };
+
+    // call xrInitializeLoaderKHR with the Android info
+    // must be called, otherwise loader library throws error "xrInitializeLoaderOCULUS"
+    let ret = unsafe {
+        (loader_init_khr.initialize_loader)(
+            &android_khr as *const _ as *const openxr::sys::LoaderInitInfoBaseHeaderKHR,
+        )
+    };
+
+    // Handle result
+    if ret == openxr::sys::Result::SUCCESS {
+        Ok(())
+    } else {
+        Err(Error::XR(ret))
+    }
+}
+
 fn get_android_vm_and_jni_context() -> Result<(*mut ffi::c_void, *mut ffi::c_void), Error> {
     // JNI & Android activity are needed by Oculus runtime
     // modified from
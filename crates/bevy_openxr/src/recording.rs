@@ -0,0 +1,451 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use bevy::app::prelude::*;
+use bevy::core::Time;
+use bevy::ecs::prelude::*;
+use bevy::math::{Quat, Vec3};
+use bevy::transform::components::Transform;
+use bevy::utils::tracing::info;
+use bevy_openxr_core::{
+    event::{XRCameraTransformsUpdated, XrSessionState},
+    hand_tracking::HandPoseState,
+    XRSystem,
+};
+use openxr::HandJointLocations;
+
+/// Records head/hand poses and session state events, and plays them back into the same events
+/// and resources. Useful for deterministic testing of interaction code without a headset.
+///
+/// During playback, live data is overridden only for hand poses. Use together with
+/// `XRSimulationPlugin` (`hand_trackers: false`) to avoid mixing live and recorded events
+#[derive(Default)]
+pub struct XRRecordingPlugin;
+
+impl Plugin for XRRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRRecorder>()
+            .init_resource::<XrSessionState>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                playback_system.system().label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(CoreStage::Update, record_system.system());
+    }
+}
+
+/// Single recorded frame
+#[derive(Debug, Clone)]
+pub struct XRRecordedFrame {
+    /// Seconds since the start of the recording
+    pub time: f64,
+    pub states: Vec<XrSessionState>,
+    pub view_transforms: Vec<Transform>,
+    pub left_hand: Option<HandJointLocations>,
+    pub right_hand: Option<HandJointLocations>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XRRecording {
+    pub frames: Vec<XRRecordedFrame>,
+}
+
+enum RecorderMode {
+    Idle,
+    Recording {
+        start: Option<f64>,
+        recording: XRRecording,
+    },
+    Playback {
+        start: Option<f64>,
+        cursor: usize,
+        recording: XRRecording,
+    },
+}
+
+pub struct XRRecorder {
+    mode: RecorderMode,
+}
+
+impl Default for XRRecorder {
+    fn default() -> Self {
+        XRRecorder {
+            mode: RecorderMode::Idle,
+        }
+    }
+}
+
+impl XRRecorder {
+    /// Start recording. Any ongoing recording or playback is discarded
+    pub fn start_recording(&mut self) {
+        self.mode = RecorderMode::Recording {
+            start: None,
+            recording: XRRecording::default(),
+        };
+    }
+
+    /// Stop recording, returning the recorded frames
+    pub fn stop_recording(&mut self) -> Option<XRRecording> {
+        match std::mem::replace(&mut self.mode, RecorderMode::Idle) {
+            RecorderMode::Recording { recording, .. } => Some(recording),
+            mode => {
+                self.mode = mode;
+                None
+            }
+        }
+    }
+
+    /// Start playing back the recording from the beginning
+    pub fn start_playback(&mut self, recording: XRRecording) {
+        self.mode = RecorderMode::Playback {
+            start: None,
+            cursor: 0,
+            recording,
+        };
+    }
+
+    pub fn stop_playback(&mut self) {
+        if self.is_playing() {
+            self.mode = RecorderMode::Idle;
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, RecorderMode::Recording { .. })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.mode, RecorderMode::Playback { .. })
+    }
+}
+
+fn record_system(
+    time: Res<Time>,
+    hand_pose: Res<HandPoseState>,
+    mut recorder: ResMut<XRRecorder>,
+    mut state_events: EventReader<XrSessionState>,
+    mut camera_transforms_updated: EventReader<XRCameraTransformsUpdated>,
+) {
+    let (start, recording) = match &mut recorder.mode {
+        RecorderMode::Recording { start, recording } => (start, recording),
+        _ => return,
+    };
+
+    let now = time.seconds_since_startup();
+    let start = *start.get_or_insert(now);
+
+    recording.frames.push(XRRecordedFrame {
+        time: now - start,
+        states: state_events.iter().cloned().collect(),
+        view_transforms: camera_transforms_updated
+            .iter()
+            .last()
+            .map(|event| event.transforms.clone())
+            .unwrap_or_default(),
+        left_hand: hand_pose.left,
+        right_hand: hand_pose.right,
+    });
+}
+
+fn playback_system(
+    time: Res<Time>,
+    mut recorder: ResMut<XRRecorder>,
+    mut hand_pose: ResMut<HandPoseState>,
+    mut session_state: ResMut<XrSessionState>,
+    mut state_events: EventWriter<XrSessionState>,
+    mut camera_transforms_updated: EventWriter<XRCameraTransformsUpdated>,
+) {
+    let (start, cursor, recording) = match &mut recorder.mode {
+        RecorderMode::Playback {
+            start,
+            cursor,
+            recording,
+        } => (start, cursor, recording),
+        _ => return,
+    };
+
+    let now = time.seconds_since_startup();
+    let elapsed = now - *start.get_or_insert(now);
+
+    while let Some(frame) = recording.frames.get(*cursor) {
+        if frame.time > elapsed {
+            break;
+        }
+
+        for state in frame.states.iter() {
+            *session_state = *state;
+            state_events.send(*state);
+        }
+
+        if !frame.view_transforms.is_empty() {
+            camera_transforms_updated.send(XRCameraTransformsUpdated {
+                transforms: frame.view_transforms.clone(),
+                fovs: Vec::new(),
+            });
+        }
+
+        hand_pose.left = frame.left_hand;
+        hand_pose.right = frame.right_hand;
+
+        *cursor += 1;
+    }
+
+    if *cursor >= recording.frames.len() {
+        info!(target: "bevy_openxr::recording", "Playback finished");
+        recorder.mode = RecorderMode::Idle;
+    }
+}
+
+// File format: line-based text, one record per line
+//
+//   frame <time>
+//   state <XrSessionState>
+//   view <tx> <ty> <tz> <rx> <ry> <rz> <rw>
+//   hand <left|right> (<flags> <px> <py> <pz> <ox> <oy> <oz> <ow> <radius>) * HAND_JOINT_COUNT
+impl XRRecording {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for frame in self.frames.iter() {
+            writeln!(writer, "frame {}", frame.time)?;
+
+            for state in frame.states.iter() {
+                writeln!(writer, "state {:?}", state)?;
+            }
+
+            for t in frame.view_transforms.iter() {
+                writeln!(
+                    writer,
+                    "view {} {} {} {} {} {} {}",
+                    t.translation.x,
+                    t.translation.y,
+                    t.translation.z,
+                    t.rotation.x,
+                    t.rotation.y,
+                    t.rotation.z,
+                    t.rotation.w
+                )?;
+            }
+
+            for (name, hand) in [("left", &frame.left_hand), ("right", &frame.right_hand)].iter() {
+                if let Some(joints) = hand {
+                    write!(writer, "hand {}", name)?;
+                    for joint in joints.iter() {
+                        let (p, o) = (&joint.pose.position, &joint.pose.orientation);
+                        write!(
+                            writer,
+                            " {} {} {} {} {} {} {} {} {}",
+                            joint.location_flags.into_raw(),
+                            p.x,
+                            p.y,
+                            p.z,
+                            o.x,
+                            o.y,
+                            o.z,
+                            o.w,
+                            joint.radius
+                        )?;
+                    }
+                    writeln!(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut frames: Vec<XRRecordedFrame> = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            let mut parts = line.split_whitespace();
+
+            let kind = match parts.next() {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            if kind == "frame" {
+                frames.push(XRRecordedFrame {
+                    time: parse_next(&mut parts)?,
+                    states: Vec::new(),
+                    view_transforms: Vec::new(),
+                    left_hand: None,
+                    right_hand: None,
+                });
+                continue;
+            }
+
+            let frame = frames
+                .last_mut()
+                .ok_or_else(|| invalid_data("record before first frame"))?;
+
+            match kind {
+                "state" => frame.states.push(parse_state(parts.next())?),
+                "view" => {
+                    let v = parse_floats::<7>(&mut parts)?;
+                    let mut transform = Transform::from_translation(Vec3::new(v[0], v[1], v[2]));
+                    transform.rotation = Quat::from_xyzw(v[3], v[4], v[5], v[6]);
+                    frame.view_transforms.push(transform);
+                }
+                "hand" => {
+                    let side = parts.next();
+                    let joints = parse_hand(&mut parts)?;
+                    match side {
+                        Some("left") => frame.left_hand = Some(joints),
+                        Some("right") => frame.right_hand = Some(joints),
+                        _ => return Err(invalid_data("unknown hand")),
+                    }
+                }
+                _ => return Err(invalid_data("unknown record")),
+            }
+        }
+
+        Ok(XRRecording { frames })
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn parse_next<'a, T: std::str::FromStr>(
+    parts: &mut impl Iterator<Item = &'a str>,
+) -> io::Result<T> {
+    parts
+        .next()
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(|| invalid_data("missing or invalid value"))
+}
+
+fn parse_floats<'a, const N: usize>(
+    parts: &mut impl Iterator<Item = &'a str>,
+) -> io::Result<[f32; N]> {
+    let mut values = [0.; N];
+    for value in values.iter_mut() {
+        *value = parse_next(parts)?;
+    }
+    Ok(values)
+}
+
+fn parse_state(state: Option<&str>) -> io::Result<XrSessionState> {
+    Ok(match state {
+        Some("Idle") => XrSessionState::Idle,
+        Some("Ready") => XrSessionState::Ready,
+        Some("Running") => XrSessionState::Running,
+        Some("Visible") => XrSessionState::Visible,
+        Some("Focused") => XrSessionState::Focused,
+        Some("Stopping") => XrSessionState::Stopping,
+        Some("Exiting") => XrSessionState::Exiting,
+        _ => return Err(invalid_data("unknown state")),
+    })
+}
+
+fn parse_hand<'a>(parts: &mut impl Iterator<Item = &'a str>) -> io::Result<HandJointLocations> {
+    let mut joints = [openxr::HandJointLocation {
+        location_flags: openxr::SpaceLocationFlags::EMPTY,
+        pose: identity_posef(),
+        radius: 0.,
+    }; openxr::HAND_JOINT_COUNT];
+
+    for joint in joints.iter_mut() {
+        joint.location_flags = openxr::SpaceLocationFlags::from_raw(parse_next(parts)?);
+        let v = parse_floats::<8>(parts)?;
+        joint.pose.position = openxr::Vector3f {
+            x: v[0],
+            y: v[1],
+            z: v[2],
+        };
+        joint.pose.orientation = openxr::Quaternionf {
+            x: v[3],
+            y: v[4],
+            z: v[5],
+            w: v[6],
+        };
+        joint.radius = v[7];
+    }
+
+    Ok(joints)
+}
+
+fn identity_posef() -> openxr::Posef {
+    openxr::Posef {
+        orientation: openxr::Quaternionf {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+            w: 1.,
+        },
+        position: openxr::Vector3f {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_roundtrip() {
+        let mut transform = Transform::from_translation(Vec3::new(0.1, 1.6, -0.25));
+        transform.rotation = Quat::from_xyzw(0., 0.38268343, 0., 0.9238795);
+
+        let mut hand = [openxr::HandJointLocation {
+            location_flags: openxr::SpaceLocationFlags::POSITION_VALID,
+            pose: identity_posef(),
+            radius: 0.01,
+        }; openxr::HAND_JOINT_COUNT];
+        hand[3].pose.position.x = 0.5;
+
+        let recording = XRRecording {
+            frames: vec![
+                XRRecordedFrame {
+                    time: 0.,
+                    states: vec![XrSessionState::Visible, XrSessionState::Focused],
+                    view_transforms: vec![transform, transform],
+                    left_hand: None,
+                    right_hand: Some(hand),
+                },
+                XRRecordedFrame {
+                    time: 0.011,
+                    states: vec![],
+                    view_transforms: vec![],
+                    left_hand: None,
+                    right_hand: None,
+                },
+            ],
+        };
+
+        let mut data = Vec::new();
+        recording.write_to(&mut data).unwrap();
+        let loaded = XRRecording::read_from(&data[..]).unwrap();
+
+        assert_eq!(loaded.frames.len(), 2);
+        assert_eq!(loaded.frames[0].states, recording.frames[0].states);
+        assert_eq!(
+            loaded.frames[0].view_transforms,
+            recording.frames[0].view_transforms
+        );
+        assert_eq!(loaded.frames[1].time, 0.011);
+
+        let loaded_hand = loaded.frames[0].right_hand.unwrap();
+        assert_eq!(loaded_hand[3].pose.position.x, 0.5);
+        assert_eq!(
+            loaded_hand[3].location_flags,
+            openxr::SpaceLocationFlags::POSITION_VALID
+        );
+    }
+}
@@ -6,8 +6,12 @@ use bevy::{
     },
 };
 
+use bevy_openxr_core::{XRViewRect, XrFovf};
+
 use super::projection::XRProjection;
 
+/// XR camera. By default named `CAMERA_3D`, i.e. rendered by the bevy main pass. Use `with_name` to
+/// render it in a custom pass instead (see `add_xr_camera_node`)
 #[derive(Bundle)]
 pub struct XRCameraBundle {
     pub camera: Camera,
@@ -15,13 +19,16 @@ pub struct XRCameraBundle {
     pub visible_entities: VisibleEntities,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
+    pub eye: XREye,
 }
 
-impl Default for XRCameraBundle {
-    fn default() -> Self {
+impl XRCameraBundle {
+    /// Camera named `name`, drawn by the passes that have it added with `PassNode::add_camera`. XR
+    /// projection and view matrices are set on any camera with an `XRProjection`, regardless of name
+    pub fn with_name(name: &str) -> Self {
         XRCameraBundle {
             camera: Camera {
-                name: Some(CAMERA_3D.to_string()),
+                name: Some(name.to_string()),
                 ..Default::default()
             },
             // FIXME: ..Default::default() here causes stack overflow? Wut?
@@ -29,6 +36,201 @@ impl Default for XRCameraBundle {
             visible_entities: Default::default(),
             transform: Default::default(),
             global_transform: Default::default(),
+            eye: XREye::Both,
+        }
+    }
+
+    /// Camera rendering only the view of `eye`, e.g. for per-eye effects. The pass drawing the
+    /// camera must render a single view (not multiview), see `add_xr_camera_node`
+    pub fn with_eye(mut self, eye: XREye) -> Self {
+        self.eye = eye;
+        self
+    }
+}
+
+/// Views an XR camera renders: its projection and view matrices are those of the views, and its
+/// `Transform` is the pose of the eye (`Left` / `Right`) or the center of the eyes (`Both`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XREye {
+    Left,
+    Right,
+    Both,
+}
+
+impl Default for XREye {
+    fn default() -> Self {
+        XREye::Both
+    }
+}
+
+impl XREye {
+    /// Indices of the views owned by the camera. With a single (mono) view, both eyes own it
+    pub(crate) fn view_range(&self, view_count: usize) -> std::ops::Range<usize> {
+        match self {
+            XREye::Both => 0..view_count,
+            XREye::Left => 0..view_count.min(1),
+            XREye::Right if view_count > 1 => 1..2,
+            XREye::Right => 0..view_count,
+        }
+    }
+}
+
+impl Default for XRCameraBundle {
+    fn default() -> Self {
+        XRCameraBundle::with_name(CAMERA_3D)
+    }
+}
+
+/// Clip planes and pose handling of the XR camera. Changes to clip planes are applied to all
+/// `XRProjection`s, and projection matrices are regenerated
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRCameraConfig {
+    pub near: f32,
+    pub far: f32,
+
+    /// Place the far plane at infinity, `far` is ignored
+    pub infinite_far: bool,
+
+    /// How the eye poses of `XRCameraTransformsUpdated` are applied to the camera
+    pub transform_mode: XRCameraTransformMode,
+
+    /// Reverse-z depth (near plane at depth 1) for better depth precision in large scenes, best
+    /// together with `infinite_far`. Read at startup: the depth texture is created as `Depth32Float`,
+    /// the main pass clears depth to 0, and depth tests of all pipelines are flipped to `Greater`
+    pub reverse_z: bool,
+}
+
+/// Processing of the eye poses located by the runtime. The poses are applied to the view matrices,
+/// and their center to the `Transform` of the XR camera
+#[derive(Debug, Clone, PartialEq)]
+pub enum XRCameraTransformMode {
+    /// Poses as located by the runtime, predicted to the display time of the frame
+    Raw,
+
+    /// Poses extrapolated further by `prediction` from the motion since the previous frame, for
+    /// setups that display the frame later than predicted by the runtime
+    Predicted { prediction: std::time::Duration },
+
+    /// Poses exponentially smoothed over frames. `factor` (0..1) is the weight of the previous pose.
+    /// Adds latency, so meant for e.g. recording or spectator views rather than the headset view
+    Smoothed { factor: f32 },
+}
+
+impl XRCameraConfig {
+    /// Far plane distance given to `XRProjection`. A far plane at or in front of the near plane is
+    /// placed at infinity
+    pub(crate) fn projection_far(&self) -> f32 {
+        if self.infinite_far {
+            0.
+        } else {
+            self.far
         }
     }
 }
+
+impl Default for XRCameraConfig {
+    fn default() -> Self {
+        let projection = XRProjection::default();
+
+        XRCameraConfig {
+            near: projection.near,
+            far: projection.far,
+            infinite_far: false,
+            transform_mode: XRCameraTransformMode::Raw,
+            reverse_z: false,
+        }
+    }
+}
+
+/// View parameters of a single eye (or other view) for the current frame
+#[derive(Debug, Clone)]
+pub struct XREyeView {
+    /// Pose in XR reference space
+    pub pose: Transform,
+
+    /// Pose in world space, i.e. `pose` relative to the parent of the XR camera
+    pub world_pose: GlobalTransform,
+
+    pub fov: XrFovf,
+
+    /// Projection matrix used for rendering the view, including render scale
+    pub projection: Mat4,
+
+    /// Rect of the view in the render target, with render scale applied, e.g. the left or right half
+    /// for `XRStereoMode::DoubleWide`. `None` before the view surface is known
+    pub viewport: Option<XRViewRect>,
+}
+
+/// Per-frame view parameters of the XR camera, e.g. for custom shaders needing the true eye poses and
+/// field of views. Updated at `CoreStage::PostUpdate`
+#[derive(Debug, Clone, Default)]
+pub struct XREyeViews {
+    pub views: Vec<XREyeView>,
+}
+
+/// Metrics of the eye views, updated (and marked changed) only when they change, e.g. for scaling
+/// effects by the eye separation, or world-scale calibration
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XRViewMetrics {
+    /// Inter-pupillary distance: distance between the first two eye poses, in meters. `0` for a
+    /// single view
+    pub ipd: f32,
+
+    pub view_count: usize,
+}
+
+impl XRViewMetrics {
+    /// IPD change (meters) below which the metrics are not updated
+    const IPD_EPSILON: f32 = 0.0001;
+
+    pub(crate) fn from_eye_poses(poses: &[Transform]) -> Self {
+        let ipd = match poses {
+            [left, right, ..] => left.translation.distance(right.translation),
+            _ => 0.,
+        };
+
+        XRViewMetrics {
+            ipd,
+            view_count: poses.len(),
+        }
+    }
+
+    pub(crate) fn differs(&self, other: &XRViewMetrics) -> bool {
+        self.view_count != other.view_count || (self.ipd - other.ipd).abs() > Self::IPD_EPSILON
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eye_view_range() {
+        assert_eq!(XREye::Both.view_range(2), 0..2);
+        assert_eq!(XREye::Left.view_range(2), 0..1);
+        assert_eq!(XREye::Right.view_range(2), 1..2);
+
+        // mono
+        assert_eq!(XREye::Right.view_range(1), 0..1);
+        assert_eq!(XREye::Left.view_range(0), 0..0);
+    }
+
+    #[test]
+    fn test_view_metrics() {
+        let poses = [
+            Transform::from_translation(Vec3::new(-0.032, 1.6, 0.)),
+            Transform::from_translation(Vec3::new(0.031, 1.6, 0.)),
+        ];
+        let metrics = XRViewMetrics::from_eye_poses(&poses);
+        assert!((metrics.ipd - 0.063).abs() < 1e-5);
+        assert_eq!(metrics.view_count, 2);
+
+        let mut moved = poses.clone();
+        moved[1].translation.x += 0.00005;
+        assert!(!metrics.differs(&XRViewMetrics::from_eye_poses(&moved)));
+        moved[1].translation.x += 0.001;
+        assert!(metrics.differs(&XRViewMetrics::from_eye_poses(&moved)));
+
+        assert_eq!(XRViewMetrics::from_eye_poses(&poses[..1]).ipd, 0.);
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::{
+    asset::{AssetEvent, Assets, Handle},
+    math::{Mat4, Vec3, Vec4},
+    prelude::*,
+    render::{
+        camera::{Camera, VisibleEntities},
+        mesh::{Mesh, VertexAttributeValues},
+    },
+    utils::HashMap,
+};
+
+use super::projection::XRProjection;
+
+/// Bounding sphere of a mesh, in mesh local coordinates
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BoundingSphere {
+    center: Vec3,
+    radius: f32,
+}
+
+impl BoundingSphere {
+    fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+            VertexAttributeValues::Float3(positions) => positions,
+            _ => return None,
+        };
+
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), position| {
+                let position = Vec3::from(*position);
+                (min.min(position), max.max(position))
+            },
+        );
+
+        if positions.is_empty() {
+            return None;
+        }
+
+        let center = (min + max) * 0.5;
+        let radius = positions
+            .iter()
+            .map(|position| Vec3::from(*position).distance(center))
+            .fold(0., f32::max);
+
+        Some(BoundingSphere { center, radius })
+    }
+}
+
+/// Frustum planes (`xyz` = inward normal, `w` = distance), extracted from a view-projection matrix
+struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    fn from_view_projection(view_projection: &Mat4, reverse_z: bool) -> Self {
+        let row = |idx: usize| {
+            Vec4::new(
+                view_projection.x_axis[idx],
+                view_projection.y_axis[idx],
+                view_projection.z_axis[idx],
+                view_projection.w_axis[idx],
+            )
+        };
+
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        // XRProjection uses a [-1, 1] clip space depth, or [1, 0] with reverse-z
+        let far = if reverse_z { r2 } else { r3 + r2 };
+        let mut planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, far, r3 - r2];
+        for plane in planes.iter_mut() {
+            let length = plane.truncate().length();
+            if length > f32::EPSILON {
+                *plane /= length;
+            } else {
+                // far plane of an infinite projection, never culls
+                *plane = Vec4::new(0., 0., 0., 1.);
+            }
+        }
+
+        Frustum { planes }
+    }
+
+    fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+    }
+}
+
+/// Culls the visible entities of the XR camera against the frusta of all views. An entity is kept
+/// if it is within any of the views, so that nothing disappears at the outer edges of either eye.
+/// Entities without a mesh are never culled
+pub(crate) fn openxr_culling_system(
+    meshes: Res<Assets<Mesh>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut bounds: Local<HashMap<Handle<Mesh>, Option<BoundingSphere>>>,
+    mut camera_query: Query<(&Camera, &XRProjection, &mut VisibleEntities)>,
+    mesh_query: Query<(&Handle<Mesh>, &GlobalTransform)>,
+) {
+    for event in mesh_events.iter() {
+        match event {
+            AssetEvent::Created { handle }
+            | AssetEvent::Modified { handle }
+            | AssetEvent::Removed { handle } => {
+                bounds.remove(handle);
+            }
+        }
+    }
+
+    for (camera, xr_projection, mut visible_entities) in camera_query.iter_mut() {
+        if camera.projection_matrices.is_empty()
+            || camera.projection_matrices.len() != camera.position_matrices.len()
+        {
+            continue;
+        }
+
+        let frusta = camera
+            .projection_matrices
+            .iter()
+            .zip(camera.position_matrices.iter())
+            .map(|(projection, position)| {
+                Frustum::from_view_projection(
+                    &(*projection * position.inverse()),
+                    xr_projection.reverse_z,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        visible_entities.value.retain(|visible_entity| {
+            let (mesh, transform) = match mesh_query.get(visible_entity.entity) {
+                Ok(mesh) => mesh,
+                Err(_) => return true,
+            };
+
+            let sphere = *bounds
+                .entry(mesh.clone_weak())
+                .or_insert_with(|| meshes.get(mesh).and_then(BoundingSphere::from_mesh));
+
+            let sphere = match sphere {
+                Some(sphere) => sphere,
+                None => return true,
+            };
+
+            let center = transform.mul_vec3(sphere.center);
+            let radius = sphere.radius * transform.scale.max_element();
+
+            frusta
+                .iter()
+                .any(|frustum| frustum.intersects_sphere(center, radius))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_openxr_core::XrFovf;
+
+    #[test]
+    fn test_frustum_culling() {
+        let projection = XRProjection::new(0.05, 100.).get_projection_matrix_fov(&XrFovf {
+            angle_left: -0.8,
+            angle_right: 0.8,
+            angle_up: 0.8,
+            angle_down: -0.8,
+        });
+
+        let frustum = Frustum::from_view_projection(&projection, false);
+
+        assert!(frustum.intersects_sphere(Vec3::new(0., 0., -5.), 0.5));
+        assert!(!frustum.intersects_sphere(Vec3::new(0., 0., 5.), 0.5));
+        assert!(!frustum.intersects_sphere(Vec3::new(20., 0., -5.), 0.5));
+        assert!(!frustum.intersects_sphere(Vec3::new(0., 0., -200.), 0.5));
+    }
+
+    #[test]
+    fn test_frustum_culling_infinite_far() {
+        let projection = XRProjection::new(0.05, 0.).get_projection_matrix_fov(&XrFovf {
+            angle_left: -0.8,
+            angle_right: 0.8,
+            angle_up: 0.8,
+            angle_down: -0.8,
+        });
+
+        let frustum = Frustum::from_view_projection(&projection, false);
+
+        assert!(frustum.intersects_sphere(Vec3::new(0., 0., -10000.), 0.5));
+        assert!(!frustum.intersects_sphere(Vec3::new(0., 0., 5.), 0.5));
+    }
+
+    #[test]
+    fn test_frustum_culling_reverse_z() {
+        let mut projection = XRProjection::new(0.05, 100.);
+        projection.reverse_z = true;
+        let projection = projection.get_projection_matrix_fov(&XrFovf {
+            angle_left: -0.8,
+            angle_right: 0.8,
+            angle_up: 0.8,
+            angle_down: -0.8,
+        });
+
+        let frustum = Frustum::from_view_projection(&projection, true);
+
+        assert!(frustum.intersects_sphere(Vec3::new(0., 0., -5.), 0.5));
+        assert!(!frustum.intersects_sphere(Vec3::new(0., 0., 5.), 0.5));
+        assert!(!frustum.intersects_sphere(Vec3::new(0., 0., -200.), 0.5));
+    }
+}
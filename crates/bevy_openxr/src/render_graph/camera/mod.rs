@@ -1,3 +1,5 @@
 pub mod camera;
+pub(crate) mod culling;
+pub(crate) mod pose;
 pub mod projection;
 pub mod system;
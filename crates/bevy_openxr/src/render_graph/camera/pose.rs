@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use super::camera::XRCameraTransformMode;
+
+/// Midpoint of the views, with the rotation halfway between the first and the last view. Used as the
+/// transform of the XR camera entity
+pub(crate) fn center_eye(transforms: &[Transform]) -> Option<Transform> {
+    let first = transforms.first()?;
+    let last = transforms.last()?;
+
+    let translation = transforms
+        .iter()
+        .fold(Vec3::ZERO, |sum, t| sum + t.translation)
+        / transforms.len() as f32;
+
+    Some(Transform {
+        translation,
+        rotation: first.rotation.slerp(last.rotation, 0.5),
+        scale: first.scale,
+    })
+}
+
+/// Processes the located eye poses according to `XRCameraTransformMode`
+#[derive(Default)]
+pub(crate) struct PoseFilter {
+    /// Poses located in the previous update
+    previous_located: Vec<Transform>,
+
+    /// Poses applied in the previous update
+    previous_applied: Vec<Transform>,
+}
+
+impl PoseFilter {
+    /// Poses to apply for `located` poses, `delta_seconds` after the previous ones
+    pub(crate) fn apply(
+        &mut self,
+        mode: &XRCameraTransformMode,
+        located: &[Transform],
+        delta_seconds: f32,
+    ) -> Vec<Transform> {
+        let same_views = self.previous_located.len() == located.len();
+
+        let applied = match mode {
+            XRCameraTransformMode::Raw => located.to_vec(),
+            XRCameraTransformMode::Predicted { prediction } if same_views && delta_seconds > 0. => {
+                let t = prediction.as_secs_f32() / delta_seconds;
+                self.previous_located
+                    .iter()
+                    .zip(located.iter())
+                    .map(|(previous, current)| extrapolate(previous, current, t))
+                    .collect()
+            }
+            XRCameraTransformMode::Predicted { .. } => located.to_vec(),
+            XRCameraTransformMode::Smoothed { factor }
+                if self.previous_applied.len() == located.len() =>
+            {
+                self.previous_applied
+                    .iter()
+                    .zip(located.iter())
+                    .map(|(previous, current)| interpolate(previous, current, 1. - *factor))
+                    .collect()
+            }
+            XRCameraTransformMode::Smoothed { .. } => located.to_vec(),
+        };
+
+        self.previous_located = located.to_vec();
+        self.previous_applied = applied.clone();
+        applied
+    }
+}
+
+fn interpolate(from: &Transform, to: &Transform, t: f32) -> Transform {
+    Transform {
+        translation: from.translation.lerp(to.translation, t),
+        rotation: from.rotation.slerp(to.rotation, t),
+        scale: to.scale,
+    }
+}
+
+/// Continues the motion from `previous` to `current` by `t` times the motion
+fn extrapolate(previous: &Transform, current: &Transform, t: f32) -> Transform {
+    let rotation_delta = current.rotation * previous.rotation.conjugate();
+    let (axis, angle) = rotation_delta.to_axis_angle();
+
+    Transform {
+        translation: current.translation + (current.translation - previous.translation) * t,
+        rotation: (Quat::from_axis_angle(axis, angle * t) * current.rotation).normalize(),
+        scale: current.scale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_center_eye() {
+        let transforms = [
+            Transform::from_xyz(-0.03, 1.6, 0.),
+            Transform::from_xyz(0.03, 1.6, 0.),
+        ];
+        let center = center_eye(&transforms).unwrap();
+        assert!(center.translation.abs_diff_eq(Vec3::new(0., 1.6, 0.), 1e-6));
+        assert!(center_eye(&[]).is_none());
+    }
+
+    #[test]
+    fn test_pose_filter() {
+        let mut filter = PoseFilter::default();
+        let mode = XRCameraTransformMode::Predicted {
+            prediction: std::time::Duration::from_millis(5),
+        };
+
+        let first = filter.apply(&mode, &[Transform::from_xyz(0., 0., 0.)], 0.01);
+        assert_eq!(first[0].translation, Vec3::ZERO);
+
+        let second = filter.apply(&mode, &[Transform::from_xyz(1., 0., 0.)], 0.01);
+        assert!(second[0]
+            .translation
+            .abs_diff_eq(Vec3::new(1.5, 0., 0.), 1e-6));
+
+        let mode = XRCameraTransformMode::Smoothed { factor: 0.5 };
+        let smoothed = filter.apply(&mode, &[Transform::from_xyz(2.5, 0., 0.)], 0.01);
+        assert!(smoothed[0]
+            .translation
+            .abs_diff_eq(Vec3::new(2., 0., 0.), 1e-6));
+    }
+}
@@ -3,13 +3,17 @@ use bevy::math::Mat4;
 use bevy::reflect::Reflect;
 use bevy::render::camera::{CameraProjection, DepthCalculation};
 
-use bevy_openxr_core::XrFovf;
+use bevy_openxr_core::{event::XRViewSurfaceCreated, XrFovf};
 
 #[derive(Debug, Clone)]
 pub struct XRProjection {
     pub near: f32,
     pub far: f32,
     pub fov: Option<f32>,
+
+    /// Map the near plane to depth 1 and the far plane to depth 0, for better depth precision.
+    /// Requires a depth test passing greater values, see `XRCameraConfig::reverse_z`
+    pub reverse_z: bool,
 }
 
 impl XRProjection {
@@ -18,6 +22,7 @@ impl XRProjection {
             near,
             far,
             fov: None,
+            reverse_z: false,
         }
     }
 }
@@ -52,6 +57,7 @@ impl Default for XRProjection {
             near: 0.05,
             far: 1000.,
             fov: None,
+            reverse_z: false,
         }
     }
 }
@@ -143,16 +149,77 @@ impl XRProjection {
             cols[15] = 0.;
         }
 
+        if self.reverse_z {
+            // [0, 1] clip space depth, near plane at 1
+            if far_z <= near_z {
+                cols[10] = 0.;
+                cols[14] = near_z;
+            } else {
+                cols[10] = near_z / (far_z - near_z);
+                cols[14] = far_z * near_z / (far_z - near_z);
+            }
+        }
+
         Mat4::from_cols_array(&cols)
     }
 }
 
+/// Maps clip space into the top-left `render_scale` sized sub-rect of the viewport, so that the
+/// scene is rendered into the sub-rect submitted to the compositor (dynamic resolution)
+pub(crate) fn render_scale_matrix(render_scale: f32) -> Mat4 {
+    sub_rect_matrix(render_scale, render_scale)
+}
+
+/// Like `render_scale_matrix`, for a view smaller than the render target. Views recommending
+/// different sizes share a render target of the largest size, each view rendered into its top-left
+pub(crate) fn view_scale_matrix(
+    render_scale: f32,
+    view_size: (u32, u32),
+    surface_size: (u32, u32),
+) -> Mat4 {
+    sub_rect_matrix(
+        render_scale * view_size.0 as f32 / surface_size.0 as f32,
+        render_scale * view_size.1 as f32 / surface_size.1 as f32,
+    )
+}
+
+/// `view_scale_matrix` of the view, or `render_scale_matrix` if the view surface is not known
+pub(crate) fn view_scale_matrix_for(
+    render_scale: f32,
+    view_surface: Option<&XRViewSurfaceCreated>,
+    view_index: usize,
+) -> Mat4 {
+    match view_surface {
+        Some(surface) if view_index < surface.view_sizes.len() => view_scale_matrix(
+            render_scale,
+            surface.view_sizes[view_index],
+            (surface.width, surface.height),
+        ),
+        _ => render_scale_matrix(render_scale),
+    }
+}
+
+fn sub_rect_matrix(scale_x: f32, scale_y: f32) -> Mat4 {
+    let mut cols: [f32; 16] = [0.0; 16];
+
+    cols[0] = scale_x;
+    cols[5] = scale_y;
+    cols[10] = 1.;
+    cols[15] = 1.;
+
+    // x: [-1, 1] -> [-1, -1 + 2 * scale], y: [-1, 1] -> [1 - 2 * scale, 1]
+    cols[12] = scale_x - 1.;
+    cols[13] = 1. - scale_y;
+
+    Mat4::from_cols_array(&cols)
+}
+
 // https://gitlab.freedesktop.org/monado/demos/openxr-simple-example/-/blob/master/main.c#L70
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bevy::math::Vec4;
+    use bevy::math::{Vec3, Vec4};
 
     #[test]
     fn test_projection() {
@@ -176,4 +243,59 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_projection_reverse_z() {
+        let fov = XrFovf {
+            angle_left: -0.8,
+            angle_right: 0.8,
+            angle_up: 0.8,
+            angle_down: -0.8,
+        };
+
+        let depth = |matrix: Mat4, z: f32| {
+            let clip = matrix * Vec4::new(0., 0., z, 1.);
+            clip.z / clip.w
+        };
+
+        let mut projection = XRProjection::new(0.1, 100.);
+        projection.reverse_z = true;
+        let matrix = projection.get_projection_matrix_fov(&fov);
+        assert!((depth(matrix, -0.1) - 1.).abs() < 1e-5);
+        assert!(depth(matrix, -100.).abs() < 1e-5);
+
+        projection.far = 0.;
+        let matrix = projection.get_projection_matrix_fov(&fov);
+        assert!((depth(matrix, -0.1) - 1.).abs() < 1e-5);
+        assert!(depth(matrix, -1e6) > 0.);
+    }
+
+    #[test]
+    fn test_render_scale_matrix() {
+        assert_eq!(render_scale_matrix(1.), Mat4::IDENTITY);
+
+        let matrix = render_scale_matrix(0.5);
+        assert_eq!(
+            matrix.transform_point3(Vec3::new(-1., -1., 0.5)),
+            Vec3::new(-1., 0., 0.5)
+        );
+        assert_eq!(
+            matrix.transform_point3(Vec3::new(1., 1., 0.5)),
+            Vec3::new(0., 1., 0.5)
+        );
+    }
+
+    #[test]
+    fn test_view_scale_matrix() {
+        assert_eq!(
+            view_scale_matrix(1., (1000, 800), (1000, 800)),
+            Mat4::IDENTITY
+        );
+
+        let matrix = view_scale_matrix(1., (500, 800), (1000, 800));
+        assert_eq!(
+            matrix.transform_point3(Vec3::new(1., -1., 0.5)),
+            Vec3::new(0., -1., 0.5)
+        );
+    }
 }
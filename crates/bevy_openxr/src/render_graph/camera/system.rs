@@ -2,48 +2,184 @@ use bevy::{
     prelude::*,
     render::camera::{Camera, CameraProjection},
 };
-use bevy_openxr_core::{event, math::XRMatrixComputation};
+use bevy_openxr_core::{event, math::XRMatrixComputation, View};
 
-use super::projection::XRProjection;
+use super::{
+    camera::{XRCameraConfig, XREye, XREyeView, XREyeViews, XRViewMetrics},
+    pose::{center_eye, PoseFilter},
+    projection::{view_scale_matrix_for, XRProjection},
+};
+use crate::{
+    dynamic_resolution::XRRenderScale, render_graph::secondary_views::XRSecondaryViewCamera,
+};
+
+/// Updates `XRViewMetrics` from the located eye poses, if changed
+pub(crate) fn view_metrics_system(
+    mut view_metrics: ResMut<XRViewMetrics>,
+    mut camera_transforms_updated: EventReader<event::XRCameraTransformsUpdated>,
+) {
+    let event = match camera_transforms_updated.iter().last() {
+        Some(event) => event,
+        None => return,
+    };
+
+    let metrics = XRViewMetrics::from_eye_poses(&event.transforms);
+    if metrics.differs(&view_metrics) {
+        *view_metrics = metrics;
+    }
+}
+
+/// Field of view change (radians) below which projection matrices are not recomputed
+const FOV_EPSILON: f32 = 1e-4;
 
 pub(crate) fn openxr_camera_system(
-    mut camera_query: Query<(&mut Camera, &mut XRProjection, &mut Transform)>,
+    mut views: Local<Vec<View>>,
+    mut view_surface: Local<Option<event::XRViewSurfaceCreated>>,
+    mut applied_render_scale: Local<Option<XRRenderScale>>,
+    mut pose_filter: Local<PoseFilter>,
+    time: Res<Time>,
+    render_scale: Option<Res<XRRenderScale>>,
+    config: Res<XRCameraConfig>,
+    mut eye_views: ResMut<XREyeViews>,
+    mut camera_query: Query<
+        (
+            &mut Camera,
+            &mut XRProjection,
+            &mut Transform,
+            Option<&Parent>,
+            Option<&XREye>,
+        ),
+        Without<XRSecondaryViewCamera>,
+    >,
+    parent_query: Query<&GlobalTransform>,
+    added_cameras: Query<(), (Added<XRProjection>, Without<XRSecondaryViewCamera>)>,
     mut view_surface_created_events: EventReader<event::XRViewSurfaceCreated>,
     mut views_created_events: EventReader<event::XRViewsCreated>,
     mut camera_transforms_updated: EventReader<event::XRCameraTransformsUpdated>,
+    mut fov_changed_events: EventWriter<event::XRFovChanged>,
 ) {
-    // FIXME: remove
+    let mut views_changed = false;
+
     for event in view_surface_created_events.iter() {
-        for (_, mut camera_projection, _) in camera_query.iter_mut() {
-            // this is actually unnecessary?
+        for (_, mut camera_projection, _, _, _) in camera_query.iter_mut() {
+            // FIXME: remove, this is actually unnecessary?
             camera_projection.update(event.width as f32, event.height as f32);
         }
+
+        *view_surface = Some(event.clone());
+        views_changed = true;
     }
 
-    // initialize projection matrices on view creation
+    let render_scale = render_scale.map(|scale| *scale).unwrap_or_default();
+
     for event in views_created_events.iter() {
-        for (mut camera, mut camera_projection, _) in camera_query.iter_mut() {
+        *views = event.views.clone();
+        views_changed = true;
+    }
+
+    let transforms_updated = camera_transforms_updated.iter().last();
+
+    // runtime may change the field of views per frame
+    if let Some(event) = transforms_updated {
+        if event.fovs.len() == views.len() {
+            let mut fov_changed = false;
+            for (view, fov) in views.iter_mut().zip(event.fovs.iter()) {
+                if !view.fov.abs_diff_eq(fov, FOV_EPSILON) {
+                    view.fov = fov.clone();
+                    fov_changed = true;
+                }
+            }
+
+            if fov_changed {
+                fov_changed_events.send(event::XRFovChanged {
+                    fovs: views.iter().map(|view| view.fov.clone()).collect(),
+                });
+                views_changed = true;
+            }
+        }
+    }
+
+    // cameras spawned after the views were created, e.g. the view cameras of `view_passes_system`
+    if added_cameras.iter().next().is_some() {
+        views_changed = true;
+    }
+
+    // initialize projection matrices on view creation, and on fov, render scale or clip plane change
+    if views_changed || *applied_render_scale != Some(render_scale) || config.is_changed() {
+        let scale_matrices = (0..views.len())
+            .map(|view_index| {
+                view_scale_matrix_for(render_scale.0, view_surface.as_ref(), view_index)
+            })
+            .collect::<Vec<_>>();
+
+        for (mut camera, mut camera_projection, _, _, eye) in camera_query.iter_mut() {
+            camera_projection.near = config.near;
+            camera_projection.far = config.projection_far();
+            camera_projection.reverse_z = config.reverse_z;
+
+            let view_range = eye.copied().unwrap_or_default().view_range(views.len());
+
             camera.depth_calculation = camera_projection.depth_calculation();
-            camera.projection_matrices = event
-                .views
+            camera.projection_matrices = views[view_range.clone()]
                 .iter()
-                .map(|view| camera_projection.get_projection_matrix_fov(&view.fov))
+                .zip(scale_matrices[view_range].iter())
+                .map(|(view, scale_matrix)| {
+                    *scale_matrix * camera_projection.get_projection_matrix_fov(&view.fov)
+                })
                 .collect::<Vec<_>>();
         }
+
+        *applied_render_scale = Some(render_scale);
     }
 
-    for event in camera_transforms_updated.iter() {
-        for (mut camera, _, mut transform) in camera_query.iter_mut() {
-            if event.transforms.len() > 0 {
-                // FIXME: get an average of cameras?
-                *transform = event.transforms[0];
-            }
+    let event = match transforms_updated {
+        Some(event) => event,
+        None => return,
+    };
 
-            camera.position_matrices = event
-                .transforms
-                .iter()
-                .map(|transform| transform.compute_xr_matrix())
-                .collect::<Vec<_>>();
+    let transforms = pose_filter.apply(
+        &config.transform_mode,
+        &event.transforms,
+        time.delta_seconds(),
+    );
+
+    for (mut camera, _, mut transform, parent, eye) in camera_query.iter_mut() {
+        let eye = eye.copied().unwrap_or_default();
+        let eye_transforms = &transforms[eye.view_range(transforms.len())];
+
+        if let Some(center) = center_eye(eye_transforms) {
+            *transform = center;
         }
+
+        camera.position_matrices = eye_transforms
+            .iter()
+            .map(|transform| transform.compute_xr_matrix())
+            .collect::<Vec<_>>();
+
+        // eye views are of all views, single eye cameras don't have all projections
+        if eye != XREye::Both {
+            continue;
+        }
+
+        let parent_transform = parent
+            .and_then(|parent| parent_query.get(parent.0).ok())
+            .cloned()
+            .unwrap_or_default();
+
+        eye_views.views = transforms
+            .iter()
+            .zip(views.iter())
+            .zip(camera.projection_matrices.iter())
+            .enumerate()
+            .map(|(view_index, ((pose, view), projection))| XREyeView {
+                pose: *pose,
+                world_pose: parent_transform.mul_transform(*pose),
+                fov: view.fov.clone(),
+                projection: *projection,
+                viewport: view_surface
+                    .as_ref()
+                    .and_then(|surface| surface.view_rect(view_index as u32, render_scale.0)),
+            })
+            .collect();
     }
 }
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+use bevy::render::pass::ClearColor;
+use bevy_openxr_core::XREnvironmentBlendMode;
+
+/// Keeps the `ClearColor` alpha at zero while the environment blend mode is see-through (additive or
+/// alpha blended), so that the real world shows through the cleared areas instead of an opaque
+/// background. With `Additive` the runtime ignores alpha, so the clear color should also be black.
+///
+/// Runs at `CoreStage::Last`, after other systems (e.g. passthrough) have adjusted the clear color
+pub(crate) fn xr_clear_color_system(
+    blend_mode: Res<XREnvironmentBlendMode>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    if blend_mode.is_see_through() && clear_color.0.a() != 0. {
+        clear_color.0.set_a(0.);
+    }
+}
@@ -1,29 +1,104 @@
-use bevy::{prelude::*, wgpu::RenderStage};
+use bevy::{prelude::*, transform::TransformSystem, wgpu::RenderStage};
+use bevy_openxr_core::{XRAvailability, XRSystem, XrOptions};
+
+use crate::dynamic_resolution::{render_scale_system, XRRenderScale};
 
 pub mod camera;
-pub(crate) mod nodes;
+pub(crate) mod clear_color;
+pub mod nodes;
+pub mod post_process;
 pub(crate) mod render_hook_systems;
+pub(crate) mod reverse_z;
+pub mod secondary_views;
+pub mod textures;
+pub mod view_passes;
 pub(crate) mod xr_render_graph;
 
 pub(crate) use render_hook_systems::*;
 pub(crate) use xr_render_graph::*;
+pub use xr_render_graph::{add_xr_camera_node, connect_xr_targets};
+
+/// Label of `view_passes::view_passes_system`
+const VIEW_PASSES: &str = "xr_view_passes";
 
 pub struct OpenXRWgpuPlugin;
 
 impl Plugin for OpenXRWgpuPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<camera::camera::XRCameraConfig>()
+            .init_resource::<camera::camera::XREyeViews>()
+            .init_resource::<camera::camera::XRViewMetrics>();
+
+        if !app.world.contains_resource::<XRRenderScale>() {
+            let supersampling = app
+                .world
+                .get_resource::<XrOptions>()
+                .map_or(1., |options| options.supersampling.max(1.));
+            app.insert_resource(XRRenderScale(supersampling));
+        }
+
+        // desktop fallback renders with the regular window graph
+        let available = app
+            .world
+            .get_resource::<XRAvailability>()
+            .map_or(true, |availability| availability.is_available());
+        if !available {
+            return;
+        }
+
         app.add_startup_system(add_xr_render_graph.system())
             .add_system_to_stage(
                 RenderStage::Draw,
-                pre_render_system.exclusive_system(), // FIXME there should maybe be some ImmediatelyBeforeRender system
+                // FIXME there should maybe be some ImmediatelyBeforeRender system
+                pre_render_system
+                    .exclusive_system()
+                    .label(XRSystem::PreRender),
             )
             .add_system_to_stage(
                 RenderStage::PostRender,
-                post_render_system.exclusive_system(), // FIXME there should maybe be some ImmediatelyAfterPost system
+                // FIXME there should maybe be some ImmediatelyAfterPost system
+                post_render_system
+                    .exclusive_system()
+                    .label(XRSystem::PostRender),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera::system::openxr_camera_system
+                    .system()
+                    .label(XRSystem::PoseUpdate)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                view_passes::view_passes_system.system().label(VIEW_PASSES),
+            )
+            .add_system_to_stage(
+                // secondary view passes draw after the nodes preceding the view passes
+                CoreStage::PreUpdate,
+                secondary_views::secondary_view_passes_system
+                    .system()
+                    .after(VIEW_PASSES),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                secondary_views::secondary_view_cameras_system
+                    .system()
+                    .before(TransformSystem::TransformPropagate),
             )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
-                camera::system::openxr_camera_system.system(),
+                reverse_z::reverse_z_pipeline_system.system(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera::system::view_metrics_system.system(),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, render_scale_system.system())
+            .add_system_to_stage(CoreStage::Last, clear_color::xr_clear_color_system.system())
+            .add_system_to_stage(
+                // after visible entities have been collected at PostUpdate
+                RenderStage::RenderResource,
+                camera::culling::openxr_culling_system.system(),
             );
     }
 }
@@ -1,5 +1,23 @@
+//! Render graph nodes providing the XR render targets, for apps building their own render graph.
+//! `OpenXRWgpuPlugin` replaces the window nodes of the default graph with these, for other graphs
+//! see `connect_xr_targets`
+
+/// Names of the XR nodes added to the render graph
+pub mod node {
+    pub const XR_SWAPCHAIN: &str = "xr_swapchain";
+    pub const XR_DEPTH_TEXTURE: &str = "xr_depth_texture";
+    pub const XR_PREVIOUS_FRAME: &str = "xr_previous_frame";
+    pub const XR_VIEW_COPY: &str = "xr_view_copy";
+}
+
+mod previous_frame_node;
+pub use previous_frame_node::XRPreviousFrameNode;
+
 mod swapchain_node;
 pub use swapchain_node::XRSwapchainNode;
 
+mod view_copy_node;
+pub use view_copy_node::XRViewCopyNode;
+
 mod window_texture_node;
 pub use window_texture_node::XRWindowTextureNode;
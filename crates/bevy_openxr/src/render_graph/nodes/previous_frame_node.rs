@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+
+use bevy::ecs::world::World;
+use bevy::render::{
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{RenderContext, RenderResourceId, RenderResourceType, TextureId},
+    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureUsage},
+};
+use bevy_openxr_core::event::XRViewSurfaceCreated;
+use bevy_openxr_core::XRConfigurationState;
+
+use crate::render_graph::textures::{TextureHandleBinding, XR_PREVIOUS_FRAME_TEXTURE_HANDLE};
+
+/// Copies the swapchain image of the frame into a texture bound to
+/// `XR_PREVIOUS_FRAME_TEXTURE_HANDLE`, sampled during the next frame. Added by `OpenXRWgpuPlugin`
+/// after the passes drawing into the swapchain if `XRRenderTextures::previous_frame` is set
+#[derive(Default)]
+pub struct XRPreviousFrameNode {
+    texture: Option<TextureId>,
+    last_view_surface: Option<XRViewSurfaceCreated>,
+    binding: TextureHandleBinding,
+}
+
+impl XRPreviousFrameNode {
+    /// Input slot with the swapchain image of the frame
+    pub const IN_TEXTURE: &'static str = "texture";
+
+    pub fn new() -> Self {
+        XRPreviousFrameNode::default()
+    }
+}
+
+impl Node for XRPreviousFrameNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+            name: Cow::Borrowed(XRPreviousFrameNode::IN_TEXTURE),
+            resource_type: RenderResourceType::Texture,
+        }];
+        INPUT
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const SWAPCHAIN_TEXTURE: usize = 0;
+        let swapchain_texture = match input.get(SWAPCHAIN_TEXTURE) {
+            Some(RenderResourceId::Texture(texture)) => texture,
+            _ => return,
+        };
+
+        let render_state = world.get_resource::<XRConfigurationState>().unwrap();
+        let view_surface = match &render_state.last_view_surface {
+            Some(view_surface) => view_surface,
+            None => return,
+        };
+
+        let (width, height, layers) = view_surface.stereo_mode.image_extent(
+            view_surface.width,
+            view_surface.height,
+            view_surface.view_count,
+        );
+        let size = Extent3d::new(width, height, layers);
+
+        // recreate the copy when the swapchain is resized
+        if self.last_view_surface.as_ref() != Some(view_surface) {
+            let render_resource_context = render_context.resources_mut();
+            if let Some(old_texture) = self.texture.take() {
+                render_resource_context.remove_texture(old_texture);
+            }
+
+            self.texture = Some(render_resource_context.create_texture(TextureDescriptor {
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: view_surface.format,
+                usage: TextureUsage::COPY_DST | TextureUsage::SAMPLED,
+            }));
+            self.last_view_surface = Some(view_surface.clone());
+        }
+
+        let texture = match self.texture {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        render_context.copy_texture_to_texture(
+            swapchain_texture,
+            [0, 0, 0],
+            0,
+            texture,
+            [0, 0, 0],
+            0,
+            size,
+        );
+
+        self.binding.set(
+            render_context.resources(),
+            &XR_PREVIOUS_FRAME_TEXTURE_HANDLE,
+            texture,
+        );
+    }
+}
@@ -4,26 +4,54 @@ use bevy::{
     ecs::world::World,
     render::{
         render_graph::{Node, ResourceSlotInfo, ResourceSlots},
-        renderer::{RenderContext, RenderResourceId, RenderResourceType},
+        renderer::{RenderContext, RenderResourceId, RenderResourceType, TextureId},
     },
+    utils::tracing::warn,
 };
 
 use bevy_openxr_core::XRConfigurationState;
 
+use crate::render_graph::textures::{TextureHandleBinding, XR_SWAPCHAIN_TEXTURE_HANDLE};
+
 /// Like `WindowSwapChainNode`, but for XR implementation
 /// XR implementation initializes the underlying textures at the startup, and after that
-/// this node will swap the textures based on texture id retrieved from XR swapchain
+/// this node will swap the textures based on texture id retrieved from XR swapchain.
+///
+/// Output slot `OUT_TEXTURE` is the swapchain image of the frame, a texture array with the layout
+/// of `XRStereoMode`. Texture ids are refreshed when the swapchain is recreated, so any number of
+/// swapchain images (see `XRSystemInfo::swapchain_image_count`) is supported. The image is also
+/// bound to `XR_SWAPCHAIN_TEXTURE_HANDLE`.
+///
+/// With `secondary`, the output is the image of a secondary view swapchain instead (not bound to the
+/// handle). In frames the view is not rendered, the previous image stays as the output
 #[derive(Default)]
 pub struct XRSwapchainNode {
     resource_ids: Option<Vec<RenderResourceId>>,
+
+    /// Texture view ids the resource ids were created from
+    texture_view_ids: Option<Vec<TextureId>>,
+
+    /// Index of the secondary view, if not the primary swapchain
+    secondary_view: Option<usize>,
+
+    binding: TextureHandleBinding,
 }
 
 impl XRSwapchainNode {
+    /// Output slot with the swapchain image of the frame
     pub const OUT_TEXTURE: &'static str = "texture";
 
     pub fn new() -> Self {
         XRSwapchainNode::default()
     }
+
+    /// Swapchain images of the secondary view (see `XRConfigurationState::secondary_views`)
+    pub fn secondary(index: usize) -> Self {
+        XRSwapchainNode {
+            secondary_view: Some(index),
+            ..Default::default()
+        }
+    }
 }
 
 impl Node for XRSwapchainNode {
@@ -38,36 +66,70 @@ impl Node for XRSwapchainNode {
     fn update(
         &mut self,
         world: &World,
-        _render_context: &mut dyn RenderContext,
+        render_context: &mut dyn RenderContext,
         _input: &ResourceSlots,
         output: &mut ResourceSlots,
     ) {
         const WINDOW_TEXTURE: usize = 0;
-        let xr_configuration_state = world.get_resource::<XRConfigurationState>().unwrap();
-
         let render_state = world.get_resource::<XRConfigurationState>().unwrap();
 
+        let (texture_view_ids, next_index) = match self.secondary_view {
+            None => (
+                &render_state.texture_view_ids,
+                Some(render_state.next_swap_chain_index),
+            ),
+            Some(index) => match render_state.secondary_views.get(index) {
+                Some(target) => (&target.texture_view_ids, target.next_image_index),
+                None => (&None, None),
+            },
+        };
+
+        // texture views change when the swapchain is recreated
+        if *texture_view_ids != self.texture_view_ids {
+            self.texture_view_ids = texture_view_ids.clone();
+            self.resource_ids = self.texture_view_ids.as_ref().map(|texture_view_ids| {
+                texture_view_ids
+                    .iter()
+                    .map(|id| RenderResourceId::Texture(*id))
+                    .collect()
+            });
+        }
+
         let resource_ids = match &self.resource_ids {
             Some(resource_ids) => resource_ids,
+            None => return,
+        };
+
+        // the graph needs an output even when the secondary view is not rendered in this frame
+        let index = match next_index {
+            Some(index) => index,
+            None if output.get(WINDOW_TEXTURE).is_none() => 0,
+            None => return,
+        };
+
+        // get next texture by id
+        let render_resource_id = match resource_ids.get(index) {
+            Some(render_resource_id) => render_resource_id,
             None => {
-                if let Some(texture_view_ids) = &render_state.texture_view_ids {
-                    self.resource_ids = Some(
-                        texture_view_ids
-                            .iter()
-                            .map(|id| RenderResourceId::Texture(*id))
-                            .collect(),
-                    );
-                    self.resource_ids.as_ref().unwrap()
-                } else {
-                    return;
-                }
+                warn!(
+                    target: "bevy_openxr::swapchain",
+                    index,
+                    image_count = resource_ids.len(),
+                    "Swapchain image index out of range, skipping"
+                );
+                return;
             }
         };
 
-        // get next texture by id
-        let render_resource_id = resource_ids
-            .get(xr_configuration_state.next_swap_chain_index)
-            .unwrap();
+        if let (RenderResourceId::Texture(texture), None) =
+            (render_resource_id, self.secondary_view)
+        {
+            self.binding.set(
+                render_context.resources(),
+                &XR_SWAPCHAIN_TEXTURE_HANDLE,
+                *texture,
+            );
+        }
 
         // set output to desired resource id
         output.set(WINDOW_TEXTURE, render_resource_id.clone());
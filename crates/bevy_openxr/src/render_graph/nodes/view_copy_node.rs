@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+
+use bevy::ecs::world::World;
+use bevy::render::{
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{RenderContext, RenderResourceId, RenderResourceType},
+    texture::Extent3d,
+};
+use bevy_openxr_core::XRConfigurationState;
+
+/// Copies views rendered in separate passes into their rects of the swapchain image, for stereo
+/// modes other than multiview (`XRStereoMode::DoubleWide`, `XRStereoMode::TwoPass`).
+///
+/// Input slot `TARGET` is the swapchain image (or a texture of the same layout), and input slot
+/// `view_name(view_index)` the texture of each view, sized like a single view
+pub struct XRViewCopyNode {
+    inputs: Vec<ResourceSlotInfo>,
+}
+
+impl XRViewCopyNode {
+    /// Input slot with the texture the views are copied into
+    pub const TARGET: &'static str = "target";
+
+    pub fn new(view_count: u32) -> Self {
+        let inputs = std::iter::once(Cow::Borrowed(XRViewCopyNode::TARGET))
+            .chain((0..view_count).map(|view_index| Cow::Owned(Self::view_name(view_index))))
+            .map(|name| ResourceSlotInfo {
+                name,
+                resource_type: RenderResourceType::Texture,
+            })
+            .collect();
+
+        XRViewCopyNode { inputs }
+    }
+
+    /// Input slot with the texture of the view
+    pub fn view_name(view_index: u32) -> String {
+        format!("view_{}", view_index)
+    }
+}
+
+impl Node for XRViewCopyNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        &self.inputs
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const TARGET_TEXTURE: usize = 0;
+        let target = match input.get(TARGET_TEXTURE) {
+            Some(RenderResourceId::Texture(texture)) => texture,
+            _ => return,
+        };
+
+        let render_state = world.get_resource::<XRConfigurationState>().unwrap();
+        let view_surface = match &render_state.last_view_surface {
+            Some(view_surface) => view_surface,
+            None => return,
+        };
+
+        for view_index in 0..self.inputs.len() as u32 - 1 {
+            let texture = match input.get(view_index as usize + 1) {
+                Some(RenderResourceId::Texture(texture)) => texture,
+                _ => continue,
+            };
+
+            // whole area of the view, the compositor reads the scaled sub-rect at its top-left
+            let rect = view_surface.stereo_mode.view_rect(
+                view_index,
+                view_surface.width,
+                (view_surface.width, view_surface.height),
+                1.,
+            );
+
+            render_context.copy_texture_to_texture(
+                texture,
+                [0, 0, 0],
+                0,
+                target,
+                [rect.x, rect.y, rect.layer],
+                0,
+                Extent3d::new(rect.width, rect.height, 1),
+            );
+        }
+    }
+}
@@ -2,26 +2,87 @@ use bevy::ecs::world::World;
 use bevy::render::{
     render_graph::{Node, ResourceSlotInfo, ResourceSlots, WindowTextureNode},
     renderer::{RenderContext, RenderResourceId, RenderResourceType},
-    texture::TextureDescriptor,
+    texture::{TextureDescriptor, TextureFormat},
 };
-use bevy_openxr_core::event::XRViewSurfaceCreated;
 use bevy_openxr_core::XRConfigurationState;
 use std::borrow::Cow;
 
 /// MAIN_SAMPLED_COLOR_ATTACHMENT node in OpenXR implementation, used instead of `WindowTextureNode`
-/// otherwise matches `WindowTextureNode`, except the descriptor.size (`Extent3d`) is set from XR viewport events
+/// otherwise matches `WindowTextureNode`, except the descriptor.size (`Extent3d`) is set from XR viewport events.
+///
+/// Output slot `OUT_TEXTURE` is a texture sized like the swapchain images, by default also with the
+/// same array layers (`XRViewSurfaceCreated::view_count` for multiview, so mono configurations get a
+/// single layer). With `with_single_view` the texture is sized like a single view instead, and with
+/// `with_secondary_view` like the swapchain images of a secondary view. With `with_swapchain_format`
+/// the texture also has the format of the swapchain images
 pub struct XRWindowTextureNode {
     descriptor: TextureDescriptor,
-    last_view_surface: Option<XRViewSurfaceCreated>,
+
+    /// Array layers of the texture, if not the layers of the swapchain images
+    layers: Option<u32>,
+
+    /// Size of a single view, for passes rendering one view
+    single_view: bool,
+
+    /// Index of the secondary view the texture is sized for
+    secondary_view: Option<usize>,
+
+    /// Format of the swapchain images, instead of the format of the descriptor
+    swapchain_format: bool,
+
+    /// Size of the texture, width, height and array layers
+    last_extent: Option<(u32, u32, u32)>,
 }
 
 impl XRWindowTextureNode {
+    /// Output slot with the texture
+    pub const OUT_TEXTURE: &'static str = WindowTextureNode::OUT_TEXTURE;
+
     pub fn new(descriptor: TextureDescriptor) -> Self {
         XRWindowTextureNode {
             descriptor,
-            last_view_surface: None,
+            layers: None,
+            single_view: false,
+            secondary_view: None,
+            swapchain_format: false,
+            last_extent: None,
         }
     }
+
+    pub fn with_format(mut self, format: TextureFormat) -> Self {
+        self.descriptor.format = format;
+        self
+    }
+
+    pub fn with_layers(mut self, layers: u32) -> Self {
+        self.layers = Some(layers);
+        self
+    }
+
+    /// Texture of a single view (`XRViewSurfaceCreated::width` x `height`, one layer), e.g. the
+    /// target of a pass rendering one view
+    pub fn with_single_view(mut self) -> Self {
+        self.single_view = true;
+        self
+    }
+
+    /// Texture sized like the swapchain images of the secondary view (one layer), e.g. the target of
+    /// the pass rendering the view
+    pub fn with_secondary_view(mut self, index: usize) -> Self {
+        self.secondary_view = Some(index);
+        self
+    }
+
+    /// Texture in the format of the swapchain images (`XRViewSurfaceCreated::format`), e.g. a texture
+    /// copied into them
+    pub fn with_swapchain_format(mut self) -> Self {
+        self.swapchain_format = true;
+        self
+    }
+
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
 }
 
 impl Node for XRWindowTextureNode {
@@ -45,25 +106,56 @@ impl Node for XRWindowTextureNode {
         // TODO performance use Change detection? (takes ~10 microseconds now, not too bad)
         let render_state = world.get_resource::<XRConfigurationState>().unwrap(); // can't be an event, as this doesn't run when event is sent
 
-        if render_state.last_view_surface != self.last_view_surface {
-            if let Some(last_view_surface) = &render_state.last_view_surface {
-                // Configure texture size. This usually happens only at the start of openxr session
-                let render_resource_context = render_context.resources_mut();
-                if let Some(RenderResourceId::Texture(old_texture)) = output.get(WINDOW_TEXTURE) {
-                    render_resource_context.remove_texture(old_texture);
-                }
-
-                self.descriptor.size.width = last_view_surface.width;
-                self.descriptor.size.height = last_view_surface.height;
+        let extent =
+            match self.secondary_view {
+                Some(index) => render_state
+                    .secondary_views
+                    .get(index)
+                    .and_then(|target| target.surface.as_ref())
+                    .map(|surface| (surface.width, surface.height, 1)),
+                // same layout as the swapchain images, e.g. one texture array layer per view
+                None => render_state.last_view_surface.as_ref().map(|view_surface| {
+                    match self.single_view {
+                        true => (view_surface.width, view_surface.height, 1),
+                        false => view_surface.stereo_mode.image_extent(
+                            view_surface.width,
+                            view_surface.height,
+                            view_surface.view_count,
+                        ),
+                    }
+                }),
+            };
 
-                // using GL multiview, two eyes - FIXME: eventually set the depth based on view count from event data
-                self.descriptor.size.depth_or_array_layers = 2;
+        let format = match self.swapchain_format {
+            true => render_state
+                .last_view_surface
+                .as_ref()
+                .map(|view_surface| view_surface.format),
+            false => None,
+        };
+        let format_changed = format.map_or(false, |format| format != self.descriptor.format);
 
-                let texture_resource = render_resource_context.create_texture(self.descriptor);
-                output.set(WINDOW_TEXTURE, RenderResourceId::Texture(texture_resource));
+        let last_extent = self.last_extent;
+        if let Some((width, height, layers)) =
+            extent.filter(|extent| last_extent != Some(*extent) || format_changed)
+        {
+            // Configure texture size. This usually happens only at the start of openxr session
+            let render_resource_context = render_context.resources_mut();
+            if let Some(RenderResourceId::Texture(old_texture)) = output.get(WINDOW_TEXTURE) {
+                render_resource_context.remove_texture(old_texture);
+            }
 
-                self.last_view_surface = Some(last_view_surface.clone());
+            self.descriptor.size.width = width;
+            self.descriptor.size.height = height;
+            self.descriptor.size.depth_or_array_layers = self.layers.unwrap_or(layers);
+            if let Some(format) = format {
+                self.descriptor.format = format;
             }
+
+            let texture_resource = render_resource_context.create_texture(self.descriptor);
+            output.set(WINDOW_TEXTURE, RenderResourceId::Texture(texture_resource));
+
+            self.last_extent = extent;
         }
     }
 }
@@ -0,0 +1,187 @@
+use std::borrow::Cow;
+
+use bevy::{
+    ecs::world::World,
+    render::{
+        render_graph::{
+            base::node, Edge, Node, NodeId, RenderGraph, ResourceSlotInfo, ResourceSlots,
+        },
+        renderer::RenderContext,
+        texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    },
+    utils::tracing::debug,
+};
+
+use super::nodes::XRWindowTextureNode;
+
+/// Post-processing passes (e.g. tone mapping, color grading, vignette) run between the main pass
+/// and the XR swapchain. Insert the resource before `OpenXRWgpuPlugin` builds the render graph at startup.
+///
+/// Passes are chained in the order they are added. Each pass node must have input slots named
+/// `XRPostProcessing::SOURCE` (texture to read, rendered by the main pass or the previous pass)
+/// and `XRPostProcessing::TARGET` (texture to write, the swapchain for the last pass). Both textures
+/// have the layout of the swapchain, e.g. one array layer per view, and pass pipelines sample the
+/// layer of each view themselves. Nodes drawing over the swapchain after the main pass (e.g. the UI
+/// pass) run after the last pass
+#[derive(Default)]
+pub struct XRPostProcessing {
+    passes: Vec<(Cow<'static, str>, Box<dyn Node>)>,
+}
+
+impl XRPostProcessing {
+    pub const SOURCE: &'static str = "source";
+    pub const TARGET: &'static str = "target";
+
+    pub fn add_pass<T: Node>(&mut self, name: impl Into<Cow<'static, str>>, node: T) -> &mut Self {
+        self.passes.push((name.into(), Box::new(node)));
+        self
+    }
+}
+
+/// Intermediate texture rendered by the main pass or a post-processing pass
+fn intermediate_texture_name(index: usize) -> String {
+    format!("xr_post_process_texture_{}", index)
+}
+
+pub(crate) fn add_post_process_passes(graph: &mut RenderGraph, post_processing: XRPostProcessing) {
+    if post_processing.passes.is_empty() {
+        return;
+    }
+
+    let swapchain_id = graph.get_node_id(node::PRIMARY_SWAP_CHAIN).unwrap();
+    let main_pass_id = graph.get_node_id(node::MAIN_PASS).unwrap();
+
+    // slots reading the swapchain
+    let swapchain_edges = graph
+        .get_node_state(swapchain_id)
+        .unwrap()
+        .edges
+        .output_edges
+        .iter()
+        .filter_map(|edge| match edge {
+            Edge::SlotEdge {
+                input_node,
+                input_index,
+                output_index,
+                ..
+            } => Some((*input_node, *input_index, *output_index)),
+            Edge::NodeEdge { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    let descriptor = TextureDescriptor {
+        size: Extent3d::new(1, 1, 1),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        // replaced by the format of the swapchain images, which the main pass would render into
+        format: TextureFormat::default(),
+        usage: TextureUsage::RENDER_ATTACHMENT | TextureUsage::SAMPLED,
+    };
+
+    let pass_count = post_processing.passes.len();
+    let mut previous_pass: Option<NodeId> = None;
+
+    for (index, (name, node)) in post_processing.passes.into_iter().enumerate() {
+        debug!(target: "bevy_openxr", pass = %name, "Adding XR post-processing pass");
+
+        let source = intermediate_texture_name(index);
+        graph.add_node(
+            source.clone(),
+            XRWindowTextureNode::new(descriptor).with_swapchain_format(),
+        );
+
+        let pass_id = graph.add_node(name, BoxedNode(node));
+        graph
+            .add_slot_edge(
+                source.clone(),
+                XRWindowTextureNode::OUT_TEXTURE,
+                pass_id,
+                XRPostProcessing::SOURCE,
+            )
+            .unwrap();
+
+        match previous_pass {
+            // main pass renders into the first intermediate texture instead of the swapchain
+            None => {
+                for (input_node, input_index, output_index) in swapchain_edges.iter() {
+                    if *input_node != main_pass_id {
+                        continue;
+                    }
+
+                    graph
+                        .remove_slot_edge(swapchain_id, *output_index, main_pass_id, *input_index)
+                        .unwrap();
+                    graph
+                        .add_slot_edge(
+                            source.clone(),
+                            XRWindowTextureNode::OUT_TEXTURE,
+                            main_pass_id,
+                            *input_index,
+                        )
+                        .unwrap();
+                }
+
+                graph.add_node_edge(main_pass_id, pass_id).unwrap();
+            }
+            Some(previous_pass) => {
+                graph
+                    .add_slot_edge(
+                        source,
+                        XRWindowTextureNode::OUT_TEXTURE,
+                        previous_pass,
+                        XRPostProcessing::TARGET,
+                    )
+                    .unwrap();
+                graph.add_node_edge(previous_pass, pass_id).unwrap();
+            }
+        }
+
+        if index + 1 == pass_count {
+            graph
+                .add_slot_edge(
+                    swapchain_id,
+                    super::nodes::XRSwapchainNode::OUT_TEXTURE,
+                    pass_id,
+                    XRPostProcessing::TARGET,
+                )
+                .unwrap();
+
+            // other nodes drawing into the swapchain draw over the post-processed image
+            for (input_node, _, _) in swapchain_edges.iter() {
+                if *input_node != main_pass_id {
+                    graph.add_node_edge(pass_id, *input_node).unwrap();
+                }
+            }
+        }
+
+        previous_pass = Some(pass_id);
+    }
+}
+
+/// Post-processing pass stored in `XRPostProcessing`, added to the render graph as is
+struct BoxedNode(Box<dyn Node>);
+
+impl Node for BoxedNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        self.0.input()
+    }
+
+    fn output(&self) -> &[ResourceSlotInfo] {
+        self.0.output()
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        self.0.prepare(world)
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        self.0.update(world, render_context, input, output)
+    }
+}
@@ -1,19 +1,14 @@
 use bevy::{prelude::*, render::renderer::TextureId};
-use bevy_openxr_core::{event::XRState, XRConfigurationState, XRDevice};
+use bevy_openxr_core::{event::XrFrameStatus, XRConfigurationState, XRDevice};
 
 pub(crate) fn pre_render_system(
     mut xr_device: ResMut<XRDevice>,
     wgpu_handles: ResMut<bevy::wgpu::WgpuRendererHandles>,
     mut wgpu_render_state: ResMut<bevy::wgpu::WgpuRenderState>,
     mut xr_configuration_state: ResMut<XRConfigurationState>,
+    mut frame_status: ResMut<XrFrameStatus>,
 ) {
-    let (state, texture_views) = xr_device.prepare_update(&wgpu_handles.device);
-
-    let should_render = if let XRState::Running = state {
-        true
-    } else {
-        false
-    };
+    let (status, texture_views) = xr_device.prepare_update(&wgpu_handles.device);
 
     if let Some(texture_views) = texture_views {
         wgpu_render_state.add_textures = texture_views
@@ -34,16 +29,54 @@ pub(crate) fn pre_render_system(
         );
     }
 
-    if should_render {
-        xr_configuration_state.next_swap_chain_index = xr_device
-            .get_swapchain_mut()
-            .unwrap()
-            .get_next_swapchain_image_index();
+    for (index, texture_views) in xr_device.take_secondary_texture_views() {
+        let texture_views = texture_views
+            .into_iter()
+            .map(|texture_view| bevy::wgpu::TextureView {
+                id: TextureId::new(),
+                texture_view,
+            })
+            .collect::<Vec<_>>();
+
+        xr_configuration_state
+            .secondary_view_mut(index)
+            .texture_view_ids = Some(texture_views.iter().map(|tv| tv.id).collect());
+        wgpu_render_state.add_textures.extend(texture_views);
+    }
+
+    let should_render = status.should_render()
+        && !xr_device.is_headless()
+        && match xr_device.get_next_swapchain_image_index() {
+            Some(index) => {
+                xr_configuration_state.next_swap_chain_index = index;
+                true
+            }
+            None => false,
+        };
+
+    // secondary views are rendered with the primary views, once their render graph nodes are added
+    for (index, target) in xr_configuration_state
+        .secondary_views
+        .iter_mut()
+        .enumerate()
+    {
+        target.next_image_index = match should_render && target.rendered {
+            true => xr_device.get_next_secondary_image_index(index),
+            false => None,
+        };
     }
 
+    *frame_status = match should_render {
+        true => XrFrameStatus::ShouldRender,
+        false => XrFrameStatus::Skip,
+    };
     wgpu_render_state.should_render = should_render;
 }
 
-pub(crate) fn post_render_system(mut xr_device: ResMut<XRDevice>) {
-    xr_device.finalize_update();
+pub(crate) fn post_render_system(world: &mut World) {
+    // swapchain image is readable until finalize_update releases it
+    world.resource_scope(|world, mut xr_device: Mut<XRDevice>| {
+        crate::capture::capture_frame(world, &xr_device);
+        xr_device.finalize_update();
+    });
 }
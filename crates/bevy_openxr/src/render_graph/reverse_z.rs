@@ -0,0 +1,84 @@
+use bevy::{
+    asset::{AssetEvent, Assets, Handle},
+    ecs::prelude::*,
+    render::{
+        pipeline::{CompareFunction, PipelineDescriptor},
+        prelude::Msaa,
+        render_graph::{
+            base::{camera, node},
+            RenderGraph,
+        },
+    },
+    utils::tracing::debug,
+};
+
+use super::{camera::camera::XRCameraConfig, xr_render_graph::main_pass_node};
+
+/// Replaces the main pass of the default graph with one clearing depth to 0 (the far plane with
+/// reverse-z). Otherwise matches the main pass of the default graph
+pub(crate) fn replace_main_pass(graph: &mut RenderGraph, msaa: &Msaa) {
+    let pass_node = main_pass_node(msaa, 0., &[camera::CAMERA_3D, camera::CAMERA_2D]);
+    graph.replace_node(node::MAIN_PASS, pass_node).unwrap();
+}
+
+/// Flips the depth test of pipelines for reverse-z. Pipelines added after startup are flipped when
+/// they are created
+pub(crate) fn reverse_z_pipeline_system(
+    config: Res<XRCameraConfig>,
+    mut initialized: Local<bool>,
+    mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    mut pipeline_events: EventReader<AssetEvent<PipelineDescriptor>>,
+) {
+    if !config.reverse_z {
+        return;
+    }
+
+    let handles = if *initialized {
+        pipeline_events
+            .iter()
+            .filter_map(|event| match event {
+                AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                    Some(handle.clone_weak())
+                }
+                AssetEvent::Removed { .. } => None,
+            })
+            .collect::<Vec<_>>()
+    } else {
+        // default pipelines are added untracked, without events
+        *initialized = true;
+        pipelines
+            .iter()
+            .map(|(id, _)| Handle::weak(id))
+            .collect::<Vec<_>>()
+    };
+
+    for handle in handles {
+        let needs_flip = pipelines
+            .get(&handle)
+            .and_then(|pipeline| pipeline.depth_stencil.as_ref())
+            .and_then(|depth_stencil| reversed_compare(depth_stencil.depth_compare))
+            .is_some();
+
+        // only accessed mutably if changed, as that sends a `Modified` event
+        if !needs_flip {
+            continue;
+        }
+
+        if let Some(depth_stencil) = pipelines
+            .get_mut(&handle)
+            .and_then(|pipeline| pipeline.depth_stencil.as_mut())
+        {
+            let compare = reversed_compare(depth_stencil.depth_compare).unwrap();
+            debug!(target: "bevy_openxr", from = ?depth_stencil.depth_compare, to = ?compare, "Flipping pipeline depth test for reverse-z");
+            depth_stencil.depth_compare = compare;
+        }
+    }
+}
+
+fn reversed_compare(compare: CompareFunction) -> Option<CompareFunction> {
+    match compare {
+        CompareFunction::Less => Some(CompareFunction::Greater),
+        CompareFunction::LessEqual => Some(CompareFunction::GreaterEqual),
+        _ => None,
+    }
+}
@@ -0,0 +1,275 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{Camera, CameraProjection},
+        prelude::Msaa,
+        render_graph::{
+            base::{camera, node, MainPass},
+            Edge, Node, PassNode, RenderGraph, ResourceSlotInfo, ResourceSlots,
+        },
+        renderer::RenderContext,
+    },
+    utils::tracing::{info, warn},
+};
+use bevy_openxr_core::{
+    event::{XRSecondaryViewSurfaceCreated, XRSecondaryViewsUpdated},
+    math::XRMatrixComputation,
+    XRConfigurationState,
+};
+
+use super::{
+    camera::{
+        camera::{XRCameraBundle, XRCameraConfig},
+        projection::XRProjection,
+    },
+    nodes::{XRSwapchainNode, XRWindowTextureNode},
+    xr_render_graph::{add_xr_camera_node, main_pass_node},
+};
+
+/// Cameras rendering the secondary views, by index of the secondary view
+pub const XR_SECONDARY_CAMERAS: [&str; 2] = ["xr_camera_secondary_0", "xr_camera_secondary_1"];
+
+/// XR camera rendering a secondary view (`XrOptions::secondary_view_types`), posed and projected by
+/// `XRSecondaryViewsUpdated` instead of the views of the primary view configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XRSecondaryViewCamera {
+    /// Index of the secondary view
+    pub index: usize,
+}
+
+/// Adds a pass per secondary view on its first activation, drawing an XR camera of the view
+/// (`XR_SECONDARY_CAMERAS`) into the swapchain of the view. The pass draws the entities of the main
+/// pass, after the nodes preceding the main pass. Post-processing, the UI pass and `CAMERA_2D` are
+/// not drawn into secondary views
+pub(crate) fn secondary_view_passes_system(
+    mut commands: Commands,
+    mut installed: Local<Vec<usize>>,
+    mut graph: ResMut<RenderGraph>,
+    (msaa, camera_config): (Res<Msaa>, Res<XRCameraConfig>),
+    mut configuration_state: ResMut<XRConfigurationState>,
+    mut surface_created: EventReader<XRSecondaryViewSurfaceCreated>,
+    xr_cameras: Query<
+        (&Camera, Option<&Parent>),
+        (With<XRProjection>, Without<XRSecondaryViewCamera>),
+    >,
+) {
+    for surface in surface_created.iter() {
+        let index = surface.index;
+        if !installed.contains(&index) {
+            let camera_name = match XR_SECONDARY_CAMERAS.get(index) {
+                Some(camera_name) => *camera_name,
+                None => {
+                    warn!(target: "bevy_openxr", index, view_type = ?surface.view_type, "Rendering only the first two secondary views");
+                    continue;
+                }
+            };
+
+            if !add_secondary_view_pass(&mut graph, &msaa, camera_config.reverse_z, index) {
+                warn!(target: "bevy_openxr", view_type = ?surface.view_type, "No main pass in the render graph, secondary views must be rendered by the app");
+                continue;
+            }
+            info!(target: "bevy_openxr", index, view_type = ?surface.view_type, width = surface.width, height = surface.height, "Rendering secondary view");
+
+            // secondary views are posed in the reference space, as siblings of the XR camera
+            let parent = xr_cameras
+                .iter()
+                .find(|(camera, _)| camera.name.as_deref() == Some(camera::CAMERA_3D))
+                .and_then(|(_, parent)| parent)
+                .map(|parent| parent.0);
+
+            let view_camera = commands
+                .spawn_bundle(XRCameraBundle::with_name(camera_name))
+                .insert(XRSecondaryViewCamera { index })
+                .id();
+
+            if let Some(parent) = parent {
+                commands.entity(parent).push_children(&[view_camera]);
+            }
+
+            installed.push(index);
+        }
+
+        // also after swapchain recreation, which recreates the swapchains of the secondary views
+        let target = configuration_state.secondary_view_mut(index);
+        if target.surface.as_ref() == Some(surface) {
+            target.rendered = true;
+        }
+    }
+}
+
+/// Sets the poses and projections of the secondary view cameras located for the frame
+pub(crate) fn secondary_view_cameras_system(
+    config: Res<XRCameraConfig>,
+    mut views_updated: EventReader<XRSecondaryViewsUpdated>,
+    mut cameras: Query<(
+        &XRSecondaryViewCamera,
+        &mut Camera,
+        &mut XRProjection,
+        &mut Transform,
+    )>,
+) {
+    let event = match views_updated.iter().last() {
+        Some(event) => event,
+        None => return,
+    };
+
+    for (view_camera, mut camera, mut camera_projection, mut transform) in cameras.iter_mut() {
+        let view = match event
+            .views
+            .iter()
+            .find(|view| view.index == view_camera.index)
+        {
+            Some(view) => view,
+            None => continue,
+        };
+
+        camera_projection.near = config.near;
+        camera_projection.far = config.projection_far();
+        camera_projection.reverse_z = config.reverse_z;
+
+        camera.depth_calculation = camera_projection.depth_calculation();
+        camera.projection_matrices = vec![camera_projection.get_projection_matrix_fov(&view.fov)];
+        camera.position_matrices = vec![view.transform.compute_xr_matrix()];
+
+        *transform = view.transform;
+    }
+}
+
+/// Adds the pass of the secondary view, with its swapchain and depth texture. `false` if the graph
+/// has no main pass (or view passes, see `view_passes`) to draw the entities of
+fn add_secondary_view_pass(
+    graph: &mut RenderGraph,
+    msaa: &Msaa,
+    reverse_z: bool,
+    index: usize,
+) -> bool {
+    let main_pass_id = match graph
+        .get_node_id(node::MAIN_PASS)
+        .or_else(|_| graph.get_node_id("xr_view_pass_0"))
+    {
+        Ok(main_pass_id) => main_pass_id,
+        Err(_) => return false,
+    };
+
+    // e.g. render resource nodes of materials and lights
+    let preceding_nodes = graph
+        .get_node_state(main_pass_id)
+        .unwrap()
+        .edges
+        .input_edges
+        .iter()
+        .filter_map(|edge| match edge {
+            Edge::NodeEdge { output_node, .. } => Some(*output_node),
+            Edge::SlotEdge { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    let depth_descriptor = match graph.get_node::<XRWindowTextureNode>(node::MAIN_DEPTH_TEXTURE) {
+        Ok(depth_texture) => *depth_texture.descriptor(),
+        Err(_) => return false,
+    };
+
+    let camera_name = XR_SECONDARY_CAMERAS[index];
+    let depth_clear = if reverse_z { 0. } else { 1. };
+    let pass_id = graph.add_node(
+        format!("xr_secondary_pass_{}", index),
+        XRSecondaryPassNode {
+            index,
+            pass: main_pass_node(msaa, depth_clear, &[camera_name]),
+        },
+    );
+
+    let swapchain_id = graph.add_node(
+        format!("xr_secondary_swapchain_{}", index),
+        XRSwapchainNode::secondary(index),
+    );
+    let depth_id = graph.add_node(
+        format!("xr_secondary_depth_{}", index),
+        XRWindowTextureNode::new(depth_descriptor).with_secondary_view(index),
+    );
+
+    // with msaa, the pass resolves into the swapchain image
+    let color_slot = match msaa.samples > 1 {
+        true => "color_resolve_target",
+        false => "color_attachment",
+    };
+
+    if msaa.samples > 1 {
+        let sampled_descriptor = *graph
+            .get_node::<XRWindowTextureNode>(node::MAIN_SAMPLED_COLOR_ATTACHMENT)
+            .unwrap()
+            .descriptor();
+
+        let sampled_id = graph.add_node(
+            format!("xr_secondary_sampled_color_{}", index),
+            XRWindowTextureNode::new(sampled_descriptor).with_secondary_view(index),
+        );
+        graph
+            .add_slot_edge(
+                sampled_id,
+                XRWindowTextureNode::OUT_TEXTURE,
+                pass_id,
+                "color_attachment",
+            )
+            .unwrap();
+    }
+
+    graph
+        .add_slot_edge(
+            swapchain_id,
+            XRSwapchainNode::OUT_TEXTURE,
+            pass_id,
+            color_slot,
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(depth_id, XRWindowTextureNode::OUT_TEXTURE, pass_id, "depth")
+        .unwrap();
+
+    for preceding in preceding_nodes {
+        graph.add_node_edge(preceding, pass_id).unwrap();
+    }
+
+    add_xr_camera_node(graph, camera_name, pass_id).unwrap();
+
+    true
+}
+
+/// Main pass of a secondary view, skipped in frames the view is not rendered (inactive, or its
+/// swapchain image was not acquired)
+struct XRSecondaryPassNode {
+    index: usize,
+    pass: PassNode<&'static MainPass>,
+}
+
+impl Node for XRSecondaryPassNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        self.pass.input()
+    }
+
+    fn output(&self) -> &[ResourceSlotInfo] {
+        self.pass.output()
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        self.pass.prepare(world)
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        let render_state = world.get_resource::<XRConfigurationState>().unwrap();
+        let rendered = render_state
+            .secondary_views
+            .get(self.index)
+            .map_or(false, |target| target.next_image_index.is_some());
+
+        if rendered {
+            self.pass.update(world, render_context, input, output)
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::{
+    asset::HandleUntyped,
+    reflect::TypeUuid,
+    render::{
+        renderer::{RenderResourceContext, RenderResourceId, SamplerId, TextureId},
+        texture::{SamplerDescriptor, Texture, SAMPLER_ASSET_INDEX, TEXTURE_ASSET_INDEX},
+    },
+};
+
+/// Swapchain image of the frame, for render graph nodes and materials sampling it (e.g. mirror or
+/// post-processing passes). Bound when `XRSwapchainNode` updates, so usable by nodes running after
+/// it. The image is a texture array with the layout of `XRStereoMode`, and sampling it requires
+/// `SAMPLED` in `XrOptions::swapchain_usage`. Passes drawing into the swapchain can't sample it
+pub const XR_SWAPCHAIN_TEXTURE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Texture::TYPE_UUID, 0x2c6a_91f4_d03b_5e87);
+
+/// Copy of the swapchain image of the previous frame, e.g. for portals or feedback effects sampling
+/// last frame's eye texture. Same layout as `XR_SWAPCHAIN_TEXTURE_HANDLE`. Enabled with
+/// `XRRenderTextures::previous_frame`
+pub const XR_PREVIOUS_FRAME_TEXTURE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Texture::TYPE_UUID, 0x7e13_0b5d_48c2_f9a6);
+
+/// XR render textures bound to `Texture` handles, in addition to `XR_SWAPCHAIN_TEXTURE_HANDLE`.
+/// Insert the resource before `OpenXRWgpuPlugin` builds the render graph at startup.
+///
+/// The handles have no `Assets<Texture>` entry, they are bound to the render resources directly:
+/// use them as e.g. `XR_PREVIOUS_FRAME_TEXTURE_HANDLE.typed::<Texture>()` in materials
+#[derive(Debug, Clone, Default)]
+pub struct XRRenderTextures {
+    /// Copy the swapchain image into `XR_PREVIOUS_FRAME_TEXTURE_HANDLE` after the passes drawing
+    /// into it. Requires `COPY_SRC` in `XrOptions::swapchain_usage` (set by default)
+    pub previous_frame: bool,
+}
+
+/// Texture and sampler bound to a texture handle
+#[derive(Default)]
+pub(crate) struct TextureHandleBinding {
+    texture: Option<TextureId>,
+    sampler: Option<SamplerId>,
+}
+
+impl TextureHandleBinding {
+    /// Binds `texture` to `handle`, with a sampler created on first use
+    pub(crate) fn set(
+        &mut self,
+        render_resources: &dyn RenderResourceContext,
+        handle: &HandleUntyped,
+        texture: TextureId,
+    ) {
+        if self.texture == Some(texture) {
+            return;
+        }
+
+        let sampler = *self
+            .sampler
+            .get_or_insert_with(|| render_resources.create_sampler(&SamplerDescriptor::default()));
+
+        render_resources.set_asset_resource_untyped(
+            handle.clone_weak(),
+            RenderResourceId::Texture(texture),
+            TEXTURE_ASSET_INDEX,
+        );
+        render_resources.set_asset_resource_untyped(
+            handle.clone_weak(),
+            RenderResourceId::Sampler(sampler),
+            SAMPLER_ASSET_INDEX,
+        );
+        self.texture = Some(texture);
+    }
+}
@@ -0,0 +1,230 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::Camera,
+        prelude::Msaa,
+        render_graph::{
+            base::{camera, node},
+            Edge, NodeId, RenderGraph,
+        },
+        texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    },
+    utils::tracing::{info, warn},
+};
+use bevy_openxr_core::{event::XRViewSurfaceCreated, XRStereoMode};
+
+use super::{
+    camera::{
+        camera::{XRCameraBundle, XRCameraConfig, XREye},
+        projection::XRProjection,
+    },
+    nodes::{node as xr_node, XRViewCopyNode, XRWindowTextureNode},
+    xr_render_graph::{add_xr_camera_node, main_pass_node},
+};
+
+/// Camera rendering the left view in stereo modes other than multiview
+pub const XR_LEFT_CAMERA: &str = "xr_camera_left";
+
+/// Camera rendering the right view in stereo modes other than multiview
+pub const XR_RIGHT_CAMERA: &str = "xr_camera_right";
+
+const VIEW_CAMERAS: [(&str, XREye); 2] = [
+    (XR_LEFT_CAMERA, XREye::Left),
+    (XR_RIGHT_CAMERA, XREye::Right),
+];
+
+/// Renders the views in a pass each when the swapchain is created with a stereo mode other than
+/// multiview (`XRStereoMode::DoubleWide`, `XRStereoMode::TwoPass`): the main pass is replaced by a
+/// pass per view, drawing an XR camera of one eye (`XR_LEFT_CAMERA`, `XR_RIGHT_CAMERA`) into a
+/// texture of the view, which `XRViewCopyNode` copies into the view rect of the swapchain image.
+/// Other passes drawing into the swapchain (e.g. the UI pass) still draw all views in one pass, and
+/// `CAMERA_2D` is not drawn
+pub(crate) fn view_passes_system(
+    mut commands: Commands,
+    mut installed: Local<bool>,
+    mut graph: ResMut<RenderGraph>,
+    (msaa, camera_config): (Res<Msaa>, Res<XRCameraConfig>),
+    mut view_surface_created: EventReader<XRViewSurfaceCreated>,
+    xr_cameras: Query<(&Camera, Option<&Parent>), With<XRProjection>>,
+) {
+    let view_surface = match view_surface_created.iter().last() {
+        Some(view_surface) => view_surface,
+        None => return,
+    };
+
+    if *installed || view_surface.stereo_mode == XRStereoMode::Multiview {
+        return;
+    }
+    *installed = true;
+
+    let view_count = view_surface.view_count.min(VIEW_CAMERAS.len() as u32);
+    if view_surface.view_count > view_count {
+        warn!(target: "bevy_openxr", view_count = view_surface.view_count, "Rendering only the first two views in separate passes");
+    }
+
+    if !add_view_passes(&mut graph, &msaa, camera_config.reverse_z, view_count) {
+        warn!(target: "bevy_openxr", stereo_mode = ?view_surface.stereo_mode, "No main pass in the render graph, views must be rendered in a pass each by the app");
+        return;
+    }
+    info!(target: "bevy_openxr", stereo_mode = ?view_surface.stereo_mode, view_count, "Rendering views in separate passes");
+
+    // view cameras are posed in the reference space, as siblings of the XR camera
+    let parent = xr_cameras
+        .iter()
+        .find(|(camera, _)| camera.name.as_deref() == Some(camera::CAMERA_3D))
+        .and_then(|(_, parent)| parent)
+        .map(|parent| parent.0);
+
+    for (name, eye) in VIEW_CAMERAS.iter().take(view_count as usize) {
+        let view_camera = commands
+            .spawn_bundle(XRCameraBundle::with_name(name).with_eye(*eye))
+            .id();
+
+        if let Some(parent) = parent {
+            commands.entity(parent).push_children(&[view_camera]);
+        }
+    }
+}
+
+/// Replaces the main pass with a pass per view, and a node copying the views into the texture the
+/// main pass rendered into (the swapchain, or the first post-processing texture). `false` if the
+/// graph has no main pass, e.g. a custom graph (see `connect_xr_targets`)
+fn add_view_passes(graph: &mut RenderGraph, msaa: &Msaa, reverse_z: bool, view_count: u32) -> bool {
+    let main_pass_id = match graph.get_node_id(node::MAIN_PASS) {
+        Ok(main_pass_id) => main_pass_id,
+        Err(_) => return false,
+    };
+    let main_pass = graph.get_node_state(main_pass_id).unwrap();
+
+    // with msaa, the main pass resolves into the target
+    let color_slot = match msaa.samples > 1 {
+        true => "color_resolve_target",
+        false => "color_attachment",
+    };
+    let color_index = main_pass.input_slots.get_slot_index(color_slot).unwrap();
+
+    let mut color_target = None;
+    let mut preceding_nodes = Vec::new();
+    for edge in main_pass.edges.input_edges.iter() {
+        match edge {
+            Edge::SlotEdge {
+                output_node,
+                output_index,
+                input_index,
+                ..
+            } if *input_index == color_index => color_target = Some((*output_node, *output_index)),
+            Edge::SlotEdge { .. } => {}
+            Edge::NodeEdge { output_node, .. } => preceding_nodes.push(*output_node),
+        }
+    }
+
+    let following_nodes = main_pass
+        .edges
+        .output_edges
+        .iter()
+        .map(|edge| match edge {
+            Edge::SlotEdge { input_node, .. } | Edge::NodeEdge { input_node, .. } => *input_node,
+        })
+        .collect::<Vec<NodeId>>();
+
+    let depth_descriptor = *graph
+        .get_node::<XRWindowTextureNode>(node::MAIN_DEPTH_TEXTURE)
+        .unwrap()
+        .descriptor();
+
+    graph.remove_node(main_pass_id).unwrap();
+
+    let copy_id = graph.add_node(xr_node::XR_VIEW_COPY, XRViewCopyNode::new(view_count));
+    if let Some((target_node, target_index)) = color_target {
+        graph
+            .add_slot_edge(target_node, target_index, copy_id, XRViewCopyNode::TARGET)
+            .unwrap();
+    }
+
+    // e.g. the UI pass or post-processing, drawing over or reading the rendered views
+    for following in following_nodes {
+        graph.add_node_edge(copy_id, following).unwrap();
+    }
+
+    // views are copied into the swapchain images, so the textures get their format
+    let color_descriptor = TextureDescriptor {
+        size: Extent3d::new(1, 1, 1),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::default(),
+        usage: TextureUsage::RENDER_ATTACHMENT | TextureUsage::COPY_SRC,
+    };
+
+    for view_index in 0..view_count {
+        let (camera_name, _) = VIEW_CAMERAS[view_index as usize];
+        let depth_clear = if reverse_z { 0. } else { 1. };
+        let pass_id = graph.add_node(
+            format!("xr_view_pass_{}", view_index),
+            main_pass_node(msaa, depth_clear, &[camera_name]),
+        );
+
+        let color_id = graph.add_node(
+            format!("xr_view_color_{}", view_index),
+            XRWindowTextureNode::new(color_descriptor)
+                .with_single_view()
+                .with_swapchain_format(),
+        );
+        let depth_id = graph.add_node(
+            format!("xr_view_depth_{}", view_index),
+            XRWindowTextureNode::new(depth_descriptor).with_single_view(),
+        );
+
+        if msaa.samples > 1 {
+            let sampled_id = graph.add_node(
+                format!("xr_view_sampled_color_{}", view_index),
+                XRWindowTextureNode::new(TextureDescriptor {
+                    sample_count: msaa.samples,
+                    usage: TextureUsage::RENDER_ATTACHMENT,
+                    ..color_descriptor
+                })
+                .with_single_view()
+                .with_swapchain_format(),
+            );
+            graph
+                .add_slot_edge(
+                    sampled_id,
+                    XRWindowTextureNode::OUT_TEXTURE,
+                    pass_id,
+                    "color_attachment",
+                )
+                .unwrap();
+        }
+
+        graph
+            .add_slot_edge(
+                color_id,
+                XRWindowTextureNode::OUT_TEXTURE,
+                pass_id,
+                color_slot,
+            )
+            .unwrap();
+        graph
+            .add_slot_edge(depth_id, XRWindowTextureNode::OUT_TEXTURE, pass_id, "depth")
+            .unwrap();
+
+        // e.g. render resource nodes of materials and lights
+        for preceding in preceding_nodes.iter() {
+            graph.add_node_edge(*preceding, pass_id).unwrap();
+        }
+
+        add_xr_camera_node(graph, camera_name, pass_id).unwrap();
+
+        graph
+            .add_slot_edge(
+                color_id,
+                XRWindowTextureNode::OUT_TEXTURE,
+                copy_id,
+                XRViewCopyNode::view_name(view_index),
+            )
+            .unwrap();
+        graph.add_node_edge(pass_id, copy_id).unwrap();
+    }
+
+    true
+}
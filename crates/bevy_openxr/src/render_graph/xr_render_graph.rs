@@ -1,13 +1,55 @@
 use bevy::{
     prelude::*,
-    render::render_graph::{base::node, RenderGraph, WindowTextureNode},
+    render::{
+        color::Color,
+        pass::{
+            LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+            TextureAttachment,
+        },
+        prelude::Msaa,
+        render_graph::{
+            base::{node, MainPass},
+            CameraNode, Edge, NodeLabel, PassNode, RenderGraph, RenderGraphError, SlotLabel,
+            WindowTextureNode,
+        },
+        texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    },
+    utils::tracing::warn,
 };
+use bevy_openxr_core::XrOptions;
 
-use super::nodes::{XRSwapchainNode, XRWindowTextureNode};
+use super::{
+    camera::camera::XRCameraConfig,
+    nodes::{node as xr_node, XRPreviousFrameNode, XRSwapchainNode, XRWindowTextureNode},
+    post_process::{add_post_process_passes, XRPostProcessing},
+    reverse_z::replace_main_pass,
+    textures::XRRenderTextures,
+};
 
-pub(crate) fn add_xr_render_graph(mut graph: ResMut<RenderGraph>) {
+pub(crate) fn add_xr_render_graph(
+    mut commands: Commands,
+    mut graph: ResMut<RenderGraph>,
+    post_processing: Option<ResMut<XRPostProcessing>>,
+    render_textures: Option<Res<XRRenderTextures>>,
+    options: Option<Res<XrOptions>>,
+    camera_config: Res<XRCameraConfig>,
+    msaa: Res<Msaa>,
+) {
     let main_depth_texture: &WindowTextureNode = graph.get_node(node::MAIN_DEPTH_TEXTURE).unwrap();
-    let descriptor = *main_depth_texture.descriptor();
+    let mut descriptor = *main_depth_texture.descriptor();
+    if let Some(depth_format) = options.and_then(|options| depth_format(&options)) {
+        descriptor.format = depth_format;
+    }
+
+    if camera_config.reverse_z {
+        // reverse-z needs floating point depth for the precision gain
+        if descriptor.format != TextureFormat::Depth32Float {
+            warn!(target: "bevy_openxr", format = ?descriptor.format, "Reverse-z requires Depth32Float depth, ignoring XrOptions::depth_format");
+            descriptor.format = TextureFormat::Depth32Float;
+        }
+
+        replace_main_pass(&mut graph, &msaa);
+    }
 
     graph
         .replace_node(
@@ -31,4 +73,154 @@ pub(crate) fn add_xr_render_graph(mut graph: ResMut<RenderGraph>) {
             XRWindowTextureNode::new(descriptor),
         )
         .unwrap();
+
+    if let Some(mut post_processing) = post_processing {
+        add_post_process_passes(&mut graph, std::mem::take(&mut *post_processing));
+        commands.remove_resource::<XRPostProcessing>();
+    }
+
+    if render_textures.map_or(false, |render_textures| render_textures.previous_frame) {
+        add_previous_frame_node(&mut graph);
+    }
+}
+
+/// Copies the swapchain image after all nodes drawing into it
+fn add_previous_frame_node(graph: &mut RenderGraph) {
+    let swapchain_id = graph.get_node_id(node::PRIMARY_SWAP_CHAIN).unwrap();
+
+    let swapchain_readers = graph
+        .get_node_state(swapchain_id)
+        .unwrap()
+        .edges
+        .output_edges
+        .iter()
+        .filter_map(|edge| match edge {
+            Edge::SlotEdge { input_node, .. } => Some(*input_node),
+            Edge::NodeEdge { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    let copy_id = graph.add_node(xr_node::XR_PREVIOUS_FRAME, XRPreviousFrameNode::new());
+    graph
+        .add_slot_edge(
+            swapchain_id,
+            XRSwapchainNode::OUT_TEXTURE,
+            copy_id,
+            XRPreviousFrameNode::IN_TEXTURE,
+        )
+        .unwrap();
+
+    for reader in swapchain_readers {
+        graph.add_node_edge(reader, copy_id).unwrap();
+    }
+}
+
+/// Pass like the main pass of the default graph, drawing `cameras` and clearing depth to `depth_clear`
+pub(crate) fn main_pass_node(
+    msaa: &Msaa,
+    depth_clear: f32,
+    cameras: &[&str],
+) -> PassNode<&'static MainPass> {
+    let mut pass_node = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![msaa.color_attachment(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Clear(Color::rgb(0.1, 0.1, 0.1)),
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(depth_clear),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: msaa.samples,
+    });
+
+    pass_node.use_default_clear_color(0);
+    for camera in cameras {
+        pass_node.add_camera(camera);
+    }
+
+    pass_node
+}
+
+/// Depth format of `XrOptions`, if valid
+fn depth_format(options: &XrOptions) -> Option<TextureFormat> {
+    match options.depth_format {
+        TextureFormat::Depth32Float
+        | TextureFormat::Depth24Plus
+        | TextureFormat::Depth24PlusStencil8 => Some(options.depth_format),
+        format => {
+            warn!(target: "bevy_openxr", ?format, "XrOptions::depth_format is not a depth format, ignoring");
+            None
+        }
+    }
+}
+
+/// Adds the camera node of an XR camera named `camera_name` (see `XRCameraBundle::with_name`), which
+/// feeds the view and projection matrices of the camera to `pass`. The node is added under the camera
+/// name. The pass must draw the camera, i.e. have it added with `PassNode::add_camera(camera_name)`
+pub fn add_xr_camera_node(
+    graph: &mut RenderGraph,
+    camera_name: &'static str,
+    pass: impl Into<NodeLabel>,
+) -> Result<(), RenderGraphError> {
+    graph.add_system_node(camera_name, CameraNode::new(camera_name));
+    graph.add_node_edge(camera_name, pass)
+}
+
+/// Connects the XR swapchain to the `color_slot` input of `pass`, and a depth texture of the same
+/// size to the `depth_slot` input, for render graphs not based on the default bevy graph. The nodes
+/// are added as `node::XR_SWAPCHAIN` and `node::XR_DEPTH_TEXTURE` if the graph does not have them yet.
+///
+/// The depth texture has the format of `XrOptions::depth_format`
+pub fn connect_xr_targets(
+    graph: &mut RenderGraph,
+    options: &XrOptions,
+    pass: impl Into<NodeLabel>,
+    color_slot: impl Into<SlotLabel>,
+    depth_slot: Option<SlotLabel>,
+) -> Result<(), RenderGraphError> {
+    let pass = pass.into();
+
+    if graph.get_node_state(xr_node::XR_SWAPCHAIN).is_err() {
+        graph.add_node(xr_node::XR_SWAPCHAIN, XRSwapchainNode::new());
+    }
+
+    graph.add_slot_edge(
+        xr_node::XR_SWAPCHAIN,
+        XRSwapchainNode::OUT_TEXTURE,
+        &pass,
+        color_slot,
+    )?;
+
+    if let Some(depth_slot) = depth_slot {
+        if graph.get_node_state(xr_node::XR_DEPTH_TEXTURE).is_err() {
+            graph.add_node(
+                xr_node::XR_DEPTH_TEXTURE,
+                XRWindowTextureNode::new(TextureDescriptor {
+                    size: Extent3d::new(1, 1, 1),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: depth_format(options).unwrap_or(TextureFormat::Depth32Float),
+                    usage: TextureUsage::RENDER_ATTACHMENT,
+                }),
+            );
+        }
+
+        graph.add_slot_edge(
+            xr_node::XR_DEPTH_TEXTURE,
+            XRWindowTextureNode::OUT_TEXTURE,
+            &pass,
+            depth_slot,
+        )?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+use bevy::ecs::system::{Local, Res};
+use bevy::utils::tracing::debug;
+use bevy_openxr_core::XRDevice;
+
+/// Update interval of the app while the session is not running, with
+/// `OpenXRSettings::use_schedule_runner`
+#[derive(Debug, Clone)]
+pub struct XRIdleInterval(pub Duration);
+
+impl Default for XRIdleInterval {
+    fn default() -> Self {
+        XRIdleInterval(Duration::from_millis(100))
+    }
+}
+
+/// Throttles the `ScheduleRunnerPlugin` loop. While the session is running, frames are paced by
+/// `xrWaitFrame` at `XRStage::FrameLoop`. Otherwise there is nothing to wait for, so the update
+/// sleeps for the rest of `XRIdleInterval`, still updating the app (and processing events) at that
+/// rate
+pub(crate) fn idle_throttle_system(
+    mut previous_update: Local<Option<Instant>>,
+    mut idle: Local<bool>,
+    interval: Res<XRIdleInterval>,
+    xr_device: Option<Res<XRDevice>>,
+) {
+    let running = xr_device.map_or(false, |xr_device| xr_device.is_session_running());
+
+    if *idle == running {
+        *idle = !running;
+        debug!(target: "bevy_openxr::session", idle = *idle, "Idle state changed");
+    }
+
+    if let (false, Some(previous_update)) = (running, *previous_update) {
+        if let Some(remaining) = interval.0.checked_sub(previous_update.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    *previous_update = Some(Instant::now());
+}
@@ -0,0 +1,454 @@
+use crate::laser_pointer::XRHand;
+use crate::synthetic_hands::synthetic_hand_joints;
+use crate::thumbstick::XRThumbstickState;
+use crate::ui_pointer::XRUiPointerButtons;
+use crate::{render_graph::camera, HandPoseEvent};
+use bevy::app::prelude::*;
+use bevy::core::Time;
+use bevy::ecs::prelude::*;
+use bevy::input::{keyboard::KeyCode, mouse::MouseButton, mouse::MouseMotion, Input};
+use bevy::math::{Quat, Vec2, Vec3};
+use bevy::render::texture::TextureFormat;
+use bevy::transform::{components::Transform, TransformSystem};
+use bevy_openxr_core::{
+    event::{
+        XRCameraTransformsUpdated, XRFovChanged, XRViewSurfaceCreated, XRViewsCreated,
+        XrSessionState,
+    },
+    hand_tracking::HandPoseState,
+    View, XRInputSources, XRStereoMode, XRSystem, XrFovf,
+};
+
+/// Runs the XR plugin stack without an OpenXR runtime. Use instead of `OpenXRPlugin`, `OpenXRCorePlugin`
+/// and `OpenXRWgpuPlugin`, together with the regular bevy window (e.g. `WinitPlugin`).
+///
+/// Session states are faked, head pose is driven by keyboard (WASD + QE) and mouse (right button held),
+/// and hand poses are generated relative to the head. Controller buttons and thumbsticks are driven
+/// by the keys and mouse buttons of `XRSimulatedControls`, see `XRSimulatedControllers`
+#[derive(Default)]
+pub struct XRSimulationPlugin;
+
+impl Plugin for XRSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(XRSimulationSettings::default)
+            .clone();
+
+        app.insert_resource(XRSimulationState::new(&settings))
+            .add_event::<XrSessionState>()
+            .init_resource::<XrSessionState>()
+            .add_event::<XRViewSurfaceCreated>()
+            .add_event::<XRViewsCreated>()
+            .add_event::<XRCameraTransformsUpdated>()
+            .add_event::<XRFovChanged>()
+            .add_event::<HandPoseEvent>()
+            .init_resource::<HandPoseState>()
+            .init_resource::<XRSimulatedControls>()
+            .init_resource::<XRSimulatedControllers>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                simulation_session_system
+                    .system()
+                    .label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                simulation_head_system.system().label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                simulation_hand_system.system().label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                simulation_controller_system
+                    .system()
+                    .label(XRSystem::EventPoll),
+            )
+            .init_resource::<camera::camera::XRViewMetrics>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera::system::openxr_camera_system
+                    .system()
+                    .label(XRSystem::PoseUpdate)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera::system::view_metrics_system.system(),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRSimulationSettings {
+    /// Resolution of the simulated view surface
+    pub resolution: (u32, u32),
+
+    /// Field of view of the simulated (mono) view
+    pub fov: XrFovf,
+
+    /// Initial height of the head from the floor, in meters
+    pub head_height: f32,
+
+    /// Head movement speed, in meters per second
+    pub move_speed: f32,
+
+    /// Head rotation, in radians per mouse motion pixel
+    pub mouse_sensitivity: f32,
+
+    /// Generate hand poses into `HandPoseState`
+    pub hand_trackers: bool,
+
+    /// Simulate connected controllers from keyboard and mouse (`XRSimulatedControls`)
+    pub controllers: bool,
+}
+
+impl Default for XRSimulationSettings {
+    fn default() -> Self {
+        let half_fov = std::f32::consts::FRAC_PI_4;
+
+        XRSimulationSettings {
+            resolution: (1280, 720),
+            fov: XrFovf {
+                angle_left: -half_fov,
+                angle_right: half_fov,
+                angle_down: -half_fov * 0.75,
+                angle_up: half_fov * 0.75,
+            },
+            head_height: 1.6,
+            move_speed: 1.5,
+            mouse_sensitivity: 0.003,
+            hand_trackers: true,
+            controllers: true,
+        }
+    }
+}
+
+/// Current simulated head pose
+#[derive(Debug)]
+pub struct XRSimulationState {
+    pub head: Transform,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl XRSimulationState {
+    fn new(settings: &XRSimulationSettings) -> Self {
+        XRSimulationState {
+            head: Transform::from_translation(Vec3::new(0., settings.head_height, 0.)),
+            yaw: 0.,
+            pitch: 0.,
+        }
+    }
+}
+
+/// Mimics the runtime: session first becomes visible, and gets focused on the next frame
+fn simulation_session_system(
+    mut frame: Local<u32>,
+    settings: Res<XRSimulationSettings>,
+    mut session_state: ResMut<XrSessionState>,
+    mut state_events: EventWriter<XrSessionState>,
+    mut view_surface_created: EventWriter<XRViewSurfaceCreated>,
+    mut views_created: EventWriter<XRViewsCreated>,
+) {
+    match *frame {
+        0 => {
+            *session_state = XrSessionState::Visible;
+            state_events.send(XrSessionState::Visible);
+
+            view_surface_created.send(XRViewSurfaceCreated {
+                width: settings.resolution.0,
+                height: settings.resolution.1,
+                view_count: 1,
+                view_sizes: vec![settings.resolution],
+                stereo_mode: XRStereoMode::Multiview,
+                // format of the window swap chain
+                format: TextureFormat::default(),
+            });
+
+            views_created.send(XRViewsCreated {
+                views: vec![View {
+                    fov: settings.fov.clone(),
+                }],
+            });
+        }
+        1 => {
+            *session_state = XrSessionState::Focused;
+            state_events.send(XrSessionState::Focused);
+        }
+        _ => return,
+    }
+
+    *frame += 1;
+}
+
+fn simulation_head_system(
+    time: Res<Time>,
+    settings: Res<XRSimulationSettings>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut state: ResMut<XRSimulationState>,
+    mut camera_transforms_updated: EventWriter<XRCameraTransformsUpdated>,
+) {
+    if mouse_buttons.pressed(MouseButton::Right) {
+        for motion in mouse_motion.iter() {
+            state.yaw -= motion.delta.x * settings.mouse_sensitivity;
+            state.pitch -= motion.delta.y * settings.mouse_sensitivity;
+        }
+
+        state.pitch = state
+            .pitch
+            .max(-std::f32::consts::FRAC_PI_2)
+            .min(std::f32::consts::FRAC_PI_2);
+    }
+
+    let rotation = Quat::from_rotation_y(state.yaw) * Quat::from_rotation_x(state.pitch);
+
+    let mut direction = Vec3::ZERO;
+    if keyboard.pressed(KeyCode::W) {
+        direction.z -= 1.;
+    }
+    if keyboard.pressed(KeyCode::S) {
+        direction.z += 1.;
+    }
+    if keyboard.pressed(KeyCode::A) {
+        direction.x -= 1.;
+    }
+    if keyboard.pressed(KeyCode::D) {
+        direction.x += 1.;
+    }
+    if keyboard.pressed(KeyCode::E) {
+        direction.y += 1.;
+    }
+    if keyboard.pressed(KeyCode::Q) {
+        direction.y -= 1.;
+    }
+
+    // move along the floor plane, regardless of pitch
+    let movement = Quat::from_rotation_y(state.yaw) * direction;
+    let translation = movement * settings.move_speed * time.delta_seconds();
+
+    state.head.translation += translation;
+    state.head.rotation = rotation;
+
+    camera_transforms_updated.send(XRCameraTransformsUpdated {
+        transforms: vec![state.head],
+        fovs: Vec::new(),
+    });
+}
+
+fn simulation_hand_system(
+    settings: Res<XRSimulationSettings>,
+    state: Res<XRSimulationState>,
+    mut hand_pose: ResMut<HandPoseState>,
+) {
+    if !settings.hand_trackers {
+        return;
+    }
+
+    hand_pose.left = Some(synthetic_hand_joints(&state.head, -1., 0., 0.));
+    hand_pose.right = Some(synthetic_hand_joints(&state.head, 1., 0., 0.));
+}
+
+/// Keyboard key or mouse button of a simulated control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRSimulatedInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// Control of a simulated controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRSimulatedControl {
+    Trigger,
+    Grip,
+
+    /// A / X button
+    Primary,
+
+    /// B / Y button
+    Secondary,
+    Menu,
+    ThumbstickUp,
+    ThumbstickDown,
+    ThumbstickLeft,
+    ThumbstickRight,
+}
+
+/// Mapping of keys and mouse buttons to the controls of the simulated controllers. Insert as a
+/// resource before adding `XRSimulationPlugin` to override the defaults
+#[derive(Debug, Clone)]
+pub struct XRSimulatedControls {
+    pub bindings: Vec<(XRSimulatedInput, XRHand, XRSimulatedControl)>,
+}
+
+impl Default for XRSimulatedControls {
+    fn default() -> Self {
+        use XRSimulatedControl as C;
+        use XRSimulatedInput::{Key, Mouse};
+
+        // WASD + QE and the right mouse button move the head
+        XRSimulatedControls {
+            bindings: vec![
+                (Mouse(MouseButton::Left), XRHand::Right, C::Trigger),
+                (Key(KeyCode::G), XRHand::Right, C::Grip),
+                (Key(KeyCode::Space), XRHand::Right, C::Primary),
+                (Key(KeyCode::B), XRHand::Right, C::Secondary),
+                (Key(KeyCode::Up), XRHand::Right, C::ThumbstickUp),
+                (Key(KeyCode::Down), XRHand::Right, C::ThumbstickDown),
+                (Key(KeyCode::Left), XRHand::Right, C::ThumbstickLeft),
+                (Key(KeyCode::Right), XRHand::Right, C::ThumbstickRight),
+                (Key(KeyCode::F), XRHand::Left, C::Trigger),
+                (Key(KeyCode::R), XRHand::Left, C::Grip),
+                (Key(KeyCode::Tab), XRHand::Left, C::Menu),
+                (Key(KeyCode::I), XRHand::Left, C::ThumbstickUp),
+                (Key(KeyCode::K), XRHand::Left, C::ThumbstickDown),
+                (Key(KeyCode::J), XRHand::Left, C::ThumbstickLeft),
+                (Key(KeyCode::L), XRHand::Left, C::ThumbstickRight),
+            ],
+        }
+    }
+}
+
+/// State of a simulated controller
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XRSimulatedController {
+    pub trigger: f32,
+    pub grip: f32,
+    pub primary: bool,
+    pub secondary: bool,
+    pub menu: bool,
+    pub thumbstick: Vec2,
+}
+
+impl XRSimulatedController {
+    fn apply(&mut self, control: XRSimulatedControl) {
+        match control {
+            XRSimulatedControl::Trigger => self.trigger = 1.,
+            XRSimulatedControl::Grip => self.grip = 1.,
+            XRSimulatedControl::Primary => self.primary = true,
+            XRSimulatedControl::Secondary => self.secondary = true,
+            XRSimulatedControl::Menu => self.menu = true,
+            XRSimulatedControl::ThumbstickUp => self.thumbstick.y += 1.,
+            XRSimulatedControl::ThumbstickDown => self.thumbstick.y -= 1.,
+            XRSimulatedControl::ThumbstickLeft => self.thumbstick.x -= 1.,
+            XRSimulatedControl::ThumbstickRight => self.thumbstick.x += 1.,
+        }
+    }
+}
+
+/// Simulated controller states, available as a resource in place of the app's actions. Also
+/// written into the resources that are fed from actions with real hardware: thumbstick axes
+/// (`XRThumbstickState::raw`) and UI pointer buttons (`XRUiPointerButtons`, from the trigger)
+#[derive(Debug, Clone, Default)]
+pub struct XRSimulatedControllers {
+    pub left: XRSimulatedController,
+    pub right: XRSimulatedController,
+}
+
+impl XRSimulatedControllers {
+    pub fn get(&self, hand: XRHand) -> &XRSimulatedController {
+        match hand {
+            XRHand::Left => &self.left,
+            XRHand::Right => &self.right,
+        }
+    }
+
+    fn get_mut(&mut self, hand: XRHand) -> &mut XRSimulatedController {
+        match hand {
+            XRHand::Left => &mut self.left,
+            XRHand::Right => &mut self.right,
+        }
+    }
+}
+
+/// Controller states of the pressed inputs
+fn simulated_controllers(
+    controls: &XRSimulatedControls,
+    pressed: impl Fn(XRSimulatedInput) -> bool,
+) -> XRSimulatedControllers {
+    let mut controllers = XRSimulatedControllers::default();
+
+    for (input, hand, control) in controls.bindings.iter() {
+        if pressed(*input) {
+            controllers.get_mut(*hand).apply(*control);
+        }
+    }
+
+    for controller in [&mut controllers.left, &mut controllers.right].iter_mut() {
+        if controller.thumbstick.length_squared() > 1. {
+            controller.thumbstick = controller.thumbstick.normalize();
+        }
+    }
+
+    controllers
+}
+
+fn simulation_controller_system(
+    settings: Res<XRSimulationSettings>,
+    controls: Res<XRSimulatedControls>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut controllers: ResMut<XRSimulatedControllers>,
+    input_sources: Option<ResMut<XRInputSources>>,
+    thumbsticks: Option<ResMut<XRThumbstickState>>,
+    ui_pointer_buttons: Option<ResMut<XRUiPointerButtons>>,
+) {
+    if !settings.controllers {
+        return;
+    }
+
+    *controllers = simulated_controllers(&controls, |input| match input {
+        XRSimulatedInput::Key(key) => keyboard.pressed(key),
+        XRSimulatedInput::Mouse(button) => mouse_buttons.pressed(button),
+    });
+
+    if let Some(mut input_sources) = input_sources {
+        if !input_sources.left_controller || !input_sources.right_controller {
+            input_sources.left_controller = true;
+            input_sources.right_controller = true;
+        }
+    }
+
+    if let Some(mut thumbsticks) = thumbsticks {
+        thumbsticks.left.raw = controllers.left.thumbstick;
+        thumbsticks.right.raw = controllers.right.thumbstick;
+    }
+
+    if let Some(mut buttons) = ui_pointer_buttons {
+        let (left, right) = (
+            controllers.left.trigger > 0.5,
+            controllers.right.trigger > 0.5,
+        );
+        if buttons.left != left || buttons.right != right {
+            buttons.left = left;
+            buttons.right = right;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_controllers() {
+        let controls = XRSimulatedControls::default();
+        let pressed = [
+            XRSimulatedInput::Mouse(MouseButton::Left),
+            XRSimulatedInput::Key(KeyCode::Up),
+            XRSimulatedInput::Key(KeyCode::Right),
+            XRSimulatedInput::Key(KeyCode::Tab),
+        ];
+
+        let controllers = simulated_controllers(&controls, |input| pressed.contains(&input));
+        assert_eq!(controllers.right.trigger, 1.);
+        assert!((controllers.right.thumbstick.length() - 1.).abs() < 1e-6);
+        assert!(controllers.right.thumbstick.x > 0. && controllers.right.thumbstick.y > 0.);
+        assert!(controllers.left.menu);
+        assert_eq!(controllers.left.trigger, 0.);
+    }
+}
@@ -0,0 +1,242 @@
+use bevy::app::prelude::*;
+use bevy::core::Time;
+use bevy::ecs::prelude::*;
+use bevy::math::Vec3;
+use bevy::transform::prelude::*;
+use bevy_openxr_core::{event::XRCameraTransformsUpdated, XRDevice};
+
+use crate::render_graph::camera::pose::center_eye;
+
+/// Spatial data of the XR session for audio engines: the listener (head) pose of each frame, and the
+/// room geometry around the user. Audio crates implement `XRSpatialAudioBackend` (e.g. occlusion or
+/// reverb) and add it with `XRSpatialAudio::add_backend`, or read the `XRSpatialAudio` resource.
+///
+/// Poses and geometry are in the XR reference space. The room geometry is the floor of the play area,
+/// if the runtime knows its bounds, and the surfaces set with `XRSpatialAudio::set_surfaces`
+#[derive(Default)]
+pub struct XRSpatialAudioPlugin;
+
+impl Plugin for XRSpatialAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRSpatialAudio>()
+            .add_system_to_stage(CoreStage::PostUpdate, spatial_audio_system.system());
+    }
+}
+
+/// Listener pose of a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct XRAudioListener {
+    /// Head pose, between the eyes
+    pub transform: Transform,
+
+    /// Velocity of the head in m/s, e.g. for doppler
+    pub velocity: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XRSurfaceKind {
+    Floor,
+    Ceiling,
+    Wall,
+    Furniture,
+    Other,
+}
+
+/// Surface of the room as a triangle mesh
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRAudioSurface {
+    pub kind: XRSurfaceKind,
+    pub vertices: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+impl XRAudioSurface {
+    /// Horizontal rect of `width` x `depth` centered at `center`, facing up
+    pub fn rect(kind: XRSurfaceKind, center: Vec3, width: f32, depth: f32) -> Self {
+        let (x, z) = (width / 2., depth / 2.);
+        XRAudioSurface {
+            kind,
+            vertices: vec![
+                center + Vec3::new(-x, 0., -z),
+                center + Vec3::new(-x, 0., z),
+                center + Vec3::new(x, 0., z),
+                center + Vec3::new(x, 0., -z),
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3],
+        }
+    }
+}
+
+/// Surfaces around the user. `generation` increases on every change
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XRRoomGeometry {
+    pub surfaces: Vec<XRAudioSurface>,
+    pub generation: u32,
+}
+
+/// Audio engine integration, called from `XRSpatialAudioPlugin` at `CoreStage::PostUpdate`
+pub trait XRSpatialAudioBackend: Send + Sync + 'static {
+    /// Listener pose of the frame
+    fn update_listener(&mut self, listener: &XRAudioListener);
+
+    /// Room geometry, when the backend is added and on every change
+    fn update_geometry(&mut self, geometry: &XRRoomGeometry);
+}
+
+#[derive(Default)]
+pub struct XRSpatialAudio {
+    listener: Option<XRAudioListener>,
+    geometry: XRRoomGeometry,
+
+    /// Floor of the play area, queried once the session has started
+    play_area: Option<XRAudioSurface>,
+    play_area_queried: bool,
+
+    /// Surfaces set by the app
+    surfaces: Vec<XRAudioSurface>,
+    geometry_changed: bool,
+
+    backends: Vec<Box<dyn XRSpatialAudioBackend>>,
+}
+
+impl XRSpatialAudio {
+    /// Listener of the most recent frame, `None` before the first frame
+    pub fn listener(&self) -> Option<&XRAudioListener> {
+        self.listener.as_ref()
+    }
+
+    pub fn geometry(&self) -> &XRRoomGeometry {
+        &self.geometry
+    }
+
+    /// Replaces the surfaces of the room geometry set by the app, e.g. walls of the virtual room or
+    /// surfaces detected by the app. Walls and furniture are not detected by the plugin
+    pub fn set_surfaces(&mut self, surfaces: Vec<XRAudioSurface>) {
+        self.surfaces = surfaces;
+        self.geometry_changed = true;
+    }
+
+    pub fn add_backend(&mut self, mut backend: impl XRSpatialAudioBackend) {
+        backend.update_geometry(&self.geometry);
+        if let Some(listener) = &self.listener {
+            backend.update_listener(listener);
+        }
+        self.backends.push(Box::new(backend));
+    }
+
+    fn update_geometry(&mut self) {
+        if !self.geometry_changed {
+            return;
+        }
+        self.geometry_changed = false;
+
+        self.geometry.surfaces = self
+            .play_area
+            .iter()
+            .chain(self.surfaces.iter())
+            .cloned()
+            .collect();
+        self.geometry.generation += 1;
+
+        for backend in self.backends.iter_mut() {
+            backend.update_geometry(&self.geometry);
+        }
+    }
+
+    fn update_listener(&mut self, head: Transform, delta_seconds: f32) {
+        let velocity = match self.listener {
+            Some(previous) if delta_seconds > 0. => {
+                (head.translation - previous.transform.translation) / delta_seconds
+            }
+            _ => Vec3::ZERO,
+        };
+
+        let listener = XRAudioListener {
+            transform: head,
+            velocity,
+        };
+        self.listener = Some(listener);
+
+        for backend in self.backends.iter_mut() {
+            backend.update_listener(&listener);
+        }
+    }
+}
+
+fn spatial_audio_system(
+    time: Res<Time>,
+    xr_device: Option<ResMut<XRDevice>>,
+    mut camera_transforms_updated: EventReader<XRCameraTransformsUpdated>,
+    mut spatial_audio: ResMut<XRSpatialAudio>,
+) {
+    let head = camera_transforms_updated
+        .iter()
+        .last()
+        .and_then(|event| center_eye(&event.transforms));
+
+    // bounds are known once views are located, i.e. the session is running
+    if head.is_some() && !spatial_audio.play_area_queried {
+        if let Some(mut xr_device) = xr_device {
+            spatial_audio.play_area_queried = true;
+            spatial_audio.play_area = xr_device.play_area_bounds().map(|(width, depth)| {
+                XRAudioSurface::rect(XRSurfaceKind::Floor, Vec3::ZERO, width, depth)
+            });
+            spatial_audio.geometry_changed = true;
+        }
+    }
+
+    spatial_audio.update_geometry();
+
+    if let Some(head) = head {
+        spatial_audio.update_listener(head, time.delta_seconds());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        generations: Arc<Mutex<Vec<u32>>>,
+        velocities: Arc<Mutex<Vec<Vec3>>>,
+    }
+
+    impl XRSpatialAudioBackend for Recorder {
+        fn update_listener(&mut self, listener: &XRAudioListener) {
+            self.velocities.lock().unwrap().push(listener.velocity);
+        }
+
+        fn update_geometry(&mut self, geometry: &XRRoomGeometry) {
+            self.generations.lock().unwrap().push(geometry.generation);
+        }
+    }
+
+    #[test]
+    fn test_backend_updates() {
+        let recorder = Recorder::default();
+        let generations = recorder.generations.clone();
+        let velocities = recorder.velocities.clone();
+
+        let mut spatial_audio = XRSpatialAudio::default();
+        spatial_audio.add_backend(recorder);
+
+        spatial_audio.set_surfaces(vec![XRAudioSurface::rect(
+            XRSurfaceKind::Ceiling,
+            Vec3::new(0., 2.5, 0.),
+            4.,
+            4.,
+        )]);
+        spatial_audio.update_geometry();
+        spatial_audio.update_geometry();
+        assert_eq!(*generations.lock().unwrap(), vec![0, 1]);
+
+        spatial_audio.update_listener(Transform::from_xyz(0., 1.6, 0.), 0.1);
+        spatial_audio.update_listener(Transform::from_xyz(0.1, 1.6, 0.), 0.1);
+        let velocities = velocities.lock().unwrap();
+        assert_eq!(velocities[0], Vec3::ZERO);
+        assert!(velocities[1].abs_diff_eq(Vec3::new(1., 0., 0.), 1e-5));
+    }
+}
@@ -0,0 +1,241 @@
+//! Generated hand poses, for developing hand interaction without hand tracking: used by
+//! `XRSimulationPlugin`, and by `XRSyntheticHandsPlugin` (feature `synthetic-hands`) on runtimes
+//! without `XR_EXT_hand_tracking` (e.g. SteamVR or Monado with controllers)
+
+use bevy::math::Vec3;
+use bevy::transform::components::Transform;
+use openxr::HandJointLocations;
+
+#[cfg(feature = "synthetic-hands")]
+pub use plugin::{XRSyntheticHandsPlugin, XRSyntheticHandsSettings};
+
+const THUMB_DISTAL: usize = 4;
+const THUMB_TIP: usize = 5;
+const INDEX_DISTAL: usize = 9;
+const INDEX_TIP: usize = 10;
+
+/// Generates an open, palm-down hand in front of the head. `side` is -1 for left hand, 1 for right
+/// hand. `pinch` (0..=1) moves the thumb and index finger tips together, and `phase` (radians) sways
+/// the hand up and down for an idle animation
+pub(crate) fn synthetic_hand_joints(
+    head: &Transform,
+    side: f32,
+    pinch: f32,
+    phase: f32,
+) -> HandJointLocations {
+    let hand = Transform {
+        translation: head.mul_vec3(Vec3::new(side * 0.2, -0.3 + phase.sin() * 0.01, -0.4)),
+        rotation: head.rotation,
+        scale: Vec3::ONE,
+    };
+
+    let mut positions = [Vec3::ZERO; openxr::HAND_JOINT_COUNT];
+
+    // palm = 0, wrist = 1
+    positions[1] = Vec3::new(0., 0., 0.08);
+
+    // thumb: 2..=5 (4 joints), other fingers: 5 joints each
+    for (idx, position) in positions.iter_mut().enumerate().skip(2) {
+        let (finger, segment) = match idx {
+            2..=5 => (0, idx - 2),
+            _ => (1 + (idx - 6) / 5, (idx - 6) % 5),
+        };
+
+        *position = match finger {
+            0 => Vec3::new(
+                -side * (0.03 + segment as f32 * 0.015),
+                0.,
+                0.03 - segment as f32 * 0.02,
+            ),
+            _ => Vec3::new(
+                -side * (0.03 - finger as f32 * 0.02),
+                0.,
+                0.04 - segment as f32 * 0.025,
+            ),
+        };
+    }
+
+    let pinch = pinch.max(0.).min(1.);
+    if pinch > 0. {
+        let pinch_point = (positions[THUMB_TIP] + positions[INDEX_TIP]) * 0.5;
+
+        for (tip, distal) in [(THUMB_TIP, THUMB_DISTAL), (INDEX_TIP, INDEX_DISTAL)].iter() {
+            positions[*distal] = positions[*distal].lerp(pinch_point, pinch * 0.5);
+            positions[*tip] = positions[*tip].lerp(pinch_point, pinch);
+        }
+    }
+
+    let mut joints = [openxr::HandJointLocation {
+        location_flags: openxr::SpaceLocationFlags::POSITION_VALID
+            | openxr::SpaceLocationFlags::ORIENTATION_VALID
+            | openxr::SpaceLocationFlags::POSITION_TRACKED
+            | openxr::SpaceLocationFlags::ORIENTATION_TRACKED,
+        pose: to_posef(&hand, Vec3::ZERO),
+        radius: 0.008,
+    }; openxr::HAND_JOINT_COUNT];
+
+    for (idx, (joint, position)) in joints.iter_mut().zip(positions.iter()).enumerate() {
+        joint.pose = to_posef(&hand, *position);
+        if idx < 2 {
+            joint.radius = 0.01;
+        }
+    }
+
+    joints
+}
+
+fn to_posef(parent: &Transform, local: Vec3) -> openxr::Posef {
+    let position = parent.mul_vec3(local);
+    let orientation = parent.rotation;
+
+    openxr::Posef {
+        orientation: openxr::Quaternionf {
+            x: orientation.x,
+            y: orientation.y,
+            z: orientation.z,
+            w: orientation.w,
+        },
+        position: openxr::Vector3f {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        },
+    }
+}
+
+#[cfg(feature = "synthetic-hands")]
+mod plugin {
+    use bevy::app::prelude::*;
+    use bevy::core::Time;
+    use bevy::ecs::prelude::*;
+    use bevy::input::{keyboard::KeyCode, Input};
+    use bevy::transform::components::Transform;
+    use bevy_openxr_core::{
+        event::XRCameraTransformsUpdated, hand_tracking::HandPoseState, XRDevice, XRSystem,
+    };
+
+    use super::synthetic_hand_joints;
+    use crate::render_graph::camera::pose::center_eye;
+
+    /// Generates hand poses into `HandPoseState` while the runtime does not track hands. Hands are
+    /// placed in front of the head, and pinch while the pinch key is held. Keyboard input requires a
+    /// window, e.g. `WinitPlugin`
+    #[derive(Default)]
+    pub struct XRSyntheticHandsPlugin;
+
+    impl Plugin for XRSyntheticHandsPlugin {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<XRSyntheticHandsSettings>()
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    synthetic_hands_system.system().label(XRSystem::EventPoll),
+                );
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct XRSyntheticHandsSettings {
+        pub left_pinch_key: KeyCode,
+        pub right_pinch_key: KeyCode,
+
+        /// Sway the hands slightly, so that they are not perfectly still
+        pub idle_animation: bool,
+
+        /// Time to fully close the pinch, in seconds
+        pub pinch_duration: f32,
+    }
+
+    impl Default for XRSyntheticHandsSettings {
+        fn default() -> Self {
+            XRSyntheticHandsSettings {
+                left_pinch_key: KeyCode::Z,
+                right_pinch_key: KeyCode::X,
+                idle_animation: true,
+                pinch_duration: 0.15,
+            }
+        }
+    }
+
+    /// Current pinch amount of the left and right hand
+    #[derive(Default)]
+    struct SyntheticPinch {
+        left: f32,
+        right: f32,
+    }
+
+    fn synthetic_hands_system(
+        mut head: Local<Option<Transform>>,
+        mut pinch: Local<SyntheticPinch>,
+        time: Res<Time>,
+        settings: Res<XRSyntheticHandsSettings>,
+        keyboard: Option<Res<Input<KeyCode>>>,
+        xr_device: Res<XRDevice>,
+        mut camera_transforms_updated: EventReader<XRCameraTransformsUpdated>,
+        mut hand_pose: ResMut<HandPoseState>,
+    ) {
+        if let Some(event) = camera_transforms_updated.iter().last() {
+            *head = center_eye(&event.transforms);
+        }
+
+        if xr_device.has_hand_tracking() {
+            return;
+        }
+
+        let head = match *head {
+            Some(head) => head,
+            None => return,
+        };
+
+        let step = time.delta_seconds() / settings.pinch_duration.max(f32::EPSILON);
+        let pressed = |key| {
+            keyboard
+                .as_ref()
+                .map_or(false, |keyboard| keyboard.pressed(key))
+        };
+        let approach = |value: f32, pressed: bool| match pressed {
+            true => (value + step).min(1.),
+            false => (value - step).max(0.),
+        };
+
+        pinch.left = approach(pinch.left, pressed(settings.left_pinch_key));
+        pinch.right = approach(pinch.right, pressed(settings.right_pinch_key));
+
+        let phase = match settings.idle_animation {
+            true => time.seconds_since_startup() as f32 * 1.5,
+            false => 0.,
+        };
+
+        hand_pose.left = Some(synthetic_hand_joints(&head, -1., pinch.left, phase));
+        hand_pose.right = Some(synthetic_hand_joints(
+            &head,
+            1.,
+            pinch.right,
+            phase + std::f32::consts::FRAC_PI_2,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(joints: &HandJointLocations, idx: usize) -> Vec3 {
+        let position = &joints[idx].pose.position;
+        Vec3::new(position.x, position.y, position.z)
+    }
+
+    #[test]
+    fn test_synthetic_pinch() {
+        let head = Transform::from_translation(Vec3::new(0., 1.6, 0.));
+
+        let open = synthetic_hand_joints(&head, 1., 0., 0.);
+        assert!(position(&open, THUMB_TIP).distance(position(&open, INDEX_TIP)) > 0.02);
+
+        let pinched = synthetic_hand_joints(&head, 1., 1., 0.);
+        assert!(position(&pinched, THUMB_TIP).distance(position(&pinched, INDEX_TIP)) < 1e-5);
+        assert_eq!(
+            position(&open, 0).to_array(),
+            position(&pinched, 0).to_array()
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Harness for rendering tests against an OpenXR runtime (e.g. Monado in headless mode). Renders
+//! frames of a scene, reads back the swapchain views and compares them against stored golden images.
+//!
+//! A missing golden image fails the comparison. Set `BEVY_OPENXR_UPDATE_GOLDEN=1` to write all golden
+//! images from the captured views, e.g. for a new test or after an intended change
+
+use std::path::{Path, PathBuf};
+
+use bevy::app::{App, Events, ManualEventReader};
+use bevy::asset::Assets;
+use bevy::diagnostic::DiagnosticsPlugin;
+use bevy::ecs::{component::Component, prelude::*};
+use bevy::log::LogPlugin;
+use bevy::math::Vec3;
+use bevy::pbr::{prelude::StandardMaterial, PbrBundle};
+use bevy::render::{
+    color::Color,
+    mesh::{shape, Mesh},
+};
+use bevy::transform::components::Transform;
+use bevy::utils::tracing::warn;
+use bevy_openxr_core::XRCapturedImage;
+
+use crate::capture::{XRCapture, XRCapturePlugin, XRFrameCaptured};
+use crate::prelude::XRCameraBundle;
+use crate::{OpenXRHandTrackingPlugin, OpenXRPlugins, OpenXRSettings};
+
+/// Frames rendered at most while waiting for a capture, the session needs a few frames to start
+const MAX_CAPTURE_FRAMES: usize = 100;
+
+/// Updates at most in `update_until`
+const MAX_UPDATE_FRAMES: usize = 100;
+
+pub struct XRTestHarness {
+    pub app: App,
+    captured_reader: ManualEventReader<XRFrameCaptured>,
+}
+
+impl XRTestHarness {
+    /// App with the plugins required for XR rendering. The app is updated manually with `update`
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.insert_resource(OpenXRSettings {
+            use_schedule_runner: false,
+            ..Default::default()
+        })
+        // hand joints would show up in the golden images
+        .add_plugins_with(OpenXRPlugins, |group| {
+            group
+                .disable::<LogPlugin>()
+                .disable::<DiagnosticsPlugin>()
+                .disable::<OpenXRHandTrackingPlugin>()
+        })
+        .add_plugin(XRCapturePlugin);
+
+        XRTestHarness {
+            app,
+            captured_reader: Default::default(),
+        }
+    }
+
+    /// Runs `frames` updates of the app
+    pub fn update(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Runs updates until `done` returns `true` after an update. `false` if it did not in
+    /// `MAX_UPDATE_FRAMES` updates, e.g. the session did not start. The runtime may take a varying
+    /// number of frames to move through the session states, so wait for the state under test
+    /// instead of a fixed number of updates
+    pub fn update_until<F: FnMut(&World) -> bool>(&mut self, mut done: F) -> bool {
+        for _ in 0..MAX_UPDATE_FRAMES {
+            self.app.update();
+            if done(&self.app.world) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Events sent since the last two updates
+    pub fn read_events<T: Component>(&self) -> Vec<&T> {
+        let events = self.app.world.get_resource::<Events<T>>().unwrap();
+        ManualEventReader::<T>::default().iter(events).collect()
+    }
+
+    /// Renders frames until `views` have been captured. `None` if nothing was captured in
+    /// `MAX_CAPTURE_FRAMES` frames, e.g. the session did not start
+    pub fn capture(&mut self, views: &[u32]) -> Option<Vec<XRCapturedImage>> {
+        self.app
+            .world
+            .get_resource_mut::<XRCapture>()
+            .unwrap()
+            .capture(views);
+
+        for _ in 0..MAX_CAPTURE_FRAMES {
+            self.app.update();
+
+            let events = self
+                .app
+                .world
+                .get_resource::<Events<XRFrameCaptured>>()
+                .unwrap();
+            if let Some(captured) = self.captured_reader.iter(events).last() {
+                return Some(captured.images.clone());
+            }
+        }
+
+        None
+    }
+
+    /// Captures `views` and compares each of them against golden image
+    /// `<golden_dir>/<name>_view<index>.png`
+    pub fn assert_golden<P: AsRef<Path>>(
+        &mut self,
+        views: &[u32],
+        golden_dir: P,
+        name: &str,
+        tolerance: &XRImageTolerance,
+    ) {
+        let images = self
+            .capture(views)
+            .expect("No frame was captured, is the session running?");
+        assert_eq!(images.len(), views.len(), "Not all views were captured");
+
+        for image in images.iter() {
+            let path = golden_dir
+                .as_ref()
+                .join(format!("{}_view{}.png", name, image.view_index));
+
+            if let Err(e) = compare_golden(image, &path, tolerance) {
+                panic!(
+                    "View {} does not match {:?}: {:?}",
+                    image.view_index, path, e
+                );
+            }
+        }
+    }
+}
+
+impl Default for XRTestHarness {
+    fn default() -> Self {
+        XRTestHarness::new()
+    }
+}
+
+/// Allowed difference between a captured and a golden image, e.g. for rounding differences
+/// between GPUs and drivers
+#[derive(Debug, Clone)]
+pub struct XRImageTolerance {
+    /// Largest difference of a channel for which pixels are considered equal
+    pub channel: u8,
+
+    /// Fraction of pixels that may differ more than `channel`
+    pub differing_pixels: f32,
+}
+
+impl Default for XRImageTolerance {
+    fn default() -> Self {
+        XRImageTolerance {
+            channel: 2,
+            differing_pixels: 0.001,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenImageError {
+    Io(std::io::Error),
+    Decode(png::DecodingError),
+    Encode(png::EncodingError),
+
+    /// Golden image does not exist, write it with `BEVY_OPENXR_UPDATE_GOLDEN=1`
+    Missing(PathBuf),
+
+    /// Golden image is not 8-bit RGBA
+    Format(PathBuf),
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    Mismatch {
+        differing_pixels: usize,
+        total_pixels: usize,
+    },
+}
+
+impl From<std::io::Error> for GoldenImageError {
+    fn from(e: std::io::Error) -> Self {
+        GoldenImageError::Io(e)
+    }
+}
+
+impl From<png::DecodingError> for GoldenImageError {
+    fn from(e: png::DecodingError) -> Self {
+        GoldenImageError::Decode(e)
+    }
+}
+
+impl From<png::EncodingError> for GoldenImageError {
+    fn from(e: png::EncodingError) -> Self {
+        GoldenImageError::Encode(e)
+    }
+}
+
+/// Compares `image` against the golden image at `path`. Writes the golden image instead if
+/// `BEVY_OPENXR_UPDATE_GOLDEN` is set
+pub fn compare_golden(
+    image: &XRCapturedImage,
+    path: &Path,
+    tolerance: &XRImageTolerance,
+) -> Result<(), GoldenImageError> {
+    if std::env::var("BEVY_OPENXR_UPDATE_GOLDEN").is_ok() {
+        warn!(target: "bevy_openxr", path = ?path, "Writing golden image");
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        return Ok(crate::capture::write_png(path, image)?);
+    }
+
+    if !path.exists() {
+        return Err(GoldenImageError::Missing(path.to_path_buf()));
+    }
+
+    let decoder = png::Decoder::new(std::fs::File::open(path)?);
+    let (info, mut reader) = decoder.read_info()?;
+    if info.color_type != png::ColorType::RGBA || info.bit_depth != png::BitDepth::Eight {
+        return Err(GoldenImageError::Format(path.to_path_buf()));
+    }
+
+    if (info.width, info.height) != (image.width, image.height) {
+        return Err(GoldenImageError::SizeMismatch {
+            expected: (info.width, info.height),
+            actual: (image.width, image.height),
+        });
+    }
+
+    let mut expected = vec![0; info.buffer_size()];
+    reader.next_frame(&mut expected)?;
+
+    let differing_pixels = count_differing_pixels(&expected, &image.data, tolerance.channel);
+    let total_pixels = (image.width * image.height) as usize;
+    if differing_pixels as f32 > tolerance.differing_pixels * total_pixels as f32 {
+        return Err(GoldenImageError::Mismatch {
+            differing_pixels,
+            total_pixels,
+        });
+    }
+
+    Ok(())
+}
+
+/// Number of RGBA8 pixels that have a channel differing more than `channel_tolerance`
+fn count_differing_pixels(expected: &[u8], actual: &[u8], channel_tolerance: u8) -> usize {
+    expected
+        .chunks(4)
+        .zip(actual.chunks(4))
+        .filter(|(expected, actual)| {
+            expected
+                .iter()
+                .zip(actual.iter())
+                .any(|(e, a)| (*e as i16 - *a as i16).abs() > channel_tolerance as i16)
+        })
+        .count()
+}
+
+/// Reference scene for golden image tests: XR camera and colored cubes in front of, left and
+/// right of the origin
+pub fn reference_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn_bundle(XRCameraBundle::default());
+
+    let mesh = meshes.add(Mesh::from(shape::Cube { size: 0.3 }));
+    let cubes = [
+        (Vec3::new(0., 0., -2.), Color::rgb(0.8, 0.1, 0.1)),
+        (Vec3::new(-1., 0., -2.), Color::rgb(0.1, 0.8, 0.1)),
+        (Vec3::new(1., 0., -2.), Color::rgb(0.1, 0.1, 0.8)),
+    ];
+
+    for (translation, color) in cubes.iter() {
+        commands.spawn_bundle(PbrBundle {
+            mesh: mesh.clone(),
+            material: materials.add(StandardMaterial {
+                base_color: *color,
+                ..Default::default()
+            }),
+            transform: Transform::from_translation(*translation),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_differing_pixels() {
+        let expected = [10, 10, 10, 255, 10, 10, 10, 255];
+        let actual = [12, 10, 10, 255, 10, 20, 10, 255];
+        assert_eq!(count_differing_pixels(&expected, &actual, 2), 1);
+        assert_eq!(count_differing_pixels(&expected, &actual, 10), 0);
+        assert_eq!(count_differing_pixels(&expected, &actual, 1), 2);
+    }
+}
@@ -0,0 +1,212 @@
+//! Fixtures for testing XR gameplay logic without an OpenXR runtime. `XRTestApp` runs the core
+//! plugins without a session (as in the desktop fallback), and the test drives the session state,
+//! head and hand poses and input sources that the runtime would provide, stepping the app frame by
+//! frame:
+//!
+//! ```ignore
+//! let mut test_app = XRTestApp::new();
+//! test_app.app.add_plugin(MyGamePlugin);
+//! test_app.start_session();
+//!
+//! test_app.set_head_pose(Transform::from_xyz(0., 1.6, 0.));
+//! test_app.set_hand(XRHand::Right, 1.);
+//! test_app.step();
+//! ```
+
+use bevy::app::{App, Events, ManualEventReader};
+use bevy::ecs::component::Component;
+use bevy::math::Vec3;
+use bevy::render::texture::TextureFormat;
+use bevy::transform::components::Transform;
+use bevy_openxr_core::{
+    event::{XRCameraTransformsUpdated, XRViewSurfaceCreated, XRViewsCreated, XrSessionState},
+    hand_tracking::HandPoseState,
+    OpenXRCorePlugin, View, XRAvailability, XRInputSources, XRStereoMode, XrFovf,
+};
+use openxr::HandJointLocations;
+
+use crate::laser_pointer::XRHand;
+use crate::synthetic_hands::synthetic_hand_joints;
+
+/// Resolution of the view surface of `start_session`
+const VIEW_RESOLUTION: (u32, u32) = (640, 480);
+
+pub struct XRTestApp {
+    pub app: App,
+
+    /// Head pose of the most recent `set_head_pose`, hands of `set_hand` are placed relative to it
+    head: Transform,
+}
+
+impl XRTestApp {
+    /// App with the core, transform and XR core plugins, without a session. Add the plugins under
+    /// test to `app`
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.insert_resource(XRAvailability::Unavailable("XRTestApp".to_string()))
+            .add_plugin(bevy::core::CorePlugin::default())
+            .add_plugin(bevy::transform::TransformPlugin::default())
+            .add_plugin(OpenXRCorePlugin);
+
+        XRTestApp {
+            app,
+            head: Transform::from_translation(Vec3::new(0., 1.6, 0.)),
+        }
+    }
+
+    /// Runs one update of the app
+    pub fn step(&mut self) {
+        self.app.update();
+    }
+
+    /// Runs `frames` updates of the app
+    pub fn step_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Moves the session to `state` as the runtime would, and runs one update
+    pub fn set_session_state(&mut self, state: XrSessionState) {
+        *self.app.world.get_resource_mut::<XrSessionState>().unwrap() = state;
+        self.send_event(state);
+        self.step();
+    }
+
+    /// Creates a (mono) view, and moves the session through the states of a starting session up
+    /// to `XrSessionState::Focused`, one update per state
+    pub fn start_session(&mut self) {
+        self.send_event(XRViewSurfaceCreated {
+            width: VIEW_RESOLUTION.0,
+            height: VIEW_RESOLUTION.1,
+            view_count: 1,
+            view_sizes: vec![VIEW_RESOLUTION],
+            stereo_mode: XRStereoMode::Multiview,
+            format: TextureFormat::default(),
+        });
+
+        let half_fov = std::f32::consts::FRAC_PI_4;
+        self.send_event(XRViewsCreated {
+            views: vec![View {
+                fov: XrFovf {
+                    angle_left: -half_fov,
+                    angle_right: half_fov,
+                    angle_down: -half_fov,
+                    angle_up: half_fov,
+                },
+            }],
+        });
+
+        for state in [
+            XrSessionState::Ready,
+            XrSessionState::Running,
+            XrSessionState::Visible,
+            XrSessionState::Focused,
+        ]
+        .iter()
+        {
+            self.set_session_state(*state);
+        }
+    }
+
+    /// Locates the (mono) view at `head` for the next update
+    pub fn set_head_pose(&mut self, head: Transform) {
+        self.head = head;
+        self.send_event(XRCameraTransformsUpdated {
+            transforms: vec![head],
+            fovs: Vec::new(),
+        });
+    }
+
+    /// Tracks `hand` as an open hand in front of the head, `pinch` (0..=1) moving the thumb and
+    /// index finger tips together
+    pub fn set_hand(&mut self, hand: XRHand, pinch: f32) {
+        let side = match hand {
+            XRHand::Left => -1.,
+            XRHand::Right => 1.,
+        };
+
+        let joints = synthetic_hand_joints(&self.head, side, pinch, 0.);
+        self.set_hand_joints(hand, Some(joints));
+    }
+
+    /// Sets the joints of `hand`, `None` if the hand is not tracked
+    pub fn set_hand_joints(&mut self, hand: XRHand, joints: Option<HandJointLocations>) {
+        let mut hand_pose = self.app.world.get_resource_mut::<HandPoseState>().unwrap();
+        match hand {
+            XRHand::Left => hand_pose.left = joints,
+            XRHand::Right => hand_pose.right = joints,
+        }
+
+        let mut input_sources = self.app.world.get_resource_mut::<XRInputSources>().unwrap();
+        match hand {
+            XRHand::Left => input_sources.left_hand = joints.is_some(),
+            XRHand::Right => input_sources.right_hand = joints.is_some(),
+        }
+    }
+
+    /// Connects or disconnects the controller of `hand`
+    pub fn set_controller(&mut self, hand: XRHand, connected: bool) {
+        let mut input_sources = self.app.world.get_resource_mut::<XRInputSources>().unwrap();
+        match hand {
+            XRHand::Left => input_sources.left_controller = connected,
+            XRHand::Right => input_sources.right_controller = connected,
+        }
+    }
+
+    /// Sends `event` to be read in the next update, e.g. an input event of the app
+    pub fn send_event<T: Component>(&mut self, event: T) {
+        self.app
+            .world
+            .get_resource_mut::<Events<T>>()
+            .unwrap()
+            .send(event);
+    }
+
+    /// Events sent in the last two updates
+    pub fn read_events<T: Component>(&self) -> Vec<&T> {
+        let events = self.app.world.get_resource::<Events<T>>().unwrap();
+        ManualEventReader::<T>::default().iter(events).collect()
+    }
+}
+
+impl Default for XRTestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::prelude::*;
+
+    use super::*;
+    use crate::calibration::{XRCalibrationPlugin, XRCalibrationRequest, XRRig};
+
+    #[test]
+    fn test_seated_calibration() {
+        let mut test_app = XRTestApp::new();
+        test_app.app.add_plugin(XRCalibrationPlugin);
+        let rig = test_app
+            .app
+            .world
+            .spawn()
+            .insert(XRRig)
+            .insert(Transform::default())
+            .id();
+
+        test_app.start_session();
+        assert_eq!(
+            *test_app.app.world.get_resource::<XrSessionState>().unwrap(),
+            XrSessionState::Focused
+        );
+
+        test_app.set_head_pose(Transform::from_xyz(0.2, 1.0, 0.1));
+        test_app.send_event(XRCalibrationRequest::Seated);
+        test_app.step();
+
+        let transform = test_app.app.world.get::<Transform>(rig).unwrap();
+        let head = transform.mul_vec3(Vec3::new(0.2, 1.0, 0.1));
+        assert!(head.abs_diff_eq(Vec3::new(0., 1.2, 0.), 1e-5));
+    }
+}
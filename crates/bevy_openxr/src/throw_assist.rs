@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use bevy::app::prelude::*;
+use bevy::core::Time;
+use bevy::ecs::prelude::*;
+use bevy::math::Vec3;
+use bevy::transform::prelude::*;
+
+/// Release velocity for throwing: entities with an `XRVelocityTracker` (e.g. a hand joint, or an
+/// entity following a controller pose) record their `GlobalTransform` translation each frame, and
+/// `XRVelocityTracker::release_velocity` fits the velocity over the last frames. Single-frame
+/// velocities are noisy, and throws tend to go off in random directions with them
+#[derive(Default)]
+pub struct XRThrowAssistPlugin;
+
+impl Plugin for XRThrowAssistPlugin {
+    fn build(&self, app: &mut App) {
+        // after transform propagation
+        app.add_system_to_stage(CoreStage::Last, velocity_tracker_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRVelocityTracker {
+    /// Number of frames the velocity is fitted over
+    pub window: usize,
+
+    /// Multiplier of the release velocity, e.g. `1.2` to make throws reach further
+    pub scale: f32,
+
+    /// Recorded positions with the time (seconds since startup), oldest first
+    samples: VecDeque<(f64, Vec3)>,
+}
+
+impl Default for XRVelocityTracker {
+    fn default() -> Self {
+        XRVelocityTracker::new(6)
+    }
+}
+
+impl XRVelocityTracker {
+    pub fn new(window: usize) -> Self {
+        XRVelocityTracker {
+            window,
+            scale: 1.,
+            samples: VecDeque::with_capacity(window),
+        }
+    }
+
+    pub fn push(&mut self, time: f64, position: Vec3) {
+        while self.samples.len() >= self.window.max(2) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((time, position));
+    }
+
+    /// Forgets the recorded positions, e.g. when the object is grabbed
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Velocity over the recorded frames (least squares fit of the positions), multiplied by
+    /// `scale`. `None` until at least two frames have been recorded
+    pub fn release_velocity(&self) -> Option<Vec3> {
+        fit_velocity(self.samples.iter().copied()).map(|velocity| velocity * self.scale)
+    }
+}
+
+/// Slope of the least squares line through the positions over time
+fn fit_velocity(samples: impl Iterator<Item = (f64, Vec3)> + Clone) -> Option<Vec3> {
+    let count = samples.clone().count();
+    if count < 2 {
+        return None;
+    }
+
+    let mean_time = samples.clone().map(|(time, _)| time).sum::<f64>() / count as f64;
+    let mean_position = samples
+        .clone()
+        .fold(Vec3::ZERO, |sum, (_, position)| sum + position)
+        / count as f32;
+
+    let (covariance, variance) = samples.fold(
+        (Vec3::ZERO, 0.),
+        |(covariance, variance), (time, position)| {
+            let dt = (time - mean_time) as f32;
+            (
+                covariance + (position - mean_position) * dt,
+                variance + dt * dt,
+            )
+        },
+    );
+
+    if variance <= f32::EPSILON {
+        return None;
+    }
+
+    Some(covariance / variance)
+}
+
+fn velocity_tracker_system(
+    time: Res<Time>,
+    mut trackers: Query<(&mut XRVelocityTracker, &GlobalTransform)>,
+) {
+    let now = time.seconds_since_startup();
+    for (mut tracker, transform) in trackers.iter_mut() {
+        tracker.push(now, transform.translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_velocity() {
+        let mut tracker = XRVelocityTracker::new(4);
+        assert!(tracker.release_velocity().is_none());
+
+        // constant velocity of (1, 2, 0) m/s, at 90 fps, with jitter on the last frame
+        for frame in 0..10 {
+            let time = frame as f64 / 90.;
+            tracker.push(time, Vec3::new(1., 2., 0.) * time as f32);
+        }
+        let velocity = tracker.release_velocity().unwrap();
+        assert!((velocity - Vec3::new(1., 2., 0.)).length() < 1e-3);
+
+        tracker.push(
+            10. / 90.,
+            Vec3::new(1., 2., 0.) * (10. / 90.) + Vec3::new(0.01, 0., 0.),
+        );
+        let velocity = tracker.release_velocity().unwrap();
+        // a single frame velocity would be off by 0.9 m/s
+        assert!((velocity.x - 1.).abs() < 0.5);
+
+        tracker.scale = 2.;
+        assert!(tracker.release_velocity().unwrap().y > 3.9);
+
+        tracker.clear();
+        assert!(tracker.release_velocity().is_none());
+    }
+}
@@ -0,0 +1,208 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::math::Vec2;
+
+use crate::laser_pointer::XRHand;
+
+/// Processing of thumbstick / touchpad axes: deadzone, response curve and directional flicks
+/// (e.g. for snap turning or menu navigation).
+///
+/// Raw axes are set by the app from its actions to `XRThumbstickState::{left,right}.raw`, before
+/// the processing system (label `XR_THUMBSTICK_SYSTEM`). Processed axes are in `value`, and flicks
+/// are sent as `XRThumbstickFlick` events
+#[derive(Default)]
+pub struct XRThumbstickPlugin;
+
+impl Plugin for XRThumbstickPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRThumbstickSettings>()
+            .init_resource::<XRThumbstickState>()
+            .add_event::<XRThumbstickFlick>()
+            .add_system(thumbstick_system.system().label(XR_THUMBSTICK_SYSTEM));
+    }
+}
+
+pub const XR_THUMBSTICK_SYSTEM: &str = "xr_thumbstick";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XRResponseCurve {
+    Linear,
+
+    /// Squared magnitude, finer control near the center
+    Quadratic,
+
+    /// Magnitude raised to the power
+    Power(f32),
+}
+
+impl XRResponseCurve {
+    fn apply(&self, magnitude: f32) -> f32 {
+        match self {
+            XRResponseCurve::Linear => magnitude,
+            XRResponseCurve::Quadratic => magnitude * magnitude,
+            XRResponseCurve::Power(exponent) => magnitude.powf(*exponent),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRThumbstickSettings {
+    /// Radial deadzone. Magnitudes below are zero, and the rest is rescaled to `0..1`
+    pub deadzone: f32,
+
+    pub curve: XRResponseCurve,
+
+    /// Magnitude (of the raw axes) at which a flick is sent
+    pub flick_threshold: f32,
+
+    /// Magnitude below which the thumbstick is considered released, and the next flick can be sent
+    pub flick_release: f32,
+}
+
+impl Default for XRThumbstickSettings {
+    fn default() -> Self {
+        XRThumbstickSettings {
+            deadzone: 0.15,
+            curve: XRResponseCurve::Linear,
+            flick_threshold: 0.8,
+            flick_release: 0.4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XRThumbstick {
+    /// Axes as read from the action, x to the right and y up, in `-1..1`
+    pub raw: Vec2,
+
+    /// Axes after deadzone and response curve
+    pub value: Vec2,
+
+    /// A flick has been sent, and the thumbstick has not been released since
+    flicked: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct XRThumbstickState {
+    pub left: XRThumbstick,
+    pub right: XRThumbstick,
+}
+
+impl XRThumbstickState {
+    pub fn get(&self, hand: XRHand) -> &XRThumbstick {
+        match hand {
+            XRHand::Left => &self.left,
+            XRHand::Right => &self.right,
+        }
+    }
+
+    pub fn get_mut(&mut self, hand: XRHand) -> &mut XRThumbstick {
+        match hand {
+            XRHand::Left => &mut self.left,
+            XRHand::Right => &mut self.right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XRFlickDirection {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl XRFlickDirection {
+    /// Closest direction of the axes, with North up (+y) and East right (+x)
+    fn from_axes(axes: Vec2) -> Self {
+        if axes.x.abs() > axes.y.abs() {
+            match axes.x > 0. {
+                true => XRFlickDirection::East,
+                false => XRFlickDirection::West,
+            }
+        } else {
+            match axes.y > 0. {
+                true => XRFlickDirection::North,
+                false => XRFlickDirection::South,
+            }
+        }
+    }
+}
+
+/// Thumbstick was pushed past `XRThumbstickSettings::flick_threshold`. Sent once per push
+#[derive(Debug, Clone)]
+pub struct XRThumbstickFlick {
+    pub hand: XRHand,
+    pub direction: XRFlickDirection,
+}
+
+/// Applies the radial deadzone and response curve, keeping the direction
+fn process_axes(raw: Vec2, settings: &XRThumbstickSettings) -> Vec2 {
+    let magnitude = raw.length().min(1.);
+    if magnitude <= settings.deadzone {
+        return Vec2::ZERO;
+    }
+
+    let scaled = (magnitude - settings.deadzone) / (1. - settings.deadzone);
+    raw.normalize() * settings.curve.apply(scaled)
+}
+
+fn thumbstick_system(
+    settings: Res<XRThumbstickSettings>,
+    mut state: ResMut<XRThumbstickState>,
+    mut flicks: EventWriter<XRThumbstickFlick>,
+) {
+    for hand in [XRHand::Left, XRHand::Right].iter() {
+        let thumbstick = state.get_mut(*hand);
+        thumbstick.value = process_axes(thumbstick.raw, &settings);
+
+        let magnitude = thumbstick.raw.length();
+        if thumbstick.flicked {
+            thumbstick.flicked = magnitude >= settings.flick_release;
+        } else if magnitude >= settings.flick_threshold {
+            thumbstick.flicked = true;
+            flicks.send(XRThumbstickFlick {
+                hand: *hand,
+                direction: XRFlickDirection::from_axes(thumbstick.raw),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_axes() {
+        let settings = XRThumbstickSettings {
+            deadzone: 0.2,
+            ..Default::default()
+        };
+
+        assert_eq!(process_axes(Vec2::new(0.1, 0.1), &settings), Vec2::ZERO);
+        assert!((process_axes(Vec2::new(0., 0.6), &settings) - Vec2::new(0., 0.5)).length() < 1e-5);
+        assert!((process_axes(Vec2::new(-1., 0.), &settings) - Vec2::new(-1., 0.)).length() < 1e-5);
+
+        let settings = XRThumbstickSettings {
+            deadzone: 0.2,
+            curve: XRResponseCurve::Quadratic,
+            ..Default::default()
+        };
+        assert!(
+            (process_axes(Vec2::new(0.6, 0.), &settings) - Vec2::new(0.25, 0.)).length() < 1e-5
+        );
+    }
+
+    #[test]
+    fn test_flick_direction() {
+        assert_eq!(
+            XRFlickDirection::from_axes(Vec2::new(0.9, 0.2)),
+            XRFlickDirection::East
+        );
+        assert_eq!(
+            XRFlickDirection::from_axes(Vec2::new(-0.1, -0.9)),
+            XRFlickDirection::South
+        );
+    }
+}
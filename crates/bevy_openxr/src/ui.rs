@@ -0,0 +1,169 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::math::{Mat4, Vec2, Vec3, Vec4};
+use bevy::render::camera::{Camera, OrthographicProjection, VisibleEntities, WindowOrigin};
+use bevy::transform::prelude::*;
+use bevy::ui::camera::CAMERA_UI;
+
+use crate::render_graph::camera::{
+    camera::{XREyeView, XREyeViews},
+    pose::center_eye,
+};
+
+/// Renders bevy UI head-locked into the XR views. The UI pass draws over the swapchain, but a
+/// regular `UiCameraBundle` has a single projection and shows up in no view. Spawn an
+/// `XRUiCameraBundle` instead: its projection is repeated per view, scaled down to
+/// `XRUiCamera::scale` of the view and shifted so that both eyes converge at `XRUiCamera::distance`.
+///
+/// UI is laid out in the virtual window (`XRVirtualWindow`), sized to the view resolution. It is
+/// drawn into the projection layer, not composited as a quad layer by the runtime
+#[derive(Default)]
+pub struct XRUiPlugin;
+
+impl Plugin for XRUiPlugin {
+    fn build(&self, app: &mut App) {
+        // after XREyeViews have been updated at PostUpdate
+        app.add_system_to_stage(CoreStage::Last, xr_ui_camera_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XRUiCamera {
+    /// Fraction of the view covered by the UI. Edges of the view are hard to read, and partly hidden
+    /// by the lenses
+    pub scale: f32,
+
+    /// Distance of the UI plane from the head, in meters, for the stereo offset of the views
+    pub distance: f32,
+}
+
+impl Default for XRUiCamera {
+    fn default() -> Self {
+        XRUiCamera {
+            scale: 0.5,
+            distance: 1.5,
+        }
+    }
+}
+
+/// Like `UiCameraBundle`, with an `XRUiCamera`
+#[derive(Bundle)]
+pub struct XRUiCameraBundle {
+    pub camera: Camera,
+    pub orthographic_projection: OrthographicProjection,
+    pub visible_entities: VisibleEntities,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub xr_ui_camera: XRUiCamera,
+}
+
+impl Default for XRUiCameraBundle {
+    fn default() -> Self {
+        // same as UiCameraBundle
+        let far = 1000.0;
+
+        XRUiCameraBundle {
+            camera: Camera {
+                name: Some(CAMERA_UI.to_string()),
+                ..Default::default()
+            },
+            orthographic_projection: OrthographicProjection {
+                far,
+                window_origin: WindowOrigin::BottomLeft,
+                ..Default::default()
+            },
+            visible_entities: Default::default(),
+            transform: Transform::from_xyz(0.0, 0.0, far - 0.1),
+            global_transform: Default::default(),
+            xr_ui_camera: Default::default(),
+        }
+    }
+}
+
+/// Position of the head-locked point `distance` in front of `head` in normalized device coordinates
+/// of the view
+fn ui_center_ndc(view: &XREyeView, head: &Transform, distance: f32) -> Vec2 {
+    let point = head.translation + head.rotation * Vec3::new(0., 0., -distance);
+    let view_space = view.pose.rotation.inverse() * (point - view.pose.translation);
+    let clip = view.projection * Vec4::new(view_space.x, view_space.y, view_space.z, 1.);
+
+    if clip.w.abs() < f32::EPSILON {
+        return Vec2::ZERO;
+    }
+
+    Vec2::new(clip.x / clip.w, clip.y / clip.w)
+}
+
+fn xr_ui_camera_system(
+    eye_views: Res<XREyeViews>,
+    mut cameras: Query<(&mut Camera, &XRUiCamera, &GlobalTransform)>,
+) {
+    let poses = eye_views
+        .views
+        .iter()
+        .map(|view| view.pose)
+        .collect::<Vec<_>>();
+
+    let head = match center_eye(&poses) {
+        Some(head) => head,
+        None => return,
+    };
+
+    for (mut camera, ui_camera, global_transform) in cameras.iter_mut() {
+        let scale = Mat4::from_scale(Vec3::new(ui_camera.scale, ui_camera.scale, 1.));
+        let projection = scale * camera.projection_matrix;
+
+        camera.projection_matrices = eye_views
+            .views
+            .iter()
+            .map(|view| {
+                let center = ui_center_ndc(view, &head, ui_camera.distance);
+                Mat4::from_translation(center.extend(0.)) * projection
+            })
+            .collect();
+
+        camera.position_matrices = vec![global_transform.compute_matrix(); eye_views.views.len()];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::Quat;
+    use bevy_openxr_core::XrFovf;
+
+    fn eye_view(x: f32) -> XREyeView {
+        XREyeView {
+            pose: Transform::from_translation(Vec3::new(x, 1.6, 0.)),
+            world_pose: Default::default(),
+            fov: XrFovf {
+                angle_left: -0.8,
+                angle_right: 0.8,
+                angle_down: -0.8,
+                angle_up: 0.8,
+            },
+            projection: Mat4::perspective_rh(1.6, 1., 0.1, 100.),
+            viewport: None,
+        }
+    }
+
+    #[test]
+    fn test_ui_center_ndc() {
+        let head = Transform {
+            translation: Vec3::new(0., 1.6, 0.),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        };
+
+        // point straight ahead is right of center for the left eye, and left for the right eye
+        let left = ui_center_ndc(&eye_view(-0.032), &head, 1.5);
+        let right = ui_center_ndc(&eye_view(0.032), &head, 1.5);
+        assert!(left.x > 0.);
+        assert!(right.x < 0.);
+        assert!((left.x + right.x).abs() < 1e-5);
+        assert!(left.y.abs() < 1e-5);
+
+        // converges towards the view center with distance
+        assert!(ui_center_ndc(&eye_view(-0.032), &head, 10.).x < left.x);
+    }
+}
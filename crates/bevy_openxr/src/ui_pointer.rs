@@ -0,0 +1,237 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::input::{mouse::MouseButtonInput, ElementState};
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::MouseButton;
+use bevy::transform::prelude::*;
+use bevy::window::{CursorMoved, Windows};
+
+use crate::laser_pointer::{XRAimPoses, XRHand};
+
+/// Emulated mouse cursor for 2D UI shown on panels in the XR scene: aim rays hitting an
+/// `XRUiPanel` move the cursor of the primary window (`CursorMoved`), and `XRUiPointerButtons`
+/// presses the left mouse button (`MouseButtonInput`). With the UI laid out in the (virtual)
+/// primary window, bevy UI interaction works with XR pointers like with a mouse. On Android, touch
+/// events of the activity are converted to the same events
+#[derive(Default)]
+pub struct XRUiPointerPlugin;
+
+impl Plugin for XRUiPointerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRUiPointerButtons>()
+            .add_system_to_stage(CoreStage::PreUpdate, ui_pointer_system.system());
+    }
+}
+
+/// Panel showing the primary window, e.g. a quad textured with the UI. The panel is the local XY
+/// plane of the entity, centered at its origin, facing +Z
+#[derive(Debug, Clone)]
+pub struct XRUiPanel {
+    /// Width and height of the panel, in local units
+    pub size: Vec2,
+}
+
+/// Select button state of each hand, set by the app from its actions (e.g. the trigger)
+#[derive(Debug, Clone, Default)]
+pub struct XRUiPointerButtons {
+    pub left: bool,
+    pub right: bool,
+}
+
+impl XRUiPointerButtons {
+    pub fn get(&self, hand: XRHand) -> bool {
+        match hand {
+            XRHand::Left => self.left,
+            XRHand::Right => self.right,
+        }
+    }
+}
+
+/// Cursor state, converted into window events on change
+#[derive(Debug, Default)]
+pub(crate) struct VirtualCursor {
+    position: Option<Vec2>,
+    pressed: bool,
+}
+
+impl VirtualCursor {
+    /// Moves the cursor to `position` (in window coordinates, origin at bottom-left), and sets the
+    /// button state. Returns the cursor move and the button change, if any. Buttons are released
+    /// when the cursor leaves the window
+    pub(crate) fn update(
+        &mut self,
+        position: Option<Vec2>,
+        pressed: bool,
+    ) -> (Option<Vec2>, Option<ElementState>) {
+        let pressed = pressed && position.is_some();
+
+        let moved = match position {
+            Some(position) if self.position != Some(position) => Some(position),
+            _ => None,
+        };
+
+        let button = match (self.pressed, pressed) {
+            (false, true) => Some(ElementState::Pressed),
+            (true, false) => Some(ElementState::Released),
+            _ => None,
+        };
+
+        self.position = position;
+        self.pressed = pressed;
+        (moved, button)
+    }
+}
+
+/// Position of the ray hit on the panel, in window coordinates of a `window_size` window
+fn panel_hit(
+    origin: Vec3,
+    direction: Vec3,
+    panel: &XRUiPanel,
+    transform: &GlobalTransform,
+    window_size: Vec2,
+) -> Option<(f32, Vec2)> {
+    let to_local = transform.compute_matrix().inverse();
+    let local_origin = to_local.transform_point3(origin);
+    let local_direction = to_local.transform_vector3(direction);
+
+    // from the front, towards the plane
+    if local_direction.z >= 0. || local_origin.z <= 0. {
+        return None;
+    }
+
+    let t = -local_origin.z / local_direction.z;
+    let hit = local_origin + local_direction * t;
+    let uv = Vec2::new(hit.x, hit.y) / panel.size + Vec2::splat(0.5);
+
+    if uv.x < 0. || uv.x > 1. || uv.y < 0. || uv.y > 1. {
+        return None;
+    }
+
+    let distance = (transform.compute_matrix().transform_point3(hit) - origin).length();
+    Some((distance, uv * window_size))
+}
+
+fn ui_pointer_system(
+    mut cursor: Local<VirtualCursor>,
+    aim_poses: Option<Res<XRAimPoses>>,
+    buttons: Res<XRUiPointerButtons>,
+    mut windows: ResMut<Windows>,
+    mut cursor_moved_events: EventWriter<CursorMoved>,
+    mut mouse_button_events: EventWriter<MouseButtonInput>,
+    panels: Query<(&XRUiPanel, &GlobalTransform)>,
+) {
+    let window = match windows.get_primary_mut() {
+        Some(window) => window,
+        None => return,
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+
+    let aims = aim_poses
+        .map(|aim_poses| {
+            [XRHand::Left, XRHand::Right]
+                .iter()
+                .filter_map(|hand| Some((*hand, *aim_poses.get(*hand)?)))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    // closest hit of any hand
+    let hit = aims
+        .into_iter()
+        .flat_map(|(hand, aim)| {
+            let direction = aim.rotation * -Vec3::Z;
+            panels
+                .iter()
+                .filter_map(|(panel, transform)| {
+                    panel_hit(aim.translation, direction, panel, transform, window_size)
+                })
+                .map(|(distance, position)| (hand, distance, position))
+                .collect::<Vec<_>>()
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    let (position, pressed) = match hit {
+        Some((hand, _, position)) => (Some(position), buttons.get(hand)),
+        None => (None, false),
+    };
+
+    let had_position = cursor.position.is_some();
+    let (moved, button) = cursor.update(position, pressed);
+
+    if let Some(position) = moved {
+        window.update_cursor_position_from_backend(Some(position));
+        cursor_moved_events.send(CursorMoved {
+            id: window.id(),
+            position,
+        });
+    } else if had_position && position.is_none() {
+        window.update_cursor_position_from_backend(None);
+    }
+
+    if let Some(state) = button {
+        mouse_button_events.send(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panel_hit() {
+        let panel = XRUiPanel {
+            size: Vec2::new(2., 1.),
+        };
+        let transform = GlobalTransform::from_translation(Vec3::new(0., 1., -2.));
+        let window_size = Vec2::new(800., 400.);
+
+        let (distance, position) = panel_hit(
+            Vec3::new(0.5, 1.25, 0.),
+            -Vec3::Z,
+            &panel,
+            &transform,
+            window_size,
+        )
+        .unwrap();
+        assert!((distance - 2.).abs() < 1e-5);
+        assert!((position - Vec2::new(600., 300.)).length() < 1e-3);
+
+        // beside the panel, and from behind
+        assert!(panel_hit(
+            Vec3::new(1.5, 1., 0.),
+            -Vec3::Z,
+            &panel,
+            &transform,
+            window_size
+        )
+        .is_none());
+        assert!(panel_hit(
+            Vec3::new(0., 1., -3.),
+            Vec3::Z,
+            &panel,
+            &transform,
+            window_size
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_virtual_cursor() {
+        let mut cursor = VirtualCursor::default();
+        let position = Vec2::new(10., 20.);
+
+        assert_eq!(cursor.update(Some(position), false), (Some(position), None));
+        assert_eq!(
+            cursor.update(Some(position), true),
+            (None, Some(ElementState::Pressed))
+        );
+        // leaving the window releases
+        assert_eq!(
+            cursor.update(None, true),
+            (None, Some(ElementState::Released))
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::app::prelude::*;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::prelude::*;
+use bevy::math::{Vec2, Vec4};
+use bevy::prelude::{BuildChildren, DespawnRecursiveExt, MeshBundle};
+use bevy::render::{
+    camera::VisibleEntities,
+    mesh::{Indices, Mesh},
+    pipeline::{PipelineDescriptor, PrimitiveTopology, RenderPipeline, RenderPipelines},
+    shader::{Shader, ShaderStage, ShaderStages},
+    RenderStage,
+};
+use bevy::utils::tracing::debug;
+use bevy_openxr_core::{
+    event::{XRViewSurfaceCreated, XRViewsCreated, XRVisibilityMaskChanged},
+    View, XRDevice, XRVisibilityMask,
+};
+
+use crate::dynamic_resolution::XRRenderScale;
+use crate::render_graph::{
+    camera::{
+        camera::{XRCameraConfig, XREye},
+        projection::{view_scale_matrix_for, XRProjection},
+    },
+    secondary_views::XRSecondaryViewCamera,
+};
+
+/// Skips shading of the pixels not visible through the lenses (`XR_KHR_visibility_mask`).
+///
+/// The hidden area mesh of each view is drawn at the near plane, in front of everything else, so the
+/// depth test rejects all later fragments there. The mesh is attached to the `XRCameraBundle` camera,
+/// so that it is the first opaque entity drawn. Secondary view cameras and the per-eye cameras of
+/// `XREye::Left` / `XREye::Right` don't draw the mask, as they render a single view
+#[derive(Default)]
+pub struct XRVisibilityMaskPlugin;
+
+impl Plugin for XRVisibilityMaskPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisibilityMaskState>()
+            .add_startup_system(setup_visibility_mask_pipeline.system())
+            .add_system_to_stage(CoreStage::PostUpdate, visibility_mask_system.system())
+            .add_system_to_stage(
+                // after visible entities have been collected at PostUpdate
+                RenderStage::RenderResource,
+                hide_visibility_mask_system.system(),
+            );
+    }
+}
+
+/// Vertex position z holds the view index the vertex belongs to. Vertices of other views are moved
+/// outside the clip volume. `NEAR_DEPTH` is replaced with the depth of the near plane
+const VERTEX_SHADER: &str = r#"
+#version 450
+#extension GL_EXT_multiview : enable
+
+layout(location = 0) in vec3 Vertex_Position;
+
+void main() {
+    if (int(Vertex_Position.z) == gl_ViewIndex) {
+        gl_Position = vec4(Vertex_Position.xy, NEAR_DEPTH, 1.0);
+    } else {
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+    }
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+#version 450
+
+layout(location = 0) out vec4 o_Target;
+
+void main() {
+    o_Target = vec4(0.0, 0.0, 0.0, 1.0);
+}
+"#;
+
+#[derive(Default)]
+struct VisibilityMaskState {
+    pipeline: Option<Handle<PipelineDescriptor>>,
+    mesh_entity: Option<Entity>,
+    views: Vec<View>,
+    view_surface: Option<XRViewSurfaceCreated>,
+    masks: Vec<XRVisibilityMask>,
+    render_scale: Option<f32>,
+}
+
+fn setup_visibility_mask_pipeline(
+    mut state: ResMut<VisibilityMaskState>,
+    mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    mut shaders: ResMut<Assets<Shader>>,
+    camera_config: Option<Res<XRCameraConfig>>,
+) {
+    let reverse_z = camera_config.map_or(false, |config| config.reverse_z);
+    let vertex_shader = VERTEX_SHADER.replace("NEAR_DEPTH", if reverse_z { "1.0" } else { "0.0" });
+
+    state.pipeline = Some(
+        pipelines.add(PipelineDescriptor::default_config(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(ShaderStage::Vertex, &vertex_shader)),
+            fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, FRAGMENT_SHADER))),
+        })),
+    );
+}
+
+fn visibility_mask_system(
+    mut commands: Commands,
+    mut state: ResMut<VisibilityMaskState>,
+    mut xr_device: ResMut<XRDevice>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut view_surface_created: EventReader<XRViewSurfaceCreated>,
+    mut views_created: EventReader<XRViewsCreated>,
+    mut mask_changes: EventReader<XRVisibilityMaskChanged>,
+    render_scale: Option<Res<XRRenderScale>>,
+    cameras: Query<Entity, (With<XRProjection>, Without<XRSecondaryViewCamera>)>,
+) {
+    let mut changed = false;
+
+    for event in view_surface_created.iter() {
+        state.view_surface = Some(event.clone());
+        changed = true;
+    }
+
+    for event in views_created.iter() {
+        state.views = event.views.clone();
+        state.masks = (0..state.views.len() as u32)
+            .map(|view_index| {
+                xr_device
+                    .get_visibility_mask(view_index)
+                    .unwrap_or_default()
+            })
+            .collect();
+        changed = true;
+    }
+
+    for event in mask_changes.iter() {
+        let view_index = event.view_index as usize;
+        if view_index < state.masks.len() {
+            state.masks[view_index] = xr_device
+                .get_visibility_mask(event.view_index)
+                .unwrap_or_default();
+            changed = true;
+        }
+    }
+
+    let render_scale = render_scale.map(|scale| scale.0).unwrap_or(1.);
+    if state.render_scale != Some(render_scale) {
+        state.render_scale = Some(render_scale);
+        changed = true;
+    }
+
+    if !changed || state.masks.iter().all(|mask| mask.indices.is_empty()) {
+        return;
+    }
+
+    let camera = match cameras.iter().next() {
+        Some(camera) => camera,
+        None => return,
+    };
+
+    let mesh = meshes.add(build_mask_mesh(
+        &state.views,
+        state.view_surface.as_ref(),
+        &state.masks,
+        render_scale,
+    ));
+    debug!(target: "bevy_openxr::frame", "Visibility mask mesh updated");
+
+    if let Some(entity) = state.mesh_entity.take() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let pipeline = state.pipeline.clone().unwrap();
+    let entity = commands
+        .spawn_bundle(MeshBundle {
+            mesh,
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(pipeline)]),
+            ..Default::default()
+        })
+        .id();
+
+    commands.entity(camera).push_children(&[entity]);
+    state.mesh_entity = Some(entity);
+}
+
+/// Removes the mask from the visible entities of the cameras rendering a single view, where
+/// `gl_ViewIndex` is always 0 and the mask of the first view would be drawn
+fn hide_visibility_mask_system(
+    state: Res<VisibilityMaskState>,
+    mut cameras: Query<(
+        &mut VisibleEntities,
+        Option<&XREye>,
+        Option<&XRSecondaryViewCamera>,
+    )>,
+) {
+    let mesh_entity = match state.mesh_entity {
+        Some(entity) => entity,
+        None => return,
+    };
+
+    for (mut visible_entities, eye, secondary_view) in cameras.iter_mut() {
+        if secondary_view.is_none() && eye.map_or(true, |eye| *eye == XREye::Both) {
+            continue;
+        }
+
+        visible_entities
+            .value
+            .retain(|visible_entity| visible_entity.entity != mesh_entity);
+    }
+}
+
+/// Projects the masks of all views into clip space, into a single mesh
+fn build_mask_mesh(
+    views: &[View],
+    view_surface: Option<&XRViewSurfaceCreated>,
+    masks: &[XRVisibilityMask],
+    render_scale: f32,
+) -> Mesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for (view_index, (view, mask)) in views.iter().zip(masks.iter()).enumerate() {
+        let scale_matrix = view_scale_matrix_for(render_scale, view_surface, view_index);
+        let projection =
+            scale_matrix * XRProjection::default().get_projection_matrix_fov(&view.fov);
+
+        let base_index = positions.len() as u32;
+
+        positions.extend(mask.vertices.iter().map(|vertex: &Vec2| {
+            let clip = projection * Vec4::new(vertex.x, vertex.y, -1., 1.);
+            [clip.x / clip.w, clip.y / clip.w, view_index as f32]
+        }));
+
+        indices.extend(mask.indices.iter().map(|index| base_index + index));
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
@@ -0,0 +1,118 @@
+use bevy::app::prelude::*;
+use bevy::ecs::prelude::*;
+use bevy::utils::tracing::debug;
+use bevy::window::{CreateWindow, Window, WindowCreated, WindowId, WindowResized, Windows};
+use bevy_openxr_core::event::XRViewSurfaceCreated;
+
+/// Virtual primary window of the XR app. There is no OS window, so a window sized to the view
+/// resolution of the swapchain is added to `Windows`, for UI layout and camera logic that reads
+/// the window size. The window is resized (with `WindowResized` events) when the swapchain is
+/// created or its resolution changes.
+///
+/// If a primary window exists already (e.g. created by `WinitPlugin` next to the XR session), it is
+/// left untouched
+#[derive(Debug, Clone)]
+pub struct XRVirtualWindow {
+    width: u32,
+    height: u32,
+
+    /// Window is managed by `XRVirtualWindow`
+    created: bool,
+}
+
+impl Default for XRVirtualWindow {
+    fn default() -> Self {
+        // Oculus Quest view resolution, used until the swapchain has been created
+        XRVirtualWindow {
+            width: 896,
+            height: 1008,
+            created: false,
+        }
+    }
+}
+
+impl XRVirtualWindow {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Primary window is the virtual window
+    pub fn is_created(&self) -> bool {
+        self.created
+    }
+}
+
+pub(crate) struct XRVirtualWindowPlugin;
+
+impl Plugin for XRVirtualWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XRVirtualWindow>()
+            .add_system(create_virtual_window_system.system())
+            .add_system(resize_virtual_window_system.system());
+    }
+}
+
+fn create_virtual_window_system(
+    mut virtual_window: ResMut<XRVirtualWindow>,
+    mut windows: ResMut<Windows>,
+    mut create_window_events: EventReader<CreateWindow>,
+    mut window_created_events: EventWriter<WindowCreated>,
+) {
+    for create_window_event in create_window_events.iter() {
+        if windows.get_primary().is_some() || create_window_event.id != WindowId::primary() {
+            continue;
+        }
+
+        debug!(target: "bevy_openxr", width = virtual_window.width, height = virtual_window.height, "Creating virtual XR window");
+        windows.add(Window::new(
+            WindowId::primary(),
+            &create_window_event.descriptor,
+            virtual_window.width,
+            virtual_window.height,
+            1.,
+            None,
+        ));
+        virtual_window.created = true;
+
+        window_created_events.send(WindowCreated {
+            id: create_window_event.id,
+        });
+    }
+}
+
+fn resize_virtual_window_system(
+    mut virtual_window: ResMut<XRVirtualWindow>,
+    mut windows: ResMut<Windows>,
+    mut view_surface_created: EventReader<XRViewSurfaceCreated>,
+    mut window_resized_events: EventWriter<WindowResized>,
+) {
+    let surface = match view_surface_created.iter().last() {
+        Some(surface) => surface,
+        None => return,
+    };
+
+    if (virtual_window.width, virtual_window.height) == (surface.width, surface.height) {
+        return;
+    }
+
+    virtual_window.width = surface.width;
+    virtual_window.height = surface.height;
+
+    if !virtual_window.created {
+        return;
+    }
+
+    if let Some(window) = windows.get_primary_mut() {
+        debug!(target: "bevy_openxr", width = surface.width, height = surface.height, "Resizing virtual XR window");
+        window.update_actual_size_from_backend(surface.width, surface.height);
+        window_resized_events.send(WindowResized {
+            id: window.id(),
+            width: window.width(),
+            height: window.height(),
+        });
+    }
+}
@@ -0,0 +1,290 @@
+use std::{collections::HashMap, ffi::CStr, os::raw::c_char, ptr};
+
+use bevy::transform::components::Transform;
+use bevy::utils::tracing::debug;
+use openxr::sys;
+
+use crate::{
+    error::cvt,
+    math::{pose_to_transform, transform_to_pose},
+    XrError,
+};
+
+/// Identifies a spatial anchor owned by `XRDevice`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XRAnchorId(u64);
+
+struct SpatialAnchor {
+    anchor: sys::SpatialAnchorMSFT,
+    space: sys::Space,
+}
+
+/// Spatial anchors (`XR_MSFT_spatial_anchor`), and their persistence (`XR_MSFT_spatial_anchor_persistence`)
+pub(crate) struct SpatialAnchors {
+    instance: openxr::Instance,
+    anchors: HashMap<XRAnchorId, SpatialAnchor>,
+    store: Option<sys::SpatialAnchorStoreConnectionMSFT>,
+    next_id: u64,
+}
+
+impl SpatialAnchors {
+    pub(crate) fn new(instance: &openxr::Instance) -> Self {
+        SpatialAnchors {
+            instance: instance.clone(),
+            anchors: HashMap::new(),
+            store: None,
+            next_id: 0,
+        }
+    }
+
+    pub(crate) fn is_supported(&self) -> bool {
+        self.instance.exts().msft_spatial_anchor.is_some()
+    }
+
+    /// Creates an anchor at `transform` (relative to `space`) at the given time
+    pub(crate) fn create(
+        &mut self,
+        session: sys::Session,
+        space: sys::Space,
+        transform: &Transform,
+        time: openxr::Time,
+    ) -> Result<XRAnchorId, XrError> {
+        let fp = self
+            .instance
+            .exts()
+            .msft_spatial_anchor
+            .as_ref()
+            .ok_or(XrError::XR(sys::Result::ERROR_EXTENSION_NOT_PRESENT))?;
+
+        let create_info = sys::SpatialAnchorCreateInfoMSFT {
+            ty: sys::SpatialAnchorCreateInfoMSFT::TYPE,
+            next: ptr::null(),
+            space,
+            pose: transform_to_pose(transform),
+            time,
+        };
+
+        let mut anchor = sys::SpatialAnchorMSFT::NULL;
+        cvt(unsafe { (fp.create_spatial_anchor)(session, &create_info, &mut anchor) })?;
+
+        self.insert(session, anchor)
+    }
+
+    /// Current pose of the anchor relative to `base`. `None` if the anchor is not currently tracked
+    pub(crate) fn locate(
+        &self,
+        id: XRAnchorId,
+        base: sys::Space,
+        time: openxr::Time,
+    ) -> Result<Option<Transform>, XrError> {
+        let anchor = match self.anchors.get(&id) {
+            Some(anchor) => anchor,
+            None => return Ok(None),
+        };
+
+        let mut location = sys::SpaceLocation {
+            ty: sys::SpaceLocation::TYPE,
+            next: ptr::null_mut(),
+            location_flags: sys::SpaceLocationFlags::EMPTY,
+            pose: transform_to_pose(&Transform::identity()),
+        };
+
+        cvt(unsafe { (self.instance.fp().locate_space)(anchor.space, base, time, &mut location) })?;
+
+        let valid =
+            sys::SpaceLocationFlags::POSITION_VALID | sys::SpaceLocationFlags::ORIENTATION_VALID;
+        if !location.location_flags.contains(valid) {
+            return Ok(None);
+        }
+
+        Ok(Some(pose_to_transform(&location.pose)))
+    }
+
+    pub(crate) fn destroy(&mut self, id: XRAnchorId) {
+        if let Some(anchor) = self.anchors.remove(&id) {
+            self.destroy_anchor(anchor);
+        }
+    }
+
+    /// Persists the anchor into the anchor store with `name`, replacing an anchor with the same name
+    pub(crate) fn persist(
+        &mut self,
+        session: sys::Session,
+        id: XRAnchorId,
+        name: &str,
+    ) -> Result<(), XrError> {
+        let anchor = match self.anchors.get(&id) {
+            Some(anchor) => anchor.anchor,
+            None => return Err(XrError::XR(sys::Result::ERROR_HANDLE_INVALID)),
+        };
+
+        let store = self.store(session)?;
+        let fp = self.persistence_fp()?;
+
+        let info = sys::SpatialAnchorPersistenceInfoMSFT {
+            ty: sys::SpatialAnchorPersistenceInfoMSFT::TYPE,
+            next: ptr::null(),
+            spatial_anchor_persistence_name: persistence_name(name)?,
+            spatial_anchor: anchor,
+        };
+
+        cvt(unsafe { (fp.persist_spatial_anchor)(store, &info) })
+    }
+
+    /// Names of the anchors in the anchor store
+    pub(crate) fn persisted_names(
+        &mut self,
+        session: sys::Session,
+    ) -> Result<Vec<String>, XrError> {
+        let store = self.store(session)?;
+        let fp = self.persistence_fp()?;
+
+        let mut count = 0;
+        cvt(unsafe {
+            (fp.enumerate_persisted_spatial_anchor_names)(store, 0, &mut count, ptr::null_mut())
+        })?;
+
+        let mut names = vec![
+            sys::SpatialAnchorPersistenceNameMSFT {
+                name: [0; sys::MAX_SPATIAL_ANCHOR_NAME_SIZE_MSFT],
+            };
+            count as usize
+        ];
+
+        cvt(unsafe {
+            (fp.enumerate_persisted_spatial_anchor_names)(
+                store,
+                count,
+                &mut count,
+                names.as_mut_ptr(),
+            )
+        })?;
+
+        Ok(names
+            .iter()
+            .take(count as usize)
+            .map(|name| {
+                unsafe { CStr::from_ptr(name.name.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect())
+    }
+
+    /// Creates an anchor from the anchor store
+    pub(crate) fn load(
+        &mut self,
+        session: sys::Session,
+        name: &str,
+    ) -> Result<XRAnchorId, XrError> {
+        let store = self.store(session)?;
+        let fp = self.persistence_fp()?;
+
+        let create_info = sys::SpatialAnchorFromPersistedAnchorCreateInfoMSFT {
+            ty: sys::SpatialAnchorFromPersistedAnchorCreateInfoMSFT::TYPE,
+            next: ptr::null(),
+            spatial_anchor_store: store,
+            spatial_anchor_persistence_name: &persistence_name(name)?,
+        };
+
+        let mut anchor = sys::SpatialAnchorMSFT::NULL;
+        cvt(unsafe {
+            (fp.create_spatial_anchor_from_persisted_name)(session, &create_info, &mut anchor)
+        })?;
+
+        self.insert(session, anchor)
+    }
+
+    fn insert(
+        &mut self,
+        session: sys::Session,
+        anchor: sys::SpatialAnchorMSFT,
+    ) -> Result<XRAnchorId, XrError> {
+        let fp = self.instance.exts().msft_spatial_anchor.as_ref().unwrap();
+
+        let space_create_info = sys::SpatialAnchorSpaceCreateInfoMSFT {
+            ty: sys::SpatialAnchorSpaceCreateInfoMSFT::TYPE,
+            next: ptr::null(),
+            anchor,
+            pose_in_anchor_space: transform_to_pose(&Transform::identity()),
+        };
+
+        let mut space = sys::Space::NULL;
+        let ret =
+            unsafe { (fp.create_spatial_anchor_space)(session, &space_create_info, &mut space) };
+        if let Err(e) = cvt(ret) {
+            unsafe { (fp.destroy_spatial_anchor)(anchor) };
+            return Err(e);
+        }
+
+        let id = XRAnchorId(self.next_id);
+        self.next_id += 1;
+        self.anchors.insert(id, SpatialAnchor { anchor, space });
+
+        debug!(target: "bevy_openxr::session", ?id, "Spatial anchor created");
+        Ok(id)
+    }
+
+    fn store(
+        &mut self,
+        session: sys::Session,
+    ) -> Result<sys::SpatialAnchorStoreConnectionMSFT, XrError> {
+        if let Some(store) = self.store {
+            return Ok(store);
+        }
+
+        let fp = self.persistence_fp()?;
+        let mut store = sys::SpatialAnchorStoreConnectionMSFT::NULL;
+        cvt(unsafe { (fp.create_spatial_anchor_store_connection)(session, &mut store) })?;
+
+        self.store = Some(store);
+        Ok(store)
+    }
+
+    fn persistence_fp(&self) -> Result<&openxr::raw::SpatialAnchorPersistenceMSFT, XrError> {
+        self.instance
+            .exts()
+            .msft_spatial_anchor_persistence
+            .as_ref()
+            .ok_or(XrError::XR(sys::Result::ERROR_EXTENSION_NOT_PRESENT))
+    }
+
+    fn destroy_anchor(&self, anchor: SpatialAnchor) {
+        let fp = self.instance.exts().msft_spatial_anchor.as_ref().unwrap();
+        unsafe {
+            (self.instance.fp().destroy_space)(anchor.space);
+            (fp.destroy_spatial_anchor)(anchor.anchor);
+        }
+    }
+}
+
+impl Drop for SpatialAnchors {
+    fn drop(&mut self) {
+        for (_, anchor) in std::mem::take(&mut self.anchors) {
+            self.destroy_anchor(anchor);
+        }
+
+        if let (Some(store), Ok(fp)) = (self.store, self.persistence_fp()) {
+            unsafe { (fp.destroy_spatial_anchor_store_connection)(store) };
+        }
+    }
+}
+
+fn persistence_name(name: &str) -> Result<sys::SpatialAnchorPersistenceNameMSFT, XrError> {
+    let mut persistence_name = sys::SpatialAnchorPersistenceNameMSFT {
+        name: [0; sys::MAX_SPATIAL_ANCHOR_NAME_SIZE_MSFT],
+    };
+
+    // null-terminated
+    if name.is_empty() || name.len() >= persistence_name.name.len() {
+        return Err(XrError::XR(
+            sys::Result::ERROR_SPATIAL_ANCHOR_NAME_INVALID_MSFT,
+        ));
+    }
+
+    for (dst, src) in persistence_name.name.iter_mut().zip(name.bytes()) {
+        *dst = src as c_char;
+    }
+
+    Ok(persistence_name)
+}
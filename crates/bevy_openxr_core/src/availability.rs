@@ -0,0 +1,16 @@
+/// Whether the app runs with an OpenXR session, set by `OpenXRPlugin`. With
+/// `OpenXRSettings::desktop_fallback`, the app continues as a regular bevy app if the OpenXR runtime
+/// can't be loaded or initialized, so that a single binary can serve both XR and flat users
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XRAvailability {
+    Available,
+
+    /// OpenXR could not be initialized, with the reason
+    Unavailable(String),
+}
+
+impl XRAvailability {
+    pub fn is_available(&self) -> bool {
+        matches!(self, XRAvailability::Available)
+    }
+}
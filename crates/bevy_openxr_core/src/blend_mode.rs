@@ -0,0 +1,35 @@
+/// How the rendered image is composited with the real world. Selected by the runtime preference when
+/// the swapchain is created, and available as a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XREnvironmentBlendMode {
+    /// Rendered image is displayed as is, e.g. VR headsets
+    Opaque,
+
+    /// Rendered image is added to the real world, e.g. optical see-through AR. Alpha is ignored, and
+    /// black is transparent
+    Additive,
+
+    /// Rendered image is alpha blended over the real world, e.g. video see-through AR
+    AlphaBlend,
+}
+
+impl XREnvironmentBlendMode {
+    pub(crate) fn from_openxr(mode: openxr::EnvironmentBlendMode) -> Self {
+        match mode {
+            openxr::EnvironmentBlendMode::ADDITIVE => XREnvironmentBlendMode::Additive,
+            openxr::EnvironmentBlendMode::ALPHA_BLEND => XREnvironmentBlendMode::AlphaBlend,
+            _ => XREnvironmentBlendMode::Opaque,
+        }
+    }
+
+    /// Real world shows through the areas that are not rendered to
+    pub fn is_see_through(&self) -> bool {
+        !matches!(self, XREnvironmentBlendMode::Opaque)
+    }
+}
+
+impl Default for XREnvironmentBlendMode {
+    fn default() -> Self {
+        XREnvironmentBlendMode::Opaque
+    }
+}
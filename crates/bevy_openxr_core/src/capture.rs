@@ -0,0 +1,100 @@
+use std::num::NonZeroU32;
+
+use bevy::utils::tracing::info_span;
+
+/// Rendered image of a view, read back from the swapchain
+#[derive(Debug, Clone)]
+pub struct XRCapturedImage {
+    pub view_index: u32,
+    pub width: u32,
+    pub height: u32,
+
+    /// Tightly packed RGBA8 pixels, rows from top to bottom
+    pub data: Vec<u8>,
+}
+
+/// Copies the region of the texture array `layer` into a CPU buffer. Blocks until the GPU has
+/// finished the copy
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    format: wgpu::TextureFormat,
+    origin: (u32, u32),
+    layer: u32,
+    size: (u32, u32),
+) -> Option<Vec<u8>> {
+    let bgra = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        _ => return None,
+    };
+
+    let span = info_span!(target: "bevy_openxr::swapchain", "xr_read_texture");
+    let _guard = span.enter();
+
+    let (width, height) = size;
+    let unpadded_bytes_per_row = 4 * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("xr_capture_buffer"),
+        size: (padded_bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("xr_capture"),
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: origin.0,
+                y: origin.1,
+                z: layer,
+            },
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let mapping = slice.map_async(wgpu::MapMode::Read);
+    device.poll(wgpu::Maintain::Wait);
+    futures_lite::future::block_on(mapping).ok()?;
+
+    let mut data = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let mapped = slice.get_mapped_range();
+        for row in mapped.chunks(padded_bytes_per_row as usize) {
+            data.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if bgra {
+        for pixel in data.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Some(data)
+}
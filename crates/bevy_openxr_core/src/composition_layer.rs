@@ -0,0 +1,336 @@
+use std::{collections::HashMap, num::NonZeroU32, ptr, sync::Arc};
+
+use bevy::transform::components::Transform;
+use bevy::utils::tracing::{debug, info_span};
+use openxr::sys;
+
+use crate::{
+    math::transform_to_pose,
+    swapchain::{map_texture_format, map_vk_format},
+    XrError,
+};
+
+/// Identifies a composition layer owned by `XRDevice`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XRLayerId(u64);
+
+/// Submission order of a layer. Layers are composited in ascending order, each over the layers
+/// before it. The projection layer (the rendered scene) is at `PROJECTION`: layers ordered before
+/// it are underlays (the projection layer is then alpha blended over them), layers after it overlays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XRLayerOrder(pub i32);
+
+impl XRLayerOrder {
+    /// Passthrough (`XR_FB_passthrough`), below everything else
+    pub const PASSTHROUGH: XRLayerOrder = XRLayerOrder(-1000);
+    pub const PROJECTION: XRLayerOrder = XRLayerOrder(0);
+
+    /// Default of composition layers, over the projection layer
+    pub const OVERLAY: XRLayerOrder = XRLayerOrder(100);
+
+    pub fn is_underlay(&self) -> bool {
+        *self < XRLayerOrder::PROJECTION
+    }
+}
+
+impl Default for XRLayerOrder {
+    fn default() -> Self {
+        XRLayerOrder::OVERLAY
+    }
+}
+
+/// Surface shape of a composition layer, composited by the runtime (by default over the rendered scene)
+#[derive(Debug, Clone, PartialEq)]
+pub enum XRLayerShape {
+    /// Curved panel (`XR_KHR_composition_layer_cylinder`), e.g. for video
+    Cylinder {
+        radius: f32,
+
+        /// Angle of the visible section of the cylinder, in radians
+        central_angle: f32,
+
+        /// Width / height of the visible section
+        aspect_ratio: f32,
+    },
+
+    /// 360 image mapped onto a sphere (`XR_KHR_composition_layer_equirect`). Radius of 0 is infinite
+    Equirect { radius: f32 },
+}
+
+impl XRLayerShape {
+    fn is_supported(&self, instance: &openxr::Instance) -> bool {
+        match self {
+            XRLayerShape::Cylinder { .. } => {
+                instance.exts().khr_composition_layer_cylinder.is_some()
+            }
+            XRLayerShape::Equirect { .. } => {
+                instance.exts().khr_composition_layer_equirect.is_some()
+            }
+        }
+    }
+}
+
+struct CompositionLayer {
+    swapchain: openxr::Swapchain<openxr::Vulkan>,
+    textures: Vec<wgpu::Texture>,
+    width: u32,
+    height: u32,
+    shape: XRLayerShape,
+    pose: Transform,
+    visible: bool,
+    order: XRLayerOrder,
+
+    /// An image has been released, and the layer can be submitted
+    has_content: bool,
+}
+
+/// Raw layer structs, kept alive until `xrEndFrame`
+pub(crate) enum LayerData {
+    Cylinder(sys::CompositionLayerCylinderKHR),
+    Equirect(sys::CompositionLayerEquirectKHR),
+}
+
+impl LayerData {
+    pub(crate) fn as_base_header(&self) -> *const sys::CompositionLayerBaseHeader {
+        match self {
+            LayerData::Cylinder(layer) => layer as *const _ as *const _,
+            LayerData::Equirect(layer) => layer as *const _ as *const _,
+        }
+    }
+}
+
+/// Composition layers with their own swapchains, composited over the projection layer
+pub(crate) struct CompositionLayers {
+    layers: HashMap<XRLayerId, CompositionLayer>,
+    next_id: u64,
+}
+
+impl CompositionLayers {
+    pub(crate) fn new() -> Self {
+        CompositionLayers {
+            layers: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub(crate) fn create(
+        &mut self,
+        instance: &openxr::Instance,
+        session: &openxr::Session<openxr::Vulkan>,
+        device: &Arc<wgpu::Device>,
+        shape: XRLayerShape,
+        width: u32,
+        height: u32,
+    ) -> Result<XRLayerId, XrError> {
+        if !shape.is_supported(instance) {
+            return Err(XrError::XR(sys::Result::ERROR_EXTENSION_NOT_PRESENT));
+        }
+
+        // images are uploaded from RGBA8 data
+        let (vk_format, format) = session
+            .enumerate_swapchain_formats()?
+            .into_iter()
+            .map(|f| ash::vk::Format::from_raw(f as i32))
+            .filter_map(|vk_format| {
+                let format = map_texture_format(map_vk_format(vk_format)?)?;
+                Some((vk_format, format))
+            })
+            .find(|(_, format)| {
+                *format == wgpu::TextureFormat::Rgba8UnormSrgb
+                    || *format == wgpu::TextureFormat::Rgba8Unorm
+            })
+            .ok_or(XrError::NoSupportedSwapchainFormat)?;
+
+        let swapchain = session.create_swapchain(&openxr::SwapchainCreateInfo {
+            create_flags: openxr::SwapchainCreateFlags::EMPTY,
+            usage_flags: openxr::SwapchainUsageFlags::TRANSFER_DST
+                | openxr::SwapchainUsageFlags::SAMPLED,
+            format: vk_format.as_raw() as _,
+            sample_count: 1,
+            width,
+            height,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        })?;
+
+        let textures = swapchain
+            .enumerate_images()?
+            .into_iter()
+            .map(|image| {
+                // FIXME keep in sync with above usage_flags
+                device.create_openxr_texture_from_raw_image(
+                    &wgpu::TextureDescriptor {
+                        size: wgpu::Extent3d {
+                            width,
+                            height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        usage: wgpu::TextureUsage::COPY_DST | wgpu::TextureUsage::SAMPLED,
+                        label: None,
+                    },
+                    image,
+                )
+            })
+            .collect();
+
+        let id = XRLayerId(self.next_id);
+        self.next_id += 1;
+
+        debug!(target: "bevy_openxr::swapchain", ?id, ?shape, width, height, "Composition layer created");
+
+        self.layers.insert(
+            id,
+            CompositionLayer {
+                swapchain,
+                textures,
+                width,
+                height,
+                shape,
+                pose: Transform::identity(),
+                visible: true,
+                order: XRLayerOrder::default(),
+                has_content: false,
+            },
+        );
+
+        Ok(id)
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        id: XRLayerId,
+        shape: XRLayerShape,
+        pose: Transform,
+        visible: bool,
+    ) {
+        if let Some(layer) = self.layers.get_mut(&id) {
+            layer.shape = shape;
+            layer.pose = pose;
+            layer.visible = visible;
+        }
+    }
+
+    pub(crate) fn set_order(&mut self, id: XRLayerId, order: XRLayerOrder) {
+        if let Some(layer) = self.layers.get_mut(&id) {
+            layer.order = order;
+        }
+    }
+
+    /// Uploads tightly packed RGBA8 `data` into the next swapchain image of the layer
+    pub(crate) fn write_image(
+        &mut self,
+        id: XRLayerId,
+        queue: &wgpu::Queue,
+        data: &[u8],
+    ) -> Result<(), XrError> {
+        let layer = match self.layers.get_mut(&id) {
+            Some(layer) => layer,
+            None => return Ok(()),
+        };
+
+        let span = info_span!(target: "bevy_openxr::swapchain", "xr_write_layer_image");
+        let _guard = span.enter();
+
+        let image_index = layer.swapchain.acquire_image()? as usize;
+        layer.swapchain.wait_image(openxr::Duration::INFINITE)?;
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &layer.textures[image_index],
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * layer.width),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: layer.width,
+                height: layer.height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        layer.swapchain.release_image()?;
+        layer.has_content = true;
+
+        Ok(())
+    }
+
+    pub(crate) fn destroy(&mut self, id: XRLayerId) {
+        self.layers.remove(&id);
+    }
+
+    /// Raw layer structs of visible layers with their order, in reference `space`
+    pub(crate) fn layer_data(&self, space: sys::Space) -> Vec<(XRLayerOrder, LayerData)> {
+        let mut layers = self
+            .layers
+            .iter()
+            .filter(|(_, layer)| layer.visible && layer.has_content)
+            .collect::<Vec<_>>();
+
+        // creation order among layers of the same order
+        layers.sort_by_key(|(id, layer)| (layer.order, **id));
+
+        layers
+            .into_iter()
+            .map(|(_, layer)| {
+                let sub_image = sys::SwapchainSubImage {
+                    swapchain: layer.swapchain.as_raw(),
+                    image_rect: sys::Rect2Di {
+                        offset: sys::Offset2Di { x: 0, y: 0 },
+                        extent: sys::Extent2Di {
+                            width: layer.width as _,
+                            height: layer.height as _,
+                        },
+                    },
+                    image_array_index: 0,
+                };
+
+                let pose = transform_to_pose(&layer.pose);
+
+                let data = match layer.shape {
+                    XRLayerShape::Cylinder {
+                        radius,
+                        central_angle,
+                        aspect_ratio,
+                    } => LayerData::Cylinder(sys::CompositionLayerCylinderKHR {
+                        ty: sys::CompositionLayerCylinderKHR::TYPE,
+                        next: ptr::null(),
+                        layer_flags: sys::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+                        space,
+                        eye_visibility: sys::EyeVisibility::BOTH,
+                        sub_image,
+                        pose,
+                        radius,
+                        central_angle,
+                        aspect_ratio,
+                    }),
+                    XRLayerShape::Equirect { radius } => {
+                        LayerData::Equirect(sys::CompositionLayerEquirectKHR {
+                            ty: sys::CompositionLayerEquirectKHR::TYPE,
+                            next: ptr::null(),
+                            layer_flags: sys::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+                            space,
+                            eye_visibility: sys::EyeVisibility::BOTH,
+                            sub_image,
+                            pose,
+                            radius,
+                            scale: sys::Vector2f { x: 1., y: 1. },
+                            bias: sys::Vector2f { x: 0., y: 0. },
+                        })
+                    }
+                };
+
+                (layer.order, data)
+            })
+            .collect()
+    }
+}
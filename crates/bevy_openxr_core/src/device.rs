@@ -1,15 +1,44 @@
 use std::sync::Arc;
 
+use bevy::ecs::{schedule::ShouldRun, system::Res};
 use bevy::transform::components::Transform;
-use openxr::ViewConfigurationType;
+use bevy::utils::tracing::{debug, error, info, warn};
+use bevy::utils::Duration;
 
 use crate::{
-    event::{XREvent, XRViewSurfaceCreated, XRViewsCreated},
-    hand_tracking::HandPoseState,
-    OpenXRStruct, XRState, XRSwapchain,
+    anchors::{SpatialAnchors, XRAnchorId},
+    capture::XRCapturedImage,
+    composition_layer::{CompositionLayers, XRLayerId, XRLayerOrder, XRLayerShape},
+    diagnostics::XRFrameTimings,
+    event::{
+        XREvent, XRFrameSkipReason, XRFrameSkipped, XRSecondaryView, XRSwapchainRecreated,
+        XRViewSurfaceCreated, XRViewsCreated, XRVisibilityMaskChanged, XrSessionState,
+    },
+    hand_tracking::{HandPoseState, XRHandJointsMotionRange, XRHandMesh},
+    input_source,
+    math::pose_to_transform,
+    passthrough::FBPassthrough,
+    performance::{self, XRPerformanceDomain, XRPerformanceLevel},
+    system_info::XRSystemInfo,
+    view_config::XRViewConfigInfo,
+    visibility_mask::{self, XRVisibilityMask},
+    OpenXRStruct, XREnvironmentBlendMode, XRSwapchain, XrError, XrFrameStatus,
 };
 
 pub struct XRDevice {
+    /// Passthrough layer, created at first `set_passthrough` call. Declared first, so that it is
+    /// dropped before the session
+    passthrough: Option<FBPassthrough>,
+
+    /// Spatial anchors, dropped before the session as well
+    anchors: SpatialAnchors,
+
+    /// Cylinder and equirect layers, dropped before the session as well
+    layers: CompositionLayers,
+
+    /// `VIEW` reference space for head pose queries, created at first `predict_head_pose` call
+    view_space: Option<openxr::Space>,
+
     pub(crate) inner: OpenXRStruct,
 
     /// Swapchain. Must be `Option` because initializing swapchain requires access to `wgpu::Device`
@@ -18,6 +47,37 @@ pub struct XRDevice {
 
     /// Event collection to convert into bevy events
     events_to_send: Vec<XREvent>,
+
+    /// Properties of the system and the swapchain
+    system_info: XRSystemInfo,
+
+    view_config_info: XRViewConfigInfo,
+
+    /// Swapchain became unusable, and is recreated at the next `prepare_update`
+    swapchain_lost: bool,
+
+    /// Number of swapchain recreations
+    swapchain_generation: u32,
+}
+
+const PALM_POSE_EXTENSION: &str = "XR_EXT_palm_pose";
+
+/// Input path of the palm pose (`XR_EXT_palm_pose`), relative to `/user/hand/left` and `/user/hand/right`
+pub const PALM_POSE_PATH: &str = "/input/palm_ext/pose";
+
+/// Frame loop handles borrowed from `XRDevice::frame_loop`
+pub struct XRFrameLoop<'a> {
+    handles: &'a mut wgpu::OpenXRHandles,
+}
+
+impl<'a> XRFrameLoop<'a> {
+    pub fn frame_waiter(&mut self) -> &mut openxr::FrameWaiter {
+        &mut self.handles.frame_waiter
+    }
+
+    pub fn frame_stream(&mut self) -> &mut openxr::FrameStream<openxr::Vulkan> {
+        &mut self.handles.frame_stream
+    }
 }
 
 impl XRDevice {
@@ -27,81 +87,308 @@ impl XRDevice {
             .system_properties(xr_struct.handles.system)
             .unwrap();
 
-        let view_configuration_properties = xr_struct
-            .instance
-            .view_configuration_properties(
-                xr_struct.handles.system,
-                ViewConfigurationType::PRIMARY_STEREO,
-            )
-            .unwrap();
+        let view_config_info = XRViewConfigInfo::new(
+            &xr_struct.instance,
+            xr_struct.handles.system,
+            xr_struct.options.view_type,
+        )
+        .unwrap();
 
-        println!(
-            "SystemId: {:?}, view configuration: {:#?}",
-            system_properties.system_id, view_configuration_properties
+        info!(
+            target: "bevy_openxr::session",
+            system_id = ?system_properties.system_id,
+            system_name = %system_properties.system_name,
+            "OpenXR system initialized"
+        );
+        debug!(
+            target: "bevy_openxr::session",
+            "View configuration: {:#?}",
+            view_config_info
         );
 
         Self {
+            passthrough: None,
+            anchors: SpatialAnchors::new(&xr_struct.instance),
+            layers: CompositionLayers::new(),
+            view_space: None,
             inner: xr_struct,
             swapchain: None,
             events_to_send: Vec::new(),
+            system_info: XRSystemInfo::new(&system_properties),
+            view_config_info,
+            swapchain_lost: false,
+            swapchain_generation: 0,
         }
     }
 
-    pub fn touch_update(&mut self) -> XRState {
-        if self.swapchain.is_none() {
-            return XRState::Paused; // FIXME or uninitialized?
+    /// Requests the runtime to end the session (`xrRequestExitSession`), e.g. from a "Quit VR" button.
+    /// The session moves through `XrSessionState::Stopping` to `XrSessionState::Exiting`, which runs
+    /// `XRStage::Exit` and exits the app with `AppExit`. A session that is not running exits directly.
+    /// Also available as the `XRExitRequest` event
+    pub fn request_exit(&mut self) {
+        if self.inner.is_exiting() {
+            return;
+        }
+
+        info!(target: "bevy_openxr::session", state = ?self.inner.session_state(), "Exit requested");
+
+        if self.inner.is_running() {
+            let result = self
+                .inner
+                .handles
+                .session
+                .request_exit()
+                .map_err(XrError::from);
+            if self.report("xrRequestExitSession", result).is_some() {
+                return;
+            }
         }
 
+        self.inner.exit_requested = true;
+    }
+
+    /// OpenXR instance, for calls not wrapped by the crate
+    pub fn instance(&self) -> &openxr::Instance {
+        &self.inner.instance
+    }
+
+    /// OpenXR session, for calls not wrapped by the crate. The session is begun and ended by the
+    /// crate on session state changes, so `begin`, `end` and `request_exit` should not be called
+    /// directly (see `request_exit`)
+    pub fn session(&self) -> &openxr::Session<openxr::Vulkan> {
+        &self.inner.handles.session
+    }
+
+    /// Reference space the views, hands and anchors are located in
+    pub fn reference_space(&self) -> &openxr::Space {
+        &self.inner.handles.space
+    }
+
+    /// Size (width, depth) of the play area rect, centered at the origin of the `STAGE` reference
+    /// space. `None` if the runtime does not know the bounds, e.g. the user has not set up a boundary
+    pub fn play_area_bounds(&mut self) -> Option<(f32, f32)> {
+        let result = self
+            .inner
+            .handles
+            .session
+            .reference_space_bounds_rect(openxr::ReferenceSpaceType::STAGE)
+            .map_err(XrError::from);
+
+        self.report("xrGetReferenceSpaceBoundsRect", result)?
+            .map(|extent| (extent.width, extent.height))
+    }
+
+    pub fn system_id(&self) -> openxr::SystemId {
+        self.inner.handles.system
+    }
+
+    /// Frame waiter and frame stream, for a frame loop driven by the application. Fails with
+    /// `XrError::FrameLoopActive` while the XR frame loop drives frames, i.e. the session is running
+    /// and the swapchain has been created, since waiting or ending a frame in between would break
+    /// the frame submitted by `finalize_update`
+    pub fn frame_loop(&mut self) -> Result<XRFrameLoop<'_>, XrError> {
+        if self.inner.is_running() && self.swapchain.is_some() {
+            return Err(XrError::FrameLoopActive);
+        }
+
+        Ok(XRFrameLoop {
+            handles: &mut self.inner.handles,
+        })
+    }
+
+    /// Session is running, and frames are waited for and rendered
+    pub fn is_session_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    /// Session is running and its views are configured, so XR content can be spawned. Views are
+    /// configured in the first frame after the session begins
+    pub fn is_session_ready(&self) -> bool {
+        self.is_session_running() && self.views_configured()
+    }
+
+    /// Swapchain has been created, and the views have been sent as `XRViewSurfaceCreated` and
+    /// `XRViewsCreated` events
+    pub fn views_configured(&self) -> bool {
+        self.swapchain.is_some()
+    }
+
+    /// Environment blend mode used by the swapchain, `None` before the swapchain has been created
+    pub fn environment_blend_mode(&self) -> Option<XREnvironmentBlendMode> {
+        Some(self.swapchain.as_ref()?.get_environment_blend_mode())
+    }
+
+    pub fn session_state(&self) -> XrSessionState {
+        self.inner.session_state()
+    }
+
+    /// Name of the XR system (headset), as reported by the runtime
+    pub fn get_system_name(&self) -> &str {
+        &self.system_info.system_name
+    }
+
+    pub fn system_info(&self) -> &XRSystemInfo {
+        &self.system_info
+    }
+
+    /// Views of the view configuration, with their recommended and max image sizes
+    pub fn view_config_info(&self) -> &XRViewConfigInfo {
+        &self.view_config_info
+    }
+
+    /// Waits for the next frame, and acquires the swapchain image to be rendered into. The image is
+    /// waited for later at `prepare_update`, just before rendering
+    pub fn touch_update(&mut self) -> XrFrameStatus {
+        // frames are waited for and submitted only between xrBeginSession and xrEndSession, i.e. not
+        // before the session is ready, nor after it started stopping
+        if !self.inner.is_running() {
+            return XrFrameStatus::Skip;
+        }
+
+        let swapchain = match self.swapchain.as_mut() {
+            Some(swapchain) => swapchain,
+            None => return XrFrameStatus::Skip, // swapchain is created at first prepare_update
+        };
+
+        let frame_status = swapchain.prepare_update(&mut self.inner.handles);
+        let frame_status = match self.report("xrWaitFrame", frame_status) {
+            Some(frame_status) => frame_status,
+            None => return XrFrameStatus::Skip,
+        };
+
+        if !frame_status.should_render() || self.is_headless() {
+            return frame_status;
+        }
+
+        let acquired = self.swapchain.as_mut().unwrap().acquire_next_image();
+        match self.report_swapchain_image("xrAcquireSwapchainImage", acquired) {
+            Some(()) => frame_status,
+            None => XrFrameStatus::Skip,
+        }
+    }
+
+    /// Session runs without rendering (`XrOptions::headless`)
+    pub fn is_headless(&self) -> bool {
+        self.inner.options.headless
+    }
+
+    /// Hands are tracked by the runtime. `false` also before the swapchain has been created
+    pub fn has_hand_tracking(&self) -> bool {
         self.swapchain
+            .as_ref()
+            .map_or(false, |swapchain| swapchain.has_hand_trackers())
+    }
+
+    /// Sets the motion range of a tracked hand holding a controller. Returns `false` if hands are not
+    /// tracked. Ignored by runtimes without `XR_EXT_hand_joints_motion_range`
+    pub fn set_hand_joints_motion_range(
+        &mut self,
+        hand: openxr::HandEXT,
+        motion_range: XRHandJointsMotionRange,
+    ) -> bool {
+        match self
+            .swapchain
             .as_mut()
-            .unwrap()
-            .prepare_update(&mut self.inner.handles)
+            .and_then(|swapchain| swapchain.get_hand_trackers_mut())
+        {
+            Some(hand_trackers) => {
+                hand_trackers.set_motion_range(hand, motion_range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Runtime provided mesh of the user's hand (`XR_FB_hand_tracking_mesh`). The mesh does not change
+    /// during the session. `None` if hands are not tracked or the extension is not supported
+    pub fn get_hand_mesh(&mut self, hand: openxr::HandEXT) -> Option<XRHandMesh> {
+        let mesh = self
+            .swapchain
+            .as_ref()?
+            .get_hand_trackers()?
+            .get_hand_mesh(hand)?;
+
+        self.report("xrGetHandMeshFB", mesh)
     }
 
     pub fn get_hand_positions(&mut self) -> Option<HandPoseState> {
-        if self.swapchain.is_none() {
+        let swapchain = self.swapchain.as_mut()?;
+        let hand_positions = swapchain.get_hand_positions(&mut self.inner.handles);
+
+        self.report("xrLocateHandJointsEXT", hand_positions)?
+    }
+
+    /// Current interaction profiles of the left and right hand, if they changed since the last call
+    pub(crate) fn interaction_profile_changes(&mut self) -> Option<[Option<String>; 2]> {
+        if !self.inner.take_interaction_profile_changed() {
             return None;
         }
 
-        self.swapchain
-            .as_mut()
-            .unwrap()
-            .get_hand_positions(&mut self.inner.handles)
+        let profiles = input_source::current_interaction_profiles(
+            &self.inner.instance,
+            &self.inner.handles.session,
+        );
+        self.report("xrGetCurrentInteractionProfile", profiles)
     }
 
     pub fn prepare_update(
         &mut self,
         device: &Arc<wgpu::Device>,
-    ) -> (XRState, Option<Vec<wgpu::TextureView>>) {
-        // construct swapchain at first call
-        if self.swapchain.is_none() {
-            let mut swapchain = XRSwapchain::new(device.clone(), &mut self.inner);
+    ) -> (XrFrameStatus, Option<Vec<wgpu::TextureView>>) {
+        // the swapchain is created, and frames are waited for, only while the session is running
+        if !self.inner.is_running() {
+            return (XrFrameStatus::Skip, None);
+        }
+
+        if self.swapchain_lost && self.swapchain.is_some() {
+            warn!(target: "bevy_openxr::swapchain", "Recreating swapchain");
+            self.swapchain = None;
+            self.swapchain_generation += 1;
+            self.events_to_send
+                .push(XREvent::SwapchainRecreated(XRSwapchainRecreated {
+                    generation: self.swapchain_generation,
+                }));
+        }
 
-            swapchain.prepare_update(&mut self.inner.handles);
+        // construct swapchain at first call, or after the swapchain was lost
+        if self.swapchain.is_none() {
+            let swapchain = XRSwapchain::new(device.clone(), &mut self.inner);
+            let mut swapchain = match self.report("xrCreateSwapchain", swapchain) {
+                Some(swapchain) => swapchain,
+                None => return (XrFrameStatus::Skip, None),
+            };
 
             let views = swapchain
-                .get_views(&mut self.inner.handles)
-                .iter()
-                .map(|view| View {
-                    fov: XrFovf {
-                        angle_left: view.fov.angle_left,
-                        angle_right: view.fov.angle_right,
-                        angle_down: view.fov.angle_down,
-                        angle_up: view.fov.angle_up,
-                    },
-                })
-                .collect::<Vec<View>>();
+                .prepare_update(&mut self.inner.handles)
+                .and_then(|_| swapchain.get_views(&mut self.inner.handles));
+
+            let views = match self.report("xrLocateViews", views) {
+                Some(views) => views
+                    .iter()
+                    .map(|view| View {
+                        fov: view.fov.into(),
+                    })
+                    .collect::<Vec<View>>(),
+                None => return (XrFrameStatus::Skip, None),
+            };
 
             let resolution = swapchain.get_resolution();
-            println!(
-                "Swapchain configured, resolution {:?}, views: {:#?}",
-                resolution, views
+            info!(
+                target: "bevy_openxr::swapchain",
+                width = resolution.0,
+                height = resolution.1,
+                "Swapchain configured"
             );
+            debug!(target: "bevy_openxr::swapchain", "Views: {:#?}", views);
 
             self.events_to_send
                 .push(XREvent::ViewSurfaceCreated(XRViewSurfaceCreated {
                     width: resolution.0,
                     height: resolution.1,
+                    view_count: swapchain.get_view_count(),
+                    view_sizes: swapchain.get_view_sizes().to_vec(),
+                    stereo_mode: swapchain.get_stereo_mode(),
+                    format: swapchain.get_render_format(),
                 }));
 
             self.events_to_send
@@ -109,46 +396,511 @@ impl XRDevice {
                     views: views.clone(),
                 }));
 
+            self.system_info.swapchain_image_count = Some(swapchain.get_image_count());
+            debug!(
+                target: "bevy_openxr::swapchain",
+                image_count = swapchain.get_image_count(),
+                "Swapchain images"
+            );
+
             self.swapchain = Some(swapchain);
+            self.swapchain_lost = false;
 
             // hack to prevent render graph panic when output has not been sent
             // what will happen after this: event will be sent about xr view, XRWindowTextureNode will configure itself at next frame
             // and after that all will be okay
             // this doesn't actually work on all cases... have to investigate
             return (
-                XRState::SkipFrame,
+                XrFrameStatus::Skip,
                 Some(self.swapchain.as_mut().unwrap().take_texture_views()),
             );
         }
 
         // call swapchain update
-        (
-            self.swapchain
-                .as_mut()
-                .unwrap()
-                .prepare_update(&mut self.inner.handles),
-            None,
-        )
+        let frame_status = self
+            .swapchain
+            .as_mut()
+            .unwrap()
+            .prepare_update(&mut self.inner.handles);
+
+        let frame_status = match self.report("xrWaitFrame", frame_status) {
+            Some(XrFrameStatus::ShouldRender) => {
+                self.inner.graphics.frame_begin();
+                self.create_secondary_swapchains(device);
+                XrFrameStatus::ShouldRender
+            }
+            Some(XrFrameStatus::Skip) => {
+                self.frame_skipped(XRFrameSkipReason::NotRequested);
+                XrFrameStatus::Skip
+            }
+            None => {
+                self.frame_skipped(XRFrameSkipReason::WaitFrameFailed);
+                XrFrameStatus::Skip
+            }
+        };
+
+        (frame_status, None)
     }
 
-    pub fn get_view_positions(&mut self) -> Option<Vec<Transform>> {
+    /// Waits for the acquired swapchain image, and returns its index. `None` if the image
+    /// could not be acquired, and the frame should not be rendered
+    pub fn get_next_swapchain_image_index(&mut self) -> Option<usize> {
+        let image_index = self.swapchain.as_mut()?.get_next_swapchain_image_index();
+
+        let image_index = match self.report_swapchain_image("xrWaitSwapchainImage", image_index) {
+            Some(Some(image_index)) => image_index,
+            Some(None) => {
+                let timeout = self.inner.options.swapchain_image_timeout;
+                warn!(target: "bevy_openxr::swapchain", ?timeout, "Swapchain image wait timed out, skipping frame");
+                self.frame_skipped(XRFrameSkipReason::ImageWaitTimedOut { timeout });
+                return None;
+            }
+            None => {
+                self.frame_skipped(XRFrameSkipReason::ImageUnavailable);
+                return None;
+            }
+        };
+
+        // waiting longer than a display period delays the frame past its display time
+        let timings = self.swapchain.as_ref()?.get_timings();
+        if timings.display_period > Duration::ZERO && timings.image_wait > timings.display_period {
+            let wait = timings.image_wait;
+            self.frame_skipped(XRFrameSkipReason::ImageWaitStalled { wait });
+        }
+
+        Some(image_index)
+    }
+
+    /// Creates the swapchains of secondary views activated by the runtime for the first time
+    fn create_secondary_swapchains(&mut self, device: &wgpu::Device) {
+        let swapchain = match self.swapchain.as_mut() {
+            Some(swapchain) => swapchain,
+            None => return,
+        };
+
+        for surface in swapchain.create_secondary_swapchains(device, &self.inner) {
+            if let Some(surface) = self.report("xrCreateSwapchain", surface) {
+                self.events_to_send
+                    .push(XREvent::SecondaryViewSurfaceCreated(surface));
+            }
+        }
+    }
+
+    /// Texture views of the secondary view swapchains created in this frame, by index of the
+    /// secondary view (see `XRSecondaryViewSurfaceCreated`)
+    pub fn take_secondary_texture_views(&mut self) -> Vec<(usize, Vec<wgpu::TextureView>)> {
+        match self.swapchain.as_mut() {
+            Some(swapchain) => swapchain.take_secondary_texture_views(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Acquires and waits for the next image of the secondary view, and returns its index. `None` if
+    /// the view is not active in this frame, or the image could not be acquired. Call only after
+    /// `get_next_swapchain_image_index` returned an image, the image is submitted by `finalize_update`
+    pub fn get_next_secondary_image_index(&mut self, index: usize) -> Option<usize> {
+        let image_index = self
+            .swapchain
+            .as_mut()?
+            .get_next_secondary_image_index(index);
+
+        self.report("xrWaitSwapchainImage", image_index)?
+    }
+
+    fn frame_skipped(&mut self, reason: XRFrameSkipReason) {
+        debug!(target: "bevy_openxr::frame", ?reason, "Frame skipped");
+        self.events_to_send
+            .push(XREvent::FrameSkipped(XRFrameSkipped { reason }));
+    }
+
+    /// Poses and field of views of the views, located for the predicted display time of the frame
+    pub fn get_view_positions(&mut self) -> Option<(Vec<Transform>, Vec<XrFovf>)> {
         if !self.inner.is_running() {
             return None;
         }
 
-        let swapchain = match self.swapchain.as_mut() {
-            None => return None,
-            Some(sc) => sc,
+        let swapchain = self.swapchain.as_mut()?;
+        let view_positions = swapchain.get_view_positions(&mut self.inner.handles);
+
+        self.report("xrLocateViews", view_positions)?
+    }
+
+    /// Poses and field of views of the active secondary views, located for the predicted display time
+    /// of the frame. `None` without secondary views
+    pub fn get_secondary_view_positions(&mut self) -> Option<Vec<XRSecondaryView>> {
+        if !self.inner.is_running() {
+            return None;
+        }
+
+        let swapchain = self.swapchain.as_ref()?;
+        let view_positions = swapchain.get_secondary_view_positions(&self.inner.handles);
+
+        self.report("xrLocateViews", view_positions)?
+    }
+
+    /// Reads back the rendered images of the views from the swapchain, e.g. for screenshots. Must be
+    /// called after rendering, before `finalize_update`. Blocks until the GPU has rendered the frame
+    pub fn capture_views(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view_indices: &[u32],
+    ) -> Vec<XRCapturedImage> {
+        let swapchain = match self.swapchain.as_ref() {
+            Some(swapchain) => swapchain,
+            None => return Vec::new(),
         };
 
-        swapchain.get_view_positions(&mut self.inner.handles)
+        view_indices
+            .iter()
+            .filter_map(|view_index| swapchain.capture_view(device, queue, *view_index))
+            .collect()
     }
 
     pub fn finalize_update(&mut self) {
-        self.swapchain
-            .as_mut()
-            .unwrap()
-            .finalize_update(&mut self.inner.handles);
+        if !self.inner.is_running() {
+            return;
+        }
+
+        // a frame that was begun is ended also when the image can't be released, without the
+        // projection layer
+        let released = match self.swapchain.as_mut() {
+            Some(swapchain) => swapchain.release_image(),
+            None => return,
+        };
+        let released = self
+            .report_swapchain_image("xrReleaseSwapchainImage", released)
+            .unwrap_or(false);
+
+        let swapchain = self.swapchain.as_mut().unwrap();
+
+        let passthrough = self
+            .passthrough
+            .as_ref()
+            .filter(|passthrough| passthrough.is_running())
+            .map(|passthrough| passthrough.composition_layer());
+
+        let layer_data = self.layers.layer_data(self.inner.handles.space.as_raw());
+
+        let layers = passthrough
+            .iter()
+            .map(|layer| {
+                (
+                    XRLayerOrder::PASSTHROUGH,
+                    layer as *const _ as *const openxr::sys::CompositionLayerBaseHeader,
+                )
+            })
+            .chain(
+                layer_data
+                    .iter()
+                    .map(|(order, layer)| (*order, layer.as_base_header())),
+            )
+            .collect::<Vec<_>>();
+
+        if swapchain.predicted_display_time().is_some() {
+            self.inner.graphics.frame_end();
+        }
+
+        let result = swapchain.finalize_update(
+            &mut self.inner.handles,
+            &self.inner.instance,
+            &layers,
+            released,
+        );
+        self.report("xrEndFrame", result);
+    }
+
+    /// Starts or pauses passthrough (`XR_FB_passthrough`), and sets its opacity. Returns `false` if
+    /// passthrough is not supported by the runtime
+    pub fn set_passthrough(&mut self, enabled: bool, opacity: f32) -> bool {
+        if self.passthrough.is_none() {
+            if !enabled {
+                return true;
+            }
+
+            let session = self.inner.handles.session.as_raw();
+            let passthrough = match FBPassthrough::new(&self.inner.instance, session) {
+                Some(passthrough) => passthrough,
+                None => return false,
+            };
+
+            self.passthrough = match self.report("xrCreatePassthroughFB", passthrough) {
+                Some(passthrough) => Some(passthrough),
+                None => return false,
+            };
+        }
+
+        let passthrough = self.passthrough.as_mut().unwrap();
+        let result = passthrough
+            .set_opacity(opacity)
+            .and_then(|_| passthrough.set_running(enabled));
+
+        self.report("xrPassthroughStartFB", result).is_some()
+    }
+
+    /// A frame has been waited for, and not yet ended. Frame-timed queries (e.g. anchors) are available
+    pub fn is_in_frame(&self) -> bool {
+        self.inner.is_running()
+            && self
+                .swapchain
+                .as_ref()
+                .and_then(|swapchain| swapchain.predicted_display_time())
+                .is_some()
+    }
+
+    /// Predicted display time of the current frame. `None` outside of a running frame
+    pub fn predicted_display_time(&self) -> Option<openxr::Time> {
+        if !self.inner.is_running() {
+            return None;
+        }
+
+        self.swapchain.as_ref()?.predicted_display_time()
+    }
+
+    /// Poses and field of views of the views, located for `time` (e.g. the predicted display time
+    /// plus some offset). Runtimes predict poses only a short time ahead, and may fail for times far
+    /// from the current frame
+    pub fn locate_views_at(&mut self, time: openxr::Time) -> Option<(Vec<Transform>, Vec<XrFovf>)> {
+        let swapchain = self.swapchain.as_ref()?;
+        let views = swapchain.locate_views_at(&self.inner.handles, time);
+
+        self.report("xrLocateViews", views)
+    }
+
+    /// Head pose in the reference space, predicted `ahead` of the display time of the current frame,
+    /// e.g. for aiming or network interpolation. `None` outside of a running frame, or if the head
+    /// is not tracked
+    pub fn predict_head_pose(&mut self, ahead: Duration) -> Option<Transform> {
+        let display_time = self.predicted_display_time()?;
+        let time = openxr::Time::from_nanos(display_time.as_nanos() + ahead.as_nanos() as i64);
+
+        if self.view_space.is_none() {
+            let view_space = self
+                .inner
+                .handles
+                .session
+                .create_reference_space(openxr::ReferenceSpaceType::VIEW, openxr::Posef::IDENTITY);
+            self.view_space =
+                Some(self.report("xrCreateReferenceSpace", view_space.map_err(Into::into))?);
+        }
+
+        let location = self
+            .view_space
+            .as_ref()?
+            .locate(&self.inner.handles.space, time);
+        let location = self.report("xrLocateSpace", location.map_err(Into::into))?;
+
+        let valid = openxr::SpaceLocationFlags::POSITION_VALID
+            | openxr::SpaceLocationFlags::ORIENTATION_VALID;
+        if !location.location_flags.contains(valid) {
+            return None;
+        }
+
+        Some(pose_to_transform(&location.pose))
+    }
+
+    /// `XR_EXT_palm_pose` is supported by the runtime, so that actions can be bound to
+    /// `PALM_POSE_PATH` (`/user/hand/{left,right}/input/palm_ext/pose`)
+    pub fn supports_palm_pose(&self) -> bool {
+        self.inner
+            .instance
+            .exts()
+            .other
+            .iter()
+            .any(|extension| extension == PALM_POSE_EXTENSION)
+    }
+
+    /// `XR_MSFT_spatial_anchor` is supported by the runtime
+    pub fn supports_anchors(&self) -> bool {
+        self.anchors.is_supported()
+    }
+
+    /// Creates a spatial anchor at `transform`, in the reference space, at the current frame.
+    /// `None` if creation failed, or if called outside of a running frame
+    pub fn create_anchor(&mut self, transform: &Transform) -> Option<XRAnchorId> {
+        let time = self.swapchain.as_ref()?.predicted_display_time()?;
+        let session = self.inner.handles.session.as_raw();
+        let space = self.inner.handles.space.as_raw();
+
+        let id = self.anchors.create(session, space, transform, time);
+        self.report("xrCreateSpatialAnchorMSFT", id)
+    }
+
+    /// Pose of the anchor in the reference space at the current frame. `None` if not tracked
+    pub fn locate_anchor(&mut self, id: XRAnchorId) -> Option<Transform> {
+        let time = self.swapchain.as_ref()?.predicted_display_time()?;
+        let space = self.inner.handles.space.as_raw();
+
+        let transform = self.anchors.locate(id, space, time);
+        self.report("xrLocateSpace", transform)?
+    }
+
+    pub fn destroy_anchor(&mut self, id: XRAnchorId) {
+        self.anchors.destroy(id);
+    }
+
+    /// Persists the anchor with `name` (`XR_MSFT_spatial_anchor_persistence`), so that it can be
+    /// loaded in later sessions with `load_anchor`
+    pub fn persist_anchor(&mut self, id: XRAnchorId, name: &str) -> bool {
+        let session = self.inner.handles.session.as_raw();
+        let result = self.anchors.persist(session, id, name);
+        self.report("xrPersistSpatialAnchorMSFT", result).is_some()
+    }
+
+    /// Names of the persisted anchors
+    pub fn persisted_anchor_names(&mut self) -> Vec<String> {
+        let session = self.inner.handles.session.as_raw();
+        let names = self.anchors.persisted_names(session);
+        self.report("xrEnumeratePersistedSpatialAnchorNamesMSFT", names)
+            .unwrap_or_default()
+    }
+
+    /// Creates an anchor from a persisted one
+    pub fn load_anchor(&mut self, name: &str) -> Option<XRAnchorId> {
+        let session = self.inner.handles.session.as_raw();
+        let id = self.anchors.load(session, name);
+        self.report("xrCreateSpatialAnchorFromPersistedNameMSFT", id)
+    }
+
+    /// Creates a composition layer with its own swapchain of `width` x `height`. The layer is shown
+    /// after an image has been written with `write_layer_image`
+    pub fn create_layer(
+        &mut self,
+        device: &Arc<wgpu::Device>,
+        shape: XRLayerShape,
+        width: u32,
+        height: u32,
+    ) -> Option<XRLayerId> {
+        let id = self.layers.create(
+            &self.inner.instance,
+            &self.inner.handles.session,
+            device,
+            shape,
+            width,
+            height,
+        );
+        self.report("xrCreateSwapchain", id)
+    }
+
+    /// Sets the shape, pose (in the reference space) and visibility of the layer
+    pub fn update_layer(
+        &mut self,
+        id: XRLayerId,
+        shape: XRLayerShape,
+        pose: Transform,
+        visible: bool,
+    ) {
+        self.layers.update(id, shape, pose, visible);
+    }
+
+    /// Sets where the layer is composited relative to the projection layer and other layers.
+    /// Layers are `XRLayerOrder::OVERLAY` by default
+    pub fn set_layer_order(&mut self, id: XRLayerId, order: XRLayerOrder) {
+        self.layers.set_order(id, order);
+    }
+
+    /// Writes tightly packed RGBA8 image data into the layer
+    pub fn write_layer_image(&mut self, id: XRLayerId, queue: &wgpu::Queue, data: &[u8]) {
+        let result = self.layers.write_image(id, queue, data);
+        self.report("xrAcquireSwapchainImage", result);
+    }
+
+    pub fn destroy_layer(&mut self, id: XRLayerId) {
+        self.layers.destroy(id);
+    }
+
+    /// Sets the performance level of the domain (`XR_EXT_performance_settings`), e.g. lower the level
+    /// in response to `XRPerformanceNotification`. Returns `false` if not supported or failed
+    pub fn set_performance_level(
+        &mut self,
+        domain: XRPerformanceDomain,
+        level: XRPerformanceLevel,
+    ) -> bool {
+        let result = performance::set_performance_level(
+            &self.inner.instance,
+            self.inner.handles.session.as_raw(),
+            domain,
+            level,
+        );
+
+        self.report("xrPerfSettingsSetPerformanceLevelEXT", result)
+            .unwrap_or(false)
+    }
+
+    /// Hidden area mesh of the view (`XR_KHR_visibility_mask`). `None` if not supported by the runtime
+    pub fn get_visibility_mask(&mut self, view_index: u32) -> Option<XRVisibilityMask> {
+        let mask = visibility_mask::get_visibility_mask(
+            &self.inner.instance,
+            self.inner.handles.session.as_raw(),
+            self.inner.options.view_type,
+            view_index,
+        );
+
+        self.report("xrGetVisibilityMaskKHR", mask)?
+    }
+
+    /// Like `report`, for calls on the images of the primary swapchain. The swapchain is recreated
+    /// after an error invalidating it
+    fn report_swapchain_image<T>(
+        &mut self,
+        context: &'static str,
+        result: Result<T, XrError>,
+    ) -> Option<T> {
+        if let Err(error) = &result {
+            if is_swapchain_lost(context, error) {
+                self.swapchain_lost = true;
+            }
+        }
+
+        self.report(context, result)
+    }
+
+    /// Converts the error into `XRRuntimeError` event
+    fn report<T>(&mut self, context: &'static str, result: Result<T, XrError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                if let XrError::XR(openxr::sys::Result::ERROR_SESSION_LOST)
+                | XrError::XR(openxr::sys::Result::ERROR_INSTANCE_LOST) = error
+                {
+                    error!(target: "bevy_openxr::session", context, error = %error, "OpenXR session lost, restart the app to recover");
+                }
+
+                self.inner.report_error(context, error);
+                None
+            }
+        }
+    }
+
+    /// Secondary view configurations (`XR_MSFT_secondary_view_configuration`) enabled for the session.
+    /// Runtime decides when these are active
+    pub fn get_secondary_view_types(&self) -> &[openxr::ViewConfigurationType] {
+        &self.inner.secondary_view_types
+    }
+
+    /// Sets the scale of the recommended view sizes submitted to the compositor (dynamic resolution,
+    /// or supersampling above 1). Projection of the camera must be adjusted to render into the
+    /// top-left sub-rect accordingly
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        if let Some(swapchain) = self.swapchain.as_mut() {
+            swapchain.set_render_scale(render_scale);
+        }
+    }
+
+    /// Render scale submitted to the compositor, `None` until the swapchain has been created
+    pub fn get_render_scale(&self) -> Option<f32> {
+        Some(self.swapchain.as_ref()?.get_render_scale())
+    }
+
+    /// Largest render scale the swapchain has been created for (`XrOptions::supersampling`), `None`
+    /// until the swapchain has been created
+    pub fn get_max_render_scale(&self) -> Option<f32> {
+        Some(self.swapchain.as_ref()?.get_max_render_scale())
+    }
+
+    /// Timings of the most recent XR frame, `None` until the swapchain has been created
+    pub fn get_frame_timings(&self) -> Option<&XRFrameTimings> {
+        Some(self.swapchain.as_ref()?.get_timings())
     }
 
     pub fn get_swapchain_mut(&mut self) -> Option<&mut XRSwapchain> {
@@ -156,23 +908,116 @@ impl XRDevice {
     }
 
     pub(crate) fn drain_events(&mut self) -> Vec<XREvent> {
-        self.events_to_send.drain(..).collect()
+        let errors = self.inner.drain_errors();
+        let notifications = self.inner.drain_performance_notifications();
+        let visibility_mask_changes = self.inner.drain_visibility_mask_changes();
+
+        self.events_to_send
+            .drain(..)
+            .chain(errors.into_iter().map(XREvent::RuntimeError))
+            .chain(
+                notifications
+                    .into_iter()
+                    .map(XREvent::PerformanceNotification),
+            )
+            .chain(visibility_mask_changes.into_iter().map(|view_index| {
+                XREvent::VisibilityMaskChanged(XRVisibilityMaskChanged { view_index })
+            }))
+            .collect()
     }
 }
 
+/// Error of a swapchain image call after which the swapchain is recreated, e.g. after a runtime
+/// restart invalidated the swapchain handle. Other errors (e.g. `ERROR_CALL_ORDER_INVALID`) are
+/// reported without recreating, as a new swapchain would fail the same way
+fn is_swapchain_lost(context: &str, error: &XrError) -> bool {
+    let image_call = matches!(
+        context,
+        "xrWaitSwapchainImage" | "xrAcquireSwapchainImage" | "xrReleaseSwapchainImage"
+    );
+
+    image_call
+        && matches!(
+            error,
+            XrError::XR(openxr::sys::Result::ERROR_HANDLE_INVALID)
+        )
+}
+
 // FIXME FIXME FIXME ?!
 unsafe impl Sync for XRDevice {}
 unsafe impl Send for XRDevice {}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct View {
     pub fov: XrFovf,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct XrFovf {
     pub angle_left: f32,
     pub angle_right: f32,
     pub angle_down: f32,
     pub angle_up: f32,
 }
+
+impl XrFovf {
+    /// All angles differ by at most `epsilon` (radians)
+    pub fn abs_diff_eq(&self, other: &XrFovf, epsilon: f32) -> bool {
+        (self.angle_left - other.angle_left).abs() <= epsilon
+            && (self.angle_right - other.angle_right).abs() <= epsilon
+            && (self.angle_down - other.angle_down).abs() <= epsilon
+            && (self.angle_up - other.angle_up).abs() <= epsilon
+    }
+}
+
+impl From<openxr::Fovf> for XrFovf {
+    fn from(fov: openxr::Fovf) -> Self {
+        XrFovf {
+            angle_left: fov.angle_left,
+            angle_right: fov.angle_right,
+            angle_down: fov.angle_down,
+            angle_up: fov.angle_up,
+        }
+    }
+}
+
+/// Run criteria for systems that should only run while the XR session is running and its views are
+/// configured (`XRDevice::is_session_ready`), e.g. spawning XR content:
+///
+/// ```ignore
+/// app.add_system(spawn_scene.system().with_run_criteria(xr_session_running.system()));
+/// ```
+///
+/// Without `XRDevice` (e.g. `XRSimulationPlugin`), only the `XrSessionState` resource is checked
+pub fn xr_session_running(
+    session_state: Option<Res<XrSessionState>>,
+    xr_device: Option<Res<XRDevice>>,
+) -> ShouldRun {
+    let running = session_state.map_or(false, |state| state.is_running())
+        && xr_device.map_or(true, |xr_device| xr_device.is_session_ready());
+
+    match running {
+        true => ShouldRun::Yes,
+        false => ShouldRun::No,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_swapchain_lost() {
+        let handle_invalid = XrError::XR(openxr::sys::Result::ERROR_HANDLE_INVALID);
+        assert!(is_swapchain_lost("xrWaitSwapchainImage", &handle_invalid));
+        assert!(is_swapchain_lost(
+            "xrReleaseSwapchainImage",
+            &handle_invalid
+        ));
+        assert!(!is_swapchain_lost("xrWaitFrame", &handle_invalid));
+
+        let call_order = XrError::XR(openxr::sys::Result::ERROR_CALL_ORDER_INVALID);
+        assert!(!is_swapchain_lost("xrAcquireSwapchainImage", &call_order));
+        assert!(!is_swapchain_lost("xrEndFrame", &call_order));
+    }
+}
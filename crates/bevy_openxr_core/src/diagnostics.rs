@@ -0,0 +1,319 @@
+use bevy::app::{prelude::*, EventReader};
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy::ecs::system::{IntoSystem, Local, Res, ResMut};
+use bevy::utils::tracing::debug;
+use bevy::utils::{Duration, Instant};
+
+use crate::event::{
+    XRFrameSkipReason, XRFrameSkipped, XRPauseChanged, XRPerformanceNotification, XRRuntimeError,
+    XRSwapchainRecreated, XRVisibilityMaskChanged, XrSessionState,
+};
+use crate::XRDevice;
+
+/// Timings of a single XR frame, collected by `XRSwapchain`
+#[derive(Debug, Clone, Default)]
+pub struct XRFrameTimings {
+    /// Time spent blocked in `xrWaitFrame`
+    pub frame_wait: Duration,
+
+    /// Time spent in `xrAcquireSwapchainImage` + `xrWaitSwapchainImage`
+    pub image_wait: Duration,
+
+    /// Total count of frames that were not rendered (runtime told not to render, or wait failed)
+    pub dropped_frames: u64,
+
+    /// Predicted display period of the most recent frame (e.g. 11.1ms at 90Hz)
+    pub display_period: Duration,
+
+    /// When `xrWaitFrame` of the most recent frame returned
+    pub frame_waited_at: Option<Instant>,
+}
+
+/// Adds XR frame pacing diagnostics to `Diagnostics`, so that they show up e.g. in `LogDiagnosticsPlugin` output
+#[derive(Default)]
+pub struct XRDiagnosticsPlugin;
+
+impl XRDiagnosticsPlugin {
+    pub const CPU_FRAME_TIME: DiagnosticId =
+        DiagnosticId::from_u128(208_424_436_213_867_146_118_907_437_436_011_331_051);
+    pub const FRAME_WAIT_TIME: DiagnosticId =
+        DiagnosticId::from_u128(29_764_816_548_027_109_227_519_186_432_187_650_413);
+    pub const IMAGE_WAIT_TIME: DiagnosticId =
+        DiagnosticId::from_u128(114_280_539_620_364_087_512_604_213_958_726_470_952);
+    pub const DROPPED_FRAMES: DiagnosticId =
+        DiagnosticId::from_u128(301_457_296_054_131_822_715_006_487_613_264_578_619);
+
+    /// Frames not rendered per update, including frames without a swapchain image (`XRFrameSkipped`)
+    pub const SKIPPED_FRAMES: DiagnosticId =
+        DiagnosticId::from_u128(52_870_913_447_126_398_105_664_271_903_518_206_374);
+
+    /// Frames delayed by a swapchain image wait longer than the display period, per update
+    pub const STALLED_FRAMES: DiagnosticId =
+        DiagnosticId::from_u128(239_114_685_702_953_416_877_320_018_645_291_750_163);
+
+    const MAX_HISTORY: usize = 20;
+}
+
+impl Plugin for XRDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CpuFrameStart>()
+            .add_startup_system(setup_system.system())
+            .add_system_to_stage(CoreStage::First, cpu_frame_start_system.system())
+            .add_system_to_stage(CoreStage::Last, diagnostic_system.system());
+    }
+}
+
+struct CpuFrameStart(Instant);
+
+impl Default for CpuFrameStart {
+    fn default() -> Self {
+        CpuFrameStart(Instant::now())
+    }
+}
+
+fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+    let max_history = XRDiagnosticsPlugin::MAX_HISTORY;
+    diagnostics.add(Diagnostic::new(
+        XRDiagnosticsPlugin::CPU_FRAME_TIME,
+        "xr_cpu_frame_time_ms",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XRDiagnosticsPlugin::FRAME_WAIT_TIME,
+        "xr_wait_frame_ms",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XRDiagnosticsPlugin::IMAGE_WAIT_TIME,
+        "xr_wait_image_ms",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XRDiagnosticsPlugin::DROPPED_FRAMES,
+        "xr_dropped_frames",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XRDiagnosticsPlugin::SKIPPED_FRAMES,
+        "xr_skipped_frames",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XRDiagnosticsPlugin::STALLED_FRAMES,
+        "xr_stalled_frames",
+        max_history,
+    ));
+}
+
+fn cpu_frame_start_system(mut frame_start: ResMut<CpuFrameStart>) {
+    frame_start.0 = Instant::now();
+}
+
+/// Dropped frames since the previous update, from the total count of the swapchain
+#[derive(Default)]
+struct DroppedFrames {
+    previous_total: u64,
+}
+
+impl DroppedFrames {
+    /// A recreated swapchain counts its dropped frames from zero
+    fn since_previous(&mut self, total: u64, swapchain_recreated: bool) -> u64 {
+        if swapchain_recreated {
+            self.previous_total = 0;
+        }
+
+        let dropped = total.saturating_sub(self.previous_total);
+        self.previous_total = total;
+        dropped
+    }
+}
+
+fn diagnostic_system(
+    mut diagnostics: ResMut<Diagnostics>,
+    mut dropped_frames: Local<DroppedFrames>,
+    frame_start: Res<CpuFrameStart>,
+    xr_device: Option<Res<XRDevice>>,
+    (mut frames_skipped, mut swapchain_recreated): (
+        EventReader<XRFrameSkipped>,
+        EventReader<XRSwapchainRecreated>,
+    ),
+) {
+    diagnostics.add_measurement(
+        XRDiagnosticsPlugin::CPU_FRAME_TIME,
+        frame_start.0.elapsed().as_secs_f64() * 1000.,
+    );
+
+    let (stalled, skipped): (Vec<_>, Vec<_>) = frames_skipped
+        .iter()
+        .partition(|skipped| matches!(skipped.reason, XRFrameSkipReason::ImageWaitStalled { .. }));
+    diagnostics.add_measurement(XRDiagnosticsPlugin::SKIPPED_FRAMES, skipped.len() as f64);
+    diagnostics.add_measurement(XRDiagnosticsPlugin::STALLED_FRAMES, stalled.len() as f64);
+
+    let recreated = swapchain_recreated.iter().next_back().is_some();
+
+    // no device when running without XR (`OpenXRSettings::desktop_fallback`)
+    let xr_device = match xr_device {
+        Some(xr_device) => xr_device,
+        None => return,
+    };
+
+    let timings = match xr_device.get_frame_timings() {
+        Some(timings) => timings,
+        None => return,
+    };
+
+    diagnostics.add_measurement(
+        XRDiagnosticsPlugin::FRAME_WAIT_TIME,
+        timings.frame_wait.as_secs_f64() * 1000.,
+    );
+    diagnostics.add_measurement(
+        XRDiagnosticsPlugin::IMAGE_WAIT_TIME,
+        timings.image_wait.as_secs_f64() * 1000.,
+    );
+    diagnostics.add_measurement(
+        XRDiagnosticsPlugin::DROPPED_FRAMES,
+        dropped_frames.since_previous(timings.dropped_frames, recreated) as f64,
+    );
+}
+
+/// Logs the XR events (session states, runtime errors, performance notifications, pause and
+/// swapchain changes) at debug level, and counts them per update in `Diagnostics`. Opt-in, as the
+/// events are already handled by the crate and the app; enable the logs with e.g.
+/// `RUST_LOG=bevy_openxr::events=debug`
+#[derive(Default)]
+pub struct XREventDebugPlugin;
+
+impl XREventDebugPlugin {
+    /// All XR events of the update
+    pub const EVENTS: DiagnosticId =
+        DiagnosticId::from_u128(163_905_208_449_352_671_926_400_327_516_944_823_197);
+    pub const SESSION_STATE_EVENTS: DiagnosticId =
+        DiagnosticId::from_u128(86_211_713_377_594_004_553_258_790_305_016_455_820);
+    pub const RUNTIME_ERRORS: DiagnosticId =
+        DiagnosticId::from_u128(270_582_393_196_813_455_038_113_964_101_730_944_631);
+
+    const MAX_HISTORY: usize = 20;
+}
+
+impl Plugin for XREventDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(event_debug_setup_system.system())
+            .add_system_to_stage(CoreStage::Last, event_debug_system.system());
+    }
+}
+
+fn event_debug_setup_system(mut diagnostics: ResMut<Diagnostics>) {
+    let max_history = XREventDebugPlugin::MAX_HISTORY;
+    diagnostics.add(Diagnostic::new(
+        XREventDebugPlugin::EVENTS,
+        "xr_events",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XREventDebugPlugin::SESSION_STATE_EVENTS,
+        "xr_session_state_events",
+        max_history,
+    ));
+    diagnostics.add(Diagnostic::new(
+        XREventDebugPlugin::RUNTIME_ERRORS,
+        "xr_runtime_errors",
+        max_history,
+    ));
+}
+
+fn event_debug_system(
+    mut diagnostics: ResMut<Diagnostics>,
+    mut state_events: EventReader<XrSessionState>,
+    mut runtime_errors: EventReader<XRRuntimeError>,
+    mut performance_notifications: EventReader<XRPerformanceNotification>,
+    mut visibility_mask_changes: EventReader<XRVisibilityMaskChanged>,
+    mut swapchain_recreated: EventReader<XRSwapchainRecreated>,
+    mut pause_changes: EventReader<XRPauseChanged>,
+) {
+    let mut states = 0;
+    for state in state_events.iter() {
+        debug!(target: "bevy_openxr::events", ?state, "XrSessionState");
+        states += 1;
+    }
+
+    let mut errors = 0;
+    for error in runtime_errors.iter() {
+        debug!(target: "bevy_openxr::events", context = error.context, error = ?error.error, "XRRuntimeError");
+        errors += 1;
+    }
+
+    let mut others = 0;
+    for notification in performance_notifications.iter() {
+        debug!(target: "bevy_openxr::events", ?notification, "XRPerformanceNotification");
+        others += 1;
+    }
+    for change in visibility_mask_changes.iter() {
+        debug!(target: "bevy_openxr::events", view_index = change.view_index, "XRVisibilityMaskChanged");
+        others += 1;
+    }
+    for recreated in swapchain_recreated.iter() {
+        debug!(target: "bevy_openxr::events", generation = recreated.generation, "XRSwapchainRecreated");
+        others += 1;
+    }
+    for change in pause_changes.iter() {
+        debug!(target: "bevy_openxr::events", paused = change.paused, "XRPauseChanged");
+        others += 1;
+    }
+
+    diagnostics.add_measurement(
+        XREventDebugPlugin::EVENTS,
+        (states + errors + others) as f64,
+    );
+    diagnostics.add_measurement(XREventDebugPlugin::SESSION_STATE_EVENTS, states as f64);
+    diagnostics.add_measurement(XREventDebugPlugin::RUNTIME_ERRORS, errors as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Events;
+
+    use super::*;
+
+    #[test]
+    fn test_dropped_frames_after_swapchain_recreation() {
+        let mut dropped_frames = DroppedFrames::default();
+        assert_eq!(dropped_frames.since_previous(5, false), 5);
+        assert_eq!(dropped_frames.since_previous(7, false), 2);
+
+        // timings of the new swapchain start from zero
+        assert_eq!(dropped_frames.since_previous(1, true), 1);
+        assert_eq!(dropped_frames.since_previous(3, false), 2);
+
+        // recreation not seen (e.g. events missed while the system did not run)
+        assert_eq!(dropped_frames.since_previous(0, false), 0);
+        assert_eq!(dropped_frames.since_previous(4, false), 4);
+    }
+
+    #[test]
+    fn test_diagnostic_system_after_swapchain_recreation() {
+        let mut app = App::new();
+        app.init_resource::<Diagnostics>()
+            .add_event::<XRFrameSkipped>()
+            .add_event::<XRSwapchainRecreated>()
+            .add_plugin(XRDiagnosticsPlugin);
+        app.update();
+
+        app.world
+            .get_resource_mut::<Events<XRSwapchainRecreated>>()
+            .unwrap()
+            .send(XRSwapchainRecreated { generation: 1 });
+        app.world
+            .get_resource_mut::<Events<XRFrameSkipped>>()
+            .unwrap()
+            .send(XRFrameSkipped {
+                reason: XRFrameSkipReason::NotRequested,
+            });
+        app.update();
+
+        let diagnostics = app.world.get_resource::<Diagnostics>().unwrap();
+        let skipped = diagnostics
+            .get(XRDiagnosticsPlugin::SKIPPED_FRAMES)
+            .and_then(|diagnostic| diagnostic.value());
+        assert_eq!(skipped, Some(1.));
+    }
+}
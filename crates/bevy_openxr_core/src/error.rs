@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// Errors from the OpenXR session and swapchain. Recoverable errors at runtime are
+/// also sent as `XRRuntimeError` events
+#[derive(Debug, Clone)]
+pub enum XrError {
+    /// Error returned by the OpenXR runtime
+    XR(openxr::sys::Result),
+
+    /// Runtime did not offer any swapchain format usable by wgpu
+    NoSupportedSwapchainFormat,
+
+    /// Runtime did not report any environment blend mode
+    NoEnvironmentBlendMode,
+
+    /// Runtime did not report any views for the view configuration
+    NoViews,
+
+    /// Frame loop handles were requested while the XR frame loop is driving frames
+    FrameLoopActive,
+
+    /// Swapchain images were requested in a headless session (`XrOptions::headless`)
+    Headless,
+
+    /// Graphics backend failed, e.g. to create the session (see `XrGraphicsBackend`)
+    Graphics(String),
+}
+
+impl fmt::Display for XrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XrError::XR(e) => write!(f, "OpenXR runtime error: {}", e),
+            XrError::NoSupportedSwapchainFormat => {
+                write!(
+                    f,
+                    "OpenXR runtime has no swapchain formats supported by wgpu"
+                )
+            }
+            XrError::NoEnvironmentBlendMode => {
+                write!(f, "OpenXR runtime has no environment blend modes")
+            }
+            XrError::NoViews => write!(f, "OpenXR runtime has no views for view configuration"),
+            XrError::FrameLoopActive => {
+                write!(f, "OpenXR frame loop is in use by the XR frame loop")
+            }
+            XrError::Headless => write!(f, "OpenXR session is headless and has no swapchain"),
+            XrError::Graphics(e) => write!(f, "XR graphics backend error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for XrError {}
+
+impl From<openxr::sys::Result> for XrError {
+    fn from(e: openxr::sys::Result) -> Self {
+        XrError::XR(e)
+    }
+}
+
+/// Converts a raw OpenXR call result, for extension functions called through `openxr::raw`
+pub(crate) fn cvt(ret: openxr::sys::Result) -> Result<(), XrError> {
+    if ret.into_raw() < 0 {
+        Err(XrError::XR(ret))
+    } else {
+        Ok(())
+    }
+}
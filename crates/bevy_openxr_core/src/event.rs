@@ -1,21 +1,96 @@
+use bevy::render::texture::TextureFormat;
 use bevy::transform::components::Transform;
+use bevy::utils::Duration;
 
-use crate::View;
+use crate::{
+    stereo::{XRStereoMode, XRViewRect},
+    View, XRPerformanceDomain, XRPerformanceNotificationLevel, XRPerformanceSubDomain, XrError,
+    XrFovf,
+};
 
 #[derive(Debug)]
 pub(crate) enum XREvent {
     ViewSurfaceCreated(XRViewSurfaceCreated),
     ViewsCreated(XRViewsCreated),
+    SecondaryViewSurfaceCreated(XRSecondaryViewSurfaceCreated),
+    RuntimeError(XRRuntimeError),
+    PerformanceNotification(XRPerformanceNotification),
+    VisibilityMaskChanged(XRVisibilityMaskChanged),
+    SwapchainRecreated(XRSwapchainRecreated),
+    FrameSkipped(XRFrameSkipped),
 }
 
-/// Current state of XR hardware/session
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub enum XRState {
-    Paused,
+/// Lifecycle state of the XR session, mirrors `openxr::SessionState`. Sent as an event on each
+/// change, and available as a resource
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum XrSessionState {
+    /// Session created, or ended after `Stopping`. No frames are waited for
+    Idle,
+
+    /// Runtime is ready for the session to begin
+    Ready,
+
+    /// Session has begun and the frame loop is synchronized, but the app is not visible
     Running,
-    RunningFocused,
+
+    /// App is visible to the user, but does not receive XR input
+    Visible,
+
+    /// App is visible and receives XR input
+    Focused,
+
+    /// Runtime requested the session to end
+    Stopping,
+
+    /// Session is exiting or lost, and frames must no longer be waited for or submitted
     Exiting,
-    SkipFrame,
+}
+
+impl XrSessionState {
+    /// Session has begun, and frames are waited for
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self,
+            XrSessionState::Running | XrSessionState::Visible | XrSessionState::Focused
+        )
+    }
+
+    pub fn is_visible(&self) -> bool {
+        matches!(self, XrSessionState::Visible | XrSessionState::Focused)
+    }
+
+    pub fn is_focused(&self) -> bool {
+        matches!(self, XrSessionState::Focused)
+    }
+}
+
+impl Default for XrSessionState {
+    fn default() -> Self {
+        XrSessionState::Idle
+    }
+}
+
+/// Whether the current frame is rendered. Decided each frame after `xrWaitFrame` (`should_render`
+/// of the frame state), and available as a resource
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum XrFrameStatus {
+    ShouldRender,
+
+    /// Frame is not rendered, e.g. the session is not visible, the swapchain was just (re)created or
+    /// the frame could not be waited for
+    Skip,
+}
+
+impl XrFrameStatus {
+    pub fn should_render(&self) -> bool {
+        matches!(self, XrFrameStatus::ShouldRender)
+    }
+}
+
+impl Default for XrFrameStatus {
+    fn default() -> Self {
+        XrFrameStatus::Skip
+    }
 }
 
 /// XR View has been configured/created
@@ -23,6 +98,32 @@ pub enum XRState {
 pub struct XRViewSurfaceCreated {
     pub width: u32,
     pub height: u32,
+
+    /// Number of views, e.g. 2 for `PRIMARY_STEREO` and 1 for `PRIMARY_MONO`
+    pub view_count: u32,
+
+    /// Recommended size of each view. Views smaller than `width` x `height` (or scaled by the render
+    /// scale) are rendered into the top-left of their area
+    pub view_sizes: Vec<(u32, u32)>,
+
+    /// Layout of the views in the render target
+    pub stereo_mode: XRStereoMode,
+
+    /// Format of the render target, e.g. for textures copied into it
+    pub format: TextureFormat,
+}
+
+impl XRViewSurfaceCreated {
+    /// Rect rendered for the view in the render target, with `render_scale` applied. `None` if there
+    /// is no such view
+    pub fn view_rect(&self, view_index: u32, render_scale: f32) -> Option<XRViewRect> {
+        let view_size = *self.view_sizes.get(view_index as usize)?;
+
+        Some(
+            self.stereo_mode
+                .view_rect(view_index, self.width, view_size, render_scale),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -33,4 +134,121 @@ pub struct XRViewsCreated {
 #[derive(Debug)]
 pub struct XRCameraTransformsUpdated {
     pub transforms: Vec<Transform>,
+
+    /// Field of view of each view for this frame. Empty if not known (e.g. simulation and playback),
+    /// in which case the field of views of `XRViewsCreated` apply
+    pub fovs: Vec<XrFovf>,
+}
+
+/// Swapchain of a secondary view (`XrOptions::secondary_view_types`) was created, at the first
+/// activation of the view by the runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRSecondaryViewSurfaceCreated {
+    /// Index of the view configuration in the enabled secondary view configurations
+    pub index: usize,
+    pub view_type: openxr::ViewConfigurationType,
+
+    /// Size of the swapchain images, a single view
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Poses and field of views of the secondary views active in this frame. Inactive views are not
+/// included, and are not rendered
+#[derive(Debug, Clone)]
+pub struct XRSecondaryViewsUpdated {
+    pub views: Vec<XRSecondaryView>,
+}
+
+#[derive(Debug, Clone)]
+pub struct XRSecondaryView {
+    /// Index of the view configuration in the enabled secondary view configurations
+    pub index: usize,
+    pub view_type: openxr::ViewConfigurationType,
+
+    /// Pose in the reference space
+    pub transform: Transform,
+    pub fov: XrFovf,
 }
+
+/// Field of view of the views changed mid-session, e.g. by an IPD adjustment or dynamic foveation
+/// of the runtime, and projection matrices have been recomputed. Sent by the XR camera system
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRFovChanged {
+    /// Field of view of each view
+    pub fovs: Vec<XrFovf>,
+}
+
+/// Recoverable OpenXR runtime error. The failed operation is skipped (e.g. frame is not rendered),
+/// and the app may decide how to degrade
+#[derive(Debug, Clone)]
+pub struct XRRuntimeError {
+    /// OpenXR call or operation that failed, e.g. `xrLocateViews`
+    pub context: &'static str,
+    pub error: XrError,
+}
+
+/// Performance level of a domain changed (`XR_EXT_performance_settings`). Sent by the runtime, e.g.
+/// when the device is getting too hot, so that the app can scale its quality
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRPerformanceNotification {
+    pub domain: XRPerformanceDomain,
+    pub sub_domain: XRPerformanceSubDomain,
+    pub from_level: XRPerformanceNotificationLevel,
+    pub to_level: XRPerformanceNotificationLevel,
+}
+
+/// App was paused or resumed, see `XRPauseBehavior`
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRPauseChanged {
+    pub paused: bool,
+}
+
+/// Swapchain has been recreated after a swapchain error. Texture views of the previous swapchain
+/// are no longer valid, render graph nodes caching them must refresh. A lost device or session is
+/// not recovered by recreating the swapchain, the app must be restarted
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRSwapchainRecreated {
+    /// Number of recreations since the session started
+    pub generation: u32,
+}
+
+/// A frame was not rendered, or rendered late. Sent so that the app can react, e.g. by reducing its
+/// quality, instead of silently losing frames. Counted by `XRDiagnosticsPlugin`
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRFrameSkipped {
+    pub reason: XRFrameSkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XRFrameSkipReason {
+    /// Runtime told not to render the frame (`should_render` of the frame state), e.g. while the
+    /// session is not visible
+    NotRequested,
+
+    /// `xrWaitFrame` failed, see `XRRuntimeError`
+    WaitFrameFailed,
+
+    /// Swapchain image could not be acquired or waited for, see `XRRuntimeError`
+    ImageUnavailable,
+
+    /// Frame is rendered, but waiting for the swapchain image took longer than the display period,
+    /// so the frame likely misses its display time
+    ImageWaitStalled { wait: Duration },
+
+    /// Compositor did not release the swapchain image within `XrOptions::swapchain_image_timeout`.
+    /// The wait is retried in the next frame
+    ImageWaitTimedOut { timeout: Duration },
+}
+
+/// Visibility mask of the view has changed, and should be fetched again with `XRDevice::get_visibility_mask`
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRVisibilityMaskChanged {
+    pub view_index: u32,
+}
+
+/// Send to exit XR and the app, e.g. from a "Quit VR" button. With a session, the exit is requested
+/// from the runtime (`XRDevice::request_exit`), so that the session ends properly before `AppExit`.
+/// Without a session (desktop fallback), `AppExit` is sent directly
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XRExitRequest;
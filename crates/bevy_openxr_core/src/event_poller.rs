@@ -0,0 +1,128 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread::JoinHandle;
+
+use bevy::utils::tracing::{debug, error};
+use bevy::utils::Duration;
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::event::XRPerformanceNotification;
+
+/// Owned copy of an OpenXR event. `openxr::Event` borrows the `EventDataBuffer` it was read into,
+/// so the polling thread converts events into this before sending them to the main thread
+#[derive(Debug, Clone)]
+pub(crate) enum OpenXREvent {
+    SessionStateChanged(openxr::SessionState),
+    InstanceLossPending,
+    EventsLost(u32),
+    ReferenceSpaceChangePending(openxr::ReferenceSpaceType),
+    PerfSettingsEXT(XRPerformanceNotification),
+    VisibilityMaskChangedKHR(u32),
+    InteractionProfileChanged,
+    MainSessionVisibilityChangedEXTX,
+    Unknown,
+}
+
+impl OpenXREvent {
+    fn from_event(event: openxr::Event<'_>) -> Self {
+        match event {
+            openxr::Event::SessionStateChanged(e) => OpenXREvent::SessionStateChanged(e.state()),
+            openxr::Event::InstanceLossPending(_) => OpenXREvent::InstanceLossPending,
+            openxr::Event::EventsLost(e) => OpenXREvent::EventsLost(e.lost_event_count()),
+            openxr::Event::ReferenceSpaceChangePending(e) => {
+                OpenXREvent::ReferenceSpaceChangePending(e.reference_space_type())
+            }
+            openxr::Event::PerfSettingsEXT(e) => {
+                match XRPerformanceNotification::from_sys(
+                    e.domain(),
+                    e.sub_domain(),
+                    e.from_level(),
+                    e.to_level(),
+                ) {
+                    Some(notification) => OpenXREvent::PerfSettingsEXT(notification),
+                    None => OpenXREvent::Unknown,
+                }
+            }
+            openxr::Event::VisibilityMaskChangedKHR(e) => {
+                OpenXREvent::VisibilityMaskChangedKHR(e.view_index())
+            }
+            openxr::Event::InteractionProfileChanged(_) => OpenXREvent::InteractionProfileChanged,
+            openxr::Event::MainSessionVisibilityChangedEXTX(_) => {
+                OpenXREvent::MainSessionVisibilityChangedEXTX
+            }
+            _ => OpenXREvent::Unknown,
+        }
+    }
+}
+
+/// Polls OpenXR events (`xrPollEvent`) in a dedicated thread, so that no events are missed
+/// even if the main loop stalls. Events are consumed with `try_recv()`
+pub(crate) struct XREventPoller {
+    receiver: Receiver<OpenXREvent>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl XREventPoller {
+    /// Interval between polls, when the runtime event queue has been drained
+    const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+    pub fn new(instance: openxr::Instance) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_shutdown = shutdown.clone();
+        let thread = std::thread::Builder::new()
+            .name("openxr-event-poller".to_string())
+            .spawn(move || poll_events(instance, sender, thread_shutdown))
+            .expect("Could not spawn OpenXR event polling thread");
+
+        Self {
+            receiver,
+            shutdown,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn try_recv(&self) -> Option<OpenXREvent> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for XREventPoller {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!(target: "bevy_openxr::session", "OpenXR event polling thread panicked");
+            }
+        }
+    }
+}
+
+fn poll_events(instance: openxr::Instance, sender: Sender<OpenXREvent>, shutdown: Arc<AtomicBool>) {
+    let mut event_storage = openxr::EventDataBuffer::new();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        loop {
+            let event = match instance.poll_event(&mut event_storage) {
+                Ok(Some(event)) => OpenXREvent::from_event(event),
+                Ok(None) => break,
+                Err(e) => {
+                    error!(target: "bevy_openxr::session", error = ?e, "xrPollEvent failed");
+                    break;
+                }
+            };
+
+            if sender.send(event).is_err() {
+                debug!(target: "bevy_openxr::session", "Event receiver dropped, stopping poller");
+                return;
+            }
+        }
+
+        std::thread::sleep(XREventPoller::POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,52 @@
+use openxr::{raw, sys};
+
+use crate::error::{cvt, XrError};
+
+/// `XR_FB_display_refresh_rate`
+pub(crate) struct DisplayRefreshRateFB {
+    fp: raw::DisplayRefreshRateFB,
+}
+
+impl DisplayRefreshRateFB {
+    pub(crate) fn new(instance: &openxr::Instance) -> Option<Self> {
+        Some(DisplayRefreshRateFB {
+            fp: *instance.exts().fb_display_refresh_rate.as_ref()?,
+        })
+    }
+
+    /// Refresh rates supported by the display, in Hz (`xrEnumerateDisplayRefreshRatesFB`)
+    pub(crate) fn enumerate(&self, session: sys::Session) -> Result<Vec<f32>, XrError> {
+        // first call for the count, second for the data
+        let mut count = 0;
+        cvt(unsafe {
+            (self.fp.enumerate_display_refresh_rates)(session, 0, &mut count, std::ptr::null_mut())
+        })?;
+
+        let mut rates = vec![0.; count as usize];
+        cvt(unsafe {
+            (self.fp.enumerate_display_refresh_rates)(
+                session,
+                rates.len() as u32,
+                &mut count,
+                rates.as_mut_ptr(),
+            )
+        })?;
+        rates.truncate(count as usize);
+
+        Ok(rates)
+    }
+
+    /// Current refresh rate of the display, in Hz (`xrGetDisplayRefreshRateFB`)
+    pub(crate) fn get(&self, session: sys::Session) -> Result<f32, XrError> {
+        let mut rate = 0.;
+        cvt(unsafe { (self.fp.get_display_refresh_rate)(session, &mut rate) })?;
+
+        Ok(rate)
+    }
+
+    /// Requests the display to switch to `rate` Hz (`xrRequestDisplayRefreshRateFB`). The change is
+    /// applied by the runtime asynchronously
+    pub(crate) fn request(&self, session: sys::Session, rate: f32) -> Result<(), XrError> {
+        cvt(unsafe { (self.fp.request_display_refresh_rate)(session, rate) })
+    }
+}
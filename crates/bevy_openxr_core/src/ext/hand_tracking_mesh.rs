@@ -0,0 +1,107 @@
+use std::ptr;
+
+use openxr::{raw, sys};
+
+use crate::error::{cvt, XrError};
+use crate::hand_tracking::XRHandMesh;
+use crate::math::pose_to_transform;
+
+/// `XR_FB_hand_tracking_mesh`: skinned hand mesh of the user's hand, calibrated by the runtime
+pub(crate) struct HandTrackingMeshFB {
+    fp: raw::HandTrackingMeshFB,
+}
+
+impl HandTrackingMeshFB {
+    pub(crate) fn new(instance: &openxr::Instance) -> Option<Self> {
+        Some(HandTrackingMeshFB {
+            fp: *instance.exts().fb_hand_tracking_mesh.as_ref()?,
+        })
+    }
+
+    /// Mesh and bind pose of the hand of `tracker` (`xrGetHandMeshFB`)
+    pub(crate) fn get_hand_mesh(
+        &self,
+        tracker: &openxr::HandTracker,
+    ) -> Result<XRHandMesh, XrError> {
+        // first call queries the counts
+        let mut mesh = empty_mesh();
+        cvt(unsafe { (self.fp.get_hand_mesh)(tracker.as_raw(), &mut mesh) })?;
+
+        let joint_count = mesh.joint_count_output as usize;
+        let vertex_count = mesh.vertex_count_output as usize;
+        let index_count = mesh.index_count_output as usize;
+
+        let mut joint_bind_poses = vec![openxr::Posef::IDENTITY; joint_count];
+        let mut joint_radii = vec![0f32; joint_count];
+        let mut joint_parents = vec![sys::HandJointEXT::from_raw(0); joint_count];
+        let mut positions = vec![sys::Vector3f::default(); vertex_count];
+        let mut normals = vec![sys::Vector3f::default(); vertex_count];
+        let mut uvs = vec![sys::Vector2f::default(); vertex_count];
+        let mut blend_indices = vec![sys::Vector4sFB::default(); vertex_count];
+        let mut blend_weights = vec![sys::Vector4f::default(); vertex_count];
+        let mut indices = vec![0i16; index_count];
+
+        let mut mesh = sys::HandTrackingMeshFB {
+            joint_capacity_input: joint_count as u32,
+            joint_bind_poses: joint_bind_poses.as_mut_ptr(),
+            joint_radii: joint_radii.as_mut_ptr(),
+            joint_parents: joint_parents.as_mut_ptr(),
+            vertex_capacity_input: vertex_count as u32,
+            vertex_positions: positions.as_mut_ptr(),
+            vertex_normals: normals.as_mut_ptr(),
+            vertex_u_vs: uvs.as_mut_ptr(),
+            vertex_blend_indices: blend_indices.as_mut_ptr(),
+            vertex_blend_weights: blend_weights.as_mut_ptr(),
+            index_capacity_input: index_count as u32,
+            indices: indices.as_mut_ptr(),
+            ..empty_mesh()
+        };
+        cvt(unsafe { (self.fp.get_hand_mesh)(tracker.as_raw(), &mut mesh) })?;
+
+        Ok(XRHandMesh {
+            joint_bind_poses: joint_bind_poses.iter().map(pose_to_transform).collect(),
+            joint_radii,
+            joint_parents: joint_parents
+                .iter()
+                .map(|parent| {
+                    let parent = parent.into_raw();
+                    match parent >= 0 && (parent as usize) < joint_count {
+                        true => Some(parent as usize),
+                        false => None,
+                    }
+                })
+                .collect(),
+            positions: positions.iter().map(|v| [v.x, v.y, v.z]).collect(),
+            normals: normals.iter().map(|v| [v.x, v.y, v.z]).collect(),
+            uvs: uvs.iter().map(|v| [v.x, v.y]).collect(),
+            blend_indices: blend_indices
+                .iter()
+                .map(|v| [v.x as u16, v.y as u16, v.z as u16, v.w as u16])
+                .collect(),
+            blend_weights: blend_weights.iter().map(|v| [v.x, v.y, v.z, v.w]).collect(),
+            indices: indices.iter().map(|index| *index as u16).collect(),
+        })
+    }
+}
+
+fn empty_mesh() -> sys::HandTrackingMeshFB {
+    sys::HandTrackingMeshFB {
+        ty: sys::HandTrackingMeshFB::TYPE,
+        next: ptr::null_mut(),
+        joint_capacity_input: 0,
+        joint_count_output: 0,
+        joint_bind_poses: ptr::null_mut(),
+        joint_radii: ptr::null_mut(),
+        joint_parents: ptr::null_mut(),
+        vertex_capacity_input: 0,
+        vertex_count_output: 0,
+        vertex_positions: ptr::null_mut(),
+        vertex_normals: ptr::null_mut(),
+        vertex_u_vs: ptr::null_mut(),
+        vertex_blend_indices: ptr::null_mut(),
+        vertex_blend_weights: ptr::null_mut(),
+        index_capacity_input: 0,
+        index_count_output: 0,
+        indices: ptr::null_mut(),
+    }
+}
@@ -0,0 +1,105 @@
+use std::mem::MaybeUninit;
+
+use openxr::{raw, sys};
+
+use crate::error::{cvt, XrError};
+use crate::hand_tracking::XRHandAimState;
+
+/// `XR_EXT_hand_tracking` with the extensions that chain into its structs: locates hand joints with
+/// a motion range (`XR_EXT_hand_joints_motion_range`, chained into `XrHandJointsLocateInfoEXT`), and
+/// the aim state of the hand (`XR_FB_hand_tracking_aim`, chained into `XrHandJointLocationsEXT`)
+pub(crate) struct LocateHandJointsEXT {
+    fp: raw::HandTrackingEXT,
+    motion_range: bool,
+    aim: bool,
+}
+
+impl LocateHandJointsEXT {
+    /// `None` also if neither of the chained extensions is enabled
+    pub(crate) fn new(instance: &openxr::Instance) -> Option<Self> {
+        let motion_range = instance.exts().ext_hand_joints_motion_range.is_some();
+        let aim = instance.exts().fb_hand_tracking_aim.is_some();
+        if !motion_range && !aim {
+            return None;
+        }
+
+        Some(LocateHandJointsEXT {
+            fp: *instance.exts().ext_hand_tracking.as_ref()?,
+            motion_range,
+            aim,
+        })
+    }
+
+    pub(crate) fn supports_motion_range(&self) -> bool {
+        self.motion_range
+    }
+
+    pub(crate) fn supports_aim(&self) -> bool {
+        self.aim
+    }
+
+    /// Joint locations and aim state of `tracker` in `base` at `time` (`xrLocateHandJointsEXT`).
+    /// Joints are `None` if the hand is not tracked, the aim state if it is not computed
+    pub(crate) fn locate_hand_joints(
+        &self,
+        tracker: &openxr::HandTracker,
+        base: &openxr::Space,
+        time: openxr::Time,
+        motion_range: sys::HandJointsMotionRangeEXT,
+    ) -> Result<(Option<openxr::HandJointLocations>, Option<XRHandAimState>), XrError> {
+        let motion_range_info = sys::HandJointsMotionRangeInfoEXT {
+            ty: sys::HandJointsMotionRangeInfoEXT::TYPE,
+            next: std::ptr::null(),
+            hand_joints_motion_range: motion_range,
+        };
+
+        let locate_info = sys::HandJointsLocateInfoEXT {
+            ty: sys::HandJointsLocateInfoEXT::TYPE,
+            next: match self.motion_range {
+                true => &motion_range_info as *const _ as *const _,
+                false => std::ptr::null(),
+            },
+            base_space: base.as_raw(),
+            time,
+        };
+
+        let mut aim_state = sys::HandTrackingAimStateFB {
+            ty: sys::HandTrackingAimStateFB::TYPE,
+            next: std::ptr::null_mut(),
+            status: sys::HandTrackingAimFlagsFB::EMPTY,
+            aim_pose: openxr::Posef::IDENTITY,
+            pinch_strength_index: 0.,
+            pinch_strength_middle: 0.,
+            pinch_strength_ring: 0.,
+            pinch_strength_little: 0.,
+        };
+
+        let mut joints = MaybeUninit::<openxr::HandJointLocations>::uninit();
+        let mut locations = sys::HandJointLocationsEXT {
+            ty: sys::HandJointLocationsEXT::TYPE,
+            next: match self.aim {
+                true => &mut aim_state as *mut _ as *mut _,
+                false => std::ptr::null_mut(),
+            },
+            is_active: false.into(),
+            joint_count: openxr::HAND_JOINT_COUNT as u32,
+            joint_locations: joints.as_mut_ptr() as *mut _,
+        };
+
+        cvt(unsafe {
+            (self.fp.locate_hand_joints)(tracker.as_raw(), &locate_info, &mut locations)
+        })?;
+
+        let joints = match locations.is_active.into() {
+            true => Some(unsafe { joints.assume_init() }),
+            false => None,
+        };
+
+        let aim_state = match self.aim {
+            true => XRHandAimState::from_openxr(&aim_state),
+            false => None,
+        };
+
+        Ok((joints, aim_state))
+    }
+}
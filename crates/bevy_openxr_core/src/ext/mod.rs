@@ -0,0 +1,18 @@
+//! Safe wrappers of OpenXR extension functions that the `openxr` crate exposes only as raw function
+//! pointers (`Instance::exts()`).
+//!
+//! Each extension is a type created with `new(instance)`, which returns `None` if the extension is
+//! not enabled. Its methods take the session, call the function pointers and convert the results
+//! into `XrError`s, so that callers do not need `unsafe`
+
+mod display_refresh_rate;
+mod hand_tracking_mesh;
+mod locate_hand_joints;
+mod performance_settings;
+mod view_configuration_fov;
+
+pub(crate) use display_refresh_rate::DisplayRefreshRateFB;
+pub(crate) use hand_tracking_mesh::HandTrackingMeshFB;
+pub(crate) use locate_hand_joints::LocateHandJointsEXT;
+pub(crate) use performance_settings::PerformanceSettingsEXT;
+pub(crate) use view_configuration_fov::ViewConfigurationFovEPIC;
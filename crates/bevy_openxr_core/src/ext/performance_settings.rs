@@ -0,0 +1,26 @@
+use openxr::{raw, sys};
+
+use crate::error::{cvt, XrError};
+
+/// `XR_EXT_performance_settings`
+pub(crate) struct PerformanceSettingsEXT {
+    fp: raw::PerformanceSettingsEXT,
+}
+
+impl PerformanceSettingsEXT {
+    pub(crate) fn new(instance: &openxr::Instance) -> Option<Self> {
+        Some(PerformanceSettingsEXT {
+            fp: *instance.exts().ext_performance_settings.as_ref()?,
+        })
+    }
+
+    /// `xrPerfSettingsSetPerformanceLevelEXT`
+    pub(crate) fn set_performance_level(
+        &self,
+        session: sys::Session,
+        domain: sys::PerfSettingsDomainEXT,
+        level: sys::PerfSettingsLevelEXT,
+    ) -> Result<(), XrError> {
+        cvt(unsafe { (self.fp.perf_settings_set_performance_level)(session, domain, level) })
+    }
+}
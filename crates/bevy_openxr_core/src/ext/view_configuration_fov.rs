@@ -0,0 +1,101 @@
+use openxr::sys;
+
+use crate::error::{cvt, XrError};
+use crate::view_config::XRViewConfigView;
+
+/// `XR_EPIC_view_configuration_fov`, chained into `XrViewConfigurationView`
+pub(crate) struct ViewConfigurationFovEPIC {
+    instance: openxr::Instance,
+}
+
+impl ViewConfigurationFovEPIC {
+    pub(crate) fn new(instance: &openxr::Instance) -> Option<Self> {
+        instance.exts().epic_view_configuration_fov.as_ref()?;
+
+        Some(ViewConfigurationFovEPIC {
+            instance: instance.clone(),
+        })
+    }
+
+    /// Views of `view_type` with their recommended and max fov (`xrEnumerateViewConfigurationViews`)
+    pub(crate) fn enumerate_views(
+        &self,
+        system: openxr::SystemId,
+        view_type: openxr::ViewConfigurationType,
+    ) -> Result<Vec<XRViewConfigView>, XrError> {
+        let fp = self.instance.fp();
+        let instance = self.instance.as_raw();
+
+        // first call for the count, second for the data
+        let mut count = 0;
+        cvt(unsafe {
+            (fp.enumerate_view_configuration_views)(
+                instance,
+                system,
+                view_type,
+                0,
+                &mut count,
+                std::ptr::null_mut(),
+            )
+        })?;
+
+        let empty_fov = openxr::Fovf {
+            angle_left: 0.,
+            angle_right: 0.,
+            angle_up: 0.,
+            angle_down: 0.,
+        };
+
+        let mut fovs = (0..count)
+            .map(|_| sys::ViewConfigurationViewFovEPIC {
+                ty: sys::ViewConfigurationViewFovEPIC::TYPE,
+                next: std::ptr::null(),
+                recommended_fov: empty_fov,
+                max_fov: empty_fov,
+            })
+            .collect::<Vec<_>>();
+
+        // fovs are not reallocated after this, so the chained pointers stay valid
+        let mut views = fovs
+            .iter_mut()
+            .map(|fov| sys::ViewConfigurationView {
+                ty: sys::ViewConfigurationView::TYPE,
+                next: fov as *mut _ as *mut _,
+                recommended_image_rect_width: 0,
+                max_image_rect_width: 0,
+                recommended_image_rect_height: 0,
+                max_image_rect_height: 0,
+                recommended_swapchain_sample_count: 0,
+                max_swapchain_sample_count: 0,
+            })
+            .collect::<Vec<_>>();
+
+        cvt(unsafe {
+            (fp.enumerate_view_configuration_views)(
+                instance,
+                system,
+                view_type,
+                views.len() as u32,
+                &mut count,
+                views.as_mut_ptr(),
+            )
+        })?;
+        views.truncate(count as usize);
+
+        Ok(views
+            .iter()
+            .zip(fovs.iter())
+            .map(|(view, fov)| XRViewConfigView {
+                recommended_image_rect: (
+                    view.recommended_image_rect_width,
+                    view.recommended_image_rect_height,
+                ),
+                max_image_rect: (view.max_image_rect_width, view.max_image_rect_height),
+                recommended_sample_count: view.recommended_swapchain_sample_count,
+                max_sample_count: view.max_swapchain_sample_count,
+                recommended_fov: Some(fov.recommended_fov.into()),
+                max_fov: Some(fov.max_fov.into()),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,68 @@
+use wgpu::{wgpu_openxr::WGPUOpenXR, OpenXRHandles};
+
+use crate::XrError;
+
+/// Graphics API integration of the XR session: the graphics device bound to the OpenXR instance,
+/// the session created with its graphics binding, and the import of swapchain images as textures.
+/// Session and frame logic (`XRDevice`, `XRSwapchain`) only goes through this trait, so that other
+/// wgpu versions or backends can be supported by implementing it, see `XrInstance::with_graphics`.
+///
+/// Implemented by `WgpuVulkanBackend` for the Vulkan device created by the wgpu fork. The types of
+/// the trait (`OpenXRHandles`, `wgpu::Device`) still come from the fork, and swapchains are Vulkan
+/// swapchains
+pub trait XrGraphicsBackend: Send + Sync + 'static {
+    /// Session, frame waiter / stream and reference space of the session created with the
+    /// graphics binding of the backend
+    fn session_handles(&self) -> Result<OpenXRHandles, XrError>;
+
+    /// Texture of a swapchain image (`xrEnumerateSwapchainImages`, a `VkImage` handle), rendered
+    /// into by the render graph
+    fn import_swapchain_image(
+        &self,
+        device: &wgpu::Device,
+        descriptor: &wgpu::TextureDescriptor,
+        image: u64,
+    ) -> wgpu::Texture;
+
+    /// Called after `xrBeginFrame` of a frame that is rendered
+    fn frame_begin(&self) {}
+
+    /// Called before `xrEndFrame` of a frame that was rendered
+    fn frame_end(&self) {}
+
+    /// Destroys the session and the graphics device, once the app is exiting
+    fn destroy(&self);
+}
+
+/// Vulkan device and session created through OpenXR by the wgpu fork (`wgpu_openxr`), which also
+/// provides the device to bevy_wgpu
+pub struct WgpuVulkanBackend {
+    wgpu_openxr: WGPUOpenXR,
+}
+
+impl WgpuVulkanBackend {
+    pub fn new(wgpu_openxr: WGPUOpenXR) -> Self {
+        WgpuVulkanBackend { wgpu_openxr }
+    }
+}
+
+impl XrGraphicsBackend for WgpuVulkanBackend {
+    fn session_handles(&self) -> Result<OpenXRHandles, XrError> {
+        self.wgpu_openxr
+            .get_session_handles()
+            .map_err(|error| XrError::Graphics(format!("{:?}", error)))
+    }
+
+    fn import_swapchain_image(
+        &self,
+        device: &wgpu::Device,
+        descriptor: &wgpu::TextureDescriptor,
+        image: u64,
+    ) -> wgpu::Texture {
+        device.create_openxr_texture_from_raw_image(descriptor, image)
+    }
+
+    fn destroy(&self) {
+        self.wgpu_openxr.destroy().unwrap();
+    }
+}
@@ -1,25 +1,299 @@
+use bevy::app::EventWriter;
+use bevy::ecs::system::{Local, Res};
+use bevy::transform::components::Transform;
+use bevy::utils::tracing::debug;
 use openxr::HandJointLocations;
 
+use crate::ext::{HandTrackingMeshFB, LocateHandJointsEXT};
+use crate::math::pose_to_transform;
+use crate::XRInputSource;
+
+/// Motion range of tracked hand joints while the hand holds a controller (`XR_EXT_hand_joints_motion_range`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRHandJointsMotionRange {
+    /// Joints as if the hand was not holding anything, e.g. a fist closes through the controller
+    Unobstructed,
+
+    /// Joints wrap around the controller, e.g. for rendering hands together with a controller model
+    ConformingToController,
+}
+
+impl Default for XRHandJointsMotionRange {
+    fn default() -> Self {
+        XRHandJointsMotionRange::Unobstructed
+    }
+}
+
+impl XRHandJointsMotionRange {
+    fn to_openxr(self) -> openxr::sys::HandJointsMotionRangeEXT {
+        match self {
+            XRHandJointsMotionRange::Unobstructed => {
+                openxr::sys::HandJointsMotionRangeEXT::UNOBSTRUCTED
+            }
+            XRHandJointsMotionRange::ConformingToController => {
+                openxr::sys::HandJointsMotionRangeEXT::CONFORMING_TO_CONTROLLER
+            }
+        }
+    }
+}
+
 pub struct HandTrackers {
     pub tracker_l: openxr::HandTracker,
     pub tracker_r: openxr::HandTracker,
+
+    /// Motion range of the left hand. Ignored if `XR_EXT_hand_joints_motion_range` is not supported
+    pub motion_range_l: XRHandJointsMotionRange,
+
+    /// Motion range of the right hand
+    pub motion_range_r: XRHandJointsMotionRange,
+
+    locate_ext: Option<LocateHandJointsEXT>,
+    mesh_ext: Option<HandTrackingMeshFB>,
 }
 
 impl HandTrackers {
-    pub fn new(session: &openxr::Session<openxr::Vulkan>) -> Result<Self, crate::Error> {
+    pub fn new(
+        instance: &openxr::Instance,
+        session: &openxr::Session<openxr::Vulkan>,
+        motion_range: XRHandJointsMotionRange,
+    ) -> Result<Self, crate::XrError> {
+        let locate_ext = LocateHandJointsEXT::new(instance);
+        if !locate_ext
+            .as_ref()
+            .map_or(false, |ext| ext.supports_motion_range())
+        {
+            debug!(target: "bevy_openxr::session", "XR_EXT_hand_joints_motion_range not supported, hand joints motion range is not set");
+        }
+        if !locate_ext.as_ref().map_or(false, |ext| ext.supports_aim()) {
+            debug!(target: "bevy_openxr::session", "XR_FB_hand_tracking_aim not supported, hand aim states are not available");
+        }
+
         let ht = HandTrackers {
             tracker_l: session.create_hand_tracker(openxr::HandEXT::LEFT)?,
             tracker_r: session.create_hand_tracker(openxr::HandEXT::RIGHT)?,
+            motion_range_l: motion_range,
+            motion_range_r: motion_range,
+            locate_ext,
+            mesh_ext: HandTrackingMeshFB::new(instance),
         };
 
         Ok(ht)
     }
+
+    /// Sets the motion range of a hand
+    pub fn set_motion_range(
+        &mut self,
+        hand: openxr::HandEXT,
+        motion_range: XRHandJointsMotionRange,
+    ) {
+        match hand {
+            openxr::HandEXT::LEFT => self.motion_range_l = motion_range,
+            _ => self.motion_range_r = motion_range,
+        }
+    }
+
+    /// Runtime provided mesh of a hand. `None` if `XR_FB_hand_tracking_mesh` is not supported
+    pub fn get_hand_mesh(
+        &self,
+        hand: openxr::HandEXT,
+    ) -> Option<Result<XRHandMesh, crate::XrError>> {
+        let tracker = match hand {
+            openxr::HandEXT::LEFT => &self.tracker_l,
+            _ => &self.tracker_r,
+        };
+
+        Some(self.mesh_ext.as_ref()?.get_hand_mesh(tracker))
+    }
+
+    /// Joint locations and aim states of both hands in `base` at `time`
+    pub fn locate(
+        &self,
+        base: &openxr::Space,
+        time: openxr::Time,
+    ) -> Result<HandPoseState, crate::XrError> {
+        let (left, left_aim) =
+            self.locate_hand(&self.tracker_l, self.motion_range_l, base, time)?;
+        let (right, right_aim) =
+            self.locate_hand(&self.tracker_r, self.motion_range_r, base, time)?;
+
+        Ok(HandPoseState {
+            left,
+            right,
+            left_aim,
+            right_aim,
+        })
+    }
+
+    fn locate_hand(
+        &self,
+        tracker: &openxr::HandTracker,
+        motion_range: XRHandJointsMotionRange,
+        base: &openxr::Space,
+        time: openxr::Time,
+    ) -> Result<(Option<HandJointLocations>, Option<XRHandAimState>), crate::XrError> {
+        match &self.locate_ext {
+            Some(ext) => ext.locate_hand_joints(tracker, base, time, motion_range.to_openxr()),
+            None => Ok((base.locate_hand_joints(tracker, time)?, None)),
+        }
+    }
+}
+
+/// Skinned mesh of the user's hand (`XR_FB_hand_tracking_mesh`), in the bind pose. Each vertex is
+/// skinned by up to four joints of `joint_bind_poses`, which are in the order of `XrHandJointEXT`
+#[derive(Debug, Clone, Default)]
+pub struct XRHandMesh {
+    /// Joint poses of the bind pose, in mesh space
+    pub joint_bind_poses: Vec<Transform>,
+    pub joint_radii: Vec<f32>,
+
+    /// Index of the parent joint, `None` for the root
+    pub joint_parents: Vec<Option<usize>>,
+
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+
+    /// Joints skinning each vertex, indices into `joint_bind_poses`
+    pub blend_indices: Vec<[u16; 4]>,
+    pub blend_weights: Vec<[f32; 4]>,
+
+    /// Triangle list
+    pub indices: Vec<u16>,
+}
+
+/// Aim state of a tracked hand computed by the runtime (`XR_FB_hand_tracking_aim`), with the same
+/// pinch and menu gestures as the system UI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XRHandAimState {
+    /// Aim pose in the XR reference space, -Z points along the ray
+    pub aim: Transform,
+
+    /// Aim pose is valid, e.g. the hand is not facing the head
+    pub valid: bool,
+
+    /// Pinch strength (`0.0..=1.0`) of the index, middle, ring and little finger with the thumb
+    pub pinch_strength: [f32; 4],
+
+    /// Index, middle, ring and little finger are pinching
+    pub pinching: [bool; 4],
+
+    /// System gesture is in progress (palm facing the head), apps should not react to pinches
+    pub system_gesture: bool,
+
+    /// Hand is the dominant hand of the user
+    pub dominant_hand: bool,
+
+    /// Menu gesture has been performed (the pinch of the system gesture on the non-dominant hand)
+    pub menu_pressed: bool,
+}
+
+impl XRHandAimState {
+    /// `None` if the runtime did not compute the aim state
+    pub(crate) fn from_openxr(state: &openxr::sys::HandTrackingAimStateFB) -> Option<Self> {
+        use openxr::sys::HandTrackingAimFlagsFB as Flags;
+
+        if !state.status.contains(Flags::COMPUTED) {
+            return None;
+        }
+
+        Some(XRHandAimState {
+            aim: pose_to_transform(&state.aim_pose),
+            valid: state.status.contains(Flags::VALID),
+            pinch_strength: [
+                state.pinch_strength_index,
+                state.pinch_strength_middle,
+                state.pinch_strength_ring,
+                state.pinch_strength_little,
+            ],
+            pinching: [
+                state.status.contains(Flags::INDEX_PINCHING),
+                state.status.contains(Flags::MIDDLE_PINCHING),
+                state.status.contains(Flags::RING_PINCHING),
+                state.status.contains(Flags::LITTLE_PINCHING),
+            ],
+            system_gesture: state.status.contains(Flags::SYSTEM_GESTURE),
+            dominant_hand: state.status.contains(Flags::DOMINANT_HAND),
+            menu_pressed: state.status.contains(Flags::MENU_PRESSED),
+        })
+    }
+
+    /// Index finger is pinching, and not as part of the system gesture
+    pub fn is_selecting(&self) -> bool {
+        self.pinching[0] && !self.system_gesture
+    }
+}
+
+/// Gesture of `XRHandAimState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRHandGesture {
+    /// Index finger pinch started, outside of the system gesture
+    PinchStarted,
+    PinchEnded,
+
+    /// Menu gesture of the non-dominant hand
+    MenuPressed,
+}
+
+/// Sent when a tracked hand starts or ends a gesture (`XR_FB_hand_tracking_aim`). `source` is
+/// `XRInputSource::LeftHand` or `XRInputSource::RightHand`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XRHandGestureEvent {
+    pub source: XRInputSource,
+    pub gesture: XRHandGesture,
+}
+
+/// Gestures between the `previous` and `current` aim state of a hand. A pinch ends also when the
+/// hand is lost
+fn aim_gestures(
+    previous: Option<&XRHandAimState>,
+    current: Option<&XRHandAimState>,
+) -> Vec<XRHandGesture> {
+    let was_selecting = previous.map_or(false, |state| state.is_selecting());
+    let is_selecting = current.map_or(false, |state| state.is_selecting());
+    let was_menu_pressed = previous.map_or(false, |state| state.menu_pressed);
+    let is_menu_pressed = current.map_or(false, |state| state.menu_pressed);
+
+    let mut gestures = Vec::new();
+    match (was_selecting, is_selecting) {
+        (false, true) => gestures.push(XRHandGesture::PinchStarted),
+        (true, false) => gestures.push(XRHandGesture::PinchEnded),
+        _ => (),
+    }
+    if is_menu_pressed && !was_menu_pressed {
+        gestures.push(XRHandGesture::MenuPressed);
+    }
+    gestures
+}
+
+pub(crate) fn hand_gesture_system(
+    mut previous: Local<[Option<XRHandAimState>; 2]>,
+    hand_pose: Res<HandPoseState>,
+    mut gesture_events: EventWriter<XRHandGestureEvent>,
+) {
+    let current = [hand_pose.left_aim, hand_pose.right_aim];
+    let sources = [XRInputSource::LeftHand, XRInputSource::RightHand];
+
+    for ((previous, current), source) in previous.iter().zip(current.iter()).zip(sources.iter()) {
+        for gesture in aim_gestures(previous.as_ref(), current.as_ref()) {
+            gesture_events.send(XRHandGestureEvent {
+                source: *source,
+                gesture,
+            });
+        }
+    }
+
+    *previous = current;
 }
 
 #[derive(Default)]
 pub struct HandPoseState {
     pub left: Option<HandJointLocations>,
     pub right: Option<HandJointLocations>,
+
+    /// Aim state of the left hand, `None` if `XR_FB_hand_tracking_aim` is not supported or the hand
+    /// is not tracked
+    pub left_aim: Option<XRHandAimState>,
+    pub right_aim: Option<XRHandAimState>,
 }
 
 impl std::fmt::Debug for HandPoseState {
@@ -32,3 +306,47 @@ impl std::fmt::Debug for HandPoseState {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aim_gestures() {
+        let idle = XRHandAimState {
+            aim: Transform::identity(),
+            valid: true,
+            pinch_strength: [0.; 4],
+            pinching: [false; 4],
+            system_gesture: false,
+            dominant_hand: true,
+            menu_pressed: false,
+        };
+        let pinching = XRHandAimState {
+            pinch_strength: [1., 0., 0., 0.],
+            pinching: [true, false, false, false],
+            ..idle
+        };
+
+        assert_eq!(
+            aim_gestures(Some(&idle), Some(&pinching)),
+            vec![XRHandGesture::PinchStarted]
+        );
+        assert_eq!(
+            aim_gestures(Some(&pinching), None),
+            vec![XRHandGesture::PinchEnded]
+        );
+        assert!(aim_gestures(Some(&pinching), Some(&pinching)).is_empty());
+
+        // pinch of the system gesture
+        let system_pinch = XRHandAimState {
+            system_gesture: true,
+            menu_pressed: true,
+            ..pinching
+        };
+        assert_eq!(
+            aim_gestures(Some(&idle), Some(&system_pinch)),
+            vec![XRHandGesture::MenuPressed]
+        );
+    }
+}
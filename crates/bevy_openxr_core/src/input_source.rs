@@ -0,0 +1,208 @@
+use bevy::app::EventWriter;
+use bevy::ecs::system::{Res, ResMut};
+use bevy::utils::tracing::info;
+
+use crate::{hand_tracking::HandPoseState, XRDevice, XrError};
+
+/// Tracked input device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XRInputSource {
+    LeftHand,
+    RightHand,
+    LeftController,
+    RightController,
+}
+
+impl XRInputSource {
+    pub const ALL: [XRInputSource; 4] = [
+        XRInputSource::LeftHand,
+        XRInputSource::RightHand,
+        XRInputSource::LeftController,
+        XRInputSource::RightController,
+    ];
+
+    pub fn is_left(&self) -> bool {
+        matches!(
+            self,
+            XRInputSource::LeftHand | XRInputSource::LeftController
+        )
+    }
+
+    pub fn is_controller(&self) -> bool {
+        matches!(
+            self,
+            XRInputSource::LeftController | XRInputSource::RightController
+        )
+    }
+}
+
+/// Sent when an input source is connected or disconnected, e.g. when a controller is turned off or
+/// the runtime switches between hand tracking and controllers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XRInputSourceChanged {
+    pub source: XRInputSource,
+    pub connected: bool,
+}
+
+/// Connected input sources, available as a resource.
+///
+/// Hands are connected while hand tracking reports valid joint locations. Controllers are connected
+/// while the runtime reports a (non-hand) interaction profile for the hand. Runtimes only report
+/// interaction profiles once an action set has been attached to the session, so without app-created
+/// actions controllers are never reported as connected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XRInputSources {
+    pub left_hand: bool,
+    pub right_hand: bool,
+    pub left_controller: bool,
+    pub right_controller: bool,
+
+    /// Current interaction profile of each hand (left, right), e.g. `/interaction_profiles/oculus/touch_controller`
+    pub interaction_profiles: [Option<String>; 2],
+}
+
+impl XRInputSources {
+    pub fn is_connected(&self, source: XRInputSource) -> bool {
+        match source {
+            XRInputSource::LeftHand => self.left_hand,
+            XRInputSource::RightHand => self.right_hand,
+            XRInputSource::LeftController => self.left_controller,
+            XRInputSource::RightController => self.right_controller,
+        }
+    }
+
+    /// Any controller is connected
+    pub fn controllers_active(&self) -> bool {
+        self.left_controller || self.right_controller
+    }
+
+    /// Any hand is tracked
+    pub fn hands_active(&self) -> bool {
+        self.left_hand || self.right_hand
+    }
+
+    fn set_interaction_profiles(&mut self, profiles: [Option<String>; 2]) {
+        self.left_controller = is_controller_profile(profiles[0].as_deref());
+        self.right_controller = is_controller_profile(profiles[1].as_deref());
+        self.interaction_profiles = profiles;
+    }
+
+    /// Sources whose connection state differs from `previous`
+    fn changes(&self, previous: &XRInputSources) -> Vec<XRInputSourceChanged> {
+        XRInputSource::ALL
+            .iter()
+            .filter(|source| self.is_connected(**source) != previous.is_connected(**source))
+            .map(|source| XRInputSourceChanged {
+                source: *source,
+                connected: self.is_connected(*source),
+            })
+            .collect()
+    }
+}
+
+/// Hand interaction profiles are reported for tracked hands, not for controllers
+const HAND_INTERACTION_PROFILES: &[&str] = &[
+    "/interaction_profiles/ext/hand_interaction_ext",
+    "/interaction_profiles/microsoft/hand_interaction",
+];
+
+fn is_controller_profile(profile: Option<&str>) -> bool {
+    match profile {
+        Some(profile) => !HAND_INTERACTION_PROFILES.contains(&profile),
+        None => false,
+    }
+}
+
+/// Current interaction profile of `/user/hand/left` and `/user/hand/right`
+pub(crate) fn current_interaction_profiles(
+    instance: &openxr::Instance,
+    session: &openxr::Session<openxr::Vulkan>,
+) -> Result<[Option<String>; 2], XrError> {
+    let mut profiles = [None, None];
+
+    for (profile, user_path) in profiles
+        .iter_mut()
+        .zip(["/user/hand/left", "/user/hand/right"].iter())
+    {
+        let user_path = instance.string_to_path(user_path)?;
+        let path = session.current_interaction_profile(user_path)?;
+        if path != openxr::Path::NULL {
+            *profile = Some(instance.path_to_string(path)?);
+        }
+    }
+
+    Ok(profiles)
+}
+
+pub(crate) fn input_source_system(
+    mut xr_device: ResMut<XRDevice>,
+    hand_pose: Res<HandPoseState>,
+    mut input_sources: ResMut<XRInputSources>,
+    mut input_source_changes: EventWriter<XRInputSourceChanged>,
+) {
+    let mut current = input_sources.clone();
+    current.left_hand = hand_pose.left.is_some();
+    current.right_hand = hand_pose.right.is_some();
+
+    if let Some(profiles) = xr_device.interaction_profile_changes() {
+        current.set_interaction_profiles(profiles);
+    }
+
+    if current == *input_sources {
+        return;
+    }
+
+    for change in current.changes(&input_sources) {
+        info!(
+            target: "bevy_openxr::session",
+            source = ?change.source,
+            connected = change.connected,
+            "Input source changed"
+        );
+        input_source_changes.send(change);
+    }
+
+    *input_sources = current;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_source_changes() {
+        let previous = XRInputSources {
+            left_hand: true,
+            right_hand: true,
+            ..Default::default()
+        };
+
+        let mut current = previous.clone();
+        current.left_hand = false;
+        current.right_hand = false;
+        current.set_interaction_profiles([
+            Some("/interaction_profiles/oculus/touch_controller".to_string()),
+            Some("/interaction_profiles/ext/hand_interaction_ext".to_string()),
+        ]);
+
+        assert!(current.controllers_active());
+        assert!(!current.hands_active());
+        assert_eq!(
+            current.changes(&previous),
+            vec![
+                XRInputSourceChanged {
+                    source: XRInputSource::LeftHand,
+                    connected: false,
+                },
+                XRInputSourceChanged {
+                    source: XRInputSource::RightHand,
+                    connected: false,
+                },
+                XRInputSourceChanged {
+                    source: XRInputSource::LeftController,
+                    connected: true,
+                },
+            ]
+        );
+    }
+}
@@ -2,7 +2,11 @@ use bevy::input::keyboard::{KeyCode, KeyboardInput};
 use bevy::input::mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::input::ElementState;
 use bevy::prelude::*;
-use bevy::window::WindowId;
+use bevy::utils::tracing::{debug, trace, warn};
+use bevy::window::{ReceivedCharacter, WindowId};
+use jni::objects::JValue;
+
+use crate::text_input::{XRKeycodeMap, XRKeymap, XRSystemKeyboard};
 
 pub(crate) struct InputMetadata {
     window_size: Option<Vec2>,
@@ -20,11 +24,14 @@ pub(crate) fn setup_android_keyboard_event(mut commands: Commands) {
 
 pub(crate) fn android_keyboard_event(
     mut keyboard_input_events: EventWriter<KeyboardInput>,
+    mut received_character_events: EventWriter<ReceivedCharacter>,
     mut mouse_wheel_events: EventWriter<MouseWheel>,
     mut mouse_button_input_events: EventWriter<MouseButtonInput>,
     mut cursor_moved_events: EventWriter<CursorMoved>,
     mut mouse_motion_events: EventWriter<MouseMotion>,
     mut keyboard_metadata: ResMut<InputMetadata>,
+    keymap: Res<XRKeymap>,
+    keycode_map: Res<XRKeycodeMap>,
 ) {
     if let None = keyboard_metadata.window_size {
         if let Some(native_window) = ndk_glue::native_window().as_ref() {
@@ -61,40 +68,68 @@ pub(crate) fn android_keyboard_event(
                 let key_code = key_event.key_code();
                 let action = key_event.action();
 
-                let converted_key_code = convert_key_code(key_code);
-                let state = convert_key_state(action);
-
-                if converted_key_code.is_some() && state.is_some() {
-                    let keyboard_input = KeyboardInput {
-                        scan_code: scan_code as u32,
-                        key_code: converted_key_code,
-                        state: state.unwrap(),
-                    };
+                let converted_key_code =
+                    keycode_map.convert(key_code as u32, convert_key_code(key_code));
+
+                // `Multiple` is a key repeated `repeat_count` times, sent as that many presses
+                let (states, repeats): (&[ElementState], i32) = match action {
+                    ndk::event::KeyAction::Down => (&[ElementState::Pressed], 1),
+                    ndk::event::KeyAction::Up => (&[ElementState::Released], 1),
+                    ndk::event::KeyAction::Multiple => (
+                        &[ElementState::Pressed, ElementState::Released],
+                        key_event.repeat_count().max(1),
+                    ),
+                };
+
+                // text typed on the system keyboard, or on a physical keyboard
+                if let ndk::event::KeyAction::Down | ndk::event::KeyAction::Multiple = action {
+                    let meta_state = key_event.meta_state();
+                    let character = converted_key_code
+                        .and_then(|key_code| {
+                            keymap.character(
+                                key_code,
+                                meta_state.shift_on(),
+                                meta_state.caps_lock_on(),
+                            )
+                        })
+                        .or_else(|| unicode_char(key_event));
+
+                    if let Some(character) = character {
+                        for _ in 0..repeats {
+                            received_character_events.send(ReceivedCharacter {
+                                id: WindowId::default(),
+                                char: character,
+                            });
+                        }
+                    }
+                }
 
-                    //println!("Key event: {:?}", keyboard_input);
-                    keyboard_input_events.send(keyboard_input);
+                if converted_key_code.is_some() {
+                    for _ in 0..repeats {
+                        for state in states.iter() {
+                            keyboard_input_events.send(KeyboardInput {
+                                scan_code: scan_code as u32,
+                                key_code: converted_key_code,
+                                state: *state,
+                            });
+                        }
+                    }
                     let handled = true;
                 } else {
-                    /* do not print by default
-                    println!(
-                        "!! Unknown android key event scan_code={:?}, key_code={:?}, action={:?}",
-                        scan_code, key_code, action
-                    );
-                    */
+                    debug!(target: "bevy_openxr::platform", scan_code, ?key_code, ?action, "Unknown android key event");
                 }
 
-                /*
-                println!(
-                    "KEY EVENT: device_id={:?} action={:?} down_time={:?} event_time={:?} key_code={:?} repeat_count={:?} scan_code={:?}",
-                    key_event.device_id(),
-                    key_event.action(),
-                    key_event.down_time(),
-                    key_event.event_time(),
-                    key_event.key_code(),
-                    key_event.repeat_count(),
-                    key_event.scan_code()
+                trace!(
+                    target: "bevy_openxr::platform",
+                    device_id = key_event.device_id(),
+                    action = ?key_event.action(),
+                    down_time = key_event.down_time(),
+                    event_time = key_event.event_time(),
+                    key_code = ?key_event.key_code(),
+                    repeat_count = key_event.repeat_count(),
+                    scan_code = key_event.scan_code(),
+                    "Key event"
                 );
-                */
             }
             ndk::event::InputEvent::MotionEvent(motion_event) => {
                 let action = motion_event.action();
@@ -185,6 +220,31 @@ pub(crate) fn android_keyboard_event(
                         }
                     }
 
+                    // touch, as the left mouse button. Mouse buttons are ButtonPress / ButtonRelease
+                    ndk::event::MotionAction::Down | ndk::event::MotionAction::Up
+                        if motion_event.source() == ndk::event::Source::Touchscreen =>
+                    {
+                        if let Some(pointer) = motion_event.pointers().next() {
+                            let position = Vec2::new(
+                                pointer.x(),
+                                keyboard_metadata.window_size.unwrap().y - pointer.y() - 1.,
+                            );
+
+                            cursor_moved_events.send(CursorMoved {
+                                id: WindowId::default(),
+                                position,
+                            });
+                            keyboard_metadata.previous_mouse_position = Some(position);
+                        }
+
+                        mouse_button_input_events.send(MouseButtonInput {
+                            button: MouseButton::Left,
+                            state: match action {
+                                ndk::event::MotionAction::Down => ElementState::Pressed,
+                                _ => ElementState::Released,
+                            },
+                        });
+                    }
                     ndk::event::MotionAction::Down => (),
                     ndk::event::MotionAction::Up => (),
                     ndk::event::MotionAction::Cancel => (),
@@ -195,18 +255,17 @@ pub(crate) fn android_keyboard_event(
                     ndk::event::MotionAction::HoverExit => (),
                 }
 
-                /*
-                println!(
-                    "MOTION EVENT: device_id={:?} source={:?} action={:?} pointer_index={:?} history_size={:?} button_state={:?} down_time={:?}",
-                    motion_event.device_id(),
-                    motion_event.source(),
-                    motion_event.action(),
-                    motion_event.pointer_index(),
-                    motion_event.history_size(),
-                    motion_event.button_state(),
-                    motion_event.down_time()
+                trace!(
+                    target: "bevy_openxr::platform",
+                    device_id = motion_event.device_id(),
+                    source = ?motion_event.source(),
+                    action = ?motion_event.action(),
+                    pointer_index = motion_event.pointer_index(),
+                    history_size = motion_event.history_size(),
+                    button_state = ?motion_event.button_state(),
+                    down_time = motion_event.down_time(),
+                    "Motion event"
                 );
-                */
             }
         }
 
@@ -217,16 +276,60 @@ pub(crate) fn android_keyboard_event(
     }
 }
 
-fn convert_key_state(input: ndk::event::KeyAction) -> Option<ElementState> {
-    match input {
-        ndk::event::KeyAction::Down => Some(ElementState::Pressed),
-        ndk::event::KeyAction::Up => Some(ElementState::Released),
-        ndk::event::KeyAction::Multiple => None, // ??
+/// Shows or hides the system keyboard overlay, as requested through `XRSystemKeyboard`
+pub(crate) fn android_system_keyboard(mut system_keyboard: ResMut<XRSystemKeyboard>) {
+    let visible = match system_keyboard.take_request() {
+        Some(visible) => visible,
+        None => return,
+    };
+
+    debug!(target: "bevy_openxr::platform", visible, "System keyboard visibility");
+
+    let native_activity = ndk_glue::native_activity();
+    if visible {
+        native_activity.show_soft_input(true);
+    } else {
+        native_activity.hide_soft_input(false);
+    }
+}
+
+/// Character produced by the key event. NDK key events have no `getUnicodeChar`, so it is called on
+/// an equivalent `android.view.KeyEvent`
+fn unicode_char(key_event: &ndk::event::KeyEvent) -> Option<char> {
+    let result = (|| -> jni::errors::Result<i32> {
+        let native_activity = ndk_glue::native_activity();
+        let vm = unsafe { jni::JavaVM::from_raw(native_activity.vm()) }?;
+        let env = vm.attach_current_thread()?;
+
+        let event = env.new_object(
+            "android/view/KeyEvent",
+            "(JJIIII)V",
+            &[
+                JValue::Long(key_event.down_time()),
+                JValue::Long(key_event.event_time()),
+                JValue::Int(key_event.action() as i32),
+                JValue::Int(key_event.key_code() as i32),
+                JValue::Int(key_event.repeat_count()),
+                JValue::Int(key_event.meta_state().0 as i32),
+            ],
+        )?;
+
+        env.call_method(event, "getUnicodeChar", "()I", &[])?.i()
+    })();
+
+    match result {
+        // 0 for keys without a character
+        Ok(unicode) if unicode > 0 => std::char::from_u32(unicode as u32),
+        Ok(_) => None,
+        Err(e) => {
+            warn!(target: "bevy_openxr::platform", error = ?e, "Could not get key event character");
+            None
+        }
     }
 }
 
 fn convert_key_code(input: ndk::event::Keycode) -> Option<KeyCode> {
-    // FIXME: untested and incomplete list!
+    // FIXME: untested and incomplete list! Missing keys can be added with `XRKeycodeMap`
 
     match input {
         ndk::event::Keycode::Unknown => None,
@@ -305,14 +408,15 @@ fn convert_key_code(input: ndk::event::Keycode) -> Option<KeyCode> {
         ndk::event::Keycode::Envelope => None,
 
         ndk::event::Keycode::Enter => Some(KeyCode::Return),
-        ndk::event::Keycode::Del => Some(KeyCode::Delete),
+        // KEYCODE_DEL is backspace, KEYCODE_FORWARD_DEL is delete
+        ndk::event::Keycode::Del => Some(KeyCode::Back),
 
-        ndk::event::Keycode::Grave => None,
+        ndk::event::Keycode::Grave => Some(KeyCode::Grave),
         ndk::event::Keycode::Minus => Some(KeyCode::Minus),
         ndk::event::Keycode::Equals => Some(KeyCode::Equals),
 
-        ndk::event::Keycode::LeftBracket => None,
-        ndk::event::Keycode::RightBracket => None,
+        ndk::event::Keycode::LeftBracket => Some(KeyCode::LBracket),
+        ndk::event::Keycode::RightBracket => Some(KeyCode::RBracket),
 
         ndk::event::Keycode::Backslash => Some(KeyCode::Backslash),
         ndk::event::Keycode::Semicolon => Some(KeyCode::Semicolon),
@@ -361,15 +465,15 @@ fn convert_key_code(input: ndk::event::Keycode) -> Option<KeyCode> {
 
         ndk::event::Keycode::Escape => Some(KeyCode::Escape),
 
-        ndk::event::Keycode::ForwardDel => None,
-        ndk::event::Keycode::CtrlLeft => None,
-        ndk::event::Keycode::CtrlRight => None,
-        ndk::event::Keycode::CapsLock => None,
+        ndk::event::Keycode::ForwardDel => Some(KeyCode::Delete),
+        ndk::event::Keycode::CtrlLeft => Some(KeyCode::LControl),
+        ndk::event::Keycode::CtrlRight => Some(KeyCode::RControl),
+        ndk::event::Keycode::CapsLock => Some(KeyCode::Capital),
 
         ndk::event::Keycode::ScrollLock => Some(KeyCode::Scroll),
 
-        ndk::event::Keycode::MetaLeft => None,
-        ndk::event::Keycode::MetaRight => None,
+        ndk::event::Keycode::MetaLeft => Some(KeyCode::LWin),
+        ndk::event::Keycode::MetaRight => Some(KeyCode::RWin),
         ndk::event::Keycode::Function => None,
         ndk::event::Keycode::Sysrq => None,
         ndk::event::Keycode::Break => None,
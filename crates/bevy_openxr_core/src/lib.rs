@@ -1,62 +1,309 @@
-use bevy::app::{prelude::*, EventReader};
-use bevy::ecs::system::IntoSystem;
+use std::sync::Arc;
 
+use bevy::app::prelude::*;
+use bevy::ecs::schedule::{StageLabel, SystemLabel, SystemStage};
+use bevy::ecs::system::{IntoExclusiveSystem, IntoSystem};
+
+mod anchors;
+mod availability;
+mod blend_mode;
+mod capture;
+mod composition_layer;
 mod device;
+pub mod diagnostics;
+mod error;
 pub mod event;
+mod event_poller;
+mod ext;
+mod graphics;
 pub mod hand_tracking;
+mod input_source;
 
 #[cfg(target_os = "android")]
 mod keyboard;
 
 pub mod math;
-mod runner;
+mod passthrough;
+mod pause;
+mod performance;
+mod secondary_view;
+mod stereo;
 mod swapchain;
+mod system_info;
 mod systems;
+mod text_input;
+mod time;
+mod view_config;
+mod visibility_mask;
 mod xr_instance;
 
-use bevy::render::renderer::TextureId;
-use bevy::utils::tracing::debug;
+pub use anchors::XRAnchorId;
+pub use availability::XRAvailability;
+use bevy::render::{renderer::TextureId, texture::TextureFormat};
+use bevy::utils::tracing::{debug, info, warn};
+use bevy::utils::Duration;
+pub use blend_mode::XREnvironmentBlendMode;
+pub use capture::XRCapturedImage;
+pub use composition_layer::{XRLayerId, XRLayerOrder, XRLayerShape};
 pub use device::*;
-use event::{XRState, XRViewSurfaceCreated};
+pub use error::XrError;
+use event::{
+    XRPerformanceNotification, XRRuntimeError, XRSecondaryViewSurfaceCreated, XRViewSurfaceCreated,
+    XrFrameStatus, XrSessionState,
+};
+use event_poller::{OpenXREvent, XREventPoller};
+use ext::DisplayRefreshRateFB;
+pub use graphics::{WgpuVulkanBackend, XrGraphicsBackend};
+pub use hand_tracking::{
+    XRHandAimState, XRHandGesture, XRHandGestureEvent, XRHandJointsMotionRange, XRHandMesh,
+};
+pub use input_source::{XRInputSource, XRInputSourceChanged, XRInputSources};
+pub use pause::{XRPauseBehavior, XRPauseState, XRSimulationTime};
+pub use performance::{
+    XRPerformanceDomain, XRPerformanceLevel, XRPerformanceNotificationLevel, XRPerformanceSubDomain,
+};
+pub use stereo::{XRStereoMode, XRViewRect};
 pub use swapchain::*;
+pub use system_info::XRSystemInfo;
 use systems::*;
-pub use xr_instance::{set_xr_instance, XrInstance};
+pub use text_input::{XRKeycodeMap, XRKeymap, XRSystemKeyboard};
+pub use time::XrTime;
+pub use view_config::{XRViewConfigInfo, XRViewConfigView};
+pub use visibility_mask::XRVisibilityMask;
+pub use xr_instance::XrInstance;
 
 #[derive(Default)]
 pub struct OpenXRCorePlugin;
 
+/// Stages of the XR frame loop. The frame loop runs inside the regular bevy schedule, so any runner
+/// (e.g. `ScheduleRunnerPlugin` or `WinitPlugin`) can be used to drive the app
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub enum XRStage {
+    /// Polls OpenXR events and waits for the next XR frame (`xrWaitFrame`). Runs before `CoreStage::PreUpdate`
+    FrameLoop,
+
+    /// Runs once, in the update in which the session started exiting (`XrSessionState::Exiting`), after
+    /// rendering and before `CoreStage::Last`. Add cleanup systems (e.g. saving state) to this stage,
+    /// `AppExit` is sent after them
+    Exit,
+}
+
+/// Labels of the XR systems, for ordering app systems against the XR work of the frame. Labels are
+/// used in several stages, ordering applies between systems of the same stage
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum XRSystem {
+    /// Publishes the runtime state as events and resources: OpenXR events, frame status and located
+    /// views at `XRStage::FrameLoop`, and input sources, hand gestures and `XrTime` at
+    /// `CoreStage::PreUpdate`. Simulated and recorded input use the same label
+    EventPoll,
+
+    /// Updates tracked poses: hand and controller entities at `CoreStage::PreUpdate` and
+    /// `CoreStage::Update`, and the XR camera transforms from the located views at
+    /// `CoreStage::PostUpdate`, before `TransformSystem::TransformPropagate`, so that
+    /// `GlobalTransform`s of the camera and its children are of the current frame. App systems
+    /// moving the rig run before this label
+    PoseUpdate,
+
+    /// Waits for the acquired swapchain image, just before rendering (`RenderStage::Draw`)
+    PreRender,
+
+    /// Releases the swapchain image and submits the frame (`xrEndFrame`), after rendering
+    /// (`RenderStage::PostRender`)
+    PostRender,
+}
+
 impl Plugin for OpenXRCorePlugin {
     fn build(&self, app: &mut App) {
         debug!("Building OpenXRCorePlugin");
-        let xr_instance = xr_instance::take_xr_instance();
-        let options = XrOptions::default(); // FIXME user configurable?
-        let (xr_device, wgpu_openxr) = xr_instance.into_device_with_options(options);
 
-        app.insert_resource(xr_device)
-            .add_event::<event::XRState>()
+        // resources and events are available also without a session, so that XR-aware systems
+        // keep working in the desktop fallback
+        app.add_event::<XrSessionState>()
             .add_event::<event::XRViewSurfaceCreated>()
             .add_event::<event::XRViewsCreated>()
             .add_event::<event::XRCameraTransformsUpdated>()
+            .add_event::<event::XRSecondaryViewSurfaceCreated>()
+            .add_event::<event::XRSecondaryViewsUpdated>()
+            .add_event::<event::XRFovChanged>()
+            .add_event::<event::XRRuntimeError>()
+            .add_event::<event::XRPerformanceNotification>()
+            .add_event::<event::XRVisibilityMaskChanged>()
+            .add_event::<event::XRSwapchainRecreated>()
+            .add_event::<event::XRFrameSkipped>()
+            .add_event::<event::XRPauseChanged>()
+            .add_event::<XRInputSourceChanged>()
+            .add_event::<event::XRExitRequest>()
+            .add_event::<hand_tracking::XRHandGestureEvent>()
+            .init_resource::<XrSessionState>()
+            .init_resource::<XrFrameStatus>()
+            .init_resource::<XREnvironmentBlendMode>()
+            .init_resource::<XRPauseState>()
+            .init_resource::<XRSystemKeyboard>()
+            .init_resource::<XRKeymap>()
+            .init_resource::<XRKeycodeMap>()
             .init_resource::<XRConfigurationState>()
             .init_resource::<hand_tracking::HandPoseState>()
-            .insert_resource(wgpu_openxr)
-            .add_system_to_stage(CoreStage::PreUpdate, openxr_event_system.system())
-            .add_system(xr_event_debug.system())
-            .set_runner(runner::xr_runner); // FIXME conditional, or extract xr_events to whole new system? probably good
+            .init_resource::<XRInputSources>()
+            .init_resource::<XrTime>()
+            .init_resource::<XRSimulationTime>()
+            .add_system_to_stage(CoreStage::PreUpdate, exit_request_system.system())
+            .add_system_to_stage(CoreStage::PreUpdate, pause::simulation_time_system.system());
+
+        let options = app
+            .world
+            .get_resource::<XrOptions>()
+            .cloned()
+            .unwrap_or_default();
+        let pause_behavior = options.pause_behavior.clone();
+        app.insert_resource(pause_behavior.clone());
+
+        if !app.world.contains_resource::<XRDevice>() {
+            let xr_instance = match app.world.remove_resource::<XrInstance>() {
+                Some(xr_instance) => xr_instance,
+                None => {
+                    match app.world.get_resource::<XRAvailability>() {
+                        Some(XRAvailability::Unavailable(reason)) => {
+                            info!(target: "bevy_openxr::platform", %reason, "XR unavailable, OpenXRCorePlugin runs without a session");
+
+                            // without a session, the app is never paused
+                            app.insert_resource(XRPauseState { paused: false });
+                            return;
+                        }
+                        _ => {
+                            panic!("XrInstance resource missing. Add OpenXRPlugin before OpenXRCorePlugin")
+                        }
+                    }
+                }
+            };
+
+            match xr_instance.into_device_with_options(options) {
+                Ok(xr_device) => app.world.insert_resource(xr_device),
+                Err(e) => panic!("Could not create OpenXR session: {}", e),
+            }
+        }
+
+        let xr_device = app.world.get_resource::<XRDevice>().unwrap();
+        let system_info = xr_device.system_info().clone();
+        let view_config_info = xr_device.view_config_info().clone();
+
+        app.insert_resource(system_info)
+            .insert_resource(view_config_info)
+            .init_resource::<XRExitState>()
+            .add_stage_before(
+                CoreStage::PreUpdate,
+                XRStage::FrameLoop,
+                SystemStage::parallel(),
+            )
+            .add_system_to_stage(
+                XRStage::FrameLoop,
+                openxr_event_system.system().label(XRSystem::EventPoll),
+            )
+            .add_stage_before(
+                CoreStage::Last,
+                XRStage::Exit,
+                SystemStage::parallel().with_run_criteria(xr_exit_criteria.system()),
+            )
+            .add_system_to_stage(
+                XRStage::Exit,
+                xr_app_exit_system.exclusive_system().at_end(),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                input_source::input_source_system
+                    .system()
+                    .label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                hand_tracking::hand_gesture_system
+                    .system()
+                    .label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                time::xr_time_system.system().label(XRSystem::EventPoll),
+            )
+            .add_system_to_stage(CoreStage::Last, xr_exit_system.system());
+
+        if pause_behavior.skip_update_stage {
+            app.stage(CoreStage::Update, |stage: &mut SystemStage| {
+                stage.set_run_criteria(pause::xr_not_paused_criteria.system())
+            });
+        }
 
         #[cfg(target_os = "android")]
         app.add_startup_system(keyboard::setup_android_keyboard_event.system())
             .add_system_to_stage(
                 CoreStage::PreUpdate,
                 keyboard::android_keyboard_event.system(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                keyboard::android_system_keyboard.system(),
             );
     }
 }
 
+/// Options for the OpenXR session. Insert as a resource before adding `OpenXRCorePlugin` to override defaults
 #[derive(Clone, Debug)]
 pub struct XrOptions {
+    /// View configuration, e.g. `PRIMARY_STEREO` (headsets) or `PRIMARY_MONO` (handheld AR)
     pub view_type: openxr::ViewConfigurationType,
+
+    /// Secondary view configurations to enable if supported (`XR_MSFT_secondary_view_configuration`),
+    /// e.g. `SECONDARY_MONO_FIRST_PERSON_OBSERVER_MSFT` for mixed reality capture. Active views are
+    /// rendered by a pass of their own, see `XRSecondaryViewSurfaceCreated`
+    pub secondary_view_types: Vec<openxr::ViewConfigurationType>,
+
     pub hand_trackers: bool,
+
+    /// Initial motion range of tracked hands holding a controller (`XR_EXT_hand_joints_motion_range`),
+    /// see `XRDevice::set_hand_joints_motion_range`
+    pub hand_joints_motion_range: XRHandJointsMotionRange,
+
+    /// CPU performance level, set when the session begins (`XR_EXT_performance_settings`)
+    pub cpu_performance_level: Option<XRPerformanceLevel>,
+
+    /// GPU performance level, set when the session begins (`XR_EXT_performance_settings`)
+    pub gpu_performance_level: Option<XRPerformanceLevel>,
+
+    /// Layout of the views in the swapchain. Selected based on runtime support if not set
+    pub stereo_mode: Option<XRStereoMode>,
+
+    /// Pausing of the app while the session is not focused
+    pub pause_behavior: XRPauseBehavior,
+
+    /// Display refresh rate to request when the session begins, in Hz (`XR_FB_display_refresh_rate`,
+    /// e.g. Quest). Ignored if the display does not support the rate
+    pub display_refresh_rate: Option<f32>,
+
+    /// Usages of the swapchain images in addition to `RENDER_ATTACHMENT`, e.g. `SAMPLED` for
+    /// postprocessing or mirror blits. `COPY_SRC` is required for capturing views. `COPY_DST` is
+    /// added for stereo modes other than multiview, as their views are copied into the images
+    pub swapchain_usage: wgpu::TextureUsage,
+
+    /// Format of the depth texture of the XR render targets: `Depth32Float`, `Depth24Plus` or
+    /// `Depth24PlusStencil8`. Pipelines drawing into the main pass must use the same format, bevy
+    /// pipelines use `Depth32Float`. The depth texture is not submitted to the runtime
+    pub depth_format: TextureFormat,
+
+    /// Largest render scale (`XRRenderScale`) relative to the recommended view size, e.g. `1.5` for
+    /// extra clarity in apps not limited by the GPU. The swapchain images are created this much
+    /// larger, limited by the max image size of the runtime, and the compositor downsamples the
+    /// submitted image rects. The render scale starts at this value
+    pub supersampling: f32,
+
+    /// Run the session without rendering, e.g. for CI against Monado: frames are waited for, and
+    /// events, input and poses processed as usual, but no swapchain is created, nothing is rendered
+    /// and frames are ended without layers (`XrFrameStatus::Skip`). The session still has the
+    /// Vulkan graphics binding. `OpenXRPlugin` enables `XR_MND_headless` if the runtime supports
+    /// it, so that the runtime does not need a display
+    pub headless: bool,
+
+    /// Longest wait for the compositor to release a swapchain image (`xrWaitSwapchainImage`). On
+    /// timeout the frame is skipped (`XRFrameSkipReason::ImageWaitTimedOut`), and the wait is
+    /// retried in the next frame
+    pub swapchain_image_timeout: Duration,
 }
 
 impl Default for XrOptions {
@@ -69,19 +316,43 @@ impl Default for XrOptions {
 
         Self {
             view_type: openxr::ViewConfigurationType::PRIMARY_STEREO,
+            secondary_view_types: Vec::new(),
             hand_trackers,
+            hand_joints_motion_range: XRHandJointsMotionRange::default(),
+            cpu_performance_level: None,
+            gpu_performance_level: None,
+            stereo_mode: None,
+            pause_behavior: XRPauseBehavior::default(),
+            display_refresh_rate: Some(90.),
+            swapchain_usage: wgpu::TextureUsage::COPY_SRC,
+            depth_format: TextureFormat::Depth32Float,
+            supersampling: 1.,
+            headless: false,
+            swapchain_image_timeout: Duration::from_millis(100),
         }
     }
 }
 
 // TODO: proposal to rename into `XRInstance`
 pub struct OpenXRStruct {
-    event_storage: EventDataBufferHolder,
-    session_state: XRState,
-    previous_frame_state: XRState,
-    pub handles: wgpu::OpenXRHandles,
+    event_poller: XREventPoller,
+    runtime_errors: Vec<XRRuntimeError>,
+    performance_notifications: Vec<XRPerformanceNotification>,
+    visibility_mask_changes: Vec<u32>,
+
+    /// Interaction profiles changed since the last `take_interaction_profile_changed`
+    interaction_profile_changed: bool,
+    session_state: XrSessionState,
+
+    /// Exit was requested while the session was not running, and can't be requested from the runtime
+    exit_requested: bool,
+    pub(crate) handles: wgpu::OpenXRHandles,
+    pub(crate) graphics: Arc<dyn XrGraphicsBackend>,
     pub instance: openxr::Instance,
     pub options: XrOptions,
+
+    /// Secondary view configurations enabled at session begin
+    pub(crate) secondary_view_types: Vec<openxr::ViewConfigurationType>,
 }
 
 impl std::fmt::Debug for OpenXRStruct {
@@ -94,180 +365,267 @@ impl OpenXRStruct {
     pub fn new(
         instance: openxr::Instance,
         handles: wgpu::OpenXRHandles,
+        graphics: Arc<dyn XrGraphicsBackend>,
         options: XrOptions,
     ) -> Self {
         OpenXRStruct {
-            event_storage: EventDataBufferHolder(openxr::EventDataBuffer::new()),
-            session_state: XRState::Paused,
-            previous_frame_state: XRState::Paused,
+            event_poller: XREventPoller::new(instance.clone()),
+            runtime_errors: Vec::new(),
+            performance_notifications: Vec::new(),
+            visibility_mask_changes: Vec::new(),
+            interaction_profile_changed: false,
+            secondary_view_types: secondary_view::supported_secondary_view_types(
+                &instance,
+                handles.system,
+                &options.secondary_view_types,
+            ),
+            session_state: XrSessionState::Idle,
+            exit_requested: false,
             instance,
             handles,
+            graphics,
             options,
         }
     }
 
-    fn change_state(&mut self, state: XRState, state_flag: &mut bool) -> bool {
+    fn change_state(&mut self, state: XrSessionState, changed_states: &mut Vec<XrSessionState>) {
         if self.session_state != state {
-            self.previous_frame_state = self.session_state;
             self.session_state = state;
-            *state_flag = true;
-            true
-        } else {
-            false
-        }
-    }
-
-    fn get_changed_state(&self, state_flag: &bool) -> Option<XRState> {
-        if *state_flag {
-            Some(self.session_state)
-        } else {
-            None
+            changed_states.push(state);
         }
     }
 
-    pub fn handle_openxr_events(&mut self) -> Option<XRState> {
-        let mut state_changed = false;
+    /// Polls OpenXR events, and returns the session states entered since the previous call, in order
+    pub fn handle_openxr_events(&mut self) -> Vec<XrSessionState> {
+        let mut changed_states = Vec::new();
 
-        while let Some(event) = self.instance.poll_event(&mut self.event_storage.0).unwrap() {
+        while let Some(event) = self.event_poller.try_recv() {
             match event {
-                openxr::Event::SessionStateChanged(e) => {
-                    println!("entered state {:?}", e.state());
+                OpenXREvent::SessionStateChanged(state) => {
+                    info!(target: "bevy_openxr::session", state = ?state, "Session state changed");
 
-                    match e.state() {
+                    match state {
                         // XR Docs: The application is ready to call xrBeginSession and sync its frame loop with the runtime.
                         openxr::SessionState::READY => {
-                            // if on oculus, set refresh rate
-                            if let Some(display_refresh_rate_fb) =
-                                self.instance.exts().fb_display_refresh_rate
-                            {
-                                let mut rate: f32 = 0.0;
-
-                                unsafe {
-                                    (display_refresh_rate_fb.get_display_refresh_rate)(
-                                        self.handles.session.as_raw(),
-                                        &mut rate,
-                                    )
-                                };
-
-                                println!("Current refresh rate: {:?}", rate);
-
-                                let request_refresh_rate = 90.;
-
-                                let ret = unsafe {
-                                    (display_refresh_rate_fb.request_display_refresh_rate)(
-                                        self.handles.session.as_raw(),
-                                        request_refresh_rate,
-                                    )
-                                };
-
-                                println!(
-                                    "Requested refresh rate change to {} - result: {:?}",
-                                    request_refresh_rate, ret
-                                );
-                            }
+                            self.change_state(XrSessionState::Ready, &mut changed_states);
+
+                            self.apply_display_refresh_rate();
+                            self.apply_performance_levels();
 
-                            self.handles.session.begin(self.options.view_type).unwrap();
-                            self.change_state(XRState::Running, &mut state_changed);
+                            match self.begin_session() {
+                                Ok(_) => {
+                                    self.change_state(XrSessionState::Running, &mut changed_states);
+                                }
+                                Err(e) => self.report_error("xrBeginSession", e),
+                            }
                         }
                         // XR Docs: The application should exit its frame loop and call xrEndSession.
                         openxr::SessionState::STOPPING => {
-                            self.handles.session.end().unwrap();
+                            if let Err(e) = self.handles.session.end() {
+                                self.report_error("xrEndSession", e.into());
+                            }
                             // TODO500: FIXME add a graceful cleanup of all OpenXR resources here
-                            self.change_state(XRState::Paused, &mut state_changed);
+                            self.change_state(XrSessionState::Stopping, &mut changed_states);
                         }
                         // XR Docs:
                         // EXITING: The application should end its XR experience and not automatically restart it.
                         // LOSS_PENDING: The session is in the process of being lost. The application should destroy the current session and can optionally recreate it.
                         openxr::SessionState::EXITING | openxr::SessionState::LOSS_PENDING => {
-                            self.change_state(XRState::Exiting, &mut state_changed);
-                            return self.get_changed_state(&state_changed);
+                            self.change_state(XrSessionState::Exiting, &mut changed_states);
+                            return changed_states;
                         }
                         // XR Docs: The application has synced its frame loop with the runtime and is visible to the user but cannot receive XR input.
                         openxr::SessionState::VISIBLE => {
-                            self.change_state(XRState::Running, &mut state_changed);
+                            self.change_state(XrSessionState::Visible, &mut changed_states);
                         }
                         // XR Docs: The application has synced its frame loop with the runtime, is visible to the user and can receive XR input.
                         openxr::SessionState::FOCUSED => {
-                            self.change_state(XRState::RunningFocused, &mut state_changed);
+                            self.change_state(XrSessionState::Focused, &mut changed_states);
                         }
                         // XR Docs: The initial state after calling xrCreateSession or returned to after calling xrEndSession.
                         openxr::SessionState::IDLE => {
-                            // FIXME is this handling ok?
-                            self.change_state(XRState::Paused, &mut state_changed);
+                            self.change_state(XrSessionState::Idle, &mut changed_states);
                         }
+                        // XR Docs: The application has synced its frame loop with the runtime but is not visible to the user.
                         openxr::SessionState::SYNCHRONIZED => {
-                            self.change_state(XRState::Running, &mut state_changed);
+                            self.change_state(XrSessionState::Running, &mut changed_states);
                         }
                         _ => {}
                     }
                 }
-                openxr::Event::InstanceLossPending(_) => {
-                    self.change_state(XRState::Exiting, &mut state_changed);
-                    return self.get_changed_state(&state_changed);
+                OpenXREvent::InstanceLossPending => {
+                    self.change_state(XrSessionState::Exiting, &mut changed_states);
+                    return changed_states;
                 }
-                openxr::Event::EventsLost(e) => {
-                    println!("lost {} events", e.lost_event_count());
+                OpenXREvent::EventsLost(lost_event_count) => {
+                    warn!(
+                        target: "bevy_openxr::session",
+                        lost_event_count,
+                        "OpenXR events lost"
+                    );
                 }
-                openxr::Event::ReferenceSpaceChangePending(reference_space) => {
-                    println!(
-                        "OpenXR: Event: ReferenceSpaceChangePending {:?}",
-                        reference_space.reference_space_type()
+                OpenXREvent::ReferenceSpaceChangePending(reference_space_type) => {
+                    debug!(
+                        target: "bevy_openxr::session",
+                        reference_space_type = ?reference_space_type,
+                        "Event: ReferenceSpaceChangePending"
                     );
                 }
-                openxr::Event::PerfSettingsEXT(_) => {
-                    println!("OpenXR: Event: PerfSettingsEXT");
+                OpenXREvent::PerfSettingsEXT(notification) => {
+                    info!(
+                        target: "bevy_openxr::session",
+                        domain = ?notification.domain,
+                        sub_domain = ?notification.sub_domain,
+                        from_level = ?notification.from_level,
+                        to_level = ?notification.to_level,
+                        "Performance notification"
+                    );
+                    self.performance_notifications.push(notification);
                 }
-                openxr::Event::VisibilityMaskChangedKHR(_) => {
-                    println!("OpenXR: Event: VisibilityMaskChangedKHR");
+                OpenXREvent::VisibilityMaskChangedKHR(view_index) => {
+                    debug!(target: "bevy_openxr::session", view_index, "Event: VisibilityMaskChangedKHR");
+                    self.visibility_mask_changes.push(view_index);
                 }
-                openxr::Event::InteractionProfileChanged(_) => {
-                    println!("OpenXR: Event: InteractionProfileChanged");
+                OpenXREvent::InteractionProfileChanged => {
+                    debug!(target: "bevy_openxr::session", "Event: InteractionProfileChanged");
+                    self.interaction_profile_changed = true;
                 }
-                openxr::Event::MainSessionVisibilityChangedEXTX(_) => {
-                    println!("OpenXR: Event: MainSessionVisibilityChangedEXTX");
+                OpenXREvent::MainSessionVisibilityChangedEXTX => {
+                    debug!(target: "bevy_openxr::session", "Event: MainSessionVisibilityChangedEXTX");
                 }
-                _ => {
-                    println!("OpenXR: Event: unknown")
+                OpenXREvent::Unknown => {
+                    debug!(target: "bevy_openxr::session", "Event: unknown")
                 }
             }
         }
 
-        match self.session_state {
-            XRState::Paused => std::thread::sleep(std::time::Duration::from_millis(100)),
-            _ => (),
+        // the runtime ends running sessions after `xrRequestExitSession`, others exit directly
+        if self.exit_requested && !self.is_running() {
+            self.change_state(XrSessionState::Exiting, &mut changed_states);
         }
 
-        self.get_changed_state(&state_changed)
+        changed_states
     }
 
-    pub fn is_running(&self) -> bool {
-        self.session_state == XRState::Running || self.session_state == XRState::RunningFocused
+    /// Requests `XrOptions::display_refresh_rate`, if supported by the display (`XR_FB_display_refresh_rate`)
+    fn apply_display_refresh_rate(&mut self) {
+        let requested = match self.options.display_refresh_rate {
+            Some(requested) => requested,
+            None => return,
+        };
+
+        let display_refresh_rate = match DisplayRefreshRateFB::new(&self.instance) {
+            Some(display_refresh_rate) => display_refresh_rate,
+            None => {
+                debug!(target: "bevy_openxr::session", "XR_FB_display_refresh_rate not supported, display refresh rate not set");
+                return;
+            }
+        };
+
+        let session = self.handles.session.as_raw();
+        match display_refresh_rate.get(session) {
+            Ok(rate) => {
+                debug!(target: "bevy_openxr::session", rate, "Current display refresh rate")
+            }
+            Err(e) => self.report_error("xrGetDisplayRefreshRateFB", e),
+        }
+
+        match display_refresh_rate.enumerate(session) {
+            Ok(rates) if !rates.contains(&requested) => {
+                warn!(target: "bevy_openxr::session", requested, ?rates, "Display refresh rate not supported by the display");
+                return;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                self.report_error("xrEnumerateDisplayRefreshRatesFB", e);
+                return;
+            }
+        }
+
+        match display_refresh_rate.request(session, requested) {
+            Ok(()) => {
+                info!(target: "bevy_openxr::session", rate = requested, "Requested display refresh rate change")
+            }
+            Err(e) => self.report_error("xrRequestDisplayRefreshRateFB", e),
+        }
     }
-}
 
-pub struct EventDataBufferHolder(openxr::EventDataBuffer);
+    fn apply_performance_levels(&mut self) {
+        let levels = [
+            (XRPerformanceDomain::Cpu, self.options.cpu_performance_level),
+            (XRPerformanceDomain::Gpu, self.options.gpu_performance_level),
+        ];
+
+        for (domain, level) in levels.iter() {
+            let level = match level {
+                Some(level) => *level,
+                None => continue,
+            };
+
+            match performance::set_performance_level(
+                &self.instance,
+                self.handles.session.as_raw(),
+                *domain,
+                level,
+            ) {
+                Ok(true) => {
+                    info!(target: "bevy_openxr::session", ?domain, ?level, "Performance level set")
+                }
+                Ok(false) => {
+                    warn!(target: "bevy_openxr::session", "XR_EXT_performance_settings not supported, performance levels not set");
+                    return;
+                }
+                Err(e) => self.report_error("xrPerfSettingsSetPerformanceLevelEXT", e),
+            }
+        }
+    }
 
-// FIXME FIXME FIXME UB AND BAD THINGS CAN/WILL HAPPEN. Required by EventDataBuffer
-// read openxr docs about whether EventDataBuffer is thread-safe
-// or move to resourcesmut?
-// FIXME or process events in own thread?
-unsafe impl Sync for EventDataBufferHolder {}
-unsafe impl Send for EventDataBufferHolder {}
+    fn begin_session(&mut self) -> Result<(), XrError> {
+        if self.secondary_view_types.is_empty() {
+            self.handles.session.begin(self.options.view_type)?;
+            return Ok(());
+        }
 
-fn xr_event_debug(mut state_events: EventReader<XRState>) {
-    for event in state_events.iter() {
-        println!("#STATE EVENT: {:#?}", event);
+        secondary_view::begin_session_with_secondary_views(
+            &self.instance,
+            &self.handles.session,
+            self.options.view_type,
+            &self.secondary_view_types,
+        )
     }
-}
 
-#[derive(Debug)]
-pub enum Error {
-    XR(openxr::sys::Result),
-}
+    pub(crate) fn report_error(&mut self, context: &'static str, error: XrError) {
+        warn!(target: "bevy_openxr::session", context, error = %error, "OpenXR runtime error");
+        self.runtime_errors.push(XRRuntimeError { context, error });
+    }
+
+    pub(crate) fn drain_errors(&mut self) -> Vec<XRRuntimeError> {
+        self.runtime_errors.drain(..).collect()
+    }
+
+    pub(crate) fn drain_performance_notifications(&mut self) -> Vec<XRPerformanceNotification> {
+        self.performance_notifications.drain(..).collect()
+    }
+
+    pub(crate) fn take_interaction_profile_changed(&mut self) -> bool {
+        std::mem::take(&mut self.interaction_profile_changed)
+    }
 
-impl From<openxr::sys::Result> for Error {
-    fn from(e: openxr::sys::Result) -> Self {
-        Error::XR(e)
+    pub(crate) fn drain_visibility_mask_changes(&mut self) -> Vec<u32> {
+        self.visibility_mask_changes.drain(..).collect()
+    }
+
+    pub fn session_state(&self) -> XrSessionState {
+        self.session_state
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.session_state.is_running()
+    }
+
+    /// Session is exiting or lost, and frames must no longer be waited for or submitted
+    pub fn is_exiting(&self) -> bool {
+        self.session_state == XrSessionState::Exiting
     }
 }
 
@@ -276,4 +634,33 @@ pub struct XRConfigurationState {
     pub texture_view_ids: Option<Vec<TextureId>>,
     pub next_swap_chain_index: usize,
     pub last_view_surface: Option<XRViewSurfaceCreated>,
+
+    /// Render targets of the secondary views, by index of `XrOptions::secondary_view_types` enabled
+    /// by the runtime
+    pub secondary_views: Vec<XRSecondaryViewTarget>,
+}
+
+impl XRConfigurationState {
+    /// Target of the secondary view, added if missing
+    pub fn secondary_view_mut(&mut self, index: usize) -> &mut XRSecondaryViewTarget {
+        if self.secondary_views.len() <= index {
+            self.secondary_views
+                .resize_with(index + 1, XRSecondaryViewTarget::default);
+        }
+
+        &mut self.secondary_views[index]
+    }
+}
+
+/// Swapchain images of a secondary view, rendered by the render graph nodes of the view
+#[derive(Debug, Clone, Default)]
+pub struct XRSecondaryViewTarget {
+    pub surface: Option<XRSecondaryViewSurfaceCreated>,
+    pub texture_view_ids: Option<Vec<TextureId>>,
+
+    /// Image acquired for this frame, `None` if the view is not rendered in this frame
+    pub next_image_index: Option<usize>,
+
+    /// Render graph nodes of the view have been added, so its images are acquired and submitted
+    pub rendered: bool,
 }
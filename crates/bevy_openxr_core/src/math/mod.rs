@@ -1,2 +1,4 @@
+mod pose;
 mod view_transform;
+pub use pose::*;
 pub use view_transform::*;
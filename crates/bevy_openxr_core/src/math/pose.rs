@@ -0,0 +1,48 @@
+use bevy::math::{Quat, Vec3};
+use bevy::transform::components::Transform;
+
+/// Converts an OpenXR pose into `Transform` (unit scale)
+pub fn pose_to_transform(pose: &openxr::Posef) -> Transform {
+    let pos = &pose.position;
+    let ori = &pose.orientation;
+
+    let mut transform = Transform::from_translation(Vec3::new(pos.x, pos.y, pos.z));
+    transform.rotation = Quat::from_xyzw(ori.x, ori.y, ori.z, ori.w);
+    transform
+}
+
+/// Converts `Transform` into an OpenXR pose. Scale is ignored
+pub fn transform_to_pose(transform: &Transform) -> openxr::Posef {
+    let pos = transform.translation;
+    let ori = transform.rotation;
+
+    openxr::Posef {
+        orientation: openxr::Quaternionf {
+            x: ori.x,
+            y: ori.y,
+            z: ori.z,
+            w: ori.w,
+        },
+        position: openxr::Vector3f {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pose_roundtrip() {
+        let mut transform = Transform::from_translation(Vec3::new(0.5, 1.6, -2.));
+        transform.rotation = Quat::from_rotation_y(0.7);
+
+        let result = pose_to_transform(&transform_to_pose(&transform));
+
+        assert_eq!(result.translation, transform.translation);
+        assert_eq!(result.rotation, transform.rotation);
+    }
+}
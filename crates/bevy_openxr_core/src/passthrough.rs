@@ -0,0 +1,151 @@
+use std::ptr;
+
+use bevy::utils::tracing::info;
+use openxr::sys;
+
+use crate::XrError;
+
+/// Passthrough feature and reconstruction layer (`XR_FB_passthrough`). Composited under the projection layer
+pub(crate) struct FBPassthrough {
+    instance: openxr::Instance,
+    passthrough: sys::PassthroughFB,
+    layer: sys::PassthroughLayerFB,
+    running: bool,
+    opacity: f32,
+}
+
+impl FBPassthrough {
+    /// Creates the passthrough feature and layer, in paused state. `None` if extension is not enabled
+    pub(crate) fn new(
+        instance: &openxr::Instance,
+        session: sys::Session,
+    ) -> Option<Result<Self, XrError>> {
+        let fp = instance.exts().fb_passthrough.as_ref()?;
+
+        let create_info = sys::PassthroughCreateInfoFB {
+            ty: sys::PassthroughCreateInfoFB::TYPE,
+            next: ptr::null(),
+            flags: sys::PassthroughFlagsFB::EMPTY,
+        };
+
+        let mut passthrough = sys::PassthroughFB::NULL;
+        let ret = unsafe { (fp.create_passthrough)(session, &create_info, &mut passthrough) };
+        if ret.into_raw() < 0 {
+            return Some(Err(XrError::XR(ret)));
+        }
+
+        let layer_create_info = sys::PassthroughLayerCreateInfoFB {
+            ty: sys::PassthroughLayerCreateInfoFB::TYPE,
+            next: ptr::null(),
+            passthrough,
+            flags: sys::PassthroughFlagsFB::EMPTY,
+            purpose: sys::PassthroughLayerPurposeFB::RECONSTRUCTION,
+        };
+
+        let mut layer = sys::PassthroughLayerFB::NULL;
+        let ret = unsafe { (fp.create_passthrough_layer)(session, &layer_create_info, &mut layer) };
+        if ret.into_raw() < 0 {
+            unsafe { (fp.destroy_passthrough)(passthrough) };
+            return Some(Err(XrError::XR(ret)));
+        }
+
+        info!(target: "bevy_openxr::session", "Passthrough layer created");
+
+        Some(Ok(FBPassthrough {
+            instance: instance.clone(),
+            passthrough,
+            layer,
+            running: false,
+            opacity: 1.,
+        }))
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Starts or pauses both the passthrough feature and the layer
+    pub(crate) fn set_running(&mut self, running: bool) -> Result<(), XrError> {
+        if self.running == running {
+            return Ok(());
+        }
+
+        let fp = self.fp();
+        let ret = unsafe {
+            match running {
+                true => {
+                    let ret = (fp.passthrough_start)(self.passthrough);
+                    if ret.into_raw() < 0 {
+                        ret
+                    } else {
+                        (fp.passthrough_layer_resume)(self.layer)
+                    }
+                }
+                false => {
+                    (fp.passthrough_layer_pause)(self.layer);
+                    (fp.passthrough_pause)(self.passthrough)
+                }
+            }
+        };
+
+        if ret.into_raw() < 0 {
+            return Err(XrError::XR(ret));
+        }
+
+        self.running = running;
+        Ok(())
+    }
+
+    /// Sets the texture opacity factor of the layer, in range 0..=1
+    pub(crate) fn set_opacity(&mut self, opacity: f32) -> Result<(), XrError> {
+        if (self.opacity - opacity).abs() < f32::EPSILON {
+            return Ok(());
+        }
+
+        let style = sys::PassthroughStyleFB {
+            ty: sys::PassthroughStyleFB::TYPE,
+            next: ptr::null(),
+            texture_opacity_factor: opacity.max(0.).min(1.),
+            edge_color: sys::Color4f {
+                r: 0.,
+                g: 0.,
+                b: 0.,
+                a: 0.,
+            },
+        };
+
+        let ret = unsafe { (self.fp().passthrough_layer_set_style)(self.layer, &style) };
+        if ret.into_raw() < 0 {
+            return Err(XrError::XR(ret));
+        }
+
+        self.opacity = opacity;
+        Ok(())
+    }
+
+    /// Composition layer to be submitted before (under) the projection layer
+    pub(crate) fn composition_layer(&self) -> sys::CompositionLayerPassthroughFB {
+        sys::CompositionLayerPassthroughFB {
+            ty: sys::CompositionLayerPassthroughFB::TYPE,
+            next: ptr::null(),
+            flags: sys::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+            space: sys::Space::NULL,
+            layer_handle: self.layer,
+        }
+    }
+
+    fn fp(&self) -> &openxr::raw::PassthroughFB {
+        // existence checked at construction
+        self.instance.exts().fb_passthrough.as_ref().unwrap()
+    }
+}
+
+impl Drop for FBPassthrough {
+    fn drop(&mut self) {
+        let fp = self.fp();
+        unsafe {
+            (fp.destroy_passthrough_layer)(self.layer);
+            (fp.destroy_passthrough)(self.passthrough);
+        }
+    }
+}
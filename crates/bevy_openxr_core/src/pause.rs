@@ -0,0 +1,168 @@
+use bevy::core::Time;
+use bevy::ecs::{
+    schedule::ShouldRun,
+    system::{Res, ResMut},
+};
+use bevy::utils::Duration;
+
+use crate::event::XrSessionState;
+
+/// What happens while the session is not focused, e.g. the headset is taken off or the system menu
+/// is open. Set with `XrOptions::pause_behavior`
+#[derive(Debug, Clone)]
+pub struct XRPauseBehavior {
+    /// Pause also when the session is visible but not focused (`XrSessionState::Visible`), not only
+    /// when it is hidden
+    pub pause_when_unfocused: bool,
+
+    /// Skip `CoreStage::Update` while paused, so that gameplay systems added with `add_system` do not run.
+    /// Frame loop and rendering continue, as required by the runtime
+    pub skip_update_stage: bool,
+
+    /// Scale of `XRSimulationTime` while paused, `0.` stops it and e.g. `0.1` runs it in slow motion
+    pub paused_time_scale: f32,
+
+    /// Mute sounds played through bevy `Audio` while paused. Requires the `audio` feature of
+    /// bevy_openxr, which plays the sounds
+    pub mute_audio: bool,
+}
+
+impl Default for XRPauseBehavior {
+    fn default() -> Self {
+        XRPauseBehavior {
+            pause_when_unfocused: true,
+            skip_update_stage: false,
+            paused_time_scale: 0.,
+            mute_audio: false,
+        }
+    }
+}
+
+impl XRPauseBehavior {
+    /// Whether the app is paused in session `state`
+    pub(crate) fn is_paused(&self, state: XrSessionState) -> bool {
+        match state {
+            XrSessionState::Focused => false,
+            XrSessionState::Visible => self.pause_when_unfocused,
+            _ => true,
+        }
+    }
+}
+
+/// Whether the app is paused according to `XRPauseBehavior`. Changes are also sent as `XRPauseChanged` events,
+/// e.g. to show a paused state in the world
+#[derive(Debug, Clone)]
+pub struct XRPauseState {
+    pub paused: bool,
+}
+
+impl Default for XRPauseState {
+    fn default() -> Self {
+        // session is not focused before it has started
+        XRPauseState { paused: true }
+    }
+}
+
+/// Time of the simulation: bevy `Time`, scaled by `XRPauseBehavior::paused_time_scale` while
+/// paused. Gameplay systems read it instead of `Time` to pause with the session. Updated at
+/// `CoreStage::PreUpdate`
+#[derive(Debug, Clone)]
+pub struct XRSimulationTime {
+    delta: Duration,
+    elapsed: Duration,
+    scale: f32,
+}
+
+impl Default for XRSimulationTime {
+    fn default() -> Self {
+        XRSimulationTime {
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            scale: 1.,
+        }
+    }
+}
+
+impl XRSimulationTime {
+    /// Scaled time elapsed since the previous update
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn delta_seconds_f64(&self) -> f64 {
+        self.delta.as_secs_f64()
+    }
+
+    /// Scaled time elapsed since startup
+    pub fn seconds_since_startup(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    /// Scale of the latest update, `1.` while not paused
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    fn update(&mut self, delta: Duration, scale: f32) {
+        self.scale = scale;
+        self.delta = delta.mul_f32(scale);
+        self.elapsed += self.delta;
+    }
+}
+
+pub(crate) fn simulation_time_system(
+    time: Res<Time>,
+    behavior: Res<XRPauseBehavior>,
+    pause_state: Res<XRPauseState>,
+    mut simulation_time: ResMut<XRSimulationTime>,
+) {
+    let scale = match pause_state.paused {
+        true => behavior.paused_time_scale.max(0.),
+        false => 1.,
+    };
+    simulation_time.update(time.delta(), scale);
+}
+
+pub(crate) fn xr_not_paused_criteria(pause_state: Res<XRPauseState>) -> ShouldRun {
+    if pause_state.paused {
+        ShouldRun::No
+    } else {
+        ShouldRun::Yes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_behavior() {
+        let behavior = XRPauseBehavior::default();
+        assert!(!behavior.is_paused(XrSessionState::Focused));
+        assert!(behavior.is_paused(XrSessionState::Visible));
+        assert!(behavior.is_paused(XrSessionState::Running));
+
+        let behavior = XRPauseBehavior {
+            pause_when_unfocused: false,
+            ..Default::default()
+        };
+        assert!(!behavior.is_paused(XrSessionState::Visible));
+        assert!(behavior.is_paused(XrSessionState::Idle));
+    }
+
+    #[test]
+    fn test_simulation_time() {
+        let mut time = XRSimulationTime::default();
+        time.update(Duration::from_millis(100), 1.);
+        time.update(Duration::from_millis(100), 0.);
+        assert_eq!(time.delta(), Duration::ZERO);
+
+        time.update(Duration::from_millis(100), 0.5);
+        assert_eq!(time.delta(), Duration::from_millis(50));
+        assert!((time.seconds_since_startup() - 0.15).abs() < 1e-9);
+    }
+}
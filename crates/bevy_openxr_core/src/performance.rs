@@ -0,0 +1,130 @@
+use openxr::sys;
+
+use crate::{event::XRPerformanceNotification, ext::PerformanceSettingsEXT, XrError};
+
+/// Processing domain for performance levels (`XR_EXT_performance_settings`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRPerformanceDomain {
+    Cpu,
+    Gpu,
+}
+
+impl XRPerformanceDomain {
+    fn to_sys(self) -> sys::PerfSettingsDomainEXT {
+        match self {
+            XRPerformanceDomain::Cpu => sys::PerfSettingsDomainEXT::CPU,
+            XRPerformanceDomain::Gpu => sys::PerfSettingsDomainEXT::GPU,
+        }
+    }
+
+    fn from_sys(domain: sys::PerfSettingsDomainEXT) -> Option<Self> {
+        match domain {
+            sys::PerfSettingsDomainEXT::CPU => Some(XRPerformanceDomain::Cpu),
+            sys::PerfSettingsDomainEXT::GPU => Some(XRPerformanceDomain::Gpu),
+            _ => None,
+        }
+    }
+}
+
+/// Part of the frame pipeline a performance notification concerns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRPerformanceSubDomain {
+    /// Runtime compositor work
+    Compositing,
+
+    /// Application rendering work
+    Rendering,
+
+    /// Device temperature
+    Thermal,
+}
+
+impl XRPerformanceSubDomain {
+    fn from_sys(sub_domain: sys::PerfSettingsSubDomainEXT) -> Option<Self> {
+        match sub_domain {
+            sys::PerfSettingsSubDomainEXT::COMPOSITING => Some(XRPerformanceSubDomain::Compositing),
+            sys::PerfSettingsSubDomainEXT::RENDERING => Some(XRPerformanceSubDomain::Rendering),
+            sys::PerfSettingsSubDomainEXT::THERMAL => Some(XRPerformanceSubDomain::Thermal),
+            _ => None,
+        }
+    }
+}
+
+/// Severity of a performance notification. On `Warning`, the app should lower its workload
+/// to avoid reaching `Impaired`, where the runtime is no longer able to keep the frame rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum XRPerformanceNotificationLevel {
+    Normal,
+    Warning,
+    Impaired,
+}
+
+impl XRPerformanceNotificationLevel {
+    fn from_sys(level: sys::PerfSettingsNotificationLevelEXT) -> Option<Self> {
+        match level {
+            sys::PerfSettingsNotificationLevelEXT::NORMAL => {
+                Some(XRPerformanceNotificationLevel::Normal)
+            }
+            sys::PerfSettingsNotificationLevelEXT::WARNING => {
+                Some(XRPerformanceNotificationLevel::Warning)
+            }
+            sys::PerfSettingsNotificationLevelEXT::IMPAIRED => {
+                Some(XRPerformanceNotificationLevel::Impaired)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl XRPerformanceNotification {
+    /// `None` for values unknown to this crate
+    pub(crate) fn from_sys(
+        domain: sys::PerfSettingsDomainEXT,
+        sub_domain: sys::PerfSettingsSubDomainEXT,
+        from_level: sys::PerfSettingsNotificationLevelEXT,
+        to_level: sys::PerfSettingsNotificationLevelEXT,
+    ) -> Option<Self> {
+        Some(XRPerformanceNotification {
+            domain: XRPerformanceDomain::from_sys(domain)?,
+            sub_domain: XRPerformanceSubDomain::from_sys(sub_domain)?,
+            from_level: XRPerformanceNotificationLevel::from_sys(from_level)?,
+            to_level: XRPerformanceNotificationLevel::from_sys(to_level)?,
+        })
+    }
+}
+
+/// Performance level hint for the runtime. On Quest, maps to CPU / GPU clock levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRPerformanceLevel {
+    PowerSavings,
+    SustainedLow,
+    SustainedHigh,
+    Boost,
+}
+
+impl XRPerformanceLevel {
+    fn to_sys(self) -> sys::PerfSettingsLevelEXT {
+        match self {
+            XRPerformanceLevel::PowerSavings => sys::PerfSettingsLevelEXT::POWER_SAVINGS,
+            XRPerformanceLevel::SustainedLow => sys::PerfSettingsLevelEXT::SUSTAINED_LOW,
+            XRPerformanceLevel::SustainedHigh => sys::PerfSettingsLevelEXT::SUSTAINED_HIGH,
+            XRPerformanceLevel::Boost => sys::PerfSettingsLevelEXT::BOOST,
+        }
+    }
+}
+
+/// `xrPerfSettingsSetPerformanceLevelEXT`. `Ok(false)` if the extension is not enabled
+pub(crate) fn set_performance_level(
+    instance: &openxr::Instance,
+    session: sys::Session,
+    domain: XRPerformanceDomain,
+    level: XRPerformanceLevel,
+) -> Result<bool, XrError> {
+    let performance_settings = match PerformanceSettingsEXT::new(instance) {
+        Some(performance_settings) => performance_settings,
+        None => return Ok(false),
+    };
+
+    performance_settings.set_performance_level(session, domain.to_sys(), level.to_sys())?;
+    Ok(true)
+}
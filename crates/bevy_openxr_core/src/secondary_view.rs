@@ -0,0 +1,399 @@
+use std::ptr;
+
+use bevy::utils::tracing::{debug, info, warn};
+use bevy::utils::Duration;
+use openxr::sys;
+
+use crate::{
+    error::cvt,
+    event::XRSecondaryView,
+    math::pose_to_transform,
+    swapchain::{import_images, Framebuffer},
+    OpenXRStruct, XrError,
+};
+
+/// Filters the requested secondary view configurations (`XR_MSFT_secondary_view_configuration`)
+/// to those supported by the runtime and system
+pub(crate) fn supported_secondary_view_types(
+    instance: &openxr::Instance,
+    system: openxr::SystemId,
+    requested: &[openxr::ViewConfigurationType],
+) -> Vec<openxr::ViewConfigurationType> {
+    if requested.is_empty() {
+        return Vec::new();
+    }
+
+    if instance.exts().msft_secondary_view_configuration.is_none() {
+        warn!(
+            target: "bevy_openxr::session",
+            "Secondary views requested, but XR_MSFT_secondary_view_configuration is not enabled"
+        );
+        return Vec::new();
+    }
+
+    let available = match instance.enumerate_view_configurations(system) {
+        Ok(available) => available,
+        Err(e) => {
+            warn!(target: "bevy_openxr::session", error = %e, "Could not enumerate view configurations");
+            return Vec::new();
+        }
+    };
+
+    let supported = requested
+        .iter()
+        .filter(|view_type| available.contains(view_type))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    info!(
+        target: "bevy_openxr::session",
+        requested = ?requested,
+        supported = ?supported,
+        "Secondary view configurations"
+    );
+
+    supported
+}
+
+/// `xrBeginSession` with `XrSecondaryViewConfigurationSessionBeginInfoMSFT` chained, so that the runtime
+/// may activate the secondary views. `openxr::Session::begin` does not support chaining, so call is made directly
+pub(crate) fn begin_session_with_secondary_views<G: openxr::Graphics>(
+    instance: &openxr::Instance,
+    session: &openxr::Session<G>,
+    primary: openxr::ViewConfigurationType,
+    secondary: &[openxr::ViewConfigurationType],
+) -> Result<(), XrError> {
+    let secondary_info = sys::SecondaryViewConfigurationSessionBeginInfoMSFT {
+        ty: sys::SecondaryViewConfigurationSessionBeginInfoMSFT::TYPE,
+        next: ptr::null(),
+        view_configuration_count: secondary.len() as u32,
+        enabled_view_configuration_types: secondary.as_ptr(),
+    };
+
+    let begin_info = sys::SessionBeginInfo {
+        ty: sys::SessionBeginInfo::TYPE,
+        next: &secondary_info as *const _ as *const _,
+        primary_view_configuration_type: primary,
+    };
+
+    let ret = unsafe { (instance.fp().begin_session)(session.as_raw(), &begin_info) };
+
+    if ret.into_raw() < 0 {
+        Err(XrError::XR(ret))
+    } else {
+        Ok(())
+    }
+}
+
+/// Secondary view configuration enabled at session begin. The runtime activates it per frame (e.g.
+/// while mixed reality capture is recording), and its swapchain is created on the first activation.
+/// The swapchain is recreated on a later activation if the recommended size has changed
+pub(crate) struct SecondaryView {
+    pub(crate) view_type: openxr::ViewConfigurationType,
+
+    /// Active in the frame being rendered, as reported by `xrWaitFrame`
+    pub(crate) active: bool,
+
+    /// Became active in the frame being rendered
+    pub(crate) activated: bool,
+
+    pub(crate) swapchain: Option<SecondarySwapchain>,
+
+    /// Swapchain creation failed, and is not retried
+    pub(crate) failed: bool,
+}
+
+impl SecondaryView {
+    pub(crate) fn new(view_type: openxr::ViewConfigurationType) -> Self {
+        SecondaryView {
+            view_type,
+            active: false,
+            activated: false,
+            swapchain: None,
+            failed: false,
+        }
+    }
+
+    /// Pose and field of view of the (first) view of the configuration, located for `time`
+    pub(crate) fn locate(
+        &self,
+        handles: &wgpu::OpenXRHandles,
+        time: openxr::Time,
+    ) -> Result<Option<openxr::View>, XrError> {
+        let (_, views) = handles
+            .session
+            .locate_views(self.view_type, time, &handles.space)?;
+
+        Ok(views.first().copied())
+    }
+
+    /// Drops the swapchain if the recommended size has changed since its creation (e.g. the capture
+    /// resolution was changed between recordings), so that it is created again at the new size
+    pub(crate) fn drop_resized_swapchain(&mut self, openxr_struct: &OpenXRStruct) {
+        let swapchain = match &self.swapchain {
+            Some(swapchain) => swapchain,
+            None => return,
+        };
+
+        let size = recommended_size(
+            &openxr_struct.instance,
+            openxr_struct.handles.system,
+            self.view_type,
+        );
+
+        match size {
+            Ok((width, height)) if (width, height) != (swapchain.width, swapchain.height) => {
+                info!(target: "bevy_openxr::swapchain", view_type = ?self.view_type, width, height, "Secondary view resized, recreating its swapchain");
+                self.swapchain = None;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                warn!(target: "bevy_openxr::swapchain", view_type = ?self.view_type, error = %e, "Could not get the recommended size of the secondary view");
+            }
+        }
+    }
+}
+
+/// Swapchain of a secondary view, rendered by a camera of its own. Secondary views (e.g. the first
+/// person observer) have a single view, so the images have a single layer
+pub(crate) struct SecondarySwapchain {
+    handle: openxr::Swapchain<openxr::Vulkan>,
+    buffers: Vec<Framebuffer>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) environment_blend_mode: openxr::EnvironmentBlendMode,
+
+    /// Image index acquired for the frame, and whether it has been waited for
+    acquired_image: Option<(usize, bool)>,
+}
+
+impl SecondarySwapchain {
+    /// Creates the swapchain with `XrSecondaryViewConfigurationSwapchainCreateInfoMSFT` chained, as
+    /// required for secondary views. `openxr::Session::create_swapchain` does not support chaining,
+    /// so the call is made directly
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        openxr_struct: &OpenXRStruct,
+        view_type: openxr::ViewConfigurationType,
+        vk_format: ash::vk::Format,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsage,
+        usage_flags: openxr::SwapchainUsageFlags,
+    ) -> Result<Self, XrError> {
+        let instance = &openxr_struct.instance;
+        let system = openxr_struct.handles.system;
+
+        let (width, height) = recommended_size(instance, system, view_type)?;
+
+        let environment_blend_mode = *instance
+            .enumerate_environment_blend_modes(system, view_type)?
+            .first()
+            .ok_or(XrError::NoEnvironmentBlendMode)?;
+
+        let secondary_info = sys::SecondaryViewConfigurationSwapchainCreateInfoMSFT {
+            ty: sys::SecondaryViewConfigurationSwapchainCreateInfoMSFT::TYPE,
+            next: ptr::null(),
+            view_configuration_type: view_type,
+        };
+
+        let create_info = sys::SwapchainCreateInfo {
+            ty: sys::SwapchainCreateInfo::TYPE,
+            next: &secondary_info as *const _ as *const _,
+            create_flags: openxr::SwapchainCreateFlags::EMPTY,
+            usage_flags,
+            format: vk_format.as_raw() as _,
+            sample_count: 1,
+            width,
+            height,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        };
+
+        let session = &openxr_struct.handles.session;
+        let mut raw_handle = sys::Swapchain::NULL;
+        cvt(unsafe {
+            (instance.fp().create_swapchain)(session.as_raw(), &create_info, &mut raw_handle)
+        })?;
+        let handle = unsafe { openxr::Swapchain::from_raw(session.clone(), raw_handle) };
+
+        let buffers = import_images(
+            openxr_struct.graphics.as_ref(),
+            device,
+            handle.enumerate_images()?,
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            format,
+            usage,
+        );
+
+        info!(target: "bevy_openxr::swapchain", ?view_type, width, height, "Secondary view swapchain configured");
+
+        Ok(SecondarySwapchain {
+            handle,
+            buffers,
+            width,
+            height,
+            environment_blend_mode,
+            acquired_image: None,
+        })
+    }
+
+    /// Texture views of the images, `None` after the first call
+    pub(crate) fn take_texture_views(&mut self) -> Option<Vec<wgpu::TextureView>> {
+        self.buffers
+            .iter_mut()
+            .map(|buffer| buffer.texture_view.take())
+            .collect()
+    }
+
+    /// Acquires and waits for the next image. `None` if the wait timed out, the image stays acquired
+    /// and is waited for again in the next frame
+    pub(crate) fn next_image_index(
+        &mut self,
+        wait_swapchain_image: sys::pfn::WaitSwapchainImage,
+        timeout: Duration,
+    ) -> Result<Option<usize>, XrError> {
+        let image_index = match self.acquired_image {
+            Some((image_index, true)) => return Ok(Some(image_index)),
+            Some((image_index, false)) => image_index,
+            None => self.handle.acquire_image()? as usize,
+        };
+        self.acquired_image = Some((image_index, false));
+
+        let info = sys::SwapchainImageWaitInfo {
+            ty: sys::SwapchainImageWaitInfo::TYPE,
+            next: ptr::null(),
+            timeout: openxr::Duration::from_nanos(timeout.as_nanos() as i64),
+        };
+
+        let result = unsafe { wait_swapchain_image(self.handle.as_raw(), &info) };
+        if result == sys::Result::TIMEOUT_EXPIRED {
+            debug!(target: "bevy_openxr::swapchain", "Secondary view image wait timed out");
+            return Ok(None);
+        }
+        cvt(result)?;
+
+        self.acquired_image = Some((image_index, true));
+        Ok(Some(image_index))
+    }
+
+    /// Releases the image rendered in this frame. `false` if no image was rendered
+    pub(crate) fn release_image(&mut self) -> Result<bool, XrError> {
+        match self.acquired_image {
+            Some((_, true)) => {
+                self.acquired_image = None;
+                self.handle.release_image()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> &openxr::Swapchain<openxr::Vulkan> {
+        &self.handle
+    }
+}
+
+/// Recommended image size of the (first) view of the secondary view configuration
+pub(crate) fn recommended_size(
+    instance: &openxr::Instance,
+    system: openxr::SystemId,
+    view_type: openxr::ViewConfigurationType,
+) -> Result<(u32, u32), XrError> {
+    let views = instance.enumerate_view_configuration_views(system, view_type)?;
+    let view = views.first().ok_or(XrError::NoViews)?;
+    if views.len() > 1 {
+        warn!(target: "bevy_openxr::swapchain", ?view_type, view_count = views.len(), "Rendering only the first view of the secondary view configuration");
+    }
+
+    Ok((
+        view.recommended_image_rect_width,
+        view.recommended_image_rect_height,
+    ))
+}
+
+/// `xrWaitFrame` with `XrSecondaryViewConfigurationFrameStateMSFT` chained, updating which secondary
+/// views are active in the frame. `openxr::FrameWaiter` does not support chaining, so the call is
+/// made directly
+pub(crate) fn wait_frame_with_secondary_views(
+    wait_frame: sys::pfn::WaitFrame,
+    session: sys::Session,
+    views: &mut [SecondaryView],
+) -> Result<openxr::FrameState, XrError> {
+    let mut states = views
+        .iter()
+        .map(|view| sys::SecondaryViewConfigurationStateMSFT {
+            ty: sys::SecondaryViewConfigurationStateMSFT::TYPE,
+            next: ptr::null_mut(),
+            view_configuration_type: view.view_type,
+            active: sys::FALSE,
+        })
+        .collect::<Vec<_>>();
+
+    let mut secondary_state = sys::SecondaryViewConfigurationFrameStateMSFT {
+        ty: sys::SecondaryViewConfigurationFrameStateMSFT::TYPE,
+        next: ptr::null_mut(),
+        view_configuration_count: states.len() as u32,
+        view_configuration_states: states.as_mut_ptr(),
+    };
+
+    let mut frame_state = sys::FrameState {
+        ty: sys::FrameState::TYPE,
+        next: &mut secondary_state as *mut _ as *mut _,
+        predicted_display_time: openxr::Time::from_nanos(0),
+        predicted_display_period: openxr::Duration::from_nanos(0),
+        should_render: sys::FALSE,
+    };
+
+    let wait_info = sys::FrameWaitInfo {
+        ty: sys::FrameWaitInfo::TYPE,
+        next: ptr::null(),
+    };
+
+    cvt(unsafe { wait_frame(session, &wait_info, &mut frame_state) })?;
+
+    for (view, state) in views.iter_mut().zip(states.iter()) {
+        let active = state.active.into();
+        view.activated = active && !view.active;
+        if view.active != active {
+            info!(target: "bevy_openxr::session", view_type = ?view.view_type, active, "Secondary view activation changed");
+            view.active = active;
+        }
+    }
+
+    Ok(openxr::FrameState {
+        predicted_display_time: frame_state.predicted_display_time,
+        predicted_display_period: frame_state.predicted_display_period,
+        should_render: frame_state.should_render.into(),
+    })
+}
+
+/// Poses and field of views of the active secondary views, located for `time`
+pub(crate) fn locate_secondary_views(
+    views: &[SecondaryView],
+    handles: &wgpu::OpenXRHandles,
+    time: openxr::Time,
+) -> Result<Vec<XRSecondaryView>, XrError> {
+    let mut located = Vec::new();
+
+    for (index, view) in views.iter().enumerate() {
+        if !view.active {
+            continue;
+        }
+
+        if let Some(located_view) = view.locate(handles, time)? {
+            located.push(XRSecondaryView {
+                index,
+                view_type: view.view_type,
+                transform: pose_to_transform(&located_view.pose),
+                fov: located_view.fov.into(),
+            });
+        }
+    }
+
+    Ok(located)
+}
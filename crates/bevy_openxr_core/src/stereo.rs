@@ -0,0 +1,115 @@
+/// How the views are laid out in the swapchain images, and rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XRStereoMode {
+    /// One texture array layer per view, all views rendered in a single pass (`GL_EXT_multiview`)
+    Multiview,
+
+    /// Views side by side in a single wide texture, rendered in a pass per view and copied into
+    /// their rects
+    DoubleWide,
+
+    /// One texture array layer per view, views rendered in sequential passes and copied into their
+    /// layers. For devices and drivers without multiview support
+    TwoPass,
+}
+
+impl XRStereoMode {
+    /// Modes to try, in order. Without a requested mode, multiview is preferred, falling back to
+    /// double-wide if the runtime does not support texture array swapchains
+    pub(crate) fn candidates(requested: Option<XRStereoMode>) -> Vec<XRStereoMode> {
+        match requested {
+            Some(mode) => vec![mode],
+            None => vec![XRStereoMode::Multiview, XRStereoMode::DoubleWide],
+        }
+    }
+
+    /// Swapchain image `(width, height, array layers)` for views of `width` x `height`
+    pub fn image_extent(&self, width: u32, height: u32, view_count: u32) -> (u32, u32, u32) {
+        match self {
+            XRStereoMode::Multiview | XRStereoMode::TwoPass => (width, height, view_count),
+            XRStereoMode::DoubleWide => (width * view_count, height, 1),
+        }
+    }
+
+    /// Location of the view in the swapchain image, `(x offset, array layer)`
+    pub(crate) fn view_location(&self, view_index: u32, width: u32) -> (u32, u32) {
+        match self {
+            XRStereoMode::Multiview | XRStereoMode::TwoPass => (0, view_index),
+            XRStereoMode::DoubleWide => (view_index * width, 0),
+        }
+    }
+
+    /// Rect rendered for the view in the swapchain image, for views `width` wide. The view of
+    /// `view_size` is scaled by `render_scale` and placed at the top-left of its area
+    pub fn view_rect(
+        &self,
+        view_index: u32,
+        width: u32,
+        view_size: (u32, u32),
+        render_scale: f32,
+    ) -> XRViewRect {
+        let (x, layer) = self.view_location(view_index, width);
+
+        XRViewRect {
+            x,
+            y: 0,
+            width: ((view_size.0 as f32 * render_scale) as u32).max(1),
+            height: ((view_size.1 as f32 * render_scale) as u32).max(1),
+            layer,
+        }
+    }
+}
+
+/// Pixel rect of a view in the swapchain image (and render targets of the same layout), i.e. the
+/// viewport of the view and the sub-image submitted to the compositor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XRViewRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+
+    /// Texture array layer
+    pub layer: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stereo_mode_layout() {
+        assert_eq!(
+            XRStereoMode::Multiview.image_extent(1000, 800, 2),
+            (1000, 800, 2)
+        );
+        assert_eq!(
+            XRStereoMode::DoubleWide.image_extent(1000, 800, 2),
+            (2000, 800, 1)
+        );
+
+        // PRIMARY_MONO
+        assert_eq!(
+            XRStereoMode::Multiview.image_extent(1000, 800, 1),
+            (1000, 800, 1)
+        );
+        assert_eq!(
+            XRStereoMode::DoubleWide.image_extent(1000, 800, 1),
+            (1000, 800, 1)
+        );
+
+        assert_eq!(XRStereoMode::TwoPass.view_location(1, 1000), (0, 1));
+        assert_eq!(XRStereoMode::DoubleWide.view_location(1, 1000), (1000, 0));
+
+        assert_eq!(
+            XRStereoMode::DoubleWide.view_rect(1, 1000, (900, 800), 0.5),
+            XRViewRect {
+                x: 1000,
+                y: 0,
+                width: 450,
+                height: 400,
+                layer: 0,
+            }
+        );
+    }
+}
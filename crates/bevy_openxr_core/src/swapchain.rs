@@ -1,26 +1,54 @@
-use bevy::math::{Quat, Vec3};
 use bevy::prelude::error;
+use bevy::render::texture::TextureFormat;
 use bevy::transform::components::Transform;
-use bevy::utils::tracing::{debug, warn};
+use bevy::utils::tracing::{debug, info, info_span, warn};
+use bevy::utils::{Duration, Instant};
 use openxr::{Time, View};
-use std::{fmt::Debug, num::NonZeroU32, sync::Arc};
+use std::{fmt::Debug, num::NonZeroU32, ptr, sync::Arc};
 use wgpu::OpenXRHandles;
 
 use crate::{
+    capture::{self, XRCapturedImage},
+    composition_layer::XRLayerOrder,
+    diagnostics::XRFrameTimings,
+    error::cvt,
+    event::{XRSecondaryView, XRSecondaryViewSurfaceCreated},
     hand_tracking::{HandPoseState, HandTrackers},
-    OpenXRStruct, XRState,
+    math::pose_to_transform,
+    secondary_view::{self, SecondarySwapchain, SecondaryView},
+    stereo::{XRStereoMode, XRViewRect},
+    OpenXRStruct, XREnvironmentBlendMode, XrError, XrFovf, XrFrameStatus, XrGraphicsBackend,
 };
 
 pub struct XRSwapchain {
-    /// OpenXR internal swapchain handle
-    sc_handle: openxr::Swapchain<openxr::Vulkan>,
+    /// OpenXR internal swapchain handle. `None` in headless sessions, which have no swapchain
+    sc_handle: Option<openxr::Swapchain<openxr::Vulkan>>,
 
     /// Swapchain Framebuffers. `XRSwapchainNode` will take ownership of the color buffer
     buffers: Vec<Framebuffer>,
 
-    /// Swapchain resolution
+    /// Resolution of a single view, and the number of views
     resolution: wgpu::Extent3d,
 
+    /// Recommended size of each view, rendered into the top-left of its area in the swapchain image
+    /// (scaled by the render scale)
+    view_sizes: Vec<(u32, u32)>,
+
+    /// Layout of the views in the swapchain images
+    stereo_mode: XRStereoMode,
+
+    /// Format of the swapchain images
+    format: wgpu::TextureFormat,
+
+    /// Format of the swapchain images, for bevy textures copied into them
+    render_format: TextureFormat,
+
+    /// Vulkan format of the swapchain images, for creating the secondary view swapchains
+    vk_format: ash::vk::Format,
+
+    /// Usages of the swapchain images
+    usage: wgpu::TextureUsage,
+
     /// Swapchain view configuration type
     view_configuration_type: openxr::ViewConfigurationType,
 
@@ -33,37 +61,116 @@ pub struct XRSwapchain {
     /// TODO: move this away, doesn't belong here
     hand_trackers: Option<HandTrackers>,
 
+    /// Timings of the most recent frame, read by `XRDiagnosticsPlugin`
+    timings: XRFrameTimings,
+
+    /// Scale of the recommended view sizes rendered and submitted to the compositor, see
+    /// `set_render_scale`
+    render_scale: f32,
+
+    /// Largest render scale fitting into the swapchain images, `XrOptions::supersampling` limited
+    /// by the max image size of the views
+    max_render_scale: f32,
+
+    /// Swapchain image index acquired for the next frame, but not necessarily waited yet
+    acquired_image: Option<usize>,
+
+    /// No swapchain is created, and frames are ended without layers (`XrOptions::headless`)
+    headless: bool,
+
+    /// `xrWaitSwapchainImage`, called directly as the `openxr` crate does not report timeouts
+    wait_swapchain_image: openxr::sys::pfn::WaitSwapchainImage,
+
+    /// Timeout of waiting for the acquired image (`XrOptions::swapchain_image_timeout`)
+    image_timeout: Duration,
+
     waited: bool,
-}
 
-const VIEW_COUNT: u32 = 2; // FIXME get from settings
+    /// Secondary view configurations enabled at session begin (`XrOptions::secondary_view_types`)
+    secondary_views: Vec<SecondaryView>,
+
+    /// `xrWaitFrame`, called directly to chain the secondary view states
+    wait_frame: openxr::sys::pfn::WaitFrame,
+}
 
 impl XRSwapchain {
-    pub fn new(device: Arc<wgpu::Device>, openxr_struct: &mut OpenXRStruct) -> Self {
-        let views = openxr_struct
-            .instance
-            .enumerate_view_configuration_views(
-                openxr_struct.handles.system,
-                openxr_struct.options.view_type,
-            )
-            .unwrap();
+    pub fn new(
+        device: Arc<wgpu::Device>,
+        openxr_struct: &mut OpenXRStruct,
+    ) -> Result<Self, XrError> {
+        let views = openxr_struct.instance.enumerate_view_configuration_views(
+            openxr_struct.handles.system,
+            openxr_struct.options.view_type,
+        )?;
+
+        debug!(target: "bevy_openxr::swapchain", "Enumerated OpenXR views: {:#?}", views);
+
+        if views.is_empty() {
+            return Err(XrError::NoViews);
+        }
 
-        assert_eq!(views.len(), VIEW_COUNT as usize);
-        assert_eq!(views[0], views[1]);
+        // Views are rendered into a single texture (array), so the image area of each view is sized
+        // by the largest view, scaled up for supersampling. Smaller views use the top-left of their area
+        let view_count = views.len() as u32;
+        let view_sizes = views
+            .iter()
+            .map(|view| {
+                (
+                    view.recommended_image_rect_width,
+                    view.recommended_image_rect_height,
+                )
+            })
+            .collect::<Vec<_>>();
 
-        println!("Enumerated OpenXR views: {:#?}", views);
+        let recommended_width = views
+            .iter()
+            .map(|view| view.recommended_image_rect_width)
+            .max()
+            .unwrap();
+        let recommended_height = views
+            .iter()
+            .map(|view| view.recommended_image_rect_height)
+            .max()
+            .unwrap();
+        let max_width = views
+            .iter()
+            .map(|view| view.max_image_rect_width)
+            .min()
+            .unwrap();
+        let max_height = views
+            .iter()
+            .map(|view| view.max_image_rect_height)
+            .min()
+            .unwrap();
 
+        let supersampling = openxr_struct.options.supersampling.max(1.);
         let resolution = wgpu::Extent3d {
-            width: views[0].recommended_image_rect_width,
-            height: views[0].recommended_image_rect_height,
-            depth_or_array_layers: 1,
+            width: ((recommended_width as f32 * supersampling) as u32)
+                .min(max_width)
+                .max(recommended_width),
+            height: ((recommended_height as f32 * supersampling) as u32)
+                .min(max_height)
+                .max(recommended_height),
+            depth_or_array_layers: view_count,
         };
 
+        let max_render_scale = (resolution.width as f32 / recommended_width as f32)
+            .min(resolution.height as f32 / recommended_height as f32);
+        if supersampling > 1. {
+            info!(
+                target: "bevy_openxr::swapchain",
+                supersampling,
+                max_render_scale,
+                width = resolution.width,
+                height = resolution.height,
+                "Supersampling"
+            );
+        }
+
         let swapchain_formats = openxr_struct
             .handles
             .session
-            .enumerate_swapchain_formats()
-            .unwrap();
+            .enumerate_swapchain_formats()?;
 
         let vk_swapchain_formats = swapchain_formats
             .iter()
@@ -83,300 +190,730 @@ impl XRSwapchain {
             })
             .collect::<Vec<_>>();
 
-        println!("OpenXR supported swapchain formats:");
         for (idx, (vk, hal, wgpu)) in vk_wgpu_formats.iter().enumerate() {
-            println!(
-                "   idx={}, vk={:?} gfx_hal={:?} wgpu={:?}",
-                idx, vk, hal, wgpu
+            debug!(
+                target: "bevy_openxr::swapchain",
+                idx,
+                vk = ?vk,
+                gfx_hal = ?hal,
+                wgpu = ?wgpu,
+                "OpenXR supported swapchain format"
             );
         }
 
+        // bevy textures are copied into the swapchain images, so bevy must have the format too
         let format = vk_wgpu_formats
             .iter()
             .enumerate()
-            .find(|(_, (_, hal, wgpu))| hal.is_some() && wgpu.is_some())
-            .map(|(idx, (vk, hal, wgpu))| (idx, vk, hal.unwrap(), wgpu.unwrap()));
+            .filter(|(_, (_, hal, _))| hal.is_some())
+            .find_map(|(idx, (vk, _, wgpu))| {
+                let wgpu = (*wgpu)?;
+                Some((idx, vk, wgpu, map_render_format(wgpu)?))
+            });
 
-        let (format_idx, vk_format, _hal_format, format) = match format {
+        let (format_idx, vk_format, format, render_format) = match format {
             Some(f) => f,
-            None => {
-                panic!(
-                    "OpenXR did not have any supported swapchain formats available. Can not continue"
-                );
-            }
+            None => return Err(XrError::NoSupportedSwapchainFormat),
         };
 
-        println!(
-            "Selected swapchain format: idx={} vk={:?} wgpu={:?}",
-            format_idx, vk_format, format
+        info!(
+            target: "bevy_openxr::swapchain",
+            idx = format_idx,
+            vk = ?vk_format,
+            wgpu = ?format,
+            "Selected swapchain format"
         );
 
-        let handle = openxr_struct
-            .handles
-            .session
-            .create_swapchain(&openxr::SwapchainCreateInfo {
-                create_flags: openxr::SwapchainCreateFlags::EMPTY,
-                usage_flags: openxr::SwapchainUsageFlags::COLOR_ATTACHMENT,
-                format: vk_format.as_raw() as _,
-                sample_count: 1,
-                width: resolution.width,
-                height: resolution.height,
-                face_count: 1,
-                array_size: VIEW_COUNT,
-                mip_count: 1,
-            })
-            .unwrap();
+        let headless = openxr_struct.options.headless;
+        let mut created = None;
+        let mut last_error = openxr::sys::Result::ERROR_VALIDATION_FAILURE;
+        for stereo_mode in XRStereoMode::candidates(openxr_struct.options.stereo_mode) {
+            let (width, height, array_size) =
+                stereo_mode.image_extent(resolution.width, resolution.height, view_count);
+
+            // views rendered in separate passes are copied into the swapchain images
+            let usage = match stereo_mode {
+                XRStereoMode::Multiview => openxr_struct.options.swapchain_usage,
+                _ => openxr_struct.options.swapchain_usage | wgpu::TextureUsage::COPY_DST,
+            } | wgpu::TextureUsage::RENDER_ATTACHMENT;
+            let usage_flags = map_usage_flags(usage);
+            debug!(target: "bevy_openxr::swapchain", ?stereo_mode, ?usage, ?usage_flags, "Swapchain usage");
+
+            if headless {
+                created = Some((None, stereo_mode, usage));
+                break;
+            }
+
+            let handle =
+                openxr_struct
+                    .handles
+                    .session
+                    .create_swapchain(&openxr::SwapchainCreateInfo {
+                        create_flags: openxr::SwapchainCreateFlags::EMPTY,
+                        usage_flags,
+                        format: vk_format.as_raw() as _,
+                        sample_count: 1,
+                        width,
+                        height,
+                        face_count: 1,
+                        array_size,
+                        mip_count: 1,
+                    });
+
+            match handle {
+                Ok(handle) => {
+                    created = Some((Some(handle), stereo_mode, usage));
+                    break;
+                }
+                Err(error) => {
+                    warn!(target: "bevy_openxr::swapchain", ?stereo_mode, ?error, "Could not create swapchain for stereo mode");
+                    last_error = error;
+                }
+            }
+        }
+
+        let (handle, stereo_mode, usage) = created.ok_or(XrError::XR(last_error))?;
+        let (image_width, image_height, image_layers) =
+            stereo_mode.image_extent(resolution.width, resolution.height, view_count);
+
+        info!(target: "bevy_openxr::swapchain", ?stereo_mode, "Selected stereo mode");
 
-        let environment_blend_mode = openxr_struct
+        let environment_blend_mode = *openxr_struct
             .instance
             .enumerate_environment_blend_modes(
                 openxr_struct.handles.system,
                 openxr_struct.options.view_type,
-            )
-            .unwrap()[0];
-
-        let images = handle.enumerate_images().unwrap();
-
-        let buffers = images
-            .into_iter()
-            .map(|color_image| {
-                // FIXME keep in sync with above usage_flags
-                let texture = device.create_openxr_texture_from_raw_image(
-                    &wgpu::TextureDescriptor {
-                        size: wgpu::Extent3d {
-                            width: resolution.width,
-                            height: resolution.height,
-                            depth_or_array_layers: 2,
-                        },
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: wgpu::TextureDimension::D2,
-                        format,
-                        usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-                        label: None,
-                    },
-                    color_image,
-                );
-
-                let color = texture.create_view(&wgpu::TextureViewDescriptor {
-                    label: None,
-                    format: Some(format),
-                    dimension: Some(wgpu::TextureViewDimension::D2Array),
-                    aspect: wgpu::TextureAspect::All,
-                    base_mip_level: 0,
-                    mip_level_count: NonZeroU32::new(1),
-                    base_array_layer: 0,
-                    array_layer_count: NonZeroU32::new(2),
-                });
-
-                Framebuffer {
-                    texture,
-                    texture_view: Some(color),
-                }
-            })
-            .collect();
+            )?
+            .first()
+            .ok_or(XrError::NoEnvironmentBlendMode)?;
+
+        let buffers = match &handle {
+            Some(handle) => import_images(
+                openxr_struct.graphics.as_ref(),
+                &device,
+                handle.enumerate_images()?,
+                wgpu::Extent3d {
+                    width: image_width,
+                    height: image_height,
+                    depth_or_array_layers: image_layers,
+                },
+                format,
+                usage,
+            ),
+            None => Vec::new(),
+        };
 
-        let hand_trackers = if openxr_struct.options.hand_trackers {
-            // FIXME check feature
-            Some(HandTrackers::new(&openxr_struct.handles.session).unwrap())
-        } else {
-            None
+        let hand_trackers = match (
+            openxr_struct.options.hand_trackers,
+            openxr_struct.instance.exts().ext_hand_tracking.is_some(),
+        ) {
+            (true, true) => Some(HandTrackers::new(
+                &openxr_struct.instance,
+                &openxr_struct.handles.session,
+                openxr_struct.options.hand_joints_motion_range,
+            )?),
+            (true, false) => {
+                warn!(target: "bevy_openxr::session", "XR_EXT_hand_tracking not supported by the runtime, hands are not tracked");
+                None
+            }
+            (false, _) => None,
         };
 
-        XRSwapchain {
+        Ok(XRSwapchain {
             sc_handle: handle,
             buffers,
             resolution,
+            view_sizes,
+            stereo_mode,
+            format,
+            render_format,
+            vk_format: *vk_format,
+            usage,
             view_configuration_type: openxr_struct.options.view_type,
             environment_blend_mode,
             next_frame_state: None,
             hand_trackers,
+            timings: XRFrameTimings::default(),
+            render_scale: max_render_scale,
+            max_render_scale,
+            acquired_image: None,
+            headless,
+            wait_swapchain_image: openxr_struct.instance.fp().wait_swapchain_image,
+            image_timeout: openxr_struct.options.swapchain_image_timeout,
             waited: false,
+            secondary_views: openxr_struct
+                .secondary_view_types
+                .iter()
+                .map(|view_type| SecondaryView::new(*view_type))
+                .collect(),
+            wait_frame: openxr_struct.instance.fp().wait_frame,
+        })
+    }
+
+    /// Acquires the next swapchain image (`xrAcquireSwapchainImage`) without waiting for it.
+    /// Called at the start of the frame, so that the compositor can release the image while simulation runs
+    pub fn acquire_next_image(&mut self) -> Result<(), XrError> {
+        if self.acquired_image.is_some() {
+            return Ok(());
         }
+
+        let span = info_span!(target: "bevy_openxr::swapchain", "xr_acquire_image");
+        let _guard = span.enter();
+
+        let start = Instant::now();
+        self.acquired_image = Some(self.handle()?.acquire_image()? as usize);
+        self.timings.image_wait = start.elapsed();
+
+        Ok(())
     }
 
     /// Return the next swapchain image index to render into
-    /// FIXME: currently waits for compositor to release image for rendering, this might cause delays in bevy system
-    ///        (e.g. should wait somewhere else - but how to use handle there)
-    pub fn get_next_swapchain_image_index(&mut self) -> usize {
-        let image_index = self.sc_handle.acquire_image().unwrap();
-        self.sc_handle
-            .wait_image(openxr::Duration::INFINITE)
-            .unwrap();
-        self.waited = true;
-        image_index as usize
+    /// Waits for the compositor to release the image acquired by `acquire_next_image()`. As the image
+    /// has been acquired at the start of the frame, the wait usually returns immediately. `None` if
+    /// the wait timed out, the image stays acquired and is waited for again in the next frame
+    pub fn get_next_swapchain_image_index(&mut self) -> Result<Option<usize>, XrError> {
+        self.acquire_next_image()?;
+        let image_index = self.acquired_image.unwrap();
+
+        if !self.waited {
+            let span = info_span!(target: "bevy_openxr::swapchain", "xr_wait_image");
+            let _guard = span.enter();
+
+            let start = Instant::now();
+            let released = self.wait_image(self.image_timeout)?;
+            self.timings.image_wait += start.elapsed();
+
+            if !released {
+                return Ok(None);
+            }
+
+            self.waited = true;
+        }
+
+        Ok(Some(image_index))
+    }
+
+    /// Waits for the acquired image (`xrWaitSwapchainImage`). `Ok(false)` on `XR_TIMEOUT_EXPIRED`,
+    /// which is a success code
+    fn wait_image(&mut self, timeout: Duration) -> Result<bool, XrError> {
+        let info = openxr::sys::SwapchainImageWaitInfo {
+            ty: openxr::sys::SwapchainImageWaitInfo::TYPE,
+            next: ptr::null(),
+            timeout: openxr::Duration::from_nanos(timeout.as_nanos() as i64),
+        };
+
+        let result = unsafe { (self.wait_swapchain_image)(self.handle()?.as_raw(), &info) };
+        if result == openxr::sys::Result::TIMEOUT_EXPIRED {
+            return Ok(false);
+        }
+
+        cvt(result)?;
+        Ok(true)
+    }
+
+    fn handle(&self) -> Result<&openxr::Swapchain<openxr::Vulkan>, XrError> {
+        self.sc_handle.as_ref().ok_or(XrError::Headless)
+    }
+
+    /// Predicted display time of the frame being rendered, `None` outside of a frame
+    pub fn predicted_display_time(&self) -> Option<Time> {
+        Some(self.next_frame_state.as_ref()?.predicted_display_time)
     }
 
     /// Prepares the device for rendering. Called before each frame is rendered
-    pub fn prepare_update(&mut self, handles: &mut OpenXRHandles) -> XRState {
+    pub fn prepare_update(
+        &mut self,
+        handles: &mut OpenXRHandles,
+    ) -> Result<XrFrameStatus, XrError> {
         // Check that previous frame was rendered
         if let Some(_) = self.next_frame_state {
             debug!("Called prepare_update() even though it was called already");
-            return XRState::Running; // <-- FIXME might change state, should keep it in memory somewhere
+            return Ok(XrFrameStatus::ShouldRender);
         }
 
-        let frame_state = match handles.frame_waiter.wait() {
-            Ok(fs) => fs,
-            Err(_) => {
-                // FIXME handle this better
-                return XRState::Paused;
+        let start = Instant::now();
+        let frame_state = {
+            let span = info_span!(target: "bevy_openxr::frame", "xr_wait_frame");
+            let _guard = span.enter();
+
+            let frame_state = match self.secondary_views.is_empty() {
+                true => handles.frame_waiter.wait().map_err(XrError::from),
+                false => secondary_view::wait_frame_with_secondary_views(
+                    self.wait_frame,
+                    handles.session.as_raw(),
+                    &mut self.secondary_views,
+                ),
+            };
+
+            match frame_state {
+                Ok(fs) => fs,
+                Err(e) => {
+                    self.timings.dropped_frames += 1;
+                    return Err(e);
+                }
             }
         };
+        self.timings.frame_wait = start.elapsed();
+        self.timings.frame_waited_at = Some(Instant::now());
+        self.timings.display_period =
+            Duration::from_nanos(frame_state.predicted_display_period.as_nanos().max(0) as u64);
 
         // 'Indicate that graphics device work is beginning'
-        handles.frame_stream.begin().unwrap();
+        {
+            let span = info_span!(target: "bevy_openxr::frame", "xr_begin_frame");
+            let _guard = span.enter();
+            handles.frame_stream.begin()?;
+        }
 
         if !frame_state.should_render {
             // if false, "the application should avoid heavy GPU work where possible" (openxr spec)
-            handles
-                .frame_stream
-                .end(
-                    frame_state.predicted_display_time,
-                    self.environment_blend_mode,
-                    &[],
-                )
-                .unwrap();
+            handles.frame_stream.end(
+                frame_state.predicted_display_time,
+                self.environment_blend_mode,
+                &[],
+            )?;
 
-            return XRState::Paused;
+            self.timings.dropped_frames += 1;
+            return Ok(XrFrameStatus::Skip);
         }
 
         // All ok for rendering
         self.next_frame_state = Some(frame_state);
-        return XRState::Running;
+        Ok(XrFrameStatus::ShouldRender)
+    }
+
+    pub fn get_hand_trackers(&self) -> Option<&HandTrackers> {
+        self.hand_trackers.as_ref()
+    }
+
+    pub fn get_hand_trackers_mut(&mut self) -> Option<&mut HandTrackers> {
+        self.hand_trackers.as_mut()
+    }
+
+    /// Hand trackers have been created (`XrOptions::hand_trackers`, and `XR_EXT_hand_tracking` is supported)
+    pub fn has_hand_trackers(&self) -> bool {
+        self.hand_trackers.is_some()
     }
 
     /// TODO: move this away, doesn't belong here
-    pub fn get_hand_positions(&mut self, handles: &mut OpenXRHandles) -> Option<HandPoseState> {
+    pub fn get_hand_positions(
+        &mut self,
+        handles: &mut OpenXRHandles,
+    ) -> Result<Option<HandPoseState>, XrError> {
         let frame_state = match self.next_frame_state {
             Some(fs) => fs,
-            None => return None,
+            None => return Ok(None),
         };
 
         let ht = match &self.hand_trackers {
             Some(ht) => ht,
-            None => return None,
+            None => return Ok(None),
         };
 
-        let hand_l = handles
-            .space
-            .locate_hand_joints(&ht.tracker_l, frame_state.predicted_display_time)
-            .unwrap();
-        let hand_r = handles
-            .space
-            .locate_hand_joints(&ht.tracker_r, frame_state.predicted_display_time)
-            .unwrap();
-
-        let hand_pose_state = HandPoseState {
-            left: hand_l,
-            right: hand_r,
-        };
+        let hand_pose_state = ht.locate(&handles.space, frame_state.predicted_display_time)?;
 
-        Some(hand_pose_state)
+        Ok(Some(hand_pose_state))
     }
 
-    pub fn get_view_positions(&mut self, handles: &mut OpenXRHandles) -> Option<Vec<Transform>> {
+    pub fn get_view_positions(
+        &mut self,
+        handles: &mut OpenXRHandles,
+    ) -> Result<Option<(Vec<Transform>, Vec<XrFovf>)>, XrError> {
         if let None = self.next_frame_state {
-            return None;
+            return Ok(None);
         }
 
         let frame_state = self.next_frame_state.as_ref().unwrap();
 
         // FIXME views acquisition should probably occur somewhere else - timing problem?
-        let (_, views) = handles
-            .session
-            .locate_views(
-                self.view_configuration_type,
-                frame_state.predicted_display_time,
-                &handles.space,
-            )
-            .unwrap();
+        let span = info_span!(target: "bevy_openxr::frame", "xr_locate_views");
+        let _guard = span.enter();
 
-        //println!("VIEWS: {:#?}", views);
+        Ok(Some(self.locate_views_at(
+            handles,
+            frame_state.predicted_display_time,
+        )?))
+    }
+
+    /// Poses and field of views of the views in the reference space, located for `time`
+    pub fn locate_views_at(
+        &self,
+        handles: &OpenXRHandles,
+        time: Time,
+    ) -> Result<(Vec<Transform>, Vec<XrFovf>), XrError> {
+        let (_, views) =
+            handles
+                .session
+                .locate_views(self.view_configuration_type, time, &handles.space)?;
 
         let transforms = views
             .iter()
-            .map(|view| {
-                let pos = &view.pose.position;
-                let ori = &view.pose.orientation;
-                let mut transform = Transform::from_translation(Vec3::new(pos.x, pos.y, pos.z));
-                transform.rotation = Quat::from_xyzw(ori.x, ori.y, ori.z, ori.w);
-                transform
-            })
+            .map(|view| pose_to_transform(&view.pose))
             .collect();
 
-        //println!("TRANSFORMS: {:#?}", transforms);
-        Some(transforms)
+        let fovs = views.iter().map(|view| view.fov.into()).collect();
+
+        Ok((transforms, fovs))
+    }
+
+    /// Releases the image rendered in this frame (`xrReleaseSwapchainImage`). `false` if nothing was
+    /// rendered, e.g. the image wait timed out: the acquired image is waited for again in the next
+    /// frame
+    pub fn release_image(&mut self) -> Result<bool, XrError> {
+        if self.headless || self.next_frame_state.is_none() || !self.waited {
+            return Ok(false);
+        }
+
+        // "Release the oldest acquired image"
+        let span = info_span!(target: "bevy_openxr::swapchain", "xr_release_image");
+        let _guard = span.enter();
+
+        self.acquired_image = None;
+        self.waited = false;
+        self.handle()?.release_image()?;
+
+        Ok(true)
     }
 
-    /// Finalizes the swapchain update - will tell openxr that GPU has rendered to textures
-    pub fn finalize_update(&mut self, handles: &mut OpenXRHandles) {
+    /// Finalizes the swapchain update - will tell openxr that GPU has rendered to textures.
+    /// `layers` (e.g. passthrough, cylinder layers) are submitted together with the projection layer,
+    /// sorted by their order. If any layer is below the projection layer, the projection layer is
+    /// blended using its alpha channel. Without a `released` image, the frame is ended without layers
+    pub fn finalize_update(
+        &mut self,
+        handles: &mut OpenXRHandles,
+        instance: &openxr::Instance,
+        layers: &[(XRLayerOrder, *const openxr::sys::CompositionLayerBaseHeader)],
+        released: bool,
+    ) -> Result<(), XrError> {
         // Take the next frame state
         let next_frame_state = match self.next_frame_state.take() {
             Some(nfst) => nfst,
             None => {
                 warn!("NO NEXT FRAME");
-                return;
+                return Ok(());
             }
         };
 
-        if !self.waited {
-            return;
+        // nothing was rendered (headless, or the image was not released)
+        if !released {
+            handles.frame_stream.end(
+                next_frame_state.predicted_display_time,
+                self.environment_blend_mode,
+                &[],
+            )?;
+            return Ok(());
         }
 
-        // "Release the oldest acquired image"
-        self.sc_handle.release_image().unwrap();
-        self.waited = false;
+        let secondary_rendered =
+            self.release_secondary_images(handles, next_frame_state.predicted_display_time)?;
+        let sc_handle = self.sc_handle.as_ref().ok_or(XrError::Headless)?;
 
         // FIXME views acquisition should probably occur somewhere else - timing problem?
         // FIXME is there a problem now, if the rendering uses different camera positions than what's used at openxr?
         // "When rendering, this should be called as late as possible before the GPU accesses it to"
-        let (_, views) = handles
-            .session
-            .locate_views(
-                self.view_configuration_type,
-                next_frame_state.predicted_display_time,
-                &handles.space,
-            )
-            .unwrap();
+        let span = info_span!(target: "bevy_openxr::frame", "xr_locate_views");
+        let guard = span.enter();
+        let (_, views) = handles.session.locate_views(
+            self.view_configuration_type,
+            next_frame_state.predicted_display_time,
+            &handles.space,
+        )?;
+        drop(guard);
 
         // Tell OpenXR what to present for this frame
-        // Because we're using GL_EXT_multiview, same rect for both eyes
-        let rect = openxr::Rect2Di {
-            offset: openxr::Offset2Di { x: 0, y: 0 },
-            extent: openxr::Extent2Di {
-                width: self.resolution.width as _,
-                height: self.resolution.height as _,
-            },
-        };
-
         // Construct views
         // TODO: for performance (no-vec allocations), use `SmallVec`?
         let views = views
             .iter()
             .enumerate()
             .map(|(idx, view)| {
+                let view_rect = self.get_view_rect(idx as u32);
+
+                let rect = openxr::Rect2Di {
+                    offset: openxr::Offset2Di {
+                        x: view_rect.x as _,
+                        y: view_rect.y as _,
+                    },
+                    extent: openxr::Extent2Di {
+                        width: view_rect.width as _,
+                        height: view_rect.height as _,
+                    },
+                };
+
                 openxr::CompositionLayerProjectionView::new()
                     .pose(view.pose)
                     .fov(view.fov)
                     .sub_image(
                         openxr::SwapchainSubImage::new()
-                            .swapchain(&self.sc_handle)
-                            .image_array_index(idx as u32)
+                            .swapchain(sc_handle)
+                            .image_array_index(view_rect.layer)
                             .image_rect(rect),
                     )
             })
             .collect::<Vec<_>>();
 
-        handles
-            .frame_stream
-            .end(
+        let projection = openxr::CompositionLayerProjection::new()
+            .space(&handles.space)
+            .views(&views);
+
+        // secondary views rendered in this frame are submitted in layers of their own
+        let secondary_views = secondary_rendered
+            .iter()
+            .map(|(index, view)| {
+                let swapchain = self.secondary_views[*index].swapchain.as_ref().unwrap();
+                let rect = openxr::Rect2Di {
+                    offset: openxr::Offset2Di { x: 0, y: 0 },
+                    extent: openxr::Extent2Di {
+                        width: swapchain.width as _,
+                        height: swapchain.height as _,
+                    },
+                };
+
+                [openxr::CompositionLayerProjectionView::new()
+                    .pose(view.pose)
+                    .fov(view.fov)
+                    .sub_image(
+                        openxr::SwapchainSubImage::new()
+                            .swapchain(swapchain.handle())
+                            .image_array_index(0)
+                            .image_rect(rect),
+                    )]
+            })
+            .collect::<Vec<_>>();
+
+        let secondary_projections = secondary_views
+            .iter()
+            .map(|views| {
+                openxr::CompositionLayerProjection::new()
+                    .space(&handles.space)
+                    .views(views)
+            })
+            .collect::<Vec<_>>();
+
+        let secondary_layers = secondary_projections
+            .iter()
+            .map(|projection| {
+                &**projection as *const openxr::CompositionLayerBase<_>
+                    as *const openxr::sys::CompositionLayerBaseHeader
+            })
+            .collect::<Vec<_>>();
+
+        let secondary_layer_infos = secondary_rendered
+            .iter()
+            .zip(secondary_layers.iter())
+            .map(|((index, _), layer)| {
+                let secondary_view = &self.secondary_views[*index];
+                openxr::sys::SecondaryViewConfigurationLayerInfoMSFT {
+                    ty: openxr::sys::SecondaryViewConfigurationLayerInfoMSFT::TYPE,
+                    next: ptr::null(),
+                    view_configuration_type: secondary_view.view_type,
+                    environment_blend_mode: secondary_view
+                        .swapchain
+                        .as_ref()
+                        .unwrap()
+                        .environment_blend_mode,
+                    layer_count: 1,
+                    layers: layer,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let secondary_end_info = openxr::sys::SecondaryViewConfigurationFrameEndInfoMSFT {
+            ty: openxr::sys::SecondaryViewConfigurationFrameEndInfoMSFT::TYPE,
+            next: ptr::null(),
+            view_configuration_count: secondary_layer_infos.len() as u32,
+            view_configuration_layers_info: secondary_layer_infos.as_ptr(),
+        };
+
+        let span = info_span!(target: "bevy_openxr::frame", "xr_end_frame");
+        let _guard = span.enter();
+
+        if layers.is_empty() && secondary_layer_infos.is_empty() {
+            handles.frame_stream.end(
                 next_frame_state.predicted_display_time,
                 self.environment_blend_mode,
-                &[&openxr::CompositionLayerProjection::new()
-                    .space(&handles.space)
-                    .views(&views)],
-            )
-            .unwrap();
+                &[&projection],
+            )?;
+            return Ok(());
+        }
+
+        // `FrameStream::end` accepts only layer types known by the `openxr` crate and does not support
+        // chaining the secondary view layers, so submit directly
+        // the projection layer must be blended over any layers below it
+        let projection = match layers.iter().any(|(order, _)| order.is_underlay()) {
+            true => {
+                projection.layer_flags(openxr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA)
+            }
+            false => projection,
+        };
+
+        let mut ordered = Vec::with_capacity(layers.len() + 1);
+        ordered.push((
+            XRLayerOrder::PROJECTION,
+            &*projection as *const openxr::CompositionLayerBase<_> as *const _,
+        ));
+        ordered.extend_from_slice(layers);
+
+        // stable: the projection layer goes below other layers of the same order
+        ordered.sort_by_key(|(order, _)| *order);
+
+        let layers = ordered
+            .into_iter()
+            .map(|(_, layer)| layer)
+            .collect::<Vec<*const openxr::sys::CompositionLayerBaseHeader>>();
+
+        let frame_end_info = openxr::sys::FrameEndInfo {
+            ty: openxr::sys::FrameEndInfo::TYPE,
+            next: match secondary_layer_infos.is_empty() {
+                true => ptr::null(),
+                false => &secondary_end_info as *const _ as *const _,
+            },
+            display_time: next_frame_state.predicted_display_time,
+            environment_blend_mode: self.environment_blend_mode,
+            layer_count: layers.len() as u32,
+            layers: layers.as_ptr(),
+        };
+
+        let ret = unsafe { (instance.fp().end_frame)(handles.session.as_raw(), &frame_end_info) };
+        if ret.into_raw() < 0 {
+            return Err(XrError::XR(ret));
+        }
+
+        Ok(())
+    }
+
+    /// Releases the images of the secondary views rendered in this frame, and locates the views for
+    /// their layers. Returns the index and located view of each rendered secondary view
+    fn release_secondary_images(
+        &mut self,
+        handles: &OpenXRHandles,
+        display_time: Time,
+    ) -> Result<Vec<(usize, View)>, XrError> {
+        let mut rendered = Vec::new();
+
+        for (index, secondary_view) in self.secondary_views.iter_mut().enumerate() {
+            let swapchain = match secondary_view.swapchain.as_mut() {
+                Some(swapchain) => swapchain,
+                None => continue,
+            };
+
+            // images are acquired only for views active in this frame
+            if !swapchain.release_image()? {
+                continue;
+            }
+
+            if let Some(view) = secondary_view.locate(handles, display_time)? {
+                rendered.push((index, view));
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    /// Creates the swapchains of the secondary views activated for the first time in this frame, or
+    /// activated again with a different recommended size. Returns the surface of each created swapchain, or the error of a failed creation, which is
+    /// not retried
+    pub(crate) fn create_secondary_swapchains(
+        &mut self,
+        device: &wgpu::Device,
+        openxr_struct: &OpenXRStruct,
+    ) -> Vec<Result<XRSecondaryViewSurfaceCreated, XrError>> {
+        if self.headless {
+            return Vec::new();
+        }
+
+        let usage = openxr_struct.options.swapchain_usage | wgpu::TextureUsage::RENDER_ATTACHMENT;
+        let mut created = Vec::new();
+
+        for (index, secondary_view) in self.secondary_views.iter_mut().enumerate() {
+            if secondary_view.activated {
+                secondary_view.drop_resized_swapchain(openxr_struct);
+            }
+
+            if !secondary_view.active || secondary_view.swapchain.is_some() || secondary_view.failed
+            {
+                continue;
+            }
+
+            let swapchain = SecondarySwapchain::new(
+                device,
+                openxr_struct,
+                secondary_view.view_type,
+                self.vk_format,
+                self.format,
+                usage,
+                map_usage_flags(usage),
+            );
+
+            created.push(match swapchain {
+                Ok(swapchain) => {
+                    let surface = XRSecondaryViewSurfaceCreated {
+                        index,
+                        view_type: secondary_view.view_type,
+                        width: swapchain.width,
+                        height: swapchain.height,
+                    };
+                    secondary_view.swapchain = Some(swapchain);
+                    Ok(surface)
+                }
+                Err(e) => {
+                    secondary_view.failed = true;
+                    Err(e)
+                }
+            });
+        }
+
+        created
+    }
+
+    /// Texture views of the secondary view swapchains created since the previous call, by index of
+    /// the secondary view
+    pub(crate) fn take_secondary_texture_views(&mut self) -> Vec<(usize, Vec<wgpu::TextureView>)> {
+        self.secondary_views
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, secondary_view)| {
+                let texture_views = secondary_view.swapchain.as_mut()?.take_texture_views()?;
+                Some((index, texture_views))
+            })
+            .collect()
+    }
+
+    /// Acquires and waits for the next image of the secondary view. `None` if the view is not active
+    /// in this frame, or the wait timed out
+    pub(crate) fn get_next_secondary_image_index(
+        &mut self,
+        index: usize,
+    ) -> Result<Option<usize>, XrError> {
+        let secondary_view = match self.secondary_views.get_mut(index) {
+            Some(secondary_view) if secondary_view.active => secondary_view,
+            _ => return Ok(None),
+        };
+
+        match secondary_view.swapchain.as_mut() {
+            Some(swapchain) => {
+                swapchain.next_image_index(self.wait_swapchain_image, self.image_timeout)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Poses and field of views of the secondary views active in the frame being rendered
+    pub(crate) fn get_secondary_view_positions(
+        &self,
+        handles: &OpenXRHandles,
+    ) -> Result<Option<Vec<XRSecondaryView>>, XrError> {
+        let frame_state = match (&self.next_frame_state, self.secondary_views.is_empty()) {
+            (Some(frame_state), false) => frame_state,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(secondary_view::locate_secondary_views(
+            &self.secondary_views,
+            handles,
+            frame_state.predicted_display_time,
+        )?))
     }
 
     /// Should be called only once by `XRSwapchainNode`
@@ -387,21 +924,123 @@ impl XRSwapchain {
             .collect()
     }
 
+    pub fn get_timings(&self) -> &XRFrameTimings {
+        &self.timings
+    }
+
+    /// Resolution of a single view
     pub fn get_resolution(&self) -> (u32, u32) {
         (self.resolution.width, self.resolution.height)
     }
 
-    pub fn get_views(&self, handles: &mut OpenXRHandles) -> Vec<View> {
-        let (_, views) = handles
-            .session
-            .locate_views(
-                self.view_configuration_type,
-                Time::from_nanos(1), // FIXME time must be non-zero, is this okay?
-                &handles.space,
-            )
-            .unwrap();
+    /// Sets the scale of the recommended view sizes (`0.1..=get_max_render_scale()`) that is submitted
+    /// to the compositor. The scene must be rendered into the top-left sub-rect of the same size
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        self.render_scale = render_scale.max(0.1).min(self.max_render_scale);
+    }
+
+    pub fn get_render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Largest render scale, above 1 if the swapchain has been created for supersampling
+    pub fn get_max_render_scale(&self) -> f32 {
+        self.max_render_scale
+    }
+
+    /// Recommended size of each view
+    pub fn get_view_sizes(&self) -> &[(u32, u32)] {
+        &self.view_sizes
+    }
+
+    /// Size of the sub-rect of the view submitted to the compositor, after applying the render scale
+    pub fn get_view_render_size(&self, view_index: usize) -> (u32, u32) {
+        let rect = self.get_view_rect(view_index as u32);
+        (rect.width, rect.height)
+    }
+
+    /// Rect of the view in the swapchain image, with render scale applied
+    pub fn get_view_rect(&self, view_index: u32) -> XRViewRect {
+        self.stereo_mode.view_rect(
+            view_index,
+            self.resolution.width,
+            self.view_sizes[view_index as usize],
+            self.render_scale,
+        )
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Number of images in the swapchain, as enumerated from the runtime
+    pub fn get_image_count(&self) -> u32 {
+        self.buffers.len() as u32
+    }
+
+    /// Number of views, laid out in the swapchain image according to `get_stereo_mode`
+    pub fn get_view_count(&self) -> u32 {
+        self.resolution.depth_or_array_layers
+    }
+
+    pub fn get_stereo_mode(&self) -> XRStereoMode {
+        self.stereo_mode
+    }
 
-        views
+    pub fn get_render_format(&self) -> TextureFormat {
+        self.render_format
+    }
+
+    pub fn get_environment_blend_mode(&self) -> XREnvironmentBlendMode {
+        XREnvironmentBlendMode::from_openxr(self.environment_blend_mode)
+    }
+
+    /// Reads back the view from the swapchain image rendered in this frame. Must be called after
+    /// rendering, before `finalize_update` releases the image
+    pub fn capture_view(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view_index: u32,
+    ) -> Option<XRCapturedImage> {
+        if !self.waited || view_index >= self.get_view_count() {
+            return None;
+        }
+
+        if !self.usage.contains(wgpu::TextureUsage::COPY_SRC) {
+            warn!(target: "bevy_openxr::swapchain", "Cannot capture view, swapchain usage is missing COPY_SRC");
+            return None;
+        }
+
+        let buffer = &self.buffers[self.acquired_image?];
+        let rect = self.get_view_rect(view_index);
+
+        let data = capture::read_texture(
+            device,
+            queue,
+            &buffer.texture,
+            self.format,
+            (rect.x, rect.y),
+            rect.layer,
+            (rect.width, rect.height),
+        )?;
+
+        Some(XRCapturedImage {
+            view_index,
+            width: rect.width,
+            height: rect.height,
+            data,
+        })
+    }
+
+    pub fn get_views(&self, handles: &mut OpenXRHandles) -> Result<Vec<View>, XrError> {
+        let (_, views) = handles.session.locate_views(
+            self.view_configuration_type,
+            Time::from_nanos(1), // FIXME time must be non-zero, is this okay?
+            &handles.space,
+        )?;
+
+        Ok(views)
     }
 }
 
@@ -413,10 +1052,54 @@ impl Debug for XRSwapchain {
 
 /// Per view framebuffer, that will contain an underlying texture and a texture view (taken away by bevy render graph)
 /// where the contents should be rendered
-struct Framebuffer {
-    #[allow(dead_code)]
-    texture: wgpu::Texture,
-    texture_view: Option<wgpu::TextureView>,
+pub(crate) struct Framebuffer {
+    pub(crate) texture: wgpu::Texture,
+    pub(crate) texture_view: Option<wgpu::TextureView>,
+}
+
+/// Imports the swapchain images as wgpu textures of `size`, viewed as texture arrays
+pub(crate) fn import_images(
+    graphics: &dyn XrGraphicsBackend,
+    device: &wgpu::Device,
+    images: Vec<u64>,
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+    usage: wgpu::TextureUsage,
+) -> Vec<Framebuffer> {
+    images
+        .into_iter()
+        .map(|color_image| {
+            let texture = graphics.import_swapchain_image(
+                device,
+                &wgpu::TextureDescriptor {
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    label: None,
+                },
+                color_image,
+            );
+
+            let color = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: None,
+                format: Some(format),
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                aspect: wgpu::TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: NonZeroU32::new(1),
+                base_array_layer: 0,
+                array_layer_count: NonZeroU32::new(size.depth_or_array_layers),
+            });
+
+            Framebuffer {
+                texture,
+                texture_view: Some(color),
+            }
+        })
+        .collect()
 }
 
 // TODO: this is based on gfx_backend_vulkan/conv.rs, can it be used directly?
@@ -572,3 +1255,106 @@ pub(crate) fn map_texture_format(
         }
     })
 }
+
+/// bevy format of a color format, see `map_texture_format`
+fn map_render_format(format: wgpu::TextureFormat) -> Option<TextureFormat> {
+    use wgpu::TextureFormat as Tf;
+    Some(match format {
+        Tf::R8Unorm => TextureFormat::R8Unorm,
+        Tf::R8Snorm => TextureFormat::R8Snorm,
+        Tf::R8Uint => TextureFormat::R8Uint,
+        Tf::R8Sint => TextureFormat::R8Sint,
+        Tf::R16Uint => TextureFormat::R16Uint,
+        Tf::R16Sint => TextureFormat::R16Sint,
+        Tf::R16Float => TextureFormat::R16Float,
+        Tf::Rg8Unorm => TextureFormat::Rg8Unorm,
+        Tf::Rg8Snorm => TextureFormat::Rg8Snorm,
+        Tf::Rg8Uint => TextureFormat::Rg8Uint,
+        Tf::Rg8Sint => TextureFormat::Rg8Sint,
+        Tf::R32Uint => TextureFormat::R32Uint,
+        Tf::R32Sint => TextureFormat::R32Sint,
+        Tf::R32Float => TextureFormat::R32Float,
+        Tf::Rg16Uint => TextureFormat::Rg16Uint,
+        Tf::Rg16Sint => TextureFormat::Rg16Sint,
+        Tf::Rg16Float => TextureFormat::Rg16Float,
+        Tf::Rgba8Unorm => TextureFormat::Rgba8Unorm,
+        Tf::Rgba8UnormSrgb => TextureFormat::Rgba8UnormSrgb,
+        Tf::Rgba8Snorm => TextureFormat::Rgba8Snorm,
+        Tf::Rgba8Uint => TextureFormat::Rgba8Uint,
+        Tf::Rgba8Sint => TextureFormat::Rgba8Sint,
+        Tf::Bgra8Unorm => TextureFormat::Bgra8Unorm,
+        Tf::Bgra8UnormSrgb => TextureFormat::Bgra8UnormSrgb,
+        Tf::Rgb10a2Unorm => TextureFormat::Rgb10a2Unorm,
+        Tf::Rg11b10Float => TextureFormat::Rg11b10Float,
+        Tf::Rg32Uint => TextureFormat::Rg32Uint,
+        Tf::Rg32Sint => TextureFormat::Rg32Sint,
+        Tf::Rg32Float => TextureFormat::Rg32Float,
+        Tf::Rgba16Uint => TextureFormat::Rgba16Uint,
+        Tf::Rgba16Sint => TextureFormat::Rgba16Sint,
+        Tf::Rgba16Float => TextureFormat::Rgba16Float,
+        Tf::Rgba32Uint => TextureFormat::Rgba32Uint,
+        Tf::Rgba32Sint => TextureFormat::Rgba32Sint,
+        Tf::Rgba32Float => TextureFormat::Rgba32Float,
+        _ => return None,
+    })
+}
+
+/// Swapchain usage flags for the usages of the wgpu texture
+fn map_usage_flags(usage: wgpu::TextureUsage) -> openxr::SwapchainUsageFlags {
+    let mapping = [
+        (
+            wgpu::TextureUsage::RENDER_ATTACHMENT,
+            openxr::SwapchainUsageFlags::COLOR_ATTACHMENT,
+        ),
+        (
+            wgpu::TextureUsage::COPY_SRC,
+            openxr::SwapchainUsageFlags::TRANSFER_SRC,
+        ),
+        (
+            wgpu::TextureUsage::COPY_DST,
+            openxr::SwapchainUsageFlags::TRANSFER_DST,
+        ),
+        (
+            wgpu::TextureUsage::SAMPLED,
+            openxr::SwapchainUsageFlags::SAMPLED,
+        ),
+        (
+            wgpu::TextureUsage::STORAGE,
+            openxr::SwapchainUsageFlags::UNORDERED_ACCESS,
+        ),
+    ];
+
+    mapping
+        .iter()
+        .filter(|(usage_flag, _)| usage.contains(*usage_flag))
+        .fold(openxr::SwapchainUsageFlags::EMPTY, |flags, (_, flag)| {
+            flags | *flag
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_usage_flags() {
+        assert_eq!(
+            map_usage_flags(wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED),
+            openxr::SwapchainUsageFlags::COLOR_ATTACHMENT | openxr::SwapchainUsageFlags::SAMPLED
+        );
+        assert_eq!(
+            map_usage_flags(wgpu::TextureUsage::COPY_SRC | wgpu::TextureUsage::STORAGE),
+            openxr::SwapchainUsageFlags::TRANSFER_SRC
+                | openxr::SwapchainUsageFlags::UNORDERED_ACCESS
+        );
+    }
+
+    #[test]
+    fn test_map_render_format() {
+        assert_eq!(
+            map_render_format(wgpu::TextureFormat::Rgba8UnormSrgb),
+            Some(TextureFormat::Rgba8UnormSrgb)
+        );
+        assert_eq!(map_render_format(wgpu::TextureFormat::Bc1RgbaUnorm), None);
+    }
+}
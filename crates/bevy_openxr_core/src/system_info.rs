@@ -0,0 +1,46 @@
+/// Properties of the XR system (headset) and of the session's swapchain. Available as a resource,
+/// updated when the swapchain is created
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRSystemInfo {
+    /// Name of the XR system, e.g. "Oculus Quest2"
+    pub system_name: String,
+    pub vendor_id: u32,
+
+    /// Largest swapchain image supported, `(width, height)`
+    pub max_swapchain_image_size: (u32, u32),
+
+    /// Maximum number of composition layers per frame, including the projection layer
+    pub max_layer_count: u32,
+
+    pub orientation_tracking: bool,
+    pub position_tracking: bool,
+
+    /// Number of images in the swapchain, as provided by the runtime. `None` before the swapchain
+    /// has been created
+    pub swapchain_image_count: Option<u32>,
+
+    /// Frames rendered concurrently. The swapchain image is acquired, rendered and released within
+    /// a single update, and bevy_wgpu submits the frame before the update ends, so this is always 1
+    pub frames_in_flight: u32,
+}
+
+impl XRSystemInfo {
+    pub(crate) fn new(properties: &openxr::SystemProperties) -> Self {
+        let graphics = &properties.graphics_properties;
+        let tracking = &properties.tracking_properties;
+
+        XRSystemInfo {
+            system_name: properties.system_name.clone(),
+            vendor_id: properties.vendor_id,
+            max_swapchain_image_size: (
+                graphics.max_swapchain_image_width,
+                graphics.max_swapchain_image_height,
+            ),
+            max_layer_count: graphics.max_layer_count,
+            orientation_tracking: tracking.orientation_tracking,
+            position_tracking: tracking.position_tracking,
+            swapchain_image_count: None,
+            frames_in_flight: 1,
+        }
+    }
+}
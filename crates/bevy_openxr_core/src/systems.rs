@@ -1,57 +1,178 @@
-use bevy::app::{AppExit, EventWriter, Events};
-use bevy::ecs::system::ResMut;
+use bevy::app::{AppExit, EventReader, EventWriter, Events};
+use bevy::ecs::{
+    schedule::ShouldRun,
+    system::{Res, ResMut},
+    world::World,
+};
+use bevy::utils::tracing::info;
 
 use crate::XRConfigurationState;
 use crate::{
-    event::{XRCameraTransformsUpdated, XREvent, XRState, XRViewSurfaceCreated, XRViewsCreated},
+    event::{
+        XRCameraTransformsUpdated, XREvent, XRExitRequest, XRFrameSkipped, XRPauseChanged,
+        XRPerformanceNotification, XRRuntimeError, XRSecondaryViewSurfaceCreated,
+        XRSecondaryViewsUpdated, XRSwapchainRecreated, XRViewSurfaceCreated, XRViewsCreated,
+        XRVisibilityMaskChanged, XrFrameStatus, XrSessionState,
+    },
     hand_tracking::HandPoseState,
-    XRDevice,
+    XRDevice, XREnvironmentBlendMode, XRPauseState, XRSystemInfo,
 };
 
 pub(crate) fn openxr_event_system(
     mut openxr: ResMut<XRDevice>,
     mut hand_pose: ResMut<HandPoseState>,
-    mut state_events: ResMut<Events<XRState>>,
+    mut session_state: ResMut<XrSessionState>,
+    mut frame_status: ResMut<XrFrameStatus>,
+    mut state_events: EventWriter<XrSessionState>,
     mut configuration_state: ResMut<XRConfigurationState>,
+    (mut environment_blend_mode, mut system_info): (
+        ResMut<XREnvironmentBlendMode>,
+        ResMut<XRSystemInfo>,
+    ),
 
     mut view_surface_created_sender: EventWriter<XRViewSurfaceCreated>,
-    mut views_created_sender: EventWriter<XRViewsCreated>,
-    mut camera_transforms_updated: EventWriter<XRCameraTransformsUpdated>,
+    (mut views_created_sender, mut secondary_surface_created): (
+        EventWriter<XRViewsCreated>,
+        EventWriter<XRSecondaryViewSurfaceCreated>,
+    ),
+    (mut camera_transforms_updated, mut secondary_views_updated): (
+        EventWriter<XRCameraTransformsUpdated>,
+        EventWriter<XRSecondaryViewsUpdated>,
+    ),
+    mut runtime_errors: EventWriter<XRRuntimeError>,
+    mut performance_notifications: EventWriter<XRPerformanceNotification>,
+    mut visibility_mask_changes: EventWriter<XRVisibilityMaskChanged>,
+    (mut swapchain_recreated, mut frames_skipped): (
+        EventWriter<XRSwapchainRecreated>,
+        EventWriter<XRFrameSkipped>,
+    ),
 
-    mut app_exit_events: EventWriter<AppExit>,
+    mut exit_state: ResMut<XRExitState>,
+    (mut pause_state, mut pause_changes): (ResMut<XRPauseState>, EventWriter<XRPauseChanged>),
 ) {
     // TODO add this drain -system as pre-render and post-render system?
     for event in openxr.drain_events() {
         match event {
             XREvent::ViewSurfaceCreated(view_created) => {
+                if let Some(blend_mode) = openxr.environment_blend_mode() {
+                    if *environment_blend_mode != blend_mode {
+                        info!(target: "bevy_openxr::session", ?blend_mode, "Environment blend mode");
+                        *environment_blend_mode = blend_mode;
+                    }
+                }
+
+                if *system_info != *openxr.system_info() {
+                    *system_info = openxr.system_info().clone();
+                }
+
                 configuration_state.last_view_surface = Some(view_created.clone());
                 view_surface_created_sender.send(view_created);
             }
             XREvent::ViewsCreated(views) => views_created_sender.send(views),
+            XREvent::SecondaryViewSurfaceCreated(surface) => {
+                let target = configuration_state.secondary_view_mut(surface.index);
+                target.surface = Some(surface.clone());
+                secondary_surface_created.send(surface);
+            }
+            XREvent::RuntimeError(error) => runtime_errors.send(error),
+            XREvent::PerformanceNotification(notification) => {
+                performance_notifications.send(notification)
+            }
+            XREvent::VisibilityMaskChanged(change) => visibility_mask_changes.send(change),
+            XREvent::SwapchainRecreated(recreated) => {
+                // secondary view swapchains are recreated at their next activation
+                configuration_state.secondary_views.clear();
+                swapchain_recreated.send(recreated);
+            }
+            XREvent::FrameSkipped(skipped) => frames_skipped.send(skipped),
         }
     }
 
     // This should be before all other events
-    match openxr.inner.handle_openxr_events() {
-        None => (),
-        Some(changed_state) => {
-            state_events.send(changed_state);
+    for changed_state in openxr.inner.handle_openxr_events() {
+        *session_state = changed_state;
+        state_events.send(changed_state);
 
-            if let XRState::Exiting = changed_state {
-                app_exit_events.send(AppExit);
-            }
+        let paused = openxr.inner.options.pause_behavior.is_paused(changed_state);
+        if paused != pause_state.paused {
+            info!(target: "bevy_openxr::session", paused, "Pause state changed");
+            pause_state.paused = paused;
+            pause_changes.send(XRPauseChanged { paused });
+        }
+
+        if let XrSessionState::Exiting = changed_state {
+            info!(target: "bevy_openxr::session", "Session exiting, running XRStage::Exit");
+            exit_state.requested = true;
         }
     }
 
     // FIXME: this should happen just before bevy render graph and / or wgpu render?
-    openxr.touch_update();
+    *frame_status = openxr.touch_update();
 
     // FIXME this should be in before-other-systems system? so that all systems can use hand pose data...
     if let Some(hp) = openxr.get_hand_positions() {
         *hand_pose = hp;
     }
 
-    if let Some(transforms) = openxr.get_view_positions() {
-        camera_transforms_updated.send(XRCameraTransformsUpdated { transforms });
+    if let Some((transforms, fovs)) = openxr.get_view_positions() {
+        camera_transforms_updated.send(XRCameraTransformsUpdated { transforms, fovs });
+    }
+
+    if let Some(views) = openxr.get_secondary_view_positions() {
+        secondary_views_updated.send(XRSecondaryViewsUpdated { views });
+    }
+}
+
+pub(crate) fn exit_request_system(
+    xr_device: Option<ResMut<XRDevice>>,
+    mut exit_requests: EventReader<XRExitRequest>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    if exit_requests.iter().next_back().is_none() {
+        return;
+    }
+
+    match xr_device {
+        Some(mut xr_device) => xr_device.request_exit(),
+        None => {
+            info!(target: "bevy_openxr::session", "Exit requested without a session");
+            app_exit_events.send(AppExit);
+        }
+    }
+}
+
+/// Shutdown progress after the session started exiting
+#[derive(Default)]
+pub(crate) struct XRExitState {
+    /// `XRStage::Exit` should run in this update
+    requested: bool,
+
+    /// `AppExit` has been sent
+    handled: bool,
+}
+
+pub(crate) fn xr_exit_criteria(exit_state: Res<XRExitState>) -> ShouldRun {
+    if exit_state.requested && !exit_state.handled {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Runs at the end of `XRStage::Exit`, after the cleanup systems
+pub(crate) fn xr_app_exit_system(world: &mut World) {
+    world.get_resource_mut::<XRExitState>().unwrap().handled = true;
+    world
+        .get_resource_mut::<Events<AppExit>>()
+        .unwrap()
+        .send(AppExit);
+}
+
+/// Destroys the OpenXR resources once the app is exiting. Runs at `CoreStage::Last`, after rendering,
+/// so that the runner can exit after the update in which `AppExit` was sent
+pub(crate) fn xr_exit_system(mut app_exit_events: EventReader<AppExit>, xr_device: Res<XRDevice>) {
+    if app_exit_events.iter().next_back().is_some() {
+        info!(target: "bevy_openxr::session", "Exit triggered, destroying OpenXR resources");
+        xr_device.inner.graphics.destroy();
     }
 }
@@ -0,0 +1,215 @@
+use bevy::input::keyboard::KeyCode;
+use bevy::utils::HashMap;
+
+/// Runtime provided virtual keyboard for text entry. On Android (Oculus Quest) this is the system
+/// keyboard overlay, shown through the soft input of the activity. Typed text is sent as
+/// `ReceivedCharacter` events, and keys as `KeyboardInput` events
+#[derive(Debug, Default)]
+pub struct XRSystemKeyboard {
+    visible: bool,
+
+    /// Visibility to apply on the next update
+    requested: Option<bool>,
+}
+
+impl XRSystemKeyboard {
+    /// Whether the platform has a system keyboard
+    pub fn is_supported(&self) -> bool {
+        cfg!(target_os = "android")
+    }
+
+    pub fn show(&mut self) {
+        self.requested = Some(true);
+    }
+
+    pub fn hide(&mut self) {
+        self.requested = Some(false);
+    }
+
+    /// Keyboard has been requested to be visible. The user may also dismiss it from the overlay
+    pub fn is_visible(&self) -> bool {
+        self.requested.unwrap_or(self.visible)
+    }
+
+    pub(crate) fn take_request(&mut self) -> Option<bool> {
+        let requested = self.requested.take()?;
+        self.visible = requested;
+        Some(requested)
+    }
+}
+
+/// Characters produced by keys, used for `ReceivedCharacter` events of Android key events. Defaults
+/// to the US layout. Keys missing from the keymap use the character of the Android key character map
+#[derive(Debug, Clone)]
+pub struct XRKeymap {
+    /// Character without and with shift
+    keys: HashMap<KeyCode, (char, char)>,
+}
+
+impl XRKeymap {
+    pub fn empty() -> Self {
+        XRKeymap {
+            keys: HashMap::default(),
+        }
+    }
+
+    /// US keyboard layout
+    pub fn us() -> Self {
+        let mut keymap = XRKeymap::empty();
+
+        let letters = [
+            KeyCode::A,
+            KeyCode::B,
+            KeyCode::C,
+            KeyCode::D,
+            KeyCode::E,
+            KeyCode::F,
+            KeyCode::G,
+            KeyCode::H,
+            KeyCode::I,
+            KeyCode::J,
+            KeyCode::K,
+            KeyCode::L,
+            KeyCode::M,
+            KeyCode::N,
+            KeyCode::O,
+            KeyCode::P,
+            KeyCode::Q,
+            KeyCode::R,
+            KeyCode::S,
+            KeyCode::T,
+            KeyCode::U,
+            KeyCode::V,
+            KeyCode::W,
+            KeyCode::X,
+            KeyCode::Y,
+            KeyCode::Z,
+        ];
+
+        for (key, lower) in letters.iter().zip('a'..='z') {
+            keymap.set(*key, lower, lower.to_ascii_uppercase());
+        }
+
+        keymap
+            .set(KeyCode::Key1, '1', '!')
+            .set(KeyCode::Key2, '2', '@')
+            .set(KeyCode::Key3, '3', '#')
+            .set(KeyCode::Key4, '4', '$')
+            .set(KeyCode::Key5, '5', '%')
+            .set(KeyCode::Key6, '6', '^')
+            .set(KeyCode::Key7, '7', '&')
+            .set(KeyCode::Key8, '8', '*')
+            .set(KeyCode::Key9, '9', '(')
+            .set(KeyCode::Key0, '0', ')')
+            .set(KeyCode::Space, ' ', ' ')
+            .set(KeyCode::Return, '\r', '\r')
+            .set(KeyCode::Tab, '\t', '\t')
+            .set(KeyCode::Minus, '-', '_')
+            .set(KeyCode::Equals, '=', '+')
+            .set(KeyCode::Comma, ',', '<')
+            .set(KeyCode::Period, '.', '>')
+            .set(KeyCode::Slash, '/', '?')
+            .set(KeyCode::Semicolon, ';', ':')
+            .set(KeyCode::Apostrophe, '\'', '"')
+            .set(KeyCode::Backslash, '\\', '|')
+            .set(KeyCode::Grave, '`', '~')
+            .set(KeyCode::LBracket, '[', '{')
+            .set(KeyCode::RBracket, ']', '}');
+
+        keymap
+    }
+
+    pub fn set(&mut self, key: KeyCode, character: char, shifted: char) -> &mut Self {
+        self.keys.insert(key, (character, shifted));
+        self
+    }
+
+    pub fn remove(&mut self, key: KeyCode) -> &mut Self {
+        self.keys.remove(&key);
+        self
+    }
+
+    /// Character of `key`. Caps lock shifts letters only
+    pub fn character(&self, key: KeyCode, shift: bool, caps_lock: bool) -> Option<char> {
+        let (character, shifted) = *self.keys.get(&key)?;
+        let shift = match character.is_alphabetic() {
+            true => shift != caps_lock,
+            false => shift,
+        };
+
+        Some(if shift { shifted } else { character })
+    }
+}
+
+impl Default for XRKeymap {
+    fn default() -> Self {
+        XRKeymap::us()
+    }
+}
+
+/// Overrides of the conversion from Android key codes (`android.view.KeyEvent.KEYCODE_*` values) to
+/// `KeyCode`s, e.g. for keys of non-US keyboards missing from the built-in conversion. Key codes
+/// not in the map use the built-in conversion
+#[derive(Debug, Clone, Default)]
+pub struct XRKeycodeMap {
+    keys: HashMap<u32, Option<KeyCode>>,
+}
+
+impl XRKeycodeMap {
+    /// Converts `android_key_code` into `key`, or ignores it if `None`
+    pub fn set(&mut self, android_key_code: u32, key: Option<KeyCode>) -> &mut Self {
+        self.keys.insert(android_key_code, key);
+        self
+    }
+
+    pub fn remove(&mut self, android_key_code: u32) -> &mut Self {
+        self.keys.remove(&android_key_code);
+        self
+    }
+
+    /// Conversion of `android_key_code`, or `converted` if not overridden
+    pub fn convert(&self, android_key_code: u32, converted: Option<KeyCode>) -> Option<KeyCode> {
+        match self.keys.get(&android_key_code) {
+            Some(key) => *key,
+            None => converted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap() {
+        let keymap = XRKeymap::us();
+        assert_eq!(keymap.character(KeyCode::A, false, false), Some('a'));
+        assert_eq!(keymap.character(KeyCode::A, true, false), Some('A'));
+        assert_eq!(keymap.character(KeyCode::A, false, true), Some('A'));
+        assert_eq!(keymap.character(KeyCode::A, true, true), Some('a'));
+        assert_eq!(keymap.character(KeyCode::Key1, false, true), Some('1'));
+        assert_eq!(keymap.character(KeyCode::Key1, true, false), Some('!'));
+        assert_eq!(keymap.character(KeyCode::F1, false, false), None);
+
+        let mut keymap = XRKeymap::us();
+        keymap.set(KeyCode::Y, 'z', 'Z').set(KeyCode::Z, 'y', 'Y');
+        assert_eq!(keymap.character(KeyCode::Y, true, false), Some('Z'));
+    }
+
+    #[test]
+    fn test_keycode_map() {
+        let mut keycode_map = XRKeycodeMap::default();
+        assert_eq!(keycode_map.convert(54, Some(KeyCode::Z)), Some(KeyCode::Z));
+
+        // KEYCODE_Z and KEYCODE_Y swapped, e.g. for a German layout
+        keycode_map
+            .set(54, Some(KeyCode::Y))
+            .set(53, Some(KeyCode::Z))
+            .set(68, None);
+        assert_eq!(keycode_map.convert(54, Some(KeyCode::Z)), Some(KeyCode::Y));
+        assert_eq!(keycode_map.convert(68, Some(KeyCode::Grave)), None);
+
+        keycode_map.remove(54);
+        assert_eq!(keycode_map.convert(54, Some(KeyCode::Z)), Some(KeyCode::Z));
+    }
+}
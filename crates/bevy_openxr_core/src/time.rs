@@ -0,0 +1,116 @@
+use bevy::core::Time;
+use bevy::ecs::system::{Res, ResMut};
+use bevy::utils::{Duration, Instant};
+
+use crate::{diagnostics::XRFrameTimings, XRDevice};
+
+/// Maps OpenXR time (`openxr::Time`, nanoseconds of the runtime clock) to bevy time. Available as a
+/// resource, updated at each rendered frame.
+///
+/// The clocks are related through the frame loop: the predicted display time of a frame is assumed
+/// to be one display period after `xrWaitFrame` returned. The absolute offset is an estimate, but
+/// it is the same for every frame, so timestamps converted either way stay consistent with each
+/// other (e.g. for input events, network sync or replay)
+#[derive(Debug, Clone, Default)]
+pub struct XrTime {
+    /// Predicted display time of the current frame. `None` while no frame is rendered
+    pub predicted_display_time: Option<openxr::Time>,
+
+    /// Predicted display period, e.g. 11.1ms at 90Hz
+    pub display_period: Duration,
+
+    /// Corresponding points in time of the runtime clock and `Instant`
+    anchor: Option<(openxr::Time, Instant)>,
+}
+
+impl XrTime {
+    /// `Instant` of an OpenXR time. `None` before the first frame
+    pub fn to_instant(&self, time: openxr::Time) -> Option<Instant> {
+        let (anchor_time, anchor_instant) = self.anchor?;
+        let offset = time.as_nanos() - anchor_time.as_nanos();
+
+        if offset >= 0 {
+            Some(anchor_instant + Duration::from_nanos(offset as u64))
+        } else {
+            anchor_instant.checked_sub(Duration::from_nanos(-offset as u64))
+        }
+    }
+
+    /// OpenXR time of an `Instant`. `None` before the first frame
+    pub fn from_instant(&self, instant: Instant) -> Option<openxr::Time> {
+        let (anchor_time, anchor_instant) = self.anchor?;
+
+        let nanos = if instant >= anchor_instant {
+            anchor_time.as_nanos() + (instant - anchor_instant).as_nanos() as i64
+        } else {
+            anchor_time.as_nanos() - (anchor_instant - instant).as_nanos() as i64
+        };
+
+        Some(openxr::Time::from_nanos(nanos))
+    }
+
+    /// OpenXR time as seconds since the startup of bevy `Time`, comparable to
+    /// `Time::seconds_since_startup`
+    pub fn seconds_since_startup(&self, time: openxr::Time, bevy_time: &Time) -> Option<f64> {
+        let instant = self.to_instant(time)?;
+        let startup = bevy_time.startup();
+
+        Some(if instant >= startup {
+            (instant - startup).as_secs_f64()
+        } else {
+            -(startup - instant).as_secs_f64()
+        })
+    }
+
+    fn update(&mut self, predicted_display_time: Option<openxr::Time>, timings: &XRFrameTimings) {
+        self.predicted_display_time = predicted_display_time;
+        self.display_period = timings.display_period;
+
+        if let (Some(time), Some(waited_at)) = (predicted_display_time, timings.frame_waited_at) {
+            self.anchor = Some((time, waited_at + timings.display_period));
+        }
+    }
+}
+
+pub(crate) fn xr_time_system(xr_device: Res<XRDevice>, mut xr_time: ResMut<XrTime>) {
+    let timings = match xr_device.get_frame_timings() {
+        Some(timings) => timings,
+        None => return,
+    };
+
+    xr_time.update(xr_device.predicted_display_time(), timings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_conversion() {
+        let mut xr_time = XrTime::default();
+        assert_eq!(xr_time.to_instant(openxr::Time::from_nanos(0)), None);
+
+        let waited_at = Instant::now();
+        xr_time.update(
+            Some(openxr::Time::from_nanos(1_000_000_000)),
+            &XRFrameTimings {
+                display_period: Duration::from_millis(10),
+                frame_waited_at: Some(waited_at),
+                ..Default::default()
+            },
+        );
+
+        let instant = xr_time
+            .to_instant(openxr::Time::from_nanos(1_005_000_000))
+            .unwrap();
+        assert_eq!(instant, waited_at + Duration::from_millis(15));
+        assert_eq!(
+            xr_time.from_instant(instant).map(|time| time.as_nanos()),
+            Some(1_005_000_000)
+        );
+        assert_eq!(
+            xr_time.from_instant(waited_at).map(|time| time.as_nanos()),
+            Some(990_000_000)
+        );
+    }
+}
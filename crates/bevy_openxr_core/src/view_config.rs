@@ -0,0 +1,120 @@
+use crate::{ext::ViewConfigurationFovEPIC, XrError, XrFovf};
+
+/// Properties of a view of the view configuration (`xrEnumerateViewConfigurationViews`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRViewConfigView {
+    /// Image size recommended by the runtime, `(width, height)`. The swapchain is created with this
+    /// size
+    pub recommended_image_rect: (u32, u32),
+
+    /// Largest image size supported for the view
+    pub max_image_rect: (u32, u32),
+
+    pub recommended_sample_count: u32,
+    pub max_sample_count: u32,
+
+    /// Recommended field of view, if the runtime supports `XR_EPIC_view_configuration_fov`
+    pub recommended_fov: Option<XrFovf>,
+
+    /// Widest field of view of the view, if the runtime supports `XR_EPIC_view_configuration_fov`
+    pub max_fov: Option<XrFovf>,
+}
+
+impl XRViewConfigView {
+    fn from_openxr(view: &openxr::ViewConfigurationView) -> Self {
+        XRViewConfigView {
+            recommended_image_rect: (
+                view.recommended_image_rect_width,
+                view.recommended_image_rect_height,
+            ),
+            max_image_rect: (view.max_image_rect_width, view.max_image_rect_height),
+            recommended_sample_count: view.recommended_swapchain_sample_count,
+            max_sample_count: view.max_swapchain_sample_count,
+            recommended_fov: None,
+            max_fov: None,
+        }
+    }
+}
+
+/// View configuration of the session (`XrOptions::view_type`), for apps choosing their own render
+/// scale or sample count. Available as a resource when the session has been created
+#[derive(Debug, Clone, PartialEq)]
+pub struct XRViewConfigInfo {
+    pub view_type: openxr::ViewConfigurationType,
+
+    /// The runtime allows rendering with a field of view other than the one located for the frame
+    pub fov_mutable: bool,
+
+    pub views: Vec<XRViewConfigView>,
+}
+
+impl XRViewConfigInfo {
+    pub(crate) fn new(
+        instance: &openxr::Instance,
+        system: openxr::SystemId,
+        view_type: openxr::ViewConfigurationType,
+    ) -> Result<Self, XrError> {
+        let properties = instance.view_configuration_properties(system, view_type)?;
+
+        let views = match ViewConfigurationFovEPIC::new(instance) {
+            Some(view_configuration_fov) => {
+                view_configuration_fov.enumerate_views(system, view_type)?
+            }
+            None => instance
+                .enumerate_view_configuration_views(system, view_type)?
+                .iter()
+                .map(XRViewConfigView::from_openxr)
+                .collect(),
+        };
+
+        Ok(XRViewConfigInfo {
+            view_type,
+            fov_mutable: properties.fov_mutable,
+            views,
+        })
+    }
+
+    /// Largest render scale (relative to the recommended image size) that fits into the max image
+    /// size of every view
+    pub fn max_render_scale(&self) -> f32 {
+        self.views
+            .iter()
+            .map(|view| {
+                let (width, height) = view.recommended_image_rect;
+                let (max_width, max_height) = view.max_image_rect;
+                (max_width as f32 / width.max(1) as f32)
+                    .min(max_height as f32 / height.max(1) as f32)
+            })
+            .fold(f32::INFINITY, f32::min)
+            .max(1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(recommended: (u32, u32), max: (u32, u32)) -> XRViewConfigView {
+        XRViewConfigView {
+            recommended_image_rect: recommended,
+            max_image_rect: max,
+            recommended_sample_count: 1,
+            max_sample_count: 4,
+            recommended_fov: None,
+            max_fov: None,
+        }
+    }
+
+    #[test]
+    fn test_max_render_scale() {
+        let info = XRViewConfigInfo {
+            view_type: openxr::ViewConfigurationType::PRIMARY_STEREO,
+            fov_mutable: true,
+            views: vec![
+                view((1440, 1584), (2880, 3168)),
+                view((1440, 1584), (2160, 3168)),
+            ],
+        };
+        assert_eq!(info.max_render_scale(), 1.5);
+    }
+}
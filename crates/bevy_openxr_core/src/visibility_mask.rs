@@ -0,0 +1,75 @@
+use std::ptr;
+
+use bevy::math::Vec2;
+use openxr::sys;
+
+use crate::{error::cvt, XrError};
+
+/// Hidden area mesh of a view (`XR_KHR_visibility_mask`): triangles covering the pixels that are
+/// not visible through the lens. Vertices are on the view space `z = -1` plane
+#[derive(Debug, Clone, Default)]
+pub struct XRVisibilityMask {
+    pub vertices: Vec<Vec2>,
+    pub indices: Vec<u32>,
+}
+
+/// `xrGetVisibilityMaskKHR` for the hidden triangle mesh. `Ok(None)` if the extension is not enabled
+pub(crate) fn get_visibility_mask(
+    instance: &openxr::Instance,
+    session: sys::Session,
+    view_configuration_type: openxr::ViewConfigurationType,
+    view_index: u32,
+) -> Result<Option<XRVisibilityMask>, XrError> {
+    let fp = match instance.exts().khr_visibility_mask.as_ref() {
+        Some(fp) => fp,
+        None => return Ok(None),
+    };
+
+    let mut mask = sys::VisibilityMaskKHR {
+        ty: sys::VisibilityMaskKHR::TYPE,
+        next: ptr::null_mut(),
+        vertex_capacity_input: 0,
+        vertex_count_output: 0,
+        vertices: ptr::null_mut(),
+        index_capacity_input: 0,
+        index_count_output: 0,
+        indices: ptr::null_mut(),
+    };
+
+    // first call for the sizes, second for the data
+    cvt(unsafe {
+        (fp.get_visibility_mask)(
+            session,
+            view_configuration_type,
+            view_index,
+            sys::VisibilityMaskTypeKHR::HIDDEN_TRIANGLE_MESH,
+            &mut mask,
+        )
+    })?;
+
+    let mut vertices = vec![sys::Vector2f { x: 0., y: 0. }; mask.vertex_count_output as usize];
+    let mut indices = vec![0u32; mask.index_count_output as usize];
+
+    mask.vertex_capacity_input = vertices.len() as u32;
+    mask.vertices = vertices.as_mut_ptr();
+    mask.index_capacity_input = indices.len() as u32;
+    mask.indices = indices.as_mut_ptr();
+
+    cvt(unsafe {
+        (fp.get_visibility_mask)(
+            session,
+            view_configuration_type,
+            view_index,
+            sys::VisibilityMaskTypeKHR::HIDDEN_TRIANGLE_MESH,
+            &mut mask,
+        )
+    })?;
+
+    vertices.truncate(mask.vertex_count_output as usize);
+    indices.truncate(mask.index_count_output as usize);
+
+    Ok(Some(XRVisibilityMask {
+        vertices: vertices.iter().map(|v| Vec2::new(v.x, v.y)).collect(),
+        indices,
+    }))
+}
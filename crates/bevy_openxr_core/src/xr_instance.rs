@@ -1,34 +1,41 @@
-use once_cell::sync::OnceCell;
 use std::fmt;
+use std::sync::Arc;
 use wgpu::wgpu_openxr::WGPUOpenXR;
 
-use crate::{OpenXRStruct, XRDevice, XrOptions};
-
-/// Used to transfer the at-app-beginning initializable openxr device for bevy
-static mut XR_INSTANCE: OnceCell<XrInstance> = OnceCell::new();
+use crate::graphics::{WgpuVulkanBackend, XrGraphicsBackend};
+use crate::{OpenXRStruct, XRDevice, XrError, XrOptions};
 
+/// At-app-beginning initialized OpenXR instance. Inserted as a resource before `OpenXRCorePlugin`
+/// is added, which takes the resource and converts it into `XRDevice` (unless an `XRDevice`
+/// resource was already converted, e.g. by `OpenXRPlugin`)
 pub struct XrInstance {
-    wgpu_openxr: WGPUOpenXR,
+    graphics: Arc<dyn XrGraphicsBackend>,
     inner: openxr::Instance,
 }
 
 impl XrInstance {
     pub fn new(wgpu_openxr: WGPUOpenXR, instance: openxr::Instance) -> Self {
+        Self::with_graphics(WgpuVulkanBackend::new(wgpu_openxr), instance)
+    }
+
+    /// Instance with the graphics device and session created by `graphics`
+    pub fn with_graphics(graphics: impl XrGraphicsBackend, instance: openxr::Instance) -> Self {
         Self {
-            wgpu_openxr,
+            graphics: Arc::new(graphics),
             inner: instance,
         }
     }
 
-    pub(crate) fn into_device_with_options(self, options: XrOptions) -> (XRDevice, WGPUOpenXR) {
-        let handles = self.wgpu_openxr.get_session_handles().unwrap();
-        let xr_struct = OpenXRStruct::new(self.inner, handles, options);
+    /// Takes the session from the graphics backend. Fails if the backend could not create it
+    pub fn into_device_with_options(self, options: XrOptions) -> Result<XRDevice, XrError> {
+        let handles = self.graphics.session_handles()?;
+        let xr_struct = OpenXRStruct::new(self.inner, handles, self.graphics, options);
 
-        (XRDevice::new(xr_struct), self.wgpu_openxr)
+        Ok(XRDevice::new(xr_struct))
     }
 
     pub fn destroy(&self) {
-        self.wgpu_openxr.destroy().unwrap();
+        self.graphics.destroy();
     }
 }
 
@@ -37,16 +44,3 @@ impl fmt::Debug for XrInstance {
         write!(f, "XrInstance[]")
     }
 }
-
-/// Set the openxr device from initialization code - will be later used by bevy
-/// Should be called exactly once
-pub fn set_xr_instance(instance: XrInstance) {
-    unsafe { XR_INSTANCE.set(instance).unwrap() };
-}
-
-pub(crate) fn take_xr_instance() -> XrInstance {
-    match unsafe { XR_INSTANCE.take() } {
-        Some(instance) => instance,
-        None => panic!("Must call set_xr_instance"),
-    }
-}